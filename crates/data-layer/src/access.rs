@@ -11,18 +11,31 @@
 //! the scoped import
 //! ([`import_namespace_scoped`](crate::SyncNode::import_namespace_scoped)).
 //! A replica the book knows nothing about is served whole to any ticket
-//! holder.
+//! holder. A device an identity has revoked is refused on every replica the
+//! book knows for that identity — its directories, retired ones included,
+//! its connection metadata stores, and its data namespace.
+//!
+//! A grantee re-serves what it re-delegated: a node holding a granted
+//! slice of an issuer's data serves the devices of a delegate its hosted
+//! identity recorded a delegation to, narrowed to what the identity's own
+//! grant from the issuer still covers.
+//!
+//! A time-bounded grant classifies as its claims only inside its validity
+//! window, read against this node's clock at each classification — before
+//! it opens and from its expiry on, as no grant.
 
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, OnceLock, RwLock};
+use std::time::SystemTime;
 
 use anyhow::Result;
 use iroh_blobs::Hash;
 use pdn_store::{api::Doc, store::Query, EntryFilter, NamespaceId, SessionAccess, SessionRole};
-use pdn_types::{ClaimId, NodeId, PdnId};
+use pdn_types::{CapabilityCid, ClaimId, NodeId, PdnId};
 
-use crate::connection_metadata::GrantRecord;
+use crate::connection_metadata::revocation_key;
 use crate::grant::{claim_id_of_key, ReadGrant};
+use crate::private_metadata::unix_ms;
 use crate::registry::{Registry, ServingPosture};
 
 /// One hosted connection: the directional stores of `identity` toward
@@ -37,6 +50,25 @@ struct HostedConnection {
     peer_doc: Doc,
 }
 
+/// Who wrote a grant record a classification reads: the data issuer, or a
+/// grantee re-delegating part of what it was granted. A record is read
+/// only as the kind its position calls for — a delegation never passes for
+/// the issuer's own grant, nor the reverse.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GrantedBy {
+    Issuer,
+    Delegator,
+}
+
+/// A grant record as the book caches it: the capability, the CIDs of
+/// every token it is made under, and who wrote it.
+#[derive(Debug, Clone)]
+struct DecodedGrant {
+    cap: ReadGrant,
+    chain: Vec<CapabilityCid>,
+    delegated: bool,
+}
+
 /// What one connection grants on one issuer's data: exactly these claims,
 /// or nothing. Every grant is capability-scoped, so a granted session is
 /// always a filtered one — no branch reaches the full view through a grant.
@@ -54,6 +86,10 @@ enum GrantWidth {
 pub(crate) struct AccessBook {
     /// identity → its directory replica (device records, Invariant 1).
     directories: RwLock<HashMap<PdnId, Doc>>,
+    /// Directories a re-hosting replaced — retired by a revocation. Still
+    /// served to the identity's devices, whose replicas learn the successor
+    /// from them, and still refusing every device they record as revoked.
+    retired: RwLock<Vec<(PdnId, Doc)>>,
     /// Hosted connections, in registration order.
     connections: RwLock<Vec<HostedConnection>>,
     /// The node's blob store, for payload-carrying reads (grant caps);
@@ -66,8 +102,10 @@ pub(crate) struct AccessBook {
     /// content-addressed, so a hash match is provably the current bytes, and
     /// a republish or withdrawal changes the hash and misses. `None` caches
     /// "these bytes decode to no usable grant"; a payload not yet replicated
-    /// is never cached, so it is re-checked until it lands.
-    grant_cache: RwLock<HashMap<NamespaceId, (Hash, Option<ReadGrant>)>>,
+    /// is never cached, so it is re-checked until it lands. A chained grant
+    /// caches its token CIDs beside the capability; whether one is revoked
+    /// is never cached — a revocation changes no grant record's hash.
+    grant_cache: RwLock<HashMap<NamespaceId, (Hash, Option<DecodedGrant>)>>,
 }
 
 impl AccessBook {
@@ -78,10 +116,18 @@ impl AccessBook {
     }
 
     pub(crate) fn host_identity(&self, identity: PdnId, directory: Doc) -> Result<()> {
-        self.directories
+        let namespace = directory.id();
+        let replaced = self
+            .directories
             .write()
             .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
             .insert(identity, directory);
+        if let Some(replaced) = replaced.filter(|doc| doc.id() != namespace) {
+            self.retired
+                .write()
+                .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
+                .push((identity, replaced));
+        }
         Ok(())
     }
 
@@ -90,6 +136,10 @@ impl AccessBook {
             .write()
             .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
             .remove(&identity);
+        self.retired
+            .write()
+            .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
+            .retain(|(retired, _doc)| *retired != identity);
         Ok(())
     }
 
@@ -147,10 +197,26 @@ impl AccessBook {
         // has nothing to narrow there. Classifying them against their own —
         // possibly not yet converged — device records would deadlock the
         // very bootstrap that delivers those records: a fresh import must
-        // sync before it can know who its peers are.
-        if self.directory_by_namespace(namespace)?.is_some()
-            || self.connection_by_namespace(namespace)?.is_some()
-        {
+        // sync before it can know who its peers are. The one refusal is
+        // the revocation record: a removed device still holds the tickets,
+        // so possession no longer bounds it, and its record is what the
+        // identity's devices wrote to say so.
+        let revoked_key = crate::private_metadata::revoked_key(&caller);
+        if let Some(directory) = self.directory_by_namespace(namespace)? {
+            return Ok(
+                if record_present(&directory, revoked_key.as_bytes()).await? {
+                    SessionAccess::Deny
+                } else {
+                    SessionAccess::Full
+                },
+            );
+        }
+        if let Some(connection) = self.connection_by_namespace(namespace)? {
+            if let Some(directory) = self.directory_of(connection.identity)? {
+                if record_present(&directory, revoked_key.as_bytes()).await? {
+                    return Ok(SessionAccess::Deny);
+                }
+            }
             return Ok(SessionAccess::Full);
         }
 
@@ -180,6 +246,10 @@ impl AccessBook {
         // The issuer's own devices see everything, judged through the
         // hosted directory.
         if let Some(directory) = self.directory_of(issuer)? {
+            let revoked_key = crate::private_metadata::revoked_key(&caller);
+            if record_present(&directory, revoked_key.as_bytes()).await? {
+                return Ok(SessionAccess::Deny);
+            }
             if device_listed(&directory, caller_key.as_bytes()).await? {
                 return Ok(SessionAccess::Full);
             }
@@ -208,8 +278,10 @@ impl AccessBook {
         // own directory, never a record a counterparty wrote — is served
         // per the locally replicated grant record: the same claim-set filter
         // the issuer applies, or nothing when the record is absent or
-        // withdrawn. Everyone else is refused, uniform with not-hosted: a
-        // third party's rights are not computable here. Dialing out toward an
+        // withdrawn. So is a device of a delegate a hosted grantee identity
+        // recorded a delegation to, narrowed to that identity's own grant.
+        // Everyone else is refused, uniform with not-hosted: a third
+        // party's rights are not computable here. Dialing out toward an
         // unresolved callee keeps a closed egress: serve nothing, receive
         // whatever the callee's own filter admits. A `Serve` binding is the
         // ticket-bounded stance: the whole replica.
@@ -228,9 +300,13 @@ impl AccessBook {
                         grants.push((directory, connection.peer_doc, connection.identity));
                     }
                 }
-                let claims = self
+                let mut claims = self
                     .union_claims(caller_key.as_bytes(), issuer, grant_key.as_bytes(), grants)
                     .await?;
+                claims.extend(
+                    self.delegated_claims(caller_key.as_bytes(), issuer, grant_key.as_bytes())
+                        .await?,
+                );
                 if !claims.is_empty() {
                     return Ok(SessionAccess::Filtered(egress_filter(issuer, claims)));
                 }
@@ -266,7 +342,7 @@ impl AccessBook {
                 continue;
             }
             if let GrantWidth::Claims(grant_claims) = self
-                .grant_width_in(&grant_doc, issuer, audience, grant_key)
+                .grant_width_in(&grant_doc, issuer, audience, grant_key, GrantedBy::Issuer)
                 .await?
             {
                 claims.extend(grant_claims);
@@ -275,16 +351,73 @@ impl AccessBook {
         Ok(claims)
     }
 
+    /// The claims of `issuer`'s data this node re-serves to the caller
+    /// under delegations. For every hosted identity holding a live grant
+    /// from the issuer — read from the replicated `peer` store of its pair
+    /// with the issuer — each delegation it recorded in its `own` store
+    /// toward another peer whose published devices list the caller counts,
+    /// narrowed to the claims the identity's own grant still covers: a
+    /// withdrawn or revoked upstream grant re-serves nothing. A delegation
+    /// serves only inside its record's window — the narrowest window of
+    /// its token chains, which the delegator validated before recording
+    /// them — as any windowed grant does.
+    async fn delegated_claims(
+        &self,
+        caller_key: &[u8],
+        issuer: PdnId,
+        grant_key: &[u8],
+    ) -> Result<HashSet<ClaimId>> {
+        let mut claims: HashSet<ClaimId> = HashSet::new();
+        for upstream in self.connections_with_peer(issuer)? {
+            let delegator = upstream.identity;
+            let GrantWidth::Claims(held) = self
+                .grant_width_in(
+                    &upstream.peer_doc,
+                    issuer,
+                    delegator,
+                    grant_key,
+                    GrantedBy::Issuer,
+                )
+                .await?
+            else {
+                continue;
+            };
+            for downstream in self.connections_of_identity(delegator)? {
+                if downstream.peer == issuer
+                    || !device_listed(&downstream.peer_doc, caller_key).await?
+                {
+                    continue;
+                }
+                if let GrantWidth::Claims(delegated) = self
+                    .grant_width_in(
+                        &downstream.own,
+                        issuer,
+                        downstream.peer,
+                        grant_key,
+                        GrantedBy::Delegator,
+                    )
+                    .await?
+                {
+                    claims.extend(delegated.into_iter().filter(|claim| held.contains(claim)));
+                }
+            }
+        }
+        Ok(claims)
+    }
+
     /// What one metadata replica records as the grant on `issuer`'s data
     /// toward `audience`, read from the one grant record at `grant_key` —
     /// the connection's `own` store when the issuer side classifies its
     /// counterparty, the replicated `peer` store when a grantee device
     /// classifies a sibling.
     ///
-    /// Claims come only from a present, *decoded* record whose capability
-    /// names this very issuer and this very audience. Everything else — no
-    /// record, a payload still replicating, a record kind this build cannot
-    /// decode, a capability addressed elsewhere — is no grant. Nothing may
+    /// Claims come only from a present, *decoded* record of the kind
+    /// `granted_by` calls for, whose capability names this very issuer and
+    /// this very audience, and — for a grant
+    /// made under `UWill` tokens — none of whose tokens has a revocation
+    /// record in the same replica. Everything else — no record, a payload
+    /// still replicating, a record kind this build cannot decode, a
+    /// capability addressed elsewhere, a revoked chain — is no grant. Nothing may
     /// be inferred from a record's mere presence: the record's position (in
     /// which store it sits) says who wrote it, but only `cap.audience` says
     /// whom it was written *for*, and a node holding two connections onto
@@ -295,6 +428,7 @@ impl AccessBook {
         issuer: PdnId,
         audience: PdnId,
         grant_key: &[u8],
+        granted_by: GrantedBy,
     ) -> Result<GrantWidth> {
         let Some(blobs) = self.blobs.get() else {
             return Ok(GrantWidth::None);
@@ -303,18 +437,39 @@ impl AccessBook {
         let Some(entry) = doc.get_one(query).await? else {
             return Ok(GrantWidth::None);
         };
-        let cap = self
+        let Some(DecodedGrant {
+            cap,
+            chain,
+            delegated,
+        }) = self
             .cached_grant(doc.id(), entry.content_hash(), blobs)
-            .await?;
-        Ok(match cap {
-            Some(cap) if cap.issuer == issuer && cap.audience == audience => {
-                GrantWidth::Claims(cap.claims.into_vec())
+            .await?
+        else {
+            return Ok(GrantWidth::None);
+        };
+        if cap.issuer != issuer
+            || cap.audience != audience
+            || delegated != (granted_by == GrantedBy::Delegator)
+        {
+            return Ok(GrantWidth::None);
+        }
+        // Read at every classification, not cached: the record does not
+        // change when its window closes.
+        if !cap.holds_at(unix_ms(SystemTime::now())) {
+            return Ok(GrantWidth::None);
+        }
+        // Record-level, like device revocation: the revocation's presence
+        // voids the chain before its signed payload has synced.
+        for cid in &chain {
+            if record_present(doc, revocation_key(cid).as_bytes()).await? {
+                return Ok(GrantWidth::None);
             }
-            Some(_) | None => GrantWidth::None,
-        })
+        }
+        Ok(GrantWidth::Claims(cap.claims.into_vec()))
     }
 
-    /// The decoded capability of the grant record with content `hash` in
+    /// The decoded capability — and the tokens it is made under — of the
+    /// grant record with content `hash` in
     /// `namespace`, from the cache when its hash matches, else fetched and
     /// decoded and then cached. Returns `None` for a record that decodes to
     /// no usable grant *and* for a payload not yet replicated — but caches
@@ -325,7 +480,7 @@ impl AccessBook {
         namespace: NamespaceId,
         hash: Hash,
         blobs: &iroh_blobs::api::Store,
-    ) -> Result<Option<ReadGrant>> {
+    ) -> Result<Option<DecodedGrant>> {
         {
             let cache = self
                 .grant_cache
@@ -341,8 +496,15 @@ impl AccessBook {
             return Ok(None);
         }
         let bytes = blobs.get_bytes(hash).await?;
-        let cap = crate::connection_metadata::decode_grant_record(&bytes)
-            .map(|GrantRecord::Scoped { cap, .. }| cap);
+        let cap = crate::connection_metadata::decode_grant_record(&bytes).map(|record| {
+            let delegated = record.is_delegated();
+            let (cap, chains, _ticket) = record.into_parts();
+            DecodedGrant {
+                cap,
+                chain: chains.into_iter().flatten().collect(),
+                delegated,
+            }
+        });
         self.grant_cache
             .write()
             .map_err(|_poisoned| anyhow::anyhow!("grant cache lock poisoned"))?
@@ -350,14 +512,25 @@ impl AccessBook {
         Ok(cap)
     }
 
+    /// The directory — current or retired — whose replica is `namespace`.
     fn directory_by_namespace(&self, namespace: NamespaceId) -> Result<Option<Doc>> {
-        Ok(self
+        let current = self
             .directories
             .read()
             .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
             .values()
             .find(|doc| doc.id() == namespace)
-            .cloned())
+            .cloned();
+        if current.is_some() {
+            return Ok(current);
+        }
+        Ok(self
+            .retired
+            .read()
+            .map_err(|_poisoned| anyhow::anyhow!("access book lock poisoned"))?
+            .iter()
+            .find(|(_identity, doc)| doc.id() == namespace)
+            .map(|(_identity, doc)| doc.clone()))
     }
 
     fn directory_of(&self, identity: PdnId) -> Result<Option<Doc>> {
@@ -409,7 +582,13 @@ impl AccessBook {
 /// probe: this membership test decides "own device", so it must never drift
 /// from what the stores write.
 async fn device_listed(doc: &Doc, device_key: &[u8]) -> Result<bool> {
-    let query = Query::single_latest_per_key().key_exact(device_key);
+    record_present(doc, device_key).await
+}
+
+/// Whether a live (not tombstoned) record sits at `key` in `doc` — the
+/// record-level probe behind both device membership and revocation.
+async fn record_present(doc: &Doc, key: &[u8]) -> Result<bool> {
+    let query = Query::single_latest_per_key().key_exact(key);
     Ok(doc.get_one(query).await?.is_some())
 }
 
@@ -18,6 +18,28 @@
 //! wholesale, and a withdrawal is one tombstone — no ordering between
 //! records to get wrong, locally or across devices.
 //!
+//! A grant made under `UWill` tokens names them by CID
+//! ([`GrantRecord::Chained`]); the signed tokens ride beside the grants as
+//! **one record per token**, at `capabilities/<cid-hex>`, opaque bytes
+//! here. A grantee re-delegating part of its grant records the result in
+//! its own store toward the delegate, under the data issuer's key
+//! ([`GrantRecord::Delegated`]). A revoked token is recorded as **one
+//! record per revoked CID**, at `revocations/<cid-hex>`, carrying the
+//! signed revocation as opaque bytes. A grant whose chains hold a revoked
+//! CID in the same store is no grant, from the moment the revocation's
+//! record arrives — its payload need not have. A time-bounded grant rides
+//! wrapped as [`GrantRecord::Windowed`]; the store carries its window, the
+//! readers enforce it.
+//!
+//! The issuing identity's key event log is mirrored here too, under `kel/`
+//! as in the directory, so the counterparty holds the log its proofs are
+//! checked against; a store carrying a delegated grant also relays the
+//! data issuer's log, under `issuers/<issuer-hex>/kel/`, which the
+//! delegate checks the issuer's links by. When a revocation moves the store onto a fresh replica
+//! the retired one is left `successor/` records, as a retired directory is
+//! ([`Successor`]): the counterparty follows one only once its proof
+//! verifies under the mirrored log.
+//!
 //! Grant payloads are blobs, so grant reads are payload-waiting:
 //! [`ConnectionMetadataStore::read_grant`] returns `None` until the payload
 //! bytes have arrived — and likewise for bytes this version cannot read,
@@ -38,16 +60,47 @@ use pdn_store::{
     store::Query,
     AuthorId, DocTicket, NamespaceId,
 };
-use pdn_types::{NodeId, PdnId};
+use pdn_types::{CapabilityCid, NodeId, NonEmpty, PdnId};
 use serde::{Deserialize, Serialize};
 
+use crate::error::NodeError;
 use crate::grant::ReadGrant;
-use crate::node::{read_payload, SyncNode};
-use crate::private_metadata::{device_key, device_of, DEVICES_PREFIX};
+use crate::node::{copy_records, read_payload, SyncNode};
+use crate::private_metadata::{
+    device_key, device_of, key_event_key, put_successor_in, read_key_events, read_successors,
+    Successor, DEVICES_PREFIX, KEL_PREFIX, SUCCESSOR_PREFIX,
+};
 
 /// Key prefix under which grant entries live.
 const GRANTS_PREFIX: &str = "grants/";
 
+/// Key prefix under which revocation records live.
+const REVOCATIONS_PREFIX: &str = "revocations/";
+
+/// Key prefix under which signed capability tokens live.
+const CAPABILITIES_PREFIX: &str = "capabilities/";
+
+/// Key prefix under which relayed key event logs of data issuers live.
+const ISSUERS_PREFIX: &str = "issuers/";
+
+/// The key prefix of the relayed key event log of `issuer`:
+/// `issuers/<issuer-hex>/kel/`, one record per event below it.
+fn issuer_log_prefix(issuer: &PdnId) -> String {
+    format!("{ISSUERS_PREFIX}{issuer}/{KEL_PREFIX}")
+}
+
+/// The entry key of the signed token `cid`: `capabilities/<cid-hex>`.
+fn token_key(cid: &CapabilityCid) -> String {
+    format!("{CAPABILITIES_PREFIX}{cid}")
+}
+
+/// The entry key of the revocation record of token `cid`:
+/// `revocations/<cid-hex>`. Shared with the access book, which probes it
+/// for every CID of a chained grant at session classification.
+pub(crate) fn revocation_key(cid: &CapabilityCid) -> String {
+    format!("{REVOCATIONS_PREFIX}{cid}")
+}
+
 /// The entry key of the grant record for `issuer`'s data store:
 /// `grants/<issuer-hex>` — one record per issuer. Shared with the access
 /// book, which reads the same record at session classification.
@@ -65,15 +118,106 @@ pub(crate) fn grant_key(issuer: &PdnId) -> String {
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub(crate) enum GrantRecord {
     /// A capability-scoped grant: exactly the capability's claims, with the
-    /// ticket carrying addressing and contacts. The one kind there is —
-    /// the tagged form stays so an unknown kind from a future version
-    /// decodes as no grant rather than as this one.
+    /// ticket carrying addressing and contacts.
     Scoped {
         /// The capability: issuer, audience, exact claims, commands.
         cap: ReadGrant,
         /// The replica's ticket, canonical string form.
         ticket: String,
     },
+    /// A scoped grant made under `UWill` tokens, named by CID: void once
+    /// any of them is revoked. Its own kind, so a build that does not know
+    /// revocation reads it as no grant rather than as a [`Scoped`] one it
+    /// would keep honoring after a revocation.
+    ///
+    /// [`Scoped`]: GrantRecord::Scoped
+    Chained {
+        /// The capability: issuer, audience, exact claims, commands.
+        cap: ReadGrant,
+        /// The CIDs of the tokens the grant executes — one per granted
+        /// claim, in the capability's claim order.
+        chain: NonEmpty<CapabilityCid>,
+        /// The replica's ticket, canonical string form.
+        ticket: String,
+    },
+    /// A re-delegation: the writer of this store grants its audience part
+    /// of a grant it holds on `cap.issuer`'s data, and re-serves that part
+    /// from its own replica. Read-only. Its own kind, so neither the issuer
+    /// side of the data nor a build that does not know delegation reads it
+    /// as a grant by the issuer.
+    Delegated {
+        /// The capability: issuer, audience, exact claims; never write.
+        cap: ReadGrant,
+        /// The token chain behind each granted claim, in the capability's
+        /// claim order: the issuer's token first, this store writer's
+        /// last.
+        chains: NonEmpty<NonEmpty<CapabilityCid>>,
+        /// The delegator's ticket onto its replica of the issuer's data,
+        /// canonical string form.
+        ticket: String,
+    },
+    /// A time-bounded grant: a record of one of the kinds above whose
+    /// capability carries a validity window. Its own kind, so a build that
+    /// does not know windows reads it as no grant rather than as the inner
+    /// record, which it would keep honoring after expiry.
+    Windowed {
+        /// The bounded record; never itself windowed.
+        record: Box<GrantRecord>,
+    },
+}
+
+impl GrantRecord {
+    /// Whether this is a [`Delegated`](Self::Delegated) grant, bounded or
+    /// not.
+    pub(crate) fn is_delegated(&self) -> bool {
+        match self {
+            Self::Delegated { .. } => true,
+            Self::Windowed { record } => record.is_delegated(),
+            Self::Scoped { .. } | Self::Chained { .. } => false,
+        }
+    }
+
+    /// The capability the record carries.
+    fn cap(&self) -> &ReadGrant {
+        match self {
+            Self::Scoped { cap, .. } | Self::Chained { cap, .. } | Self::Delegated { cap, .. } => {
+                cap
+            }
+            Self::Windowed { record } => record.cap(),
+        }
+    }
+
+    /// Whether the record is one this build acts on: a capability carries
+    /// a window exactly when its record is [`Windowed`](Self::Windowed),
+    /// and a windowed record wraps one of the other kinds.
+    fn is_well_formed(&self) -> bool {
+        match self {
+            Self::Windowed { record } => {
+                !matches!(**record, Self::Windowed { .. }) && record.cap().window.is_some()
+            }
+            Self::Scoped { cap, .. } | Self::Chained { cap, .. } | Self::Delegated { cap, .. } => {
+                cap.window.is_none()
+            }
+        }
+    }
+
+    /// The capability, the token chains it is made under — one per granted
+    /// claim, in claim order; none for a [`Scoped`](Self::Scoped) grant —
+    /// and the ticket.
+    pub(crate) fn into_parts(self) -> (ReadGrant, Vec<NonEmpty<CapabilityCid>>, String) {
+        match self {
+            Self::Scoped { cap, ticket } => (cap, Vec::new(), ticket),
+            Self::Chained { cap, chain, ticket } => {
+                (cap, chain.into_iter().map(NonEmpty::new).collect(), ticket)
+            }
+            Self::Delegated {
+                cap,
+                chains,
+                ticket,
+            } => (cap, chains.into_vec(), ticket),
+            Self::Windowed { record } => record.into_parts(),
+        }
+    }
 }
 
 /// Decode a grant record's payload, if it is one this version can read.
@@ -82,9 +226,12 @@ pub(crate) enum GrantRecord {
 /// writing nothing would — treating it as an error would let a single
 /// unreadable grant hide every readable grant beside it. The serving side
 /// leans on the same `None`: undecodable never classifies wider than
-/// absent.
+/// absent. A window outside a [`GrantRecord::Windowed`] record, or a
+/// windowed record without one, is malformed and reads as absent alike.
 pub(crate) fn decode_grant_record(bytes: &[u8]) -> Option<GrantRecord> {
-    serde_json::from_slice(bytes).ok()
+    serde_json::from_slice(bytes)
+        .ok()
+        .filter(GrantRecord::is_well_formed)
 }
 
 /// Parse a data-store issuer back out of a `grants/<hex>` key, if it
@@ -97,6 +244,16 @@ fn grant_issuer_of(key: &[u8]) -> Option<PdnId> {
         .ok()
 }
 
+/// Parse a revoked token's CID back out of a `revocations/<hex>` key, if it
+/// matches.
+fn revoked_cid_of(key: &[u8]) -> Option<CapabilityCid> {
+    std::str::from_utf8(key)
+        .ok()?
+        .strip_prefix(REVOCATIONS_PREFIX)?
+        .parse()
+        .ok()
+}
+
 /// Parse a grant record's ticket string back into a [`DocTicket`], `None`
 /// for a form this version cannot read — the same withholds-itself-only
 /// rule as [`decode_grant_record`].
@@ -122,7 +279,8 @@ pub fn peer_ticket_kind(peer: &PdnId) -> String {
 /// written by its issuing identity's devices and read whole by the
 /// connection counterparty's devices (Invariant 3). The issuing identity
 /// and the counterparty are not kept here — the handle's holder knows which
-/// connection and direction it serves.
+/// connection and direction it serves. Written as the node's default
+/// author, as the directory is: reopening a pair mints no author.
 #[derive(Debug, Clone)]
 pub struct ConnectionMetadataStore {
     doc: Doc,
@@ -145,9 +303,9 @@ pub struct ConnectionMetadata {
 impl ConnectionMetadataStore {
     /// Create a fresh metadata store on `node` — this side's `own` replica
     /// toward one counterparty.
-    pub async fn create(node: &SyncNode) -> Result<Self> {
+    pub async fn create(node: &SyncNode) -> Result<Self, NodeError> {
         let doc = node.new_doc().await?;
-        let author = node.create_author().await?;
+        let author = node.default_author().await?;
         Ok(Self {
             doc,
             author,
@@ -159,9 +317,9 @@ impl ConnectionMetadataStore {
     /// the read ticket received at establishment, or this identity's own
     /// replica from the write ticket in the directory. The handle is usable
     /// at once; content converges asynchronously.
-    pub async fn import(node: &SyncNode, ticket: DocTicket) -> Result<Self> {
+    pub async fn import(node: &SyncNode, ticket: DocTicket) -> Result<Self, NodeError> {
         let doc = node.import_doc(ticket).await?;
-        let author = node.create_author().await?;
+        let author = node.default_author().await?;
         Ok(Self {
             doc,
             author,
@@ -171,8 +329,8 @@ impl ConnectionMetadataStore {
 
     /// The namespace id of the backing replica. Lets a re-established peer
     /// store (same namespace) be told from a genuinely new one, so
-    /// re-establishment does not re-import and leak a tracked doc plus an
-    /// author per attempt.
+    /// re-establishment does not re-import and leak a tracked doc per
+    /// attempt.
     pub fn namespace(&self) -> NamespaceId {
         self.doc.id()
     }
@@ -192,7 +350,9 @@ impl ConnectionMetadataStore {
     /// yet readable, and only the payload event tells a consumer to re-read.
     /// An `Err` item reports the subscription failing; the stream ends when
     /// the node shuts down.
-    pub async fn changes(&self) -> Result<impl Stream<Item = Result<()>> + Send + Unpin + 'static> {
+    pub async fn changes(
+        &self,
+    ) -> Result<impl Stream<Item = Result<(), NodeError>> + Send + Unpin + 'static, NodeError> {
         let events = self.doc.subscribe().await?;
         Ok(events.filter_map(|event| match event {
             Ok(
@@ -203,7 +363,7 @@ impl ConnectionMetadataStore {
             // Sync-session and neighbor bookkeeping is not a change of the
             // replica's contents.
             Ok(_) => None,
-            Err(err) => Some(Err(err)),
+            Err(err) => Some(Err(NodeError::storage(err))),
         }))
     }
 
@@ -215,7 +375,7 @@ impl ConnectionMetadataStore {
         &self,
         mode: ShareMode,
         addr_options: AddrInfoOptions,
-    ) -> Result<DocTicket> {
+    ) -> Result<DocTicket, NodeError> {
         let ticket = self.doc.share(mode, addr_options).await?;
         Ok(ticket)
     }
@@ -224,7 +384,7 @@ impl ConnectionMetadataStore {
     /// available as soon as the records sync; a listed grant's ticket may
     /// still be payload-waiting in [`read_grant`](Self::read_grant)).
     /// Withdrawn grants do not list.
-    pub async fn list_grants(&self) -> Result<Vec<PdnId>> {
+    pub async fn list_grants(&self) -> Result<Vec<PdnId>, NodeError> {
         let query = Query::single_latest_per_key().key_prefix(GRANTS_PREFIX.as_bytes());
         let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
         let mut issuers = Vec::new();
@@ -245,7 +405,7 @@ impl ConnectionMetadataStore {
     /// half-withdrawn grant. Whether data already delivered under it
     /// is retained is outside this store — Invariant 2 governs acquisition,
     /// not retention.
-    pub async fn withdraw_grant(&self, issuer: PdnId) -> Result<()> {
+    pub async fn withdraw_grant(&self, issuer: PdnId) -> Result<(), NodeError> {
         self.doc
             .del(self.author, grant_key(&issuer).into_bytes())
             .await?;
@@ -258,17 +418,74 @@ impl ConnectionMetadataStore {
     /// order of replication. The ticket's mode is the caller's to mint per
     /// the grant's commands — read-only → `ShareMode::Read`, with write →
     /// `ShareMode::Write`; this store carries the pair, it does not check
-    /// it.
-    pub async fn publish_grant(&self, grant: &ReadGrant, ticket: &DocTicket) -> Result<()> {
-        let record = GrantRecord::Scoped {
+    /// it. A grant carrying a validity window is written wrapped as
+    /// [`GrantRecord::Windowed`], as by every publish below.
+    pub async fn publish_grant(
+        &self,
+        grant: &ReadGrant,
+        ticket: &DocTicket,
+    ) -> Result<(), NodeError> {
+        self.write_grant_record(GrantRecord::Scoped {
+            cap: grant.clone(),
+            ticket: ticket.to_string(),
+        })
+        .await
+    }
+
+    /// [`publish_grant`](Self::publish_grant) for a grant made under the
+    /// `UWill` tokens `chain`: one [`GrantRecord::Chained`], which reads as
+    /// no grant once any token of the chain is revoked in this store
+    /// ([`publish_revocation`](Self::publish_revocation)).
+    pub async fn publish_chained_grant(
+        &self,
+        grant: &ReadGrant,
+        chain: &NonEmpty<CapabilityCid>,
+        ticket: &DocTicket,
+    ) -> Result<(), NodeError> {
+        self.write_grant_record(GrantRecord::Chained {
+            cap: grant.clone(),
+            chain: chain.clone(),
+            ticket: ticket.to_string(),
+        })
+        .await
+    }
+
+    /// Publish a re-delegation: this store's writer grants `grant.audience`
+    /// read on `grant.claims` of `grant.issuer`'s data, which it holds a
+    /// grant on, each claim under its token chain in `chains` (claim
+    /// order), and re-serves from the replica `ticket` names. One
+    /// [`GrantRecord::Delegated`] at the issuer's key, replacing any
+    /// previous grant for that issuer.
+    pub async fn publish_delegated_grant(
+        &self,
+        grant: &ReadGrant,
+        chains: &NonEmpty<NonEmpty<CapabilityCid>>,
+        ticket: &DocTicket,
+    ) -> Result<(), NodeError> {
+        self.write_grant_record(GrantRecord::Delegated {
             cap: grant.clone(),
+            chains: chains.clone(),
             ticket: ticket.to_string(),
+        })
+        .await
+    }
+
+    /// Write `record` at its issuer's key — wrapped as
+    /// [`GrantRecord::Windowed`] when its capability carries a window.
+    async fn write_grant_record(&self, record: GrantRecord) -> Result<(), NodeError> {
+        let issuer = record.cap().issuer;
+        let record = if record.cap().window.is_some() {
+            GrantRecord::Windowed {
+                record: Box::new(record),
+            }
+        } else {
+            record
         };
         self.doc
             .set_bytes(
                 self.author,
-                grant_key(&grant.issuer).into_bytes(),
-                serde_json::to_vec(&record)?,
+                grant_key(&issuer).into_bytes(),
+                serde_json::to_vec(&record).map_err(NodeError::storage)?,
             )
             .await?;
         Ok(())
@@ -278,22 +495,141 @@ impl ConnectionMetadataStore {
     /// ticket — if present and readable.
     ///
     /// `Ok(None)` covers every "no usable grant here": no entry at all, a
-    /// payload that has not arrived (consumers poll), or a payload this
-    /// version cannot read. `Err` stays reserved for this node's own
-    /// failures, so one unreadable grant never hides the readable ones
-    /// beside it.
-    pub async fn read_grant(&self, issuer: PdnId) -> Result<Option<(ReadGrant, DocTicket)>> {
+    /// payload that has not arrived (consumers poll), a payload this
+    /// version cannot read, or a chained grant with a revoked token. A
+    /// time-bounded grant reads with its window, in force or not — whether
+    /// it holds now is the reader's to check ([`ReadGrant::holds_at`]). `Err`
+    /// stays reserved for this node's own failures, so one unreadable grant
+    /// never hides the readable ones beside it.
+    pub async fn read_grant(
+        &self,
+        issuer: PdnId,
+    ) -> Result<Option<(ReadGrant, DocTicket)>, NodeError> {
+        let Some((cap, chains, ticket)) = self.read_grant_record(issuer).await? else {
+            return Ok(None);
+        };
+        for cid in chains.iter().flatten() {
+            if self.is_revoked(cid).await? {
+                return Ok(None);
+            }
+        }
+        Ok(decode_grant_ticket(&ticket).map(|t| (cap, t)))
+    }
+
+    /// The CIDs of the tokens the grant for `issuer`'s data store is made
+    /// under — revoked ones included — or none for an unchained, absent or
+    /// unreadable grant. What a revoker looks for to find the stores a
+    /// revocation belongs in.
+    pub async fn grant_chain(&self, issuer: PdnId) -> Result<Vec<CapabilityCid>, NodeError> {
+        Ok(self
+            .grant_chains(issuer)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// The token chain behind each claim of the grant for `issuer`'s data
+    /// store, in the capability's claim order — the issuer's token first —
+    /// revoked ones included; none for an unchained, absent or unreadable
+    /// grant.
+    pub async fn grant_chains(
+        &self,
+        issuer: PdnId,
+    ) -> Result<Vec<NonEmpty<CapabilityCid>>, NodeError> {
+        Ok(self
+            .read_grant_record(issuer)
+            .await?
+            .map(|(_cap, chains, _ticket)| chains)
+            .unwrap_or_default())
+    }
+
+    /// Whether the grant for `issuer`'s data store is made under a token
+    /// revoked in this store — a grant that lists
+    /// ([`list_grants`](Self::list_grants)) but grants nothing, for good.
+    /// Record-level, as revocation is.
+    pub async fn grant_revoked(&self, issuer: PdnId) -> Result<bool, NodeError> {
+        for cid in self.grant_chain(issuer).await? {
+            if self.is_revoked(&cid).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn read_grant_record(
+        &self,
+        issuer: PdnId,
+    ) -> Result<Option<(ReadGrant, Vec<NonEmpty<CapabilityCid>>, String)>, NodeError> {
         let Some(bytes) =
             read_payload(&self.doc, &self.blobs, grant_key(&issuer).as_bytes()).await?
         else {
             return Ok(None);
         };
-        match decode_grant_record(&bytes) {
-            Some(GrantRecord::Scoped { cap, ticket }) => {
-                Ok(decode_grant_ticket(&ticket).map(|t| (cap, t)))
+        Ok(decode_grant_record(&bytes).map(GrantRecord::into_parts))
+    }
+
+    /// Publish the signed token `cid` — `token`, opaque here; its format is
+    /// the PDN layer's — for the grants that name it: one record at
+    /// `capabilities/<cid-hex>`. What a grantee builds its own delegations
+    /// on, and checks a delegation it receives by.
+    pub async fn publish_token(&self, cid: &CapabilityCid, token: &[u8]) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(self.author, token_key(cid).into_bytes(), token.to_vec())
+            .await?;
+        Ok(())
+    }
+
+    /// The signed token `cid`, once its payload has arrived.
+    pub async fn read_token(&self, cid: &CapabilityCid) -> Result<Option<Vec<u8>>, NodeError> {
+        Ok(read_payload(&self.doc, &self.blobs, token_key(cid).as_bytes()).await?)
+    }
+
+    /// Publish the revocation of token `cid`: one record at
+    /// `revocations/<cid-hex>` carrying `record`, the signed revocation —
+    /// opaque here; its format is the PDN layer's. Every chained grant in
+    /// this store whose chain holds `cid` reads as no grant from the
+    /// moment the record is present, on each side of the connection.
+    /// Revocations are final: there is no withdrawing one.
+    pub async fn publish_revocation(
+        &self,
+        cid: &CapabilityCid,
+        record: &[u8],
+    ) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(
+                self.author,
+                revocation_key(cid).into_bytes(),
+                record.to_vec(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Whether token `cid` is revoked in this store (record-level —
+    /// effective as soon as the record syncs).
+    pub async fn is_revoked(&self, cid: &CapabilityCid) -> Result<bool, NodeError> {
+        let query = Query::single_latest_per_key().key_exact(revocation_key(cid).into_bytes());
+        Ok(self.doc.get_one(query).await?.is_some())
+    }
+
+    /// The tokens revoked in this store (record-level).
+    pub async fn list_revocations(&self) -> Result<Vec<CapabilityCid>, NodeError> {
+        let query = Query::single_latest_per_key().key_prefix(REVOCATIONS_PREFIX.as_bytes());
+        let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
+        let mut cids = Vec::new();
+        while let Some(entry) = stream.next().await {
+            if let Some(cid) = revoked_cid_of(entry?.key()) {
+                cids.push(cid);
             }
-            None => Ok(None),
         }
+        Ok(cids)
+    }
+
+    /// The signed revocation record of token `cid`, once its payload has
+    /// arrived.
+    pub async fn read_revocation(&self, cid: &CapabilityCid) -> Result<Option<Vec<u8>>, NodeError> {
+        Ok(read_payload(&self.doc, &self.blobs, revocation_key(cid).as_bytes()).await?)
     }
 
     /// Publish `device` as one of the issuing identity's devices: the
@@ -302,7 +638,7 @@ impl ConnectionMetadataStore {
     /// withdrawn record included — the deliberate (re-)assertion act.
     /// Machinery that merely opens the pair uses
     /// [`ensure_device_published`](Self::ensure_device_published) instead.
-    pub async fn publish_device(&self, device: NodeId) -> Result<()> {
+    pub async fn publish_device(&self, device: NodeId) -> Result<(), NodeError> {
         self.doc
             .set_bytes(self.author, device_key(&device).into_bytes(), vec![1u8])
             .await?;
@@ -315,7 +651,7 @@ impl ConnectionMetadataStore {
     /// device must never be re-asserted as a side effect of merely opening
     /// the pair — re-assertion is the deliberate
     /// [`publish_device`](Self::publish_device).
-    pub async fn ensure_device_published(&self, device: NodeId) -> Result<()> {
+    pub async fn ensure_device_published(&self, device: NodeId) -> Result<(), NodeError> {
         // `include_empty` keeps tombstones visible: "no record at all" and
         // "withdrawn" must be told apart, and only the former publishes.
         let query = Query::single_latest_per_key()
@@ -329,7 +665,7 @@ impl ConnectionMetadataStore {
 
     /// The devices the issuing identity has published (record-level —
     /// available as soon as the records sync).
-    pub async fn published_devices(&self) -> Result<Vec<NodeId>> {
+    pub async fn published_devices(&self) -> Result<Vec<NodeId>, NodeError> {
         let query = Query::single_latest_per_key().key_prefix(DEVICES_PREFIX.as_bytes());
         let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
         let mut devices = Vec::new();
@@ -343,12 +679,92 @@ impl ConnectionMetadataStore {
 
     /// Revoke a published device record (tombstone) — a revoked device
     /// stops classifying as this identity's on the counterparty.
-    pub async fn withdraw_device(&self, device: NodeId) -> Result<()> {
+    pub async fn withdraw_device(&self, device: NodeId) -> Result<(), NodeError> {
         self.doc
             .del(self.author, device_key(&device).into_bytes())
             .await?;
         Ok(())
     }
+
+    /// Mirror the issuing identity's key event at `sn`, encoded as in the
+    /// directory.
+    pub async fn put_key_event(&self, sn: u64, event: &[u8]) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(
+                self.author,
+                key_event_key(KEL_PREFIX, sn).into_bytes(),
+                event.to_vec(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// The mirrored key events recorded at each sequence number, as far as
+    /// they have arrived — a prefix of the log, as
+    /// [`PrivateMetadataStore::key_events`](crate::PrivateMetadataStore::key_events)
+    /// reads it.
+    pub async fn key_events(&self) -> Result<Vec<Vec<Vec<u8>>>, NodeError> {
+        Ok(read_key_events(&self.doc, &self.blobs, KEL_PREFIX).await?)
+    }
+
+    /// Relay the key event at `sn` of `issuer`'s log, encoded as in its
+    /// directory: the log a delegate checks the links of a delegated
+    /// grant's chain issued by `issuer` under. The log is self-certifying,
+    /// so whoever relays it vouches for nothing but its freshness.
+    pub async fn put_issuer_key_event(
+        &self,
+        issuer: PdnId,
+        sn: u64,
+        event: &[u8],
+    ) -> Result<(), NodeError> {
+        let key = key_event_key(&issuer_log_prefix(&issuer), sn);
+        self.doc
+            .set_bytes(self.author, key.into_bytes(), event.to_vec())
+            .await?;
+        Ok(())
+    }
+
+    /// The relayed key events of `issuer` recorded at each sequence number,
+    /// as far as they have arrived.
+    pub async fn issuer_key_events(&self, issuer: PdnId) -> Result<Vec<Vec<Vec<u8>>>, NodeError> {
+        let prefix = issuer_log_prefix(&issuer);
+        Ok(read_key_events(&self.doc, &self.blobs, &prefix).await?)
+    }
+
+    /// Copy every live record of this store onto a fresh replica on `node`
+    /// — the revocation's re-keying of an own store, as
+    /// [`PrivateMetadataStore::rekeyed`](crate::PrivateMetadataStore::rekeyed)
+    /// re-keys the directory. A withdrawn device's tombstone stays behind,
+    /// and so do successor records.
+    pub async fn rekeyed(&self, node: &SyncNode) -> Result<Self, NodeError> {
+        let successor = Self::create(node).await?;
+        if let Err(err) = copy_records(
+            &self.doc,
+            &successor.doc,
+            successor.author,
+            &self.blobs,
+            |key| !key.starts_with(SUCCESSOR_PREFIX.as_bytes()),
+        )
+        .await
+        {
+            let _ = node.forget_doc(successor.namespace()).await;
+            return Err(err.into());
+        }
+        Ok(successor)
+    }
+
+    /// Mark this store retired in favor of `successor`, written last by a
+    /// revocation.
+    pub async fn put_successor(&self, successor: &Successor) -> Result<(), NodeError> {
+        put_successor_in(&self.doc, self.author, successor).await
+    }
+
+    /// The successors this store was retired in favor of, as far as their
+    /// payloads have arrived — claims to verify, as for
+    /// [`PrivateMetadataStore::successors`](crate::PrivateMetadataStore::successors).
+    pub async fn successors(&self) -> Result<Vec<Successor>, NodeError> {
+        Ok(read_successors(&self.doc, &self.blobs).await?)
+    }
 }
 
 #[cfg(test)]
@@ -357,7 +773,7 @@ mod tests {
 
     use iroh::{EndpointAddr, PublicKey};
     use pdn_store::{Capability, NamespaceSecret};
-    use pdn_types::{ClaimId, NonEmpty};
+    use pdn_types::{ClaimId, NonEmpty, ValidityWindow};
 
     use super::*;
 
@@ -394,6 +810,7 @@ mod tests {
                 audience: PdnId::from_bytes([0xb0; 32]),
                 claims: NonEmpty::new(ClaimId::from_bytes([0x11; 32])),
                 write: false,
+                window: None,
             },
             ticket: ticket().to_string(),
         })
@@ -426,4 +843,102 @@ mod tests {
             "an unreadable ticket string must read as absent"
         );
     }
+
+    /// A chained grant decodes with its chain, which must not be empty —
+    /// a chained record without tokens is malformed, not an unchained one
+    /// — a delegated one with its chains, and a revocation key round-trips
+    /// to the CID it revokes.
+    #[test]
+    fn chained_grant_records_carry_their_chain() {
+        let cid = CapabilityCid::from_bytes([0x5c; 32]);
+        let cap = ReadGrant {
+            issuer: PdnId::from_bytes([0xa1; 32]),
+            audience: PdnId::from_bytes([0xb0; 32]),
+            claims: NonEmpty::new(ClaimId::from_bytes([0x11; 32])),
+            write: false,
+            window: None,
+        };
+        let chained = serde_json::to_vec(&GrantRecord::Chained {
+            cap: cap.clone(),
+            chain: NonEmpty::new(cid),
+            ticket: ticket().to_string(),
+        })
+        .expect("serializable");
+        let record = decode_grant_record(&chained).expect("a chained record must decode");
+        assert!(!record.is_delegated());
+        let (decoded, chains, _ticket) = record.into_parts();
+        assert_eq!(decoded, cap);
+        assert_eq!(chains, vec![NonEmpty::new(cid)]);
+
+        // A delegated record keeps one chain per claim, issuer's token
+        // first.
+        let mut chain = NonEmpty::new(cid);
+        chain.push(CapabilityCid::from_bytes([0x5d; 32]));
+        let delegated = serde_json::to_vec(&GrantRecord::Delegated {
+            cap: cap.clone(),
+            chains: NonEmpty::new(chain.clone()),
+            ticket: ticket().to_string(),
+        })
+        .expect("serializable");
+        let record = decode_grant_record(&delegated).expect("a delegated record must decode");
+        assert!(record.is_delegated());
+        assert_eq!(record.into_parts().1, vec![chain]);
+
+        let mut value: serde_json::Value = serde_json::from_slice(&chained).expect("json");
+        value["chain"] = serde_json::json!([]);
+        assert!(
+            decode_grant_record(&serde_json::to_vec(&value).expect("json")).is_none(),
+            "a chained record without tokens must read as absent"
+        );
+
+        assert_eq!(revoked_cid_of(revocation_key(&cid).as_bytes()), Some(cid));
+        assert_eq!(revoked_cid_of(grant_key(&cap.issuer).as_bytes()), None);
+    }
+
+    /// A windowed grant decodes with its window; a window on a record of
+    /// another kind — what a build that does not know windows would serve
+    /// forever — and a windowed record without one read as absent.
+    #[test]
+    fn windowed_grant_records_carry_their_window() {
+        let window = ValidityWindow { nbf: 10, exp: 20 };
+        let cap = ReadGrant {
+            issuer: PdnId::from_bytes([0xa1; 32]),
+            audience: PdnId::from_bytes([0xb0; 32]),
+            claims: NonEmpty::new(ClaimId::from_bytes([0x11; 32])),
+            write: false,
+            window: Some(window),
+        };
+        let scoped = GrantRecord::Scoped {
+            cap: cap.clone(),
+            ticket: ticket().to_string(),
+        };
+        let windowed = serde_json::to_vec(&GrantRecord::Windowed {
+            record: Box::new(scoped.clone()),
+        })
+        .expect("serializable");
+        let record = decode_grant_record(&windowed).expect("a windowed record must decode");
+        assert!(!record.is_delegated());
+        let (decoded, _chains, _ticket) = record.into_parts();
+        assert_eq!(decoded.window, Some(window));
+        assert!(!decoded.holds_at(9) && decoded.holds_at(10) && !decoded.holds_at(20));
+
+        let unwrapped = serde_json::to_vec(&scoped).expect("serializable");
+        assert!(
+            decode_grant_record(&unwrapped).is_none(),
+            "a window outside a windowed record must read as absent"
+        );
+        let open = GrantRecord::Windowed {
+            record: Box::new(GrantRecord::Scoped {
+                cap: ReadGrant {
+                    window: None,
+                    ..cap
+                },
+                ticket: ticket().to_string(),
+            }),
+        };
+        assert!(
+            decode_grant_record(&serde_json::to_vec(&open).expect("serializable")).is_none(),
+            "a windowed record without a window must read as absent"
+        );
+    }
 }
@@ -0,0 +1,75 @@
+//! [`NodeError`]: what the public operations of the [`SyncNode`] and of its
+//! stores fail with — each refusal a caller can act on as its own variant,
+//! everything else as the backend's own failure.
+//!
+//! [`SyncNode`]: crate::SyncNode
+
+use crate::node::{AlpnTaken, EntriesMissed, UnknownIssuer};
+use crate::private_metadata::CatchUpTimeout;
+
+/// Error returned by the public operations of [`SyncNode`](crate::SyncNode),
+/// [`PrivateMetadataStore`](crate::PrivateMetadataStore) and
+/// [`ConnectionMetadataStore`](crate::ConnectionMetadataStore).
+#[derive(Debug, thiserror::Error)]
+pub enum NodeError {
+    /// The operation addressed a data namespace this node does not host.
+    #[error(transparent)]
+    UnknownIssuer(#[from] UnknownIssuer),
+
+    /// A bounded catch-up wait elapsed with no sync session completed.
+    #[error(transparent)]
+    Timeout(#[from] CatchUpTimeout),
+
+    /// A spawn was refused an extra protocol: its ALPN is taken.
+    #[error(transparent)]
+    AlpnTaken(#[from] AlpnTaken),
+
+    /// A payload over the node's maximum
+    /// ([`SpawnOptions::max_payload`](crate::SpawnOptions::max_payload)).
+    #[error("payload too large: {size} bytes (max {max})")]
+    PayloadTooLarge { size: usize, max: usize },
+
+    /// An entry watch fell behind the node's entry feed; it carries on.
+    #[error(transparent)]
+    EntriesMissed(#[from] EntriesMissed),
+
+    /// The store, the network, or an undecodable record: nothing the caller
+    /// can act on by kind.
+    #[error(transparent)]
+    Storage(anyhow::Error),
+}
+
+impl NodeError {
+    /// A failure of the store or the network beneath an operation — sorted
+    /// as any other, so a refusal it carries keeps its variant.
+    pub(crate) fn storage(err: impl Into<anyhow::Error>) -> Self {
+        Self::from(err.into())
+    }
+}
+
+impl From<anyhow::Error> for NodeError {
+    /// Sort an internal failure: the refusals raised inside the node keep
+    /// their variants, anything else is [`Storage`](Self::Storage).
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Self>() {
+            Ok(node) => return node,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<UnknownIssuer>() {
+            Ok(refusal) => return refusal.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<CatchUpTimeout>() {
+            Ok(refusal) => return refusal.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<AlpnTaken>() {
+            Ok(refusal) => return refusal.into(),
+            Err(err) => err,
+        };
+        match err.downcast::<EntriesMissed>() {
+            Ok(missed) => missed.into(),
+            Err(err) => Self::Storage(err),
+        }
+    }
+}
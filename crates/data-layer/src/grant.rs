@@ -1,11 +1,13 @@
 //! The minimal read grant: one issuer grants one audience read (optionally
-//! write) on an exact set of claims. No delegation chain, no revocation
-//! cryptography, no token encoding. A serving node trusts only its own
-//! recorded copy of a grant, never one presented over the wire.
+//! write) on an exact set of claims, optionally within a validity window.
+//! The grant itself carries no token: the `UWill` chains a grant is made
+//! under ride beside it in the connection metadata store, opaque at this
+//! layer. A serving node trusts only its own recorded copy of a grant,
+//! never one presented over the wire.
 
 use std::sync::OnceLock;
 
-use pdn_types::{ClaimId, EntryPath, NonEmpty, PdnId};
+use pdn_types::{ClaimId, EntryPath, NonEmpty, PdnId, ValidityWindow};
 use serde::{Deserialize, Serialize};
 
 /// Domain-separation context for the claim-identity derivation, versioned
@@ -57,7 +59,7 @@ pub(crate) fn claim_id_of_key(issuer: &PdnId, key: &[u8]) -> ClaimId {
 ///
 /// Read is always granted (it is all the egress filter consumes); write is
 /// optional and changes only which ticket the grant ships (`ShareMode`) —
-/// with no ingest hook installed (ADR-0008), the write side is carried by
+/// with no write check at ingest (ADR-0008), the write side is carried by
 /// the namespace secret and is effectively whole-store.
 ///
 /// Serialized as JSON inside the grant record of the connection metadata
@@ -74,6 +76,11 @@ pub struct ReadGrant {
     pub claims: NonEmpty<ClaimId>,
     /// Whether write is granted alongside read.
     pub write: bool,
+    /// When the grant holds: from `nbf` until `exp` (unix ms), and at no
+    /// other time — or always, when `None`. Omitted from the payload when
+    /// `None`, so an open-ended grant encodes as it always did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<ValidityWindow>,
 }
 
 impl ReadGrant {
@@ -83,6 +90,12 @@ impl ReadGrant {
     pub fn covers(&self, path: &EntryPath) -> bool {
         self.claims.contains(&claim_id_of(&self.issuer, path))
     }
+
+    /// Whether the grant holds at `now` (unix ms): inside its window, or
+    /// always without one.
+    pub fn holds_at(&self, now: u64) -> bool {
+        self.window.is_none_or(|window| window.contains(now))
+    }
 }
 
 #[cfg(test)]
@@ -124,6 +137,7 @@ mod tests {
             audience,
             claims: NonEmpty::new(claim_id_of(&issuer, &email)),
             write: false,
+            window: None,
         };
         assert!(grant.covers(&email));
         assert!(!grant.covers(&path("contact/phone")));
@@ -143,6 +157,7 @@ mod tests {
             audience: PdnId::from_bytes([0xb0; 32]),
             claims: NonEmpty::new(claim_id_of(&issuer, &path("contact/email"))),
             write: true,
+            window: None,
         };
         let json = serde_json::to_string(&grant).unwrap();
         let back: ReadGrant = serde_json::from_str(&json).unwrap();
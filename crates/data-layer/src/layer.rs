@@ -1,8 +1,14 @@
 //! The [`DataLayer`] trait: the entries-only contract the node runtime
-//! drives to read and write replicated entries.
+//! drives to read and write replicated entries — and [`NodeDataLayer`],
+//! the [`SyncNode`] backend implementing it.
 
 use futures_core::Stream;
-use pdn_types::{EntryInfo, EntryPath, PdnId};
+use futures_lite::{stream, StreamExt};
+use pdn_store::AuthorId;
+use pdn_types::{EntryEvent, EntryInfo, EntryPath, PdnId};
+
+use crate::error::NodeError;
+use crate::node::{EntriesMissed, SyncNode, UnknownIssuer};
 
 /// Error returned by [`DataLayer`] operations.
 #[derive(Debug, thiserror::Error)]
@@ -11,10 +17,19 @@ pub enum DataLayerError {
     #[error("local node is not authorized to write as {issued_by}")]
     NotAuthorizedToWrite { issued_by: PdnId },
 
+    /// The backend holds no data namespace of `issuer`.
+    #[error("data namespace not bound on this node: {issuer}")]
+    UnknownIssuer { issuer: PdnId },
+
     /// Payload exceeds the backend's maximum payload size.
     #[error("payload too large: {size} bytes (max {max})")]
     PayloadTooLarge { size: usize, max: usize },
 
+    /// A watch fell behind and `missed` events were dropped for it; the
+    /// watch carries on, and what it mirrors should be re-listed.
+    #[error("entry watch fell behind: {missed} events missed")]
+    Lagged { missed: u64 },
+
     /// Underlying storage/sync backend reported an error.
     #[error("storage backend error")]
     Storage(#[source] Box<dyn std::error::Error + Send + Sync>),
@@ -50,6 +65,22 @@ pub trait DataLayer: Send + Sync {
         path: &EntryPath,
     ) -> Result<Option<Vec<u8>>, DataLayerError>;
 
+    /// Delete the entry at `path` in the data namespace of `issuer`, as a
+    /// tombstone that replicates to the issuer's devices and to every
+    /// grantee whose grant covers `path`. Returns whether a live entry was
+    /// there.
+    async fn delete_entry(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, DataLayerError>;
+
+    /// Delete every live entry in the data namespace of `issuer` whose
+    /// `path` starts with `path_prefix` — one tombstone per entry, each
+    /// replicating as [`delete_entry`](Self::delete_entry)'s does. Returns
+    /// how many were deleted.
+    async fn delete_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: &EntryPath,
+    ) -> Result<usize, DataLayerError>;
+
     /// Stream type yielding entry metadata for [`list_entries`](Self::list_entries).
     type EntryStream: Stream<Item = Result<EntryInfo, DataLayerError>> + Send + Unpin + 'static;
 
@@ -64,4 +95,117 @@ pub trait DataLayer: Send + Sync {
         issuer: PdnId,
         path_prefix: Option<&EntryPath>,
     ) -> Result<Self::EntryStream, DataLayerError>;
+
+    /// Stream type yielding changes for [`watch_entries`](Self::watch_entries).
+    type EventStream: Stream<Item = Result<EntryEvent, DataLayerError>> + Send + Unpin + 'static;
+
+    /// Watch the data namespace of `issuer` from now on, optionally
+    /// narrowed to entries whose `path` starts with `path_prefix`: each
+    /// insert, update and deletion, local or arrived by sync, and each
+    /// payload becoming readable after its record.
+    ///
+    /// Falling behind yields [`DataLayerError::Lagged`] and the stream
+    /// carries on; it ends when the backend shuts down.
+    async fn watch_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EventStream, DataLayerError>;
+}
+
+/// A [`SyncNode`] as a [`DataLayer`], its writes made as one author
+/// ([`SyncNode::data_layer`]). A borrowed handle: cheap to make per call.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeDataLayer<'n> {
+    node: &'n SyncNode,
+    author: AuthorId,
+}
+
+impl<'n> NodeDataLayer<'n> {
+    pub(crate) fn new(node: &'n SyncNode, author: AuthorId) -> Self {
+        Self { node, author }
+    }
+}
+
+impl DataLayer for NodeDataLayer<'_> {
+    async fn insert_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+        payload: &[u8],
+    ) -> Result<(), DataLayerError> {
+        self.node
+            .write(issuer, self.author, path, payload)
+            .await
+            .map_err(from_node)
+    }
+
+    async fn get_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+    ) -> Result<Option<Vec<u8>>, DataLayerError> {
+        self.node.read(issuer, path).await.map_err(from_node)
+    }
+
+    type EntryStream = stream::Boxed<Result<EntryInfo, DataLayerError>>;
+
+    async fn list_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EntryStream, DataLayerError> {
+        let listing = self
+            .node
+            .list_stream(issuer, path_prefix)
+            .await
+            .map_err(from_node)?;
+        Ok(listing.map(|entry| entry.map_err(from_node)).boxed())
+    }
+
+    async fn delete_entry(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, DataLayerError> {
+        self.node
+            .delete(issuer, self.author, path)
+            .await
+            .map_err(from_node)
+    }
+
+    async fn delete_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: &EntryPath,
+    ) -> Result<usize, DataLayerError> {
+        self.node
+            .delete_prefix(issuer, self.author, path_prefix)
+            .await
+            .map_err(from_node)
+    }
+
+    type EventStream = stream::Boxed<Result<EntryEvent, DataLayerError>>;
+
+    async fn watch_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EventStream, DataLayerError> {
+        let watch = self
+            .node
+            .watch_entries(issuer, path_prefix)
+            .map_err(from_node)?;
+        Ok(watch.map(|event| event.map_err(from_node)).boxed())
+    }
+}
+
+/// Sort a node error into the trait's: the failures [`DataLayerError`]
+/// names by variant keep them, anything else is the backend's own.
+fn from_node(err: NodeError) -> DataLayerError {
+    match err {
+        NodeError::UnknownIssuer(UnknownIssuer { issuer }) => {
+            DataLayerError::UnknownIssuer { issuer }
+        }
+        NodeError::EntriesMissed(EntriesMissed { missed }) => DataLayerError::Lagged { missed },
+        NodeError::PayloadTooLarge { size, max } => DataLayerError::PayloadTooLarge { size, max },
+        NodeError::Storage(err) => DataLayerError::Storage(err.into()),
+        other => DataLayerError::Storage(other.into()),
+    }
 }
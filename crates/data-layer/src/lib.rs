@@ -1,8 +1,9 @@
 //! The data layer: document sync over pdn-store, our iroh-docs fork.
 //!
 //! Everything platform-specific around the fork lives here, so the fork
-//! itself stays iroh-native and minimal. The fork's ingest filter (the
-//! `validate_entry` hook, ADR-0008) is not installed. Every reconciliation
+//! itself stays iroh-native and minimal. The fork's ingest hook
+//! (`validate_entry`, ADR-0008) enforces the payload ceiling only; it
+//! checks no write authority. Every reconciliation
 //! session is classified by the access book (`access`, internal) — full
 //! view for a replica identity's own devices, a capability-filtered view
 //! for granted counterparties and for the devices of a grant's audience
@@ -14,7 +15,9 @@
 //! This crate owns:
 //!
 //! - [`layer`] — the entries-only [`DataLayer`] trait the node runtime
-//!   drives;
+//!   drives, and [`NodeDataLayer`], the sync node behind it;
+//! - `mem` (feature `mem`) — an in-memory `DataLayer` over simulated
+//!   devices, replication stepped by hand, for application tests;
 //! - [`private_metadata`] — the device-replicated [`PrivateMetadataStore`]:
 //!   the one directory of an identity's own state — its devices, the tickets
 //!   to its other stores, and its connections;
@@ -32,12 +35,17 @@
 //! Capability *semantics* (`UWill` tokens, chains) do not live here: at this
 //! level tokens are opaque payloads.
 //!
-//! Errors are `anyhow`.
+//! The node and its stores fail with [`NodeError`]: each refusal a caller
+//! can act on has its own variant, and the backend's own failures are
+//! [`NodeError::Storage`].
 
 mod access;
 pub mod connection_metadata;
+mod error;
 pub mod grant;
 pub mod layer;
+#[cfg(feature = "mem")]
+pub mod mem;
 pub mod node;
 pub mod private_metadata;
 mod registry;
@@ -45,13 +53,18 @@ mod registry;
 pub use connection_metadata::{
     own_ticket_kind, peer_ticket_kind, ConnectionMetadata, ConnectionMetadataStore,
 };
+pub use error::NodeError;
 pub use grant::{claim_id_of, ReadGrant};
-pub use layer::{DataLayer, DataLayerError};
+pub use layer::{DataLayer, DataLayerError, NodeDataLayer};
+#[cfg(feature = "mem")]
+pub use mem::{MemDataLayer, MemNetwork};
 pub use node::{
-    AlpnTaken, DialHandle, ExtraProtocol, NamespaceImport, SpawnOptions, SyncNode, UnknownIssuer,
-    BUILT_IN_ALPNS,
+    AlpnTaken, DialHandle, EntriesMissed, EntryListing, EntryWatch, ExtraProtocol, NamespaceImport,
+    SpawnOptions, SyncNode, UnknownIssuer, BUILT_IN_ALPNS,
+};
+pub use private_metadata::{
+    unix_ms, CatchUpTimeout, DeviceRecord, PrivateMetadataStore, Successor,
 };
-pub use private_metadata::{CatchUpTimeout, PrivateMetadataStore};
 
 // Re-exported pdn-store (iroh-docs fork) vocabulary for the common
 // share/import/write flows, so downstream crates don't need a direct
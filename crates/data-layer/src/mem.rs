@@ -0,0 +1,543 @@
+//! An in-memory [`DataLayer`] for application tests: simulated devices and
+//! grantees on one [`MemNetwork`], with replication stepped by hand.
+//!
+//! No endpoint, no store, no wall clock: each device keeps its replicas as
+//! maps, a write stamps its record from the network's logical clock, and
+//! records move only when the test calls [`MemNetwork::step`] — so a test
+//! decides exactly what has replicated when, and runs in microseconds. A
+//! grant's validity window is read against the network's time, which only
+//! [`MemNetwork::set_time`] moves.
+//! Records and payloads travel separately, as they do over sync: a step
+//! delivers records, and [`MemNetwork::deliver_payloads`] makes their
+//! payloads readable.
+//!
+//! Reach follows the production rules: an identity's devices receive all of
+//! its namespace; an audience's devices receive exactly the entries a
+//! [`ReadGrant`] [covers](ReadGrant::covers) while it
+//! [holds](ReadGrant::holds_at), tombstones included, and only a write
+//! grant lets them write. Newer wins, by stamp. Behind the `mem`
+//! feature.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::Result;
+use futures_lite::{stream, StreamExt};
+use pdn_types::{EntryEvent, EntryEventKind, EntryInfo, EntryOrigin, EntryPath, NodeId, PdnId};
+use tokio::sync::broadcast;
+
+use crate::grant::ReadGrant;
+use crate::layer::{DataLayer, DataLayerError};
+
+/// How many events a device's feed buffers per watch before a slow one
+/// lags.
+const FEED_CAPACITY: usize = 1024;
+
+/// The simulated network: its devices, the grants in force, and the clock
+/// records are stamped from. Cloning shares it.
+#[derive(Clone, Default)]
+pub struct MemNetwork {
+    world: Arc<Mutex<World>>,
+}
+
+#[derive(Default)]
+struct World {
+    /// The logical clock: every record, and every device id, takes the
+    /// next tick.
+    clock: u64,
+    /// The time grant windows are read against, unix ms: set by the test,
+    /// never read off a wall clock.
+    now: u64,
+    devices: Vec<Device>,
+    /// The grants in force, at most one per issuer and audience.
+    grants: Vec<ReadGrant>,
+}
+
+struct Device {
+    node: NodeId,
+    identity: PdnId,
+    /// Offline, a device neither sends nor receives in a step.
+    online: bool,
+    /// Replicas by issuer: the device's own identity's, and one for each
+    /// issuer that granted its identity.
+    replicas: HashMap<PdnId, BTreeMap<EntryPath, Record>>,
+    feed: broadcast::Sender<EntryEvent>,
+}
+
+/// One path's latest record on one device.
+#[derive(Clone)]
+struct Record {
+    stamp: u64,
+    /// Empty for a tombstone.
+    payload: Vec<u8>,
+    /// Whether the payload has arrived here.
+    readable: bool,
+    /// Where this device got the record from.
+    origin: EntryOrigin,
+}
+
+impl Record {
+    fn is_live(&self) -> bool {
+        !self.payload.is_empty()
+    }
+}
+
+impl Device {
+    /// Take `record` at `path` into `issuer`'s replica if it is newer than
+    /// what is held there, and report the change. Answers whether it was
+    /// taken.
+    fn take(&mut self, issuer: PdnId, path: &EntryPath, record: Record) -> bool {
+        let Some(replica) = self.replicas.get_mut(&issuer) else {
+            return false;
+        };
+        let held = replica.get(path);
+        if held.is_some_and(|held| held.stamp >= record.stamp) {
+            return false;
+        }
+        let kind = if !record.is_live() {
+            EntryEventKind::Deleted
+        } else if held.is_some_and(Record::is_live) {
+            EntryEventKind::Updated
+        } else {
+            EntryEventKind::Inserted
+        };
+        let info = EntryInfo {
+            issuer,
+            path: path.clone(),
+            payload_len: payload_len(&record.payload),
+        };
+        let (origin, ready) = (record.origin, record.readable && record.is_live());
+        replica.insert(path.clone(), record);
+        // No watch is not a failure: the feed is fire-and-forget.
+        let _unobserved = self.feed.send(EntryEvent {
+            info: info.clone(),
+            kind,
+            origin,
+        });
+        if ready {
+            let _unobserved = self.feed.send(EntryEvent {
+                info,
+                kind: EntryEventKind::PayloadReady,
+                origin,
+            });
+        }
+        true
+    }
+}
+
+/// The grants `issuer` made toward `identity` that hold at `now`.
+fn grants_at<'a>(
+    grants: &'a [ReadGrant],
+    now: u64,
+    identity: PdnId,
+    issuer: PdnId,
+) -> impl Iterator<Item = &'a ReadGrant> {
+    grants.iter().filter(move |grant| {
+        grant.issuer == issuer && grant.audience == identity && grant.holds_at(now)
+    })
+}
+
+/// Whether a device of `identity` may hold the entry at `path` of
+/// `issuer` at `now`: all of its own namespace, what a grant in force
+/// covers of another's.
+fn may_hold(
+    grants: &[ReadGrant],
+    now: u64,
+    identity: PdnId,
+    issuer: PdnId,
+    path: &EntryPath,
+) -> bool {
+    identity == issuer || grants_at(grants, now, identity, issuer).any(|grant| grant.covers(path))
+}
+
+/// Whether a device of `identity` may write into `issuer`'s namespace at
+/// `now`.
+fn may_write(grants: &[ReadGrant], now: u64, identity: PdnId, issuer: PdnId) -> bool {
+    identity == issuer || grants_at(grants, now, identity, issuer).any(|grant| grant.write)
+}
+
+fn payload_len(payload: &[u8]) -> u64 {
+    u64::try_from(payload.len()).unwrap_or(u64::MAX)
+}
+
+impl MemNetwork {
+    /// An empty network.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn world(&self) -> Result<MutexGuard<'_, World>> {
+        self.world
+            .lock()
+            .map_err(|_poisoned| anyhow::anyhow!("mem network lock poisoned"))
+    }
+
+    /// Add an online device of `identity`, holding its identity's namespace
+    /// and every namespace granted to it — empty until a step brings in
+    /// what its siblings and grantors hold.
+    pub fn device(&self, identity: PdnId) -> Result<MemDataLayer> {
+        let mut world = self.world()?;
+        world.clock += 1;
+        let mut id = [0u8; 32];
+        for (slot, byte) in id.iter_mut().zip(world.clock.to_le_bytes()) {
+            *slot = byte;
+        }
+        let node = NodeId::from_bytes(id);
+        let mut replicas = HashMap::from([(identity, BTreeMap::new())]);
+        for grant in world
+            .grants
+            .iter()
+            .filter(|grant| grant.audience == identity)
+        {
+            replicas.entry(grant.issuer).or_default();
+        }
+        let index = world.devices.len();
+        world.devices.push(Device {
+            node,
+            identity,
+            online: true,
+            replicas,
+            feed: broadcast::channel(FEED_CAPACITY).0,
+        });
+        Ok(MemDataLayer {
+            world: Arc::clone(&self.world),
+            index,
+            node,
+            identity,
+        })
+    }
+
+    /// Put `grant` in force, replacing the one its issuer held toward the
+    /// same audience: the audience's devices now hold the issuer's
+    /// namespace, and the next step fills it with what the grant covers.
+    pub fn grant(&self, grant: ReadGrant) -> Result<()> {
+        let mut world = self.world()?;
+        world
+            .grants
+            .retain(|held| (held.issuer, held.audience) != (grant.issuer, grant.audience));
+        for device in world
+            .devices
+            .iter_mut()
+            .filter(|device| device.identity == grant.audience)
+        {
+            device.replicas.entry(grant.issuer).or_default();
+        }
+        world.grants.push(grant);
+        Ok(())
+    }
+
+    /// Set the network's time to `now` (unix ms): grants hold, for every
+    /// later step and write, as their windows hold at it.
+    pub fn set_time(&self, now: u64) -> Result<()> {
+        self.world()?.now = now;
+        Ok(())
+    }
+
+    /// Withdraw `issuer`'s grant to `audience`. Steps deliver nothing more
+    /// under it; what was delivered stays where it is, as it does over
+    /// sync.
+    pub fn withdraw_grant(&self, issuer: PdnId, audience: PdnId) -> Result<()> {
+        self.world()?
+            .grants
+            .retain(|held| (held.issuer, held.audience) != (issuer, audience));
+        Ok(())
+    }
+
+    /// One replication round: each online device takes, from every other
+    /// online device, the records it may hold that are newer than its own —
+    /// payloads left in transit. Answers how many records moved; zero means
+    /// the online devices agree.
+    pub fn step(&self) -> Result<usize> {
+        let mut world = self.world()?;
+        let World {
+            devices,
+            grants,
+            now,
+            ..
+        } = &mut *world;
+        let mut deliveries = Vec::new();
+        for (target_index, target) in devices.iter().enumerate() {
+            for source in &*devices {
+                if source.node == target.node || !source.online || !target.online {
+                    continue;
+                }
+                for (issuer, replica) in &source.replicas {
+                    let Some(held) = target.replicas.get(issuer) else {
+                        continue;
+                    };
+                    for (path, record) in replica {
+                        if !may_hold(grants, *now, target.identity, *issuer, path)
+                            || held
+                                .get(path)
+                                .is_some_and(|mine| mine.stamp >= record.stamp)
+                        {
+                            continue;
+                        }
+                        let delivered = Record {
+                            readable: false,
+                            origin: EntryOrigin::Remote(source.node),
+                            ..record.clone()
+                        };
+                        deliveries.push((target_index, *issuer, path.clone(), delivered));
+                    }
+                }
+            }
+        }
+        let mut moved = 0;
+        for (index, issuer, path, record) in deliveries {
+            if let Some(device) = devices.get_mut(index) {
+                if device.take(issuer, &path, record) {
+                    moved += 1;
+                }
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Make every payload in transit to an online device readable there,
+    /// reporting each. Answers how many arrived.
+    pub fn deliver_payloads(&self) -> Result<usize> {
+        let mut world = self.world()?;
+        let mut arrived = 0;
+        for device in world.devices.iter_mut().filter(|device| device.online) {
+            let Device { replicas, feed, .. } = device;
+            for (issuer, replica) in replicas {
+                for (path, record) in replica {
+                    if record.readable || !record.is_live() {
+                        continue;
+                    }
+                    record.readable = true;
+                    arrived += 1;
+                    let _unobserved = feed.send(EntryEvent {
+                        info: EntryInfo {
+                            issuer: *issuer,
+                            path: path.clone(),
+                            payload_len: payload_len(&record.payload),
+                        },
+                        kind: EntryEventKind::PayloadReady,
+                        origin: record.origin,
+                    });
+                }
+            }
+        }
+        Ok(arrived)
+    }
+
+    /// Step until the online devices agree, then deliver every payload.
+    pub fn settle(&self) -> Result<()> {
+        while self.step()? > 0 {}
+        self.deliver_payloads()?;
+        Ok(())
+    }
+}
+
+/// One simulated device on a [`MemNetwork`], as a [`DataLayer`]. Cloning
+/// gives another handle onto the same device.
+#[derive(Clone)]
+pub struct MemDataLayer {
+    world: Arc<Mutex<World>>,
+    index: usize,
+    node: NodeId,
+    identity: PdnId,
+}
+
+impl MemDataLayer {
+    /// The device's id — the origin its records carry elsewhere.
+    pub fn node_id(&self) -> NodeId {
+        self.node
+    }
+
+    /// The identity whose device this is.
+    pub fn identity(&self) -> PdnId {
+        self.identity
+    }
+
+    /// Take the device off the network, or bring it back. Offline, its own
+    /// reads and writes carry on; steps pass it by.
+    pub fn set_online(&self, online: bool) -> Result<()> {
+        let mut world = self
+            .world
+            .lock()
+            .map_err(|_poisoned| anyhow::anyhow!("mem network lock poisoned"))?;
+        if let Some(device) = world.devices.get_mut(self.index) {
+            device.online = online;
+        }
+        Ok(())
+    }
+
+    fn world(&self) -> Result<MutexGuard<'_, World>, DataLayerError> {
+        self.world
+            .lock()
+            .map_err(|_poisoned| DataLayerError::Storage("mem network lock poisoned".into()))
+    }
+
+    /// Run `op` on this device's replica of `issuer`, or refuse with
+    /// [`DataLayerError::UnknownIssuer`] when it holds none.
+    fn read_replica<T>(
+        &self,
+        issuer: PdnId,
+        op: impl FnOnce(&BTreeMap<EntryPath, Record>) -> T,
+    ) -> Result<T, DataLayerError> {
+        let world = self.world()?;
+        world
+            .devices
+            .get(self.index)
+            .and_then(|device| device.replicas.get(&issuer))
+            .map(op)
+            .ok_or(DataLayerError::UnknownIssuer { issuer })
+    }
+
+    /// Write `payload` — a tombstone when empty — at `path` under `issuer`,
+    /// stamped with the next tick.
+    fn put(&self, issuer: PdnId, path: &EntryPath, payload: Vec<u8>) -> Result<(), DataLayerError> {
+        let mut world = self.world()?;
+        let World {
+            clock,
+            now,
+            devices,
+            grants,
+        } = &mut *world;
+        let device = devices
+            .get_mut(self.index)
+            .ok_or(DataLayerError::UnknownIssuer { issuer })?;
+        if !device.replicas.contains_key(&issuer) {
+            return Err(DataLayerError::UnknownIssuer { issuer });
+        }
+        if !may_write(grants, *now, device.identity, issuer) {
+            return Err(DataLayerError::NotAuthorizedToWrite { issued_by: issuer });
+        }
+        *clock += 1;
+        device.take(
+            issuer,
+            path,
+            Record {
+                stamp: *clock,
+                payload,
+                readable: true,
+                origin: EntryOrigin::Local,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl DataLayer for MemDataLayer {
+    async fn insert_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+        payload: &[u8],
+    ) -> Result<(), DataLayerError> {
+        if payload.is_empty() {
+            // As in the store: an empty record is the deletion marker.
+            return Err(DataLayerError::Storage(
+                "an empty payload is not a storable value".into(),
+            ));
+        }
+        self.put(issuer, path, payload.to_vec())
+    }
+
+    async fn get_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+    ) -> Result<Option<Vec<u8>>, DataLayerError> {
+        self.read_replica(issuer, |replica| {
+            replica
+                .get(path)
+                .filter(|record| record.is_live() && record.readable)
+                .map(|record| record.payload.clone())
+        })
+    }
+
+    type EntryStream = stream::Boxed<Result<EntryInfo, DataLayerError>>;
+
+    async fn list_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EntryStream, DataLayerError> {
+        let entries = self.read_replica(issuer, |replica| {
+            replica
+                .iter()
+                .filter(|(path, record)| {
+                    record.is_live() && path_prefix.is_none_or(|prefix| path.starts_with(prefix))
+                })
+                .map(|(path, record)| {
+                    Ok(EntryInfo {
+                        issuer,
+                        path: path.clone(),
+                        payload_len: payload_len(&record.payload),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })?;
+        Ok(stream::iter(entries).boxed())
+    }
+
+    async fn delete_entry(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, DataLayerError> {
+        let live = self.read_replica(issuer, |replica| {
+            replica.get(path).is_some_and(Record::is_live)
+        })?;
+        if live {
+            self.put(issuer, path, Vec::new())?;
+        }
+        Ok(live)
+    }
+
+    async fn delete_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: &EntryPath,
+    ) -> Result<usize, DataLayerError> {
+        let paths = self.read_replica(issuer, |replica| {
+            replica
+                .iter()
+                .filter(|(path, record)| record.is_live() && path.starts_with(path_prefix))
+                .map(|(path, _record)| path.clone())
+                .collect::<Vec<_>>()
+        })?;
+        for path in &paths {
+            self.put(issuer, path, Vec::new())?;
+        }
+        Ok(paths.len())
+    }
+
+    type EventStream = stream::Boxed<Result<EntryEvent, DataLayerError>>;
+
+    async fn watch_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EventStream, DataLayerError> {
+        let feed = {
+            let world = self.world()?;
+            world
+                .devices
+                .get(self.index)
+                .filter(|device| device.replicas.contains_key(&issuer))
+                .map(|device| device.feed.subscribe())
+                .ok_or(DataLayerError::UnknownIssuer { issuer })?
+        };
+        let prefix = path_prefix.cloned();
+        let watch = stream::unfold((feed, prefix), move |(mut feed, prefix)| async move {
+            loop {
+                match feed.recv().await {
+                    Ok(event) => {
+                        let under = prefix
+                            .as_ref()
+                            .is_none_or(|prefix| event.info.path.starts_with(prefix));
+                        if event.info.issuer == issuer && under {
+                            return Some((Ok(event), (feed, prefix)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        return Some((Err(DataLayerError::Lagged { missed }), (feed, prefix)));
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(watch.boxed())
+    }
+}
@@ -5,41 +5,50 @@
 //! point is protocol-agnostic: the ceremonies' semantics live in pdn-node.
 
 use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
 use std::net::IpAddr;
 use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use futures_lite::{FutureExt, StreamExt};
+use futures_lite::{stream, FutureExt, StreamExt};
 use iroh::{
     endpoint::{presets, Connection},
     protocol::{AcceptError, DynProtocolHandler, ProtocolHandler, Router},
-    Endpoint, EndpointAddr, EndpointId, Watcher as _,
+    Endpoint, EndpointAddr, EndpointId, SecretKey, Watcher as _,
+};
+use iroh_blobs::{
+    store::{fs::FsStore, mem::MemStore},
+    BlobsProtocol, Hash, ALPN as BLOBS_ALPN,
 };
-use iroh_blobs::{store::mem::MemStore, BlobsProtocol, ALPN as BLOBS_ALPN};
 use iroh_gossip::{net::Gossip, ALPN as GOSSIP_ALPN};
 use pdn_store::{
     api::{
         protocol::{AddrInfoOptions, ShareMode},
         Doc, DocsApi,
     },
+    engine::LiveEvent,
     protocol::Docs,
     store::Query,
-    AuthorId, DocTicket, NamespaceId, ALPN as DOCS_ALPN,
+    AuthorId, ContentStatus, DocTicket, EntryFilter, NamespaceId, ALPN as DOCS_ALPN,
 };
-use pdn_types::{EntryInfo, EntryPath, NodeId, PdnId};
-use tokio::sync::oneshot;
+use pdn_types::{EntryEvent, EntryEventKind, EntryInfo, EntryOrigin, EntryPath, NodeId, PdnId};
+use rand::{rngs::SysRng, TryRng as _};
+use tokio::sync::{broadcast, oneshot};
 
 use crate::access::{session_access_provider, AccessBook};
 use crate::connection_metadata::ConnectionMetadataStore;
+use crate::error::NodeError;
+use crate::layer::NodeDataLayer;
 use crate::private_metadata::PrivateMetadataStore;
 use crate::registry::{Registry, ServingPosture};
 
 /// An operation addressed a data namespace this node does not host: `issuer`
-/// has no created or imported namespace here. Downcast from the
-/// `anyhow::Error` of [`SyncNode::read`] / [`SyncNode::write`] /
-/// [`SyncNode::share_ticket`].
+/// has no created or imported namespace here. Carried by
+/// [`NodeError::UnknownIssuer`] from [`SyncNode::read`] /
+/// [`SyncNode::write`] / [`SyncNode::delete`] / [`SyncNode::share_ticket`].
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 #[error("data namespace not bound on this node: {issuer}")]
 pub struct UnknownIssuer {
@@ -47,6 +56,23 @@ pub struct UnknownIssuer {
     pub issuer: PdnId,
 }
 
+/// A [`SyncNode::watch_entries`] watch fell behind the node's entry feed and
+/// `missed` events were dropped for it; it carries on from there, and what
+/// it mirrors should be re-listed. Carried by [`NodeError::EntriesMissed`]
+/// in the watch's error items.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("entry watch fell behind: {missed} events missed")]
+pub struct EntriesMissed {
+    /// How many events the watch did not see.
+    pub missed: u64,
+}
+
+/// The stream of a [`SyncNode::watch_entries`] watch.
+pub type EntryWatch = stream::Boxed<Result<EntryEvent, NodeError>>;
+
+/// The stream of a [`SyncNode::list_stream`] listing.
+pub type EntryListing = stream::Boxed<Result<EntryInfo, NodeError>>;
+
 /// A protocol supplied to [`SyncNode::spawn_with_protocols`]: the ALPN it
 /// answers under, and the handler dispatched for connections arriving on it.
 pub type ExtraProtocol = (Vec<u8>, Box<dyn DynProtocolHandler>);
@@ -58,7 +84,7 @@ pub const BUILT_IN_ALPNS: [&[u8]; 3] = [BLOBS_ALPN, GOSSIP_ALPN, DOCS_ALPN];
 
 /// A spawn was handed an extra protocol whose ALPN is already taken — by a
 /// built-in protocol ([`BUILT_IN_ALPNS`]) or by another extra in the same
-/// call. Downcast from the `anyhow::Error` of
+/// call. Carried by [`NodeError::AlpnTaken`] from
 /// [`SyncNode::spawn_with_protocols`].
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("protocol ALPN already taken: {}", String::from_utf8_lossy(.alpn))]
@@ -108,6 +134,23 @@ impl ProtocolHandler for PanicGuarded {
 /// them a replica whose initial exchange died would starve permanently.
 const RECONCILE_INTERVAL: Duration = Duration::from_secs(10);
 
+/// Under a storage root: the file holding the endpoint's secret key — the
+/// node's identity on the wire, so a restarted node answers to the same
+/// [`NodeId`] its peers recorded.
+const SECRET_KEY_FILE: &str = "endpoint.key";
+/// Under a storage root: the blob store's directory (entry payloads).
+const BLOBS_DIR: &str = "blobs";
+/// Under a storage root: the docs engine's directory (replicas, authors,
+/// recorded peers).
+const DOCS_DIR: &str = "docs";
+
+/// How many entry changes the node-wide feed buffers per subscriber before
+/// a slow one starts missing them ([`SyncNode::subscribe_entries`]).
+const ENTRY_FEED_CAPACITY: usize = 1024;
+
+/// Default largest entry payload a node stores or serves, in bytes.
+const MAX_PAYLOAD: usize = 1024 * 1024;
+
 /// Spawn-time tuning of the node stack ([`SyncNode::spawn_with`]).
 /// `Default` is the production posture.
 #[derive(Debug, Clone)]
@@ -115,17 +158,32 @@ pub struct SpawnOptions {
     /// How often the periodic reconcile pass re-requests a sync for every
     /// doc this node holds open (default [`RECONCILE_INTERVAL`]).
     pub reconcile_interval: Duration,
+    /// Root directory of the node's on-disk state: the blob store, the docs
+    /// engine, and the endpoint's secret key, each under its own entry.
+    /// `None` (the default) keeps everything in memory — the node's state,
+    /// its endpoint id included, ends with it. One root serves one node at
+    /// a time: the stores lock their files while open.
+    pub storage: Option<PathBuf>,
+    /// The largest entry payload, in bytes, the node writes, stores or
+    /// serves (default [`MAX_PAYLOAD`]). A larger local write is refused
+    /// with [`NodeError::PayloadTooLarge`]; a larger record arriving by
+    /// sync is refused at ingest, its payload never fetched. One a replica
+    /// already holds — stored before a respawn under a higher ceiling — is
+    /// quarantined: kept, but never read, listed, or reported as an entry.
+    pub max_payload: usize,
 }
 
 impl Default for SpawnOptions {
     fn default() -> Self {
         Self {
             reconcile_interval: RECONCILE_INTERVAL,
+            storage: None,
+            max_payload: MAX_PAYLOAD,
         }
     }
 }
 
-/// One running node: iroh endpoint, gossip, in-memory blob store, and the
+/// One running node: iroh endpoint, gossip, blob store, and the
 /// docs engine, with data replicas addressed by their issuer [`PdnId`] and
 /// entries by [`EntryPath`]s. One node hosts the store sets of any number of
 /// identities. Every doc the node opens joins a periodic reconcile pass
@@ -134,17 +192,26 @@ impl Default for SpawnOptions {
 /// their dial sides and the node's own address are reached through
 /// [`SyncNode::dial_handle`].
 ///
-/// No ingest filter is installed (the fork's `validate_entry` hook,
-/// ADR-0008, is unused): whatever a replica syncs from a peer holding its
-/// ticket is persisted. Every read session is classified through the node's
-/// access book — full for a replica identity's own devices and connection
+/// The fork's ingest hook (`validate_entry`, ADR-0008) enforces the
+/// payload ceiling and nothing else: a record over
+/// [`SpawnOptions::max_payload`] is refused before its payload is fetched,
+/// and whatever else a replica syncs from a peer holding its ticket is
+/// persisted — write authority is the namespace secret. Every read
+/// session is classified through the node's access book — full for a replica identity's own devices and connection
 /// audiences, capability-filtered for granted counterparties, refused as
 /// not-hosted otherwise. Enforcement arms per identity by registration
 /// ([`SyncNode::host_identity`] / [`SyncNode::host_connection`]) and per
 /// replica by [`SyncNode::import_namespace_scoped`]; a node that registers
 /// nothing serves any ticket holder the whole replica.
 ///
-/// Storage is in-memory.
+/// Storage is in-memory unless [`SpawnOptions::storage`] names a root: then
+/// the blob store, the docs engine, and the endpoint's secret key live
+/// there, and a respawn on the same root comes back as the same node with
+/// every replica it held. What the node *registered* — issuer bindings,
+/// hosted identities, connection pairs — is not persisted here: it is
+/// re-established by reopening ([`SyncNode::open_namespace`],
+/// [`PrivateMetadataStore::open`]) and re-registering, which is the
+/// caller's to drive.
 #[derive(Debug)]
 pub struct SyncNode {
     router: Router,
@@ -164,6 +231,15 @@ pub struct SyncNode {
     /// per namespace at a time, so a tight poll loop cannot pile up
     /// concurrent attempts against one replica.
     nudges_in_flight: Arc<Mutex<HashSet<NamespaceId>>>,
+    /// The node-wide entry feed: every change in a bound data namespace,
+    /// local or arrived by sync ([`subscribe_entries`](Self::subscribe_entries)).
+    entry_feed: broadcast::Sender<EntryEvent>,
+    /// Data replicas with an entry watcher feeding `entry_feed` — at most
+    /// one per namespace, however often the replica is (re)bound. A watcher
+    /// removes its namespace as it exits.
+    watched_docs: Arc<Mutex<HashSet<NamespaceId>>>,
+    /// The largest payload served ([`SpawnOptions::max_payload`]).
+    max_payload: usize,
     /// Ends the periodic reconcile pass when dropped — with the node — or by
     /// the explicit send in [`SyncNode::shutdown`].
     reconciler_stop: oneshot::Sender<()>,
@@ -213,6 +289,19 @@ pub struct NamespaceImport {
     displaced_tracking: Option<TrackedDoc>,
 }
 
+impl NamespaceImport {
+    /// The namespace the issuer was bound to before the import, if any and
+    /// if it is a different replica than the imported one — what a caller
+    /// moving the issuer onto a successor replica forgets once the move
+    /// has stuck.
+    pub fn displaced_namespace(&self) -> Option<NamespaceId> {
+        self.displaced
+            .as_ref()
+            .map(|binding| binding.doc.id())
+            .filter(|namespace| *namespace != self.imported)
+    }
+}
+
 /// The dial side of a node's protocols, handed out by
 /// [`SyncNode::dial_handle`]. Wraps the node's iroh endpoint but exposes
 /// only what a dial needs — connect out, read the node's own address and
@@ -227,8 +316,11 @@ impl DialHandle {
     /// Open a connection to `addr` under `alpn`, as the dial side of an
     /// extra protocol. The peer must serve `alpn` — a built-in protocol or
     /// an extra it registered at spawn — or the dial fails.
-    pub async fn connect(&self, addr: EndpointAddr, alpn: &[u8]) -> Result<Connection> {
-        Ok(self.endpoint.connect(addr, alpn).await?)
+    pub async fn connect(&self, addr: EndpointAddr, alpn: &[u8]) -> Result<Connection, NodeError> {
+        self.endpoint
+            .connect(addr, alpn)
+            .await
+            .map_err(NodeError::storage)
     }
 
     /// This node's own address — its wire id plus the paths peers can reach
@@ -248,13 +340,13 @@ impl DialHandle {
 impl SyncNode {
     /// Spawn the full stack with no externally supplied protocols and
     /// default [`SpawnOptions`].
-    pub async fn spawn() -> Result<Self> {
+    pub async fn spawn() -> Result<Self, NodeError> {
         Self::spawn_with(Vec::new(), SpawnOptions::default()).await
     }
 
     /// Spawn the full stack with no externally supplied protocols, tuned by
     /// `options`.
-    pub async fn spawn_with_options(options: SpawnOptions) -> Result<Self> {
+    pub async fn spawn_with_options(options: SpawnOptions) -> Result<Self, NodeError> {
         Self::spawn_with(Vec::new(), options).await
     }
 
@@ -268,7 +360,9 @@ impl SyncNode {
     /// A handler's `accept` should return `Err(AcceptError)` rather than
     /// panic: a panic is contained per connection, but a `panic = "abort"`
     /// build still aborts the process.
-    pub async fn spawn_with_protocols(extra_protocols: Vec<ExtraProtocol>) -> Result<Self> {
+    pub async fn spawn_with_protocols(
+        extra_protocols: Vec<ExtraProtocol>,
+    ) -> Result<Self, NodeError> {
         Self::spawn_with(extra_protocols, SpawnOptions::default()).await
     }
 
@@ -277,7 +371,7 @@ impl SyncNode {
     pub async fn spawn_with(
         extra_protocols: Vec<ExtraProtocol>,
         options: SpawnOptions,
-    ) -> Result<Self> {
+    ) -> Result<Self, NodeError> {
         // Checked before the endpoint binds: an extra silently replacing a
         // built-in handler would leave a node that looks alive and never
         // syncs.
@@ -288,8 +382,18 @@ impl SyncNode {
             }
         }
 
-        let endpoint = bind_endpoint().await?;
-        let blobs = MemStore::default();
+        let secret_key = match &options.storage {
+            Some(root) => Some(load_or_create_secret_key(root)?),
+            None => None,
+        };
+        let endpoint = bind_endpoint(secret_key).await?;
+        let blobs: iroh_blobs::api::Store = match &options.storage {
+            Some(root) => (*FsStore::load(root.join(BLOBS_DIR))
+                .await
+                .map_err(NodeError::storage)?)
+            .clone(),
+            None => (*MemStore::default()).clone(),
+        };
         let gossip = Gossip::builder().spawn(endpoint.clone());
 
         // The access book and registry exist before the engine so the
@@ -297,16 +401,21 @@ impl SyncNode {
         // set right after the spawn, before any session can arrive.
         let registry = Arc::new(Registry::default());
         let access = Arc::new(AccessBook::default());
-        let docs = Docs::memory()
+        let docs = match &options.storage {
+            Some(root) => Docs::persistent(root.join(DOCS_DIR)),
+            None => Docs::memory(),
+        };
+        let docs = docs
             .session_access_provider(session_access_provider(
                 Arc::clone(&access),
                 Arc::clone(&registry),
             ))
-            .spawn(endpoint.clone(), (*blobs).clone(), gossip.clone())
-            .await?;
+            .validate_entry(ingest_filter(options.max_payload))
+            .spawn(endpoint.clone(), blobs.clone(), gossip.clone())
+            .await
+            .map_err(NodeError::storage)?;
         let docs_api = docs.api().clone();
-        let blobs_store: iroh_blobs::api::Store = (*blobs).clone();
-        access.set_blobs(blobs_store.clone());
+        access.set_blobs(blobs.clone());
         let mut router = Router::builder(endpoint)
             .accept(BLOBS_ALPN, BlobsProtocol::new(&blobs, None))
             .accept(GOSSIP_ALPN, gossip)
@@ -326,12 +435,15 @@ impl SyncNode {
         ));
         Ok(Self {
             router,
-            blobs: blobs_store,
+            blobs,
             docs: docs_api,
             registry,
             access,
             tracked_docs,
             nudges_in_flight: Arc::default(),
+            entry_feed: broadcast::channel(ENTRY_FEED_CAPACITY).0,
+            watched_docs: Arc::default(),
+            max_payload: options.max_payload,
             reconciler_stop,
         })
     }
@@ -339,17 +451,27 @@ impl SyncNode {
     /// Register `identity`'s directory for session classification: its
     /// device records decide which callers are this identity's own devices
     /// — full view of its replicas — and arm fail-closed serving for its
-    /// data namespace.
-    pub fn host_identity(&self, identity: PdnId, directory: &PrivateMetadataStore) -> Result<()> {
-        self.access.host_identity(identity, directory.doc_handle())
+    /// data namespace. Hosting an identity again onto another directory —
+    /// the successor of a revocation — retires the previous one: it stays
+    /// registered, refusing the devices either records as revoked.
+    pub fn host_identity(
+        &self,
+        identity: PdnId,
+        directory: &PrivateMetadataStore,
+    ) -> Result<(), NodeError> {
+        self.access
+            .host_identity(identity, directory.doc_handle())
+            .map_err(NodeError::from)
     }
 
     /// Remove `identity`'s directory from session classification — the
     /// rollback counterpart of [`host_identity`](Self::host_identity), for
     /// a ceremony that armed the identity and then failed. Registered
     /// connections are untouched.
-    pub fn unhost_identity(&self, identity: PdnId) -> Result<()> {
-        self.access.unhost_identity(identity)
+    pub fn unhost_identity(&self, identity: PdnId) -> Result<(), NodeError> {
+        self.access
+            .unhost_identity(identity)
+            .map_err(NodeError::from)
     }
 
     /// Register a connection of `identity` toward `peer` for session
@@ -362,23 +484,79 @@ impl SyncNode {
         peer: PdnId,
         own: &ConnectionMetadataStore,
         peer_store: &ConnectionMetadataStore,
-    ) -> Result<()> {
+    ) -> Result<(), NodeError> {
         self.access
             .host_connection(identity, peer, own.doc_handle(), peer_store.doc_handle())
+            .map_err(NodeError::from)
     }
 
     /// Create a fresh doc and register it as the data namespace of `issuer`.
-    pub async fn create_namespace(&self, issuer: PdnId) -> Result<()> {
+    pub async fn create_namespace(&self, issuer: PdnId) -> Result<(), NodeError> {
         let doc = self.new_doc().await?;
         // A registration cannot already exist: `issuer` is minted fresh by
         // the caller that provisions it, so there is nothing to displace or
         // restore.
         let _displaced = self
             .registry
-            .register_data(issuer, doc, ServingPosture::Serve)?;
+            .register_data(issuer, doc.clone(), ServingPosture::Serve)?;
+        self.watch_entries_of(&doc).await;
         Ok(())
     }
 
+    /// Reopen a data namespace this node already holds — a replica kept on
+    /// disk across a restart ([`SpawnOptions::storage`]) — and register it
+    /// as the data namespace of `issuer`, re-joining its swarm. The restart
+    /// counterpart of [`create_namespace`](Self::create_namespace) and of
+    /// the device-replication [`import_namespace`](Self::import_namespace):
+    /// the binding is the issuer's own (`Serve`), so a grantee binding is
+    /// reopened by re-importing from its grant instead.
+    ///
+    /// Fails when the node holds no replica of `namespace` — on an
+    /// in-memory node, every namespace after a restart.
+    pub async fn open_namespace(
+        &self,
+        issuer: PdnId,
+        namespace: NamespaceId,
+    ) -> Result<(), NodeError> {
+        let doc = self.open_doc(namespace).await?;
+        // A fresh node has no bindings, so there is nothing to displace; a
+        // caller reopening onto a live binding replaces an equivalent
+        // handle to the same replica.
+        let _displaced = self
+            .registry
+            .register_data(issuer, doc.clone(), ServingPosture::Serve)?;
+        self.watch_entries_of(&doc).await;
+        Ok(())
+    }
+
+    /// Move `issuer`'s own data namespace onto a fresh replica: every live
+    /// entry is copied over, the issuer is re-bound to the copy, and the
+    /// previous replica is dropped. The copy has a new namespace secret, so
+    /// a device that held the old one can neither write to it nor address
+    /// it — the re-keying half of a device revocation. Returns the new
+    /// namespace. Fails, leaving the previous binding in place, while an
+    /// entry's payload has not arrived on this node.
+    pub async fn rekey_namespace(&self, issuer: PdnId) -> Result<NamespaceId, NodeError> {
+        let previous = self
+            .registry
+            .binding(issuer)?
+            .ok_or(UnknownIssuer { issuer })?;
+        let doc = self.new_doc().await?;
+        let author = self.default_author().await?;
+        if let Err(err) = copy_records(&previous.doc, &doc, author, &self.blobs, |_key| true).await
+        {
+            let _ = self.forget_doc(doc.id()).await;
+            return Err(err.into());
+        }
+        let rekeyed = doc.id();
+        let _displaced = self
+            .registry
+            .register_data(issuer, doc.clone(), ServingPosture::Serve)?;
+        self.watch_entries_of(&doc).await;
+        self.forget_doc(previous.doc.id()).await?;
+        Ok(rekeyed)
+    }
+
     /// Import a doc shared via `ticket` and register it as the data
     /// namespace of `issuer` — the device-replication path: the issuer's own
     /// devices bring the replica up this way, and a device that holds it may
@@ -398,20 +576,23 @@ impl SyncNode {
         &self,
         issuer: PdnId,
         ticket: DocTicket,
-    ) -> Result<NamespaceImport> {
+    ) -> Result<NamespaceImport, NodeError> {
         let displaced_tracking = self.guard_data_import(ticket.capability.id())?;
         let doc = self.import_doc(ticket).await?;
         let imported = doc.id();
         match self
             .registry
-            .register_data(issuer, doc, ServingPosture::Serve)
+            .register_data(issuer, doc.clone(), ServingPosture::Serve)
         {
-            Ok(displaced) => Ok(NamespaceImport {
-                issuer,
-                imported,
-                displaced,
-                displaced_tracking,
-            }),
+            Ok(displaced) => {
+                self.watch_entries_of(&doc).await;
+                Ok(NamespaceImport {
+                    issuer,
+                    imported,
+                    displaced,
+                    displaced_tracking,
+                })
+            }
             Err(err) => {
                 // The one-namespace-one-issuer rejection must not clobber
                 // the rightful issuer's tracking: `import_doc` replaced it
@@ -419,7 +600,7 @@ impl SyncNode {
                 if let Some(previous) = displaced_tracking {
                     let _ = self.restore_tracking(previous).await;
                 }
-                Err(err)
+                Err(err.into())
             }
         }
     }
@@ -440,7 +621,7 @@ impl SyncNode {
         &self,
         issuer: PdnId,
         ticket: DocTicket,
-    ) -> Result<NamespaceImport> {
+    ) -> Result<NamespaceImport, NodeError> {
         self.import_grantee_namespace(issuer, ticket).await
     }
 
@@ -458,7 +639,7 @@ impl SyncNode {
         &self,
         issuer: PdnId,
         ticket: DocTicket,
-    ) -> Result<NamespaceImport> {
+    ) -> Result<NamespaceImport, NodeError> {
         self.import_grantee_namespace(issuer, ticket).await
     }
 
@@ -478,7 +659,7 @@ impl SyncNode {
         &self,
         issuer: PdnId,
         ticket: DocTicket,
-    ) -> Result<NamespaceImport> {
+    ) -> Result<NamespaceImport, NodeError> {
         let contacts = ticket.nodes.clone();
         let displaced_tracking = self.guard_data_import(ticket.capability.id())?;
         // Import the capability only — no automatic start_sync, which would
@@ -501,9 +682,10 @@ impl SyncNode {
                     if let Some(previous) = displaced_tracking {
                         let _ = self.restore_tracking(previous).await;
                     }
-                    return Err(err);
+                    return Err(err.into());
                 }
             };
+        self.watch_entries_of(&doc).await;
         // The capability, tracking, and binding are in place; the swarm
         // leave and the first sync remain. If either fails, roll the whole
         // import back through the same undo the caller would use, rather
@@ -521,11 +703,11 @@ impl SyncNode {
         // for a replica that never joined.
         if let Err(err) = doc.leave_gossip().await {
             let _ = self.undo_import_namespace(import).await;
-            return Err(err);
+            return Err(NodeError::storage(err));
         }
         if let Err(err) = doc.start_sync_scoped(contacts).await {
             let _ = self.undo_import_namespace(import).await;
-            return Err(err);
+            return Err(NodeError::storage(err));
         }
         Ok(import)
     }
@@ -542,7 +724,11 @@ impl SyncNode {
     /// without unregistering). Both are the same failure to the caller —
     /// "nowhere to record these contacts" — so both surface, rather than a
     /// silent `Ok` that drops them and starves the replica unattributably.
-    pub fn add_namespace_contacts(&self, issuer: PdnId, contacts: Vec<EndpointAddr>) -> Result<()> {
+    pub fn add_namespace_contacts(
+        &self,
+        issuer: PdnId,
+        contacts: Vec<EndpointAddr>,
+    ) -> Result<(), NodeError> {
         let doc = self
             .registry
             .data_doc(issuer)?
@@ -601,7 +787,7 @@ impl SyncNode {
     /// already-bound issuer resolves to the very replica the binding names,
     /// and dropping it would destroy the data the restore exists to preserve
     /// (`drop_doc` is permanent).
-    pub async fn undo_import_namespace(&self, import: NamespaceImport) -> Result<()> {
+    pub async fn undo_import_namespace(&self, import: NamespaceImport) -> Result<(), NodeError> {
         let NamespaceImport {
             issuer,
             imported,
@@ -612,7 +798,9 @@ impl SyncNode {
             return self.forget_namespace(issuer).await;
         };
         let previous_namespace = previous.doc.id();
+        let previous_doc = previous.doc.clone();
         let _replaced = self.registry.register_binding(issuer, previous)?;
+        self.watch_entries_of(&previous_doc).await;
         if imported != previous_namespace {
             self.forget_doc(imported).await?;
         } else if let Some(tracking) = displaced_tracking {
@@ -632,7 +820,7 @@ impl SyncNode {
     /// deliberately not offered: the issuer would keep resolving to a
     /// dropped replica, and its operations would fail as storage errors
     /// instead of the distinguishable refusal.
-    pub async fn forget_namespace(&self, issuer: PdnId) -> Result<()> {
+    pub async fn forget_namespace(&self, issuer: PdnId) -> Result<(), NodeError> {
         // Drop first, unregister second: the reverse order holds a window
         // in which the replica is alive but unknown to the book. A failed
         // drop leaves the registration in place, so a retry still resolves
@@ -664,6 +852,21 @@ impl SyncNode {
         Ok(doc)
     }
 
+    /// Reopen a replica this node already holds; the doc joins the periodic
+    /// reconcile pass and its swarm. No contacts survive a restart with it:
+    /// the engine's recorded peers and the peers that dial in are the
+    /// replica's way back to the network.
+    pub(crate) async fn open_doc(&self, namespace: NamespaceId) -> Result<Doc> {
+        let doc = self
+            .docs
+            .open(namespace)
+            .await?
+            .with_context(|| format!("no replica of namespace {namespace} on this node"))?;
+        self.track(&doc, Vec::new(), SyncStrategy::Swarm)?;
+        doc.start_sync(Vec::new()).await?;
+        Ok(doc)
+    }
+
     /// Register `doc` with the periodic reconcile pass. Keyed by namespace,
     /// so a re-import of a replica this node already tracks replaces its
     /// entry rather than accreting a second one with a contradictory
@@ -690,7 +893,7 @@ impl SyncNode {
     /// replica. (Data namespaces roll back through
     /// [`forget_namespace`](Self::forget_namespace) instead, which also
     /// unregisters the issuer.)
-    pub async fn forget_doc(&self, namespace: NamespaceId) -> Result<()> {
+    pub async fn forget_doc(&self, namespace: NamespaceId) -> Result<(), NodeError> {
         {
             let mut docs = self
                 .tracked_docs
@@ -713,13 +916,20 @@ impl SyncNode {
         issuer: PdnId,
         mode: ShareMode,
         addr_options: AddrInfoOptions,
-    ) -> Result<DocTicket> {
+    ) -> Result<DocTicket, NodeError> {
         let ticket = self.doc(issuer)?.share(mode, addr_options).await?;
         Ok(ticket)
     }
 
+    /// The node's default author — created with the docs engine and, on a
+    /// persistent node, the same author across restarts.
+    pub async fn default_author(&self) -> Result<AuthorId, NodeError> {
+        let author = self.docs.author_default().await?;
+        Ok(author)
+    }
+
     /// Create a new author keypair on this node.
-    pub async fn create_author(&self) -> Result<AuthorId> {
+    pub async fn create_author(&self) -> Result<AuthorId, NodeError> {
         let author = self.docs.author_create().await?;
         Ok(author)
     }
@@ -741,13 +951,22 @@ impl SyncNode {
     }
 
     /// Write `payload` at `path` in the data namespace of `issuer`.
+    ///
+    /// A payload over [`SpawnOptions::max_payload`] is refused with
+    /// [`NodeError::PayloadTooLarge`] before anything is written.
     pub async fn write(
         &self,
         issuer: PdnId,
         author: AuthorId,
         path: &EntryPath,
         payload: &[u8],
-    ) -> Result<()> {
+    ) -> Result<(), NodeError> {
+        if payload.len() > self.max_payload {
+            return Err(NodeError::PayloadTooLarge {
+                size: payload.len(),
+                max: self.max_payload,
+            });
+        }
         let doc = self.doc(issuer)?;
         doc.set_bytes(author, path.as_str().as_bytes().to_vec(), payload.to_vec())
             .await?;
@@ -763,11 +982,86 @@ impl SyncNode {
     /// again for the payload. Reading a grant-imported (`ContactsOnly`)
     /// namespace nudges its filtered reconciliation first (non-blocking):
     /// the answer is served from the local replica at once, and the nudge
-    /// pulls fresh entries for the next read.
-    pub async fn read(&self, issuer: PdnId, path: &EntryPath) -> Result<Option<Vec<u8>>> {
+    /// pulls fresh entries for the next read. A quarantined record — over
+    /// [`SpawnOptions::max_payload`] — reads as no entry.
+    pub async fn read(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+    ) -> Result<Option<Vec<u8>>, NodeError> {
         self.nudge_scoped(issuer);
         let doc = self.doc(issuer)?;
-        read_payload(&doc, &self.blobs, path.as_str().as_bytes()).await
+        read_bounded(
+            &doc,
+            &self.blobs,
+            path.as_str().as_bytes(),
+            self.max_payload,
+        )
+        .await
+        .map_err(NodeError::from)
+    }
+
+    /// This node as a [`DataLayer`](crate::DataLayer), writing as `author`:
+    /// the trait's operations name no author, so the handle carries one.
+    pub fn data_layer(&self, author: AuthorId) -> NodeDataLayer<'_> {
+        NodeDataLayer::new(self, author)
+    }
+
+    /// Delete the entry at `path` in the data namespace of `issuer`: a
+    /// tombstone at its key, written by `author`. Answers whether a live
+    /// entry was there; with none, nothing is written.
+    ///
+    /// The tombstone replicates like any record — to the issuer's other
+    /// devices, and to each grantee whose grant covers `path`: it sits at
+    /// the very key of the claim it deletes, so the egress filter passes it
+    /// wherever it passed the claim, and the grantee reads the claim as
+    /// gone. Newer wins across devices, as for writes: a later write at
+    /// `path` brings the entry back. A quarantined record counts as there,
+    /// so deleting its path clears it.
+    pub async fn delete(
+        &self,
+        issuer: PdnId,
+        author: AuthorId,
+        path: &EntryPath,
+    ) -> Result<bool, NodeError> {
+        let doc = self.doc(issuer)?;
+        let key = path.as_str().as_bytes().to_vec();
+        let query = Query::single_latest_per_key().key_exact(&key);
+        if doc.get_one(query).await?.is_none() {
+            return Ok(false);
+        }
+        tombstone(&doc, author, &self.blobs, &[key]).await?;
+        Ok(true)
+    }
+
+    /// Delete every live entry under `prefix` in the data namespace of
+    /// `issuer`, matching whole components as [`list`](Self::list) does —
+    /// an entry at `prefix` itself included. Answers how many were deleted.
+    ///
+    /// Each entry gets its own tombstone, exactly as [`delete`](Self::delete)
+    /// writes it, so each replicates under the same grant coverage; a
+    /// single tombstone at the prefix would hide only `author`'s own
+    /// records and pass no grant's filter. Entries that arrive later under
+    /// the prefix are not deleted. Quarantined records count as there, as
+    /// for [`delete`](Self::delete), though they do not list.
+    pub async fn delete_prefix(
+        &self,
+        issuer: PdnId,
+        author: AuthorId,
+        prefix: &EntryPath,
+    ) -> Result<usize, NodeError> {
+        let doc = self.doc(issuer)?;
+        let query = Query::single_latest_per_key().key_prefix(prefix.as_str().as_bytes());
+        let mut stream = std::pin::pin!(doc.get_many(query).await?);
+        let mut keys = Vec::new();
+        while let Some(entry) = stream.next().await {
+            let entry = entry?;
+            if path_of(entry.key()).is_some_and(|path| path.starts_with(prefix)) {
+                keys.push(entry.key().to_vec());
+            }
+        }
+        tombstone(&doc, author, &self.blobs, &keys).await?;
+        Ok(keys.len())
     }
 
     /// Fire-and-forget a filtered reconciliation of a `ContactsOnly`
@@ -818,12 +1112,26 @@ impl SyncNode {
     ///
     /// Record-level: an entry lists once its record is stored, whether or
     /// not its payload has been fetched yet. Deleted entries (tombstones)
-    /// do not list.
+    /// and quarantined ones (over [`SpawnOptions::max_payload`]) do not
+    /// list.
     pub async fn list(
         &self,
         issuer: PdnId,
         path_prefix: Option<&EntryPath>,
-    ) -> Result<Vec<EntryInfo>> {
+    ) -> Result<Vec<EntryInfo>, NodeError> {
+        self.list_stream(issuer, path_prefix)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// [`list`](Self::list) as a stream: each entry's metadata is yielded
+    /// as the store produces it, none collected first.
+    pub async fn list_stream(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<EntryListing, NodeError> {
         self.nudge_scoped(issuer);
         let doc = self.doc(issuer)?;
         // Byte-prefix query as the coarse cut (a component prefix is always
@@ -833,33 +1141,128 @@ impl SyncNode {
             Some(prefix) => query.key_prefix(prefix.as_str().as_bytes()),
             None => query,
         };
-        let mut stream = std::pin::pin!(doc.get_many(query).await?);
-        let mut entries = Vec::new();
-        while let Some(entry) = stream.next().await {
-            let entry = entry?;
+        let prefix = path_prefix.cloned();
+        let max_payload = self.max_payload;
+        let entries = doc.get_many(query).await?.filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(NodeError::storage(err))),
+            };
+            if !fits(entry.content_len(), max_payload) {
+                return None;
+            }
             // Keys that don't parse as entry paths are not data-layer
             // entries; skip them, as the store listings do for foreign keys.
-            let Some(path) = path_of(entry.key()) else {
-                continue;
-            };
-            if path_prefix.is_some_and(|prefix| !starts_with_components(&path, prefix)) {
-                continue;
+            let path = path_of(entry.key())?;
+            if prefix
+                .as_ref()
+                .is_some_and(|prefix| !path.starts_with(prefix))
+            {
+                return None;
             }
-            entries.push(EntryInfo {
+            Some(Ok(EntryInfo {
                 issuer,
                 path,
                 payload_len: entry.content_len(),
-            });
+            }))
+        });
+        Ok(entries.boxed())
+    }
+
+    /// Subscribe to the node-wide entry feed: every change in a data
+    /// namespace bound here — created, imported, or granted — whether made
+    /// locally or arrived by sync, from the moment of subscribing. Records
+    /// come first: an insert or update reports the record, and a
+    /// [`PayloadReady`](EntryEventKind::PayloadReady) follows once its
+    /// payload can be read.
+    ///
+    /// Best-effort by construction: a subscriber that falls more than the
+    /// feed's buffer behind is told how many it missed
+    /// ([`broadcast::error::RecvError::Lagged`]) and should re-list. The
+    /// feed closes once the node has shut down.
+    pub fn subscribe_entries(&self) -> broadcast::Receiver<EntryEvent> {
+        self.entry_feed.subscribe()
+    }
+
+    /// Watch the data namespace of `issuer` from now on: the node's entry
+    /// feed narrowed to that issuer and, when given, to paths under
+    /// `path_prefix` by whole components.
+    ///
+    /// Refuses with [`UnknownIssuer`] when `issuer` has no namespace here;
+    /// once running, the watch follows the issuer, so a rebinding onto a
+    /// fresh replica carries on. A watch that falls behind yields one
+    /// [`EntriesMissed`] error and continues. Ends once the node has shut
+    /// down.
+    pub fn watch_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<EntryWatch, NodeError> {
+        let _bound = self.doc(issuer)?;
+        let feed = self.entry_feed.subscribe();
+        let prefix = path_prefix.cloned();
+        let watch = stream::unfold((feed, prefix), move |(mut feed, prefix)| async move {
+            loop {
+                match feed.recv().await {
+                    Ok(event) => {
+                        if event.info.issuer == issuer && touches(&event, prefix.as_ref()) {
+                            return Some((Ok(event), (feed, prefix)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        return Some((Err(EntriesMissed { missed }.into()), (feed, prefix)));
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(watch.boxed())
+    }
+
+    /// Feed `doc`'s inserts into the entry feed, unless a watcher already
+    /// does. Called on every data binding, after it is registered, so the
+    /// watcher resolves the issuer through the registry as each insert
+    /// arrives — a rebinding is followed, and the watcher ends once the
+    /// replica is bound to no issuer. A replica whose subscription fails
+    /// goes unwatched: the feed is an observation aid, never a reason to
+    /// fail the binding.
+    async fn watch_entries_of(&self, doc: &Doc) {
+        let namespace = doc.id();
+        {
+            let Ok(mut watched) = self.watched_docs.lock() else {
+                return;
+            };
+            if !watched.insert(namespace) {
+                return;
+            }
         }
-        Ok(entries)
+        let Ok(events) = doc.subscribe().await else {
+            if let Ok(mut watched) = self.watched_docs.lock() {
+                watched.remove(&namespace);
+            }
+            return;
+        };
+        // Taken after subscribing, so no insert falls between the two; one
+        // racing the read is reported as an update. Unreadable, every
+        // existing path's next write reads as an insert.
+        let held = held_keys(doc, self.max_payload).await.unwrap_or_default();
+        let _detached = tokio::spawn(feed_entries(
+            events,
+            namespace,
+            held,
+            self.max_payload,
+            Arc::clone(&self.registry),
+            self.entry_feed.clone(),
+            Arc::clone(&self.watched_docs),
+        ));
     }
 
     /// Shut the node down, closing the endpoint and all protocols.
-    pub async fn shutdown(self) -> Result<()> {
+    pub async fn shutdown(self) -> Result<(), NodeError> {
         // Stop the reconcile pass first so it does not race the docs
         // engine's shutdown with fresh sync requests.
         let _ = self.reconciler_stop.send(());
-        self.router.shutdown().await?;
+        self.router.shutdown().await.map_err(NodeError::storage)?;
         Ok(())
     }
 
@@ -870,12 +1273,73 @@ impl SyncNode {
     }
 }
 
-/// Bind the node's endpoint. If `PDN_BIND_ADDR` holds an IP address the
-/// endpoint binds that address with an ephemeral port; unset, it binds all
-/// interfaces. Scenario tests bind `127.0.0.1` (the just recipes set it) to
-/// keep test traffic on loopback; production spawns leave it unset.
-async fn bind_endpoint() -> Result<Endpoint> {
+/// Load the endpoint's secret key from the storage `root`, minting and
+/// storing a fresh one on the root's first use. The key file is written by
+/// [`replace_file`]: owner-only, and never a truncated key the next spawn
+/// would refuse.
+fn load_or_create_secret_key(root: &Path) -> Result<SecretKey> {
+    let path = root.join(SECRET_KEY_FILE);
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let bytes: [u8; 32] = bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("{} does not hold a 32-byte key", path.display()))?;
+            Ok(SecretKey::from_bytes(&bytes))
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            std::fs::create_dir_all(root)
+                .with_context(|| format!("cannot create storage root {}", root.display()))?;
+            let mut bytes = [0u8; 32];
+            SysRng
+                .try_fill_bytes(&mut bytes)
+                .context("operating-system randomness unavailable")?;
+            replace_file(&path, &bytes)?;
+            Ok(SecretKey::from_bytes(&bytes))
+        }
+        Err(err) => Err(err).with_context(|| format!("cannot read {}", path.display())),
+    }
+}
+
+/// Replace the file at `path` with `bytes`, whole: written under a staging
+/// name, synced, and renamed into place, so a crash mid-write leaves the
+/// previous contents, never torn ones. Owner-only where the platform has
+/// modes — the endpoint key is the node's secret. A staged file an earlier
+/// crash left behind is removed first: the mode applies on create only.
+pub(crate) fn replace_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let staged = path.with_extension("staged");
+    match std::fs::remove_file(&staged) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("cannot remove {}", staged.display()));
+        }
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&staged)
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::io::Write::write_all(&mut file, bytes)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::fs::rename(&staged, path).with_context(|| format!("cannot write {}", path.display()))
+}
+
+/// Bind the node's endpoint, under `secret_key` when one is given (a
+/// persistent node) or a fresh one otherwise. If `PDN_BIND_ADDR` holds an
+/// IP address the endpoint binds that address with an ephemeral port;
+/// unset, it binds all interfaces. Scenario tests bind `127.0.0.1` (the just
+/// recipes set it) to keep test traffic on loopback; production spawns
+/// leave it unset.
+async fn bind_endpoint(secret_key: Option<SecretKey>) -> Result<Endpoint> {
     let builder = Endpoint::builder(presets::Minimal);
+    let builder = match secret_key {
+        Some(key) => builder.secret_key(key),
+        None => builder,
+    };
     let builder = match std::env::var("PDN_BIND_ADDR") {
         Ok(addr) if !addr.is_empty() => {
             let ip: IpAddr = addr
@@ -911,11 +1375,24 @@ pub(crate) async fn read_payload(
     doc: &Doc,
     blobs: &iroh_blobs::api::Store,
     key: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    read_bounded(doc, blobs, key, usize::MAX).await
+}
+
+/// [`read_payload`], with a record over `max` bytes read as no entry.
+async fn read_bounded(
+    doc: &Doc,
+    blobs: &iroh_blobs::api::Store,
+    key: &[u8],
+    max: usize,
 ) -> Result<Option<Vec<u8>>> {
     let query = Query::single_latest_per_key().key_exact(key);
     let Some(entry) = doc.get_one(query).await? else {
         return Ok(None);
     };
+    if !fits(entry.content_len(), max) {
+        return Ok(None);
+    }
     let hash = entry.content_hash();
     if !blobs.has(hash).await? {
         return Ok(None);
@@ -923,6 +1400,85 @@ pub(crate) async fn read_payload(
     Ok(Some(blobs.get_bytes(hash).await?.to_vec()))
 }
 
+/// Tombstone each of `keys` in `doc`, written by `author`.
+///
+/// The store's deletion works by byte prefix over `author`'s own records,
+/// and the fork writes no tombstone at an exact key: a tombstone at
+/// `contacts/a` also sweeps `author`'s `contacts/ab` and `contacts/a/x`.
+/// Swept records that are their key's live record are read first and
+/// written back right after their key's tombstone, before this returns,
+/// so exactly `keys` end up deleted; one that another author's newer
+/// record already shadows is let go, as nothing reads it. A live record
+/// whose payload has not arrived fails the deletion before anything is
+/// written.
+///
+/// The window: a crash between a tombstone and its write-back leaves the
+/// swept records deleted on this replica, and the deletion replicates.
+/// Taking one key at a time bounds what such a crash loses to the records
+/// beside that one key.
+async fn tombstone(
+    doc: &Doc,
+    author: AuthorId,
+    blobs: &iroh_blobs::api::Store,
+    keys: &[Vec<u8>],
+) -> Result<()> {
+    let targets: HashSet<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+    let mut swept = Vec::with_capacity(keys.len());
+    for key in keys {
+        let query = Query::single_latest_per_key().key_prefix(key.clone());
+        let mut stream = std::pin::pin!(doc.get_many(query).await?);
+        let mut beside = Vec::new();
+        while let Some(entry) = stream.next().await {
+            let entry = entry?;
+            if entry.author() != author || targets.contains(entry.key()) {
+                continue;
+            }
+            let payload = read_payload(doc, blobs, entry.key())
+                .await?
+                .context("a record the deletion would sweep has no payload on this node")?;
+            beside.push((entry.key().to_vec(), payload));
+        }
+        swept.push(beside);
+    }
+    for (key, beside) in keys.iter().zip(swept) {
+        doc.del(author, key.clone()).await?;
+        for (key, payload) in beside {
+            doc.set_bytes(author, key, payload).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the latest live record of every key `keep` admits from `from` into
+/// `to`, payload included, written by `author`. A record whose payload has
+/// not arrived fails the copy: a successor replica missing records would
+/// pass for a complete one.
+pub(crate) async fn copy_records(
+    from: &Doc,
+    to: &Doc,
+    author: AuthorId,
+    blobs: &iroh_blobs::api::Store,
+    keep: impl Fn(&[u8]) -> bool,
+) -> Result<()> {
+    let mut keys = Vec::new();
+    {
+        let mut stream = std::pin::pin!(from.get_many(Query::single_latest_per_key()).await?);
+        while let Some(entry) = stream.next().await {
+            let key = entry?.key().to_vec();
+            if keep(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    for key in keys {
+        let payload = read_payload(from, blobs, &key)
+            .await?
+            .context("a record's payload has not arrived on this node")?;
+        to.set_bytes(author, key, payload).await?;
+    }
+    Ok(())
+}
+
 /// The periodic reconcile pass: every `interval`, re-request a sync for
 /// each tracked doc with its import-time contacts (the engine unions them
 /// with the peers it recorded as useful). A request against a pair whose
@@ -952,18 +1508,210 @@ async fn reconcile_pass(
     }
 }
 
+/// The keys `doc` holds a live record at — tombstones, and records over
+/// `max_payload` bytes, excluded.
+async fn held_keys(doc: &Doc, max_payload: usize) -> Result<HashSet<Vec<u8>>> {
+    let mut stream = std::pin::pin!(doc.get_many(Query::single_latest_per_key()).await?);
+    let mut keys = HashSet::new();
+    while let Some(entry) = stream.next().await {
+        let entry = entry?;
+        if fits(entry.content_len(), max_payload) {
+            keys.insert(entry.key().to_vec());
+        }
+    }
+    Ok(keys)
+}
+
+/// The ingest filter the fork runs on every entry a sync offers, before
+/// the entry is stored or its payload fetched: admit it iff its payload is
+/// within `max_payload` bytes. A refused entry is dropped from the session
+/// — the peer keeps it, and offers it again on the next sync, to the same
+/// verdict.
+fn ingest_filter(max_payload: usize) -> EntryFilter {
+    Arc::new(move |entry: &pdn_store::SignedEntry| fits(entry.content_len(), max_payload))
+}
+
+/// Whether a payload of `len` bytes is within `max`.
+fn fits(len: u64, max: usize) -> bool {
+    usize::try_from(len).is_ok_and(|len| len <= max)
+}
+
+/// Whether `event` concerns a watch on `prefix`: it lies under the prefix.
+fn touches(event: &EntryEvent, prefix: Option<&EntryPath>) -> bool {
+    prefix.is_none_or(|prefix| event.info.path.starts_with(prefix))
+}
+
+/// One data replica's entry watcher: report each insert to the node-wide
+/// feed under the issuer the replica is bound to now — an empty record as
+/// a deletion, a record at a key in `held` as an update — and each
+/// payload's readiness after it — unless a later record at its key came
+/// first, which reports its own. A record over `max_payload` bytes is
+/// quarantined: it reads as no entry, so it reports as the deletion of the
+/// entry it shadows, and not at all over none. Sync bookkeeping is not an
+/// entry and passes by. Ends with the replica's event stream, or at the
+/// first insert after the replica was unbound — freeing its slot in
+/// `watched` either way.
+async fn feed_entries(
+    mut events: impl futures_core::Stream<Item = Result<LiveEvent>> + Unpin,
+    namespace: NamespaceId,
+    mut held: HashSet<Vec<u8>>,
+    max_payload: usize,
+    registry: Arc<Registry>,
+    feed: broadcast::Sender<EntryEvent>,
+    watched: Arc<Mutex<HashSet<NamespaceId>>>,
+) {
+    // Readiness events waiting on a payload still in transit, by its hash,
+    // and the hash each key's event waits on: a later record at the key
+    // supersedes its wait, so at most one per key is kept — bounded by the
+    // replica's keys, however often a payload that never arrives is
+    // rewritten.
+    let mut in_transit: HashMap<Hash, Vec<EntryEvent>> = HashMap::new();
+    let mut waiting: HashMap<Vec<u8>, Hash> = HashMap::new();
+    while let Some(Ok(event)) = events.next().await {
+        let (entry, origin, readable) = match event {
+            LiveEvent::InsertLocal { entry } => (entry, EntryOrigin::Local, true),
+            LiveEvent::InsertRemote {
+                from,
+                entry,
+                content_status,
+            } => (
+                entry,
+                EntryOrigin::Remote(NodeId::from_bytes(*from.as_bytes())),
+                matches!(content_status, ContentStatus::Complete),
+            ),
+            LiveEvent::ContentReady { hash } => {
+                for ready in in_transit.remove(&hash).unwrap_or_default() {
+                    waiting.remove(ready.info.path.as_str().as_bytes());
+                    let _unobserved = feed.send(ready);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let Ok(Some((issuer, _posture))) = registry.binding_of(namespace) else {
+            break;
+        };
+        let Some(path) = path_of(entry.key()) else {
+            continue;
+        };
+        let key = entry.key().to_vec();
+        if let Some(superseded) = waiting.remove(&key) {
+            if let Some(events) = in_transit.get_mut(&superseded) {
+                events.retain(|event| event.info.path != path);
+                if events.is_empty() {
+                    in_transit.remove(&superseded);
+                }
+            }
+        }
+        let kind = if entry.content_len() == 0 {
+            // A tombstone deletes the entry at its own key and nothing
+            // else: not a sibling sharing its bytes (`contacts/ab` under a
+            // tombstone at `contacts/a`), not the entries below it. The
+            // store's byte-prefix sweep takes the tombstone author's own
+            // records alone, and `tombstone` writes those back, so they
+            // stay held and report as updates.
+            held.remove(&key);
+            EntryEventKind::Deleted
+        } else if !fits(entry.content_len(), max_payload) {
+            if !held.remove(&key) {
+                continue;
+            }
+            EntryEventKind::Deleted
+        } else if held.insert(key) {
+            EntryEventKind::Inserted
+        } else {
+            EntryEventKind::Updated
+        };
+        let payload_len = if kind == EntryEventKind::Deleted {
+            0
+        } else {
+            entry.content_len()
+        };
+        let info = EntryInfo {
+            issuer,
+            path,
+            payload_len,
+        };
+        // No subscriber is not a failure: the feed is fire-and-forget.
+        let _unobserved = feed.send(EntryEvent {
+            info: info.clone(),
+            kind,
+            origin,
+        });
+        if kind == EntryEventKind::Deleted {
+            continue;
+        }
+        let ready = EntryEvent {
+            info,
+            kind: EntryEventKind::PayloadReady,
+            origin,
+        };
+        if readable {
+            let _unobserved = feed.send(ready);
+        } else {
+            waiting.insert(entry.key().to_vec(), entry.content_hash());
+            in_transit
+                .entry(entry.content_hash())
+                .or_default()
+                .push(ready);
+        }
+    }
+    if let Ok(mut watched) = watched.lock() {
+        watched.remove(&namespace);
+    }
+}
+
 /// Parse a stored key back into an [`EntryPath`], if it is one.
 fn path_of(key: &[u8]) -> Option<EntryPath> {
     let s = std::str::from_utf8(key).ok()?;
     EntryPath::new(s).ok()
 }
 
-/// Whether `path`'s leading components equal `prefix`'s components. Both
-/// are validated paths (no empty components, no trailing slash), so a byte
-/// prefix plus a component boundary is exactly component semantics.
-fn starts_with_components(path: &EntryPath, prefix: &EntryPath) -> bool {
-    match path.as_str().strip_prefix(prefix.as_str()) {
-        Some(rest) => rest.is_empty() || rest.starts_with('/'),
-        None => false,
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A record over the ceiling never reaches the laptop's blob store: it
+    /// is refused as an entry, so its payload is not fetched — while the
+    /// phone, with the default ceiling, holds both.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn an_oversized_record_is_never_fetched() -> anyhow::Result<()> {
+        let alice = PdnId::from_bytes([0xa1; 32]);
+        let phone = SyncNode::spawn().await?;
+        let laptop = SyncNode::spawn_with_options(SpawnOptions {
+            max_payload: 8,
+            ..SpawnOptions::default()
+        })
+        .await?;
+        let author = phone.create_author().await?;
+        phone.create_namespace(alice).await?;
+        let ticket = phone
+            .share_ticket(alice, ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
+            .await?;
+        laptop.import_namespace(alice, ticket).await?;
+
+        let oversized = b"far too long for the laptop";
+        phone
+            .write(alice, author, &EntryPath::new("notes/a")?, oversized)
+            .await?;
+        let later = EntryPath::new("notes/b")?;
+        phone.write(alice, author, &later, b"later").await?;
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while laptop.read(alice, &later).await?.is_none() {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            anyhow::Ok(())
+        })
+        .await??;
+
+        let hash = Hash::new(oversized);
+        assert!(phone.blobs.has(hash).await?);
+        assert!(!laptop.blobs.has(hash).await?);
+
+        phone.shutdown().await?;
+        laptop.shutdown().await?;
+        Ok(())
     }
 }
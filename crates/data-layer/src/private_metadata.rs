@@ -5,21 +5,31 @@
 //! A dedicated pdn-store replica, separate from data namespaces, that all
 //! devices of one identity replicate. It is device-internal by ticket alone
 //! (Invariant 1): its ticket is handed only to the identity's own devices,
-//! over the device-linking dialogue. Three record families live here, under
-//! disjoint prefixes: `devices/` — the device set; `tickets/` — typed
-//! tickets to the identity's other stores and its connections' metadata
-//! pairs; `connections/` — one marker record per connection counterparty.
-//! One node holds the private metadata stores of any number of identities.
+//! over the device-linking dialogue. Five record families live here, under
+//! disjoint prefixes: `devices/` — the device set; `revoked/` — the devices
+//! removed from it, kept so every replica can refuse them; `tickets/` —
+//! typed tickets to the identity's other stores and its connections'
+//! metadata pairs; `connections/` — one marker record per connection
+//! counterparty; `kel/` — the identity's key event log, one record per
+//! event. One more family, `successor/`, is written when a revocation
+//! retires the directory: each record names a replica claimed to replace
+//! it, with the proof a reader checks before following it. One node holds
+//! the private metadata stores of any number of identities.
 //!
 //! Device and connection records are record-level (visible as soon as the
-//! entry syncs — liveness never waits on payload bytes); ticket payloads are
-//! blobs, so `get_ticket` returns `None` until the payload has arrived.
+//! entry syncs — liveness never waits on payload bytes); a device record's
+//! descriptive payload ([`DeviceRecord`]), ticket and key event payloads are
+//! blobs, so `device_record` and `get_ticket` return `None`, and
+//! `key_events` stops short, until the payload has arrived.
 
-use std::time::{Duration, Instant, SystemTime};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use futures_core::Stream;
 use futures_lite::StreamExt;
+use iroh::{EndpointAddr, EndpointId};
+use iroh_blobs::Hash;
 use pdn_store::{
     api::{
         protocol::{AddrInfoOptions, ShareMode},
@@ -27,15 +37,17 @@ use pdn_store::{
     },
     engine::LiveEvent,
     store::Query,
-    AuthorId, DocTicket, NamespaceId,
+    AuthorId, Capability, DocTicket, NamespaceId,
 };
-use pdn_types::{NodeId, PdnId};
+use pdn_types::{NodeId, OperationalKey, PdnId, PdnIdentityProof};
+use serde::{Deserialize, Serialize};
 
-use crate::node::{read_payload, SyncNode};
+use crate::error::NodeError;
+use crate::node::{copy_records, read_payload, SyncNode};
 
 /// The bounded wait of [`PrivateMetadataStore::wait_caught_up`] elapsed with
 /// no successful sync session of the replica started after the given
-/// instant. Downcast from the `anyhow::Error` of that wait — how a caller
+/// instant. Carried by [`NodeError::Timeout`] from that wait — how a caller
 /// tells "did not catch up in time" apart from this node's own failures.
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 #[error("no successful sync session of the replica within the wait")]
@@ -47,10 +59,23 @@ pub struct CatchUpTimeout;
 /// purpose: this key decides who counts as an identity's own device, so a
 /// drifted copy would be an access-control bug.
 pub(crate) const DEVICES_PREFIX: &str = "devices/";
+/// Key prefix for revocation records — the same record shape as
+/// [`DEVICES_PREFIX`], read by the access book to refuse a removed device
+/// on every replica of the identity.
+pub(crate) const REVOKED_PREFIX: &str = "revoked/";
+/// Key prefix for successor records, shared with the connection metadata
+/// store's: one record per successor claimed, so no record written beside
+/// the true one can shadow it.
+pub(crate) const SUCCESSOR_PREFIX: &str = "successor/";
+/// Key prefix for sighting records.
+const SIGHTINGS_PREFIX: &str = "sightings/";
 /// Key prefix for typed tickets.
 const TICKETS_PREFIX: &str = "tickets/";
 /// Key prefix for connection records.
 const CONNECTIONS_PREFIX: &str = "connections/";
+/// Key prefix for key event records, shared with the connection metadata
+/// store's mirror of the log.
+pub(crate) const KEL_PREFIX: &str = "kel/";
 
 /// The entry key of a device record: `devices/<node-id-hex>`
 /// ([`DEVICES_PREFIX`] is the one shared definition).
@@ -58,6 +83,18 @@ pub(crate) fn device_key(device: &NodeId) -> String {
     format!("{DEVICES_PREFIX}{device}")
 }
 
+/// The entry key of a revocation record: `revoked/<node-id-hex>`.
+pub(crate) fn revoked_key(device: &NodeId) -> String {
+    format!("{REVOKED_PREFIX}{device}")
+}
+
+/// The entry key of `observer`'s sighting of `device`:
+/// `sightings/<observer-hex>/<device-hex>` — one record per observer, so
+/// devices recording their sightings never overwrite each other's.
+fn sighting_key(observer: &NodeId, device: &NodeId) -> String {
+    format!("{SIGHTINGS_PREFIX}{observer}/{device}")
+}
+
 fn ticket_key(kind: &str) -> String {
     format!("{TICKETS_PREFIX}{kind}")
 }
@@ -67,6 +104,27 @@ fn connection_key(peer: &PdnId) -> String {
     format!("{CONNECTIONS_PREFIX}{peer}")
 }
 
+/// The entry key of a successor record: `successor/<namespace-hex>`.
+pub(crate) fn successor_key(namespace: &NamespaceId) -> String {
+    format!("{SUCCESSOR_PREFIX}{namespace}")
+}
+
+/// The entry key of the key event at `sn` of the log under `prefix` —
+/// `kel/<sn>` for the identity's own — with `sn` zero-padded to the width
+/// of `u64::MAX` so key order is sequence order.
+pub(crate) fn key_event_key(prefix: &str, sn: u64) -> String {
+    format!("{prefix}{sn:020}")
+}
+
+/// Parse a sequence number back out of a `<prefix><sn>` key, if it matches.
+fn key_event_sn_of(prefix: &str, key: &[u8]) -> Option<u64> {
+    std::str::from_utf8(key)
+        .ok()?
+        .strip_prefix(prefix)?
+        .parse()
+        .ok()
+}
+
 /// Parse a `NodeId` back out of a `devices/<hex>` key, if it matches.
 pub(crate) fn device_of(key: &[u8]) -> Option<NodeId> {
     std::str::from_utf8(key)
@@ -85,9 +143,100 @@ fn connection_peer_of(key: &[u8]) -> Option<PdnId> {
         .ok()
 }
 
+/// What a device record says about its device, beyond its membership: for
+/// people recognizing their devices, not for access decisions — membership
+/// is the record's presence alone. Every field is optional: a record
+/// written before the field existed, or by a device that did not know it,
+/// reads as absent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceRecord {
+    /// A human label, chosen on the device when it was linked.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// When the device joined the identity, unix ms.
+    #[serde(default)]
+    pub linked_at: Option<u64>,
+    /// The operational key the device signs with — the identity's current
+    /// key on the device that incepted it, its own device key, authorized
+    /// by the identity's log, on a linked one.
+    #[serde(default)]
+    pub key: Option<OperationalKey>,
+}
+
+impl DeviceRecord {
+    /// A record of a device joining now, labelled `label` and otherwise
+    /// undescribed.
+    pub fn linked_now(label: Option<String>) -> Self {
+        Self {
+            label,
+            linked_at: Some(unix_ms(SystemTime::now())),
+            key: None,
+        }
+    }
+}
+
+/// The payload of a device record from before records were described: an
+/// opaque one-byte marker, read back as a record with nothing in it.
+const BARE_DEVICE_MARKER: &[u8] = &[1u8];
+
+/// `at` as unix ms — the clock of the descriptive timestamps in this store,
+/// and of the grant windows the access book enforces.
+pub fn unix_ms(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH).map_or(0, |since| {
+        u64::try_from(since.as_millis()).unwrap_or(u64::MAX)
+    })
+}
+
+/// The successor record of a retired replica — a directory, or an own
+/// connection metadata store: the replica a revocation moved its records
+/// onto, and the device that did it — the one the remaining devices ask
+/// for the new tickets. Carries no write ticket on purpose: the revoked
+/// device reads this record too. It can also write one, holding the
+/// retired replica's write secret, so a record is a claim until its
+/// `proof` verifies under a key the identity's log authorizes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Successor {
+    /// The namespace of the replica that replaced this one.
+    pub namespace: NamespaceId,
+    /// The device that wrote the successor and hosts it.
+    pub via: NodeId,
+    /// The writing device's proof over [`statement`](Self::statement).
+    pub proof: PdnIdentityProof,
+}
+
+impl Successor {
+    /// The statement a successor's proof covers: the retired replica, the
+    /// one replacing it, and the device hosting that one. Naming the
+    /// retired replica binds the proof to it — copied into another retired
+    /// replica, it proves nothing there.
+    pub fn statement(retired: NamespaceId, namespace: NamespaceId, via: NodeId) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SUCCESSOR_DOMAIN.len() + 96);
+        bytes.extend_from_slice(SUCCESSOR_DOMAIN);
+        bytes.extend_from_slice(retired.as_bytes());
+        bytes.extend_from_slice(namespace.as_bytes());
+        bytes.extend_from_slice(via.as_bytes());
+        bytes
+    }
+
+    /// A read ticket to the successor replica with `via` as its contact —
+    /// how a counterparty moves onto a retired store's successor.
+    pub fn read_ticket(&self) -> Result<DocTicket, NodeError> {
+        let via = EndpointId::from_bytes(self.via.as_bytes()).map_err(NodeError::storage)?;
+        Ok(DocTicket::new(
+            Capability::Read(self.namespace),
+            vec![EndpointAddr::new(via)],
+        ))
+    }
+}
+
+/// Domain separation of a successor statement.
+const SUCCESSOR_DOMAIN: &[u8] = b"pdn.successor.v0";
+
 /// Device-replicated directory of an identity's own state: its devices, the
 /// tickets to its other stores, and its connections. The owning identity is
 /// not kept here — the handle's holder knows which identity it serves.
+/// This device writes as the node's default author, so every handle it
+/// opens on the replica, across restarts too, writes as one author.
 #[derive(Debug)]
 pub struct PrivateMetadataStore {
     doc: Doc,
@@ -97,9 +246,9 @@ pub struct PrivateMetadataStore {
 
 impl PrivateMetadataStore {
     /// Create a fresh private metadata store on `node`.
-    pub async fn create(node: &SyncNode) -> Result<Self> {
+    pub async fn create(node: &SyncNode) -> Result<Self, NodeError> {
         let doc = node.new_doc().await?;
-        let author = node.create_author().await?;
+        let author = node.default_author().await?;
         Ok(Self {
             doc,
             author,
@@ -109,9 +258,22 @@ impl PrivateMetadataStore {
 
     /// Import an existing private metadata store via `ticket` (the write
     /// ticket handed to a newly linked device over the linking dialogue).
-    pub async fn import(node: &SyncNode, ticket: DocTicket) -> Result<Self> {
+    pub async fn import(node: &SyncNode, ticket: DocTicket) -> Result<Self, NodeError> {
         let doc = node.import_doc(ticket).await?;
-        let author = node.create_author().await?;
+        let author = node.default_author().await?;
+        Ok(Self {
+            doc,
+            author,
+            blobs: node.blobs(),
+        })
+    }
+
+    /// Reopen a private metadata store this node already holds on disk
+    /// ([`SpawnOptions::storage`](crate::SpawnOptions::storage)) — the
+    /// restart path. Fails when the node holds no replica of `namespace`.
+    pub async fn open(node: &SyncNode, namespace: NamespaceId) -> Result<Self, NodeError> {
+        let doc = node.open_doc(namespace).await?;
+        let author = node.default_author().await?;
         Ok(Self {
             doc,
             author,
@@ -125,7 +287,7 @@ impl PrivateMetadataStore {
         &self,
         mode: ShareMode,
         addr_options: AddrInfoOptions,
-    ) -> Result<DocTicket> {
+    ) -> Result<DocTicket, NodeError> {
         let ticket = self.doc.share(mode, addr_options).await?;
         Ok(ticket)
     }
@@ -136,17 +298,70 @@ impl PrivateMetadataStore {
         self.doc.clone()
     }
 
-    /// Record `device` as one of the identity's devices.
-    pub async fn add_device(&self, device: NodeId) -> Result<()> {
+    /// Record `device` as one of the identity's devices, linked now and
+    /// otherwise undescribed — the device describes itself with
+    /// [`put_device`](Self::put_device) once it holds the directory.
+    pub async fn add_device(&self, device: NodeId) -> Result<(), NodeError> {
+        self.put_device(device, &DeviceRecord::linked_now(None))
+            .await
+    }
+
+    /// Record `device` as one of the identity's devices, described by
+    /// `record` — replacing any earlier description.
+    pub async fn put_device(&self, device: NodeId, record: &DeviceRecord) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(
+                self.author,
+                device_key(&device).into_bytes(),
+                serde_json::to_vec(record).map_err(NodeError::storage)?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// The description of `device`, if it is listed and the record's payload
+    /// has arrived. A record of an earlier build, which carried a bare
+    /// marker, reads as an empty description; anything else undecodable is
+    /// an error, as for [`get_ticket`](Self::get_ticket).
+    pub async fn device_record(&self, device: NodeId) -> Result<Option<DeviceRecord>, NodeError> {
+        let Some(bytes) =
+            read_payload(&self.doc, &self.blobs, device_key(&device).as_bytes()).await?
+        else {
+            return Ok(None);
+        };
+        if bytes == BARE_DEVICE_MARKER {
+            return Ok(Some(DeviceRecord::default()));
+        }
+        Ok(Some(
+            serde_json::from_slice(&bytes).context("undecodable device record")?,
+        ))
+    }
+
+    /// Remove `device` from the identity's devices: a revocation record
+    /// first, then the tombstone over its device record. Both replicate like
+    /// any entry; the revocation record is what keeps refusing the device
+    /// on every replica once the tombstone has made it non-own, and it is
+    /// never withdrawn — re-linking the same node is not a path back.
+    pub async fn remove_device(&self, device: NodeId) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(self.author, revoked_key(&device).into_bytes(), vec![1u8])
+            .await?;
         self.doc
-            .set_bytes(self.author, device_key(&device).into_bytes(), vec![1u8])
+            .del(self.author, device_key(&device).into_bytes())
             .await?;
         Ok(())
     }
 
+    /// Whether `device` has been removed from the identity (record-level,
+    /// like [`list_devices`](Self::list_devices)).
+    pub async fn is_revoked(&self, device: NodeId) -> Result<bool, NodeError> {
+        let query = Query::single_latest_per_key().key_exact(revoked_key(&device).as_bytes());
+        Ok(self.doc.get_one(query).await?.is_some())
+    }
+
     /// List the identity's known devices (record-level — available as soon as
     /// the records sync).
-    pub async fn list_devices(&self) -> Result<Vec<NodeId>> {
+    pub async fn list_devices(&self) -> Result<Vec<NodeId>, NodeError> {
         let query = Query::single_latest_per_key().key_prefix(DEVICES_PREFIX.as_bytes());
         let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
         let mut devices = Vec::new();
@@ -161,7 +376,7 @@ impl PrivateMetadataStore {
     /// Record a live connection to `peer`. The payload is an opaque marker
     /// (the key carries the identity), replicated to the identity's other
     /// devices like any directory entry.
-    pub async fn connect(&self, peer: PdnId) -> Result<()> {
+    pub async fn connect(&self, peer: PdnId) -> Result<(), NodeError> {
         self.doc
             .set_bytes(self.author, connection_key(&peer).into_bytes(), vec![1u8])
             .await?;
@@ -170,7 +385,7 @@ impl PrivateMetadataStore {
 
     /// Drop the connection to `peer` — writes a tombstone (empty entry) that
     /// replicates like any other entry.
-    pub async fn disconnect(&self, peer: PdnId) -> Result<()> {
+    pub async fn disconnect(&self, peer: PdnId) -> Result<(), NodeError> {
         self.doc
             .del(self.author, connection_key(&peer).into_bytes())
             .await?;
@@ -182,14 +397,14 @@ impl PrivateMetadataStore {
     /// A record-level check: it returns `true` as soon as the connect entry
     /// is present, without waiting on the marker blob to download. A
     /// tombstone (latest entry empty) reads as not connected.
-    pub async fn is_connected(&self, peer: PdnId) -> Result<bool> {
+    pub async fn is_connected(&self, peer: PdnId) -> Result<bool, NodeError> {
         let query = Query::single_latest_per_key().key_exact(connection_key(&peer).as_bytes());
         Ok(self.doc.get_one(query).await?.is_some())
     }
 
     /// List currently live connections (record-level, like
     /// [`is_connected`](Self::is_connected)).
-    pub async fn list_connections(&self) -> Result<Vec<PdnId>> {
+    pub async fn list_connections(&self) -> Result<Vec<PdnId>, NodeError> {
         let query = Query::single_latest_per_key().key_prefix(CONNECTIONS_PREFIX.as_bytes());
         let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
         let mut peers = Vec::new();
@@ -201,9 +416,50 @@ impl PrivateMetadataStore {
         Ok(peers)
     }
 
+    /// Record that `observer`, a device of the identity, last finished a
+    /// sync with `device` at `at` (unix ms) — replacing its earlier record.
+    /// Descriptive, like a device record: what a device saw survives its
+    /// restarts, and says nothing about access.
+    pub async fn put_sighting(
+        &self,
+        observer: NodeId,
+        device: NodeId,
+        at: u64,
+    ) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(
+                self.author,
+                sighting_key(&observer, &device).into_bytes(),
+                serde_json::to_vec(&at).map_err(NodeError::storage)?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// When `observer` last recorded a sync with `device`, unix ms — `None`
+    /// while it has recorded none, or the payload has not arrived.
+    pub async fn sighting(
+        &self,
+        observer: NodeId,
+        device: NodeId,
+    ) -> Result<Option<u64>, NodeError> {
+        let Some(bytes) = read_payload(
+            &self.doc,
+            &self.blobs,
+            sighting_key(&observer, &device).as_bytes(),
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(
+            serde_json::from_slice(&bytes).context("undecodable sighting record")?,
+        ))
+    }
+
     /// Store the `ticket` for store `kind` (e.g. `"data"`), so the
     /// identity's other devices can discover and import that store.
-    pub async fn put_ticket(&self, kind: &str, ticket: &DocTicket) -> Result<()> {
+    pub async fn put_ticket(&self, kind: &str, ticket: &DocTicket) -> Result<(), NodeError> {
         self.doc
             .set_bytes(
                 self.author,
@@ -214,6 +470,73 @@ impl PrivateMetadataStore {
         Ok(())
     }
 
+    /// Store the encoded key event at sequence number `sn` of the identity's
+    /// key event log. The log's encoding and its verification belong to the
+    /// layer above; here an event is opaque bytes at its position.
+    ///
+    /// Each device writes its own record, so two devices appending at one
+    /// position while apart both keep theirs: the fork is the reader's to
+    /// settle, from every record [`key_events`](Self::key_events) returns.
+    pub async fn put_key_event(&self, sn: u64, event: &[u8]) -> Result<(), NodeError> {
+        self.doc
+            .set_bytes(
+                self.author,
+                key_event_key(KEL_PREFIX, sn).into_bytes(),
+                event.to_vec(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// The encoded key events recorded at each sequence number, in sequence
+    /// order: every distinct record at a position whose payload is readable
+    /// here, from sequence number 0 up to the first position with none — a
+    /// record not yet synced, or a payload still in flight. Always a prefix
+    /// of the log, never a log with a hole in it. More than one record at a
+    /// position is a fork, or garbage beside the event; which record
+    /// continues the log is for the layer that can verify them to decide.
+    pub async fn key_events(&self) -> Result<Vec<Vec<Vec<u8>>>, NodeError> {
+        Ok(read_key_events(&self.doc, &self.blobs, KEL_PREFIX).await?)
+    }
+
+    /// Copy every live record of this directory onto a fresh replica on
+    /// `node` — the re-keying half of a revocation: the copy has a new
+    /// namespace secret, which no device removed before the copy ever held.
+    /// Tombstoned records stay behind, and so do successor records. Fails
+    /// rather than copy short while a record's payload has not arrived here.
+    pub async fn rekeyed(&self, node: &SyncNode) -> Result<Self, NodeError> {
+        let successor = Self::create(node).await?;
+        if let Err(err) = copy_records(
+            &self.doc,
+            &successor.doc,
+            successor.author,
+            &self.blobs,
+            |key| !key.starts_with(SUCCESSOR_PREFIX.as_bytes()),
+        )
+        .await
+        {
+            let _ = node.forget_doc(successor.namespace()).await;
+            return Err(err.into());
+        }
+        Ok(successor)
+    }
+
+    /// Mark this directory retired in favor of `successor`. Written last by
+    /// a revocation, after the successor holds everything.
+    pub async fn put_successor(&self, successor: &Successor) -> Result<(), NodeError> {
+        put_successor_in(&self.doc, self.author, successor).await
+    }
+
+    /// The successors this directory was retired in favor of, as far as
+    /// their payloads have arrived — claims, not facts. Unlike the
+    /// directory's other records these are not only own devices' writing:
+    /// a revoked device keeps the retired directory's write secret and may
+    /// add one. An undecodable record is therefore skipped rather than an
+    /// error, and a decoded one is followed only once its proof verifies.
+    pub async fn successors(&self) -> Result<Vec<Successor>, NodeError> {
+        Ok(read_successors(&self.doc, &self.blobs).await?)
+    }
+
     /// Subscribe to this store's replica events (inserts, sync sessions).
     /// Crate-private on purpose: the fork's event type stays behind this
     /// layer; consumers get the two narrow properties stated by
@@ -230,7 +553,9 @@ impl PrivateMetadataStore {
     /// stays behind this layer, and "something changed, look again" is
     /// exactly what a re-reading consumer needs. An `Err` item reports the
     /// subscription failing; the stream ends when the node shuts down.
-    pub async fn changes(&self) -> Result<impl Stream<Item = Result<()>> + Send + Unpin + 'static> {
+    pub async fn changes(
+        &self,
+    ) -> Result<impl Stream<Item = Result<(), NodeError>> + Send + Unpin + 'static, NodeError> {
         let events = self.events().await?;
         Ok(events.filter_map(|event| match event {
             Ok(
@@ -241,7 +566,29 @@ impl PrivateMetadataStore {
             // Sync-session and neighbor bookkeeping is not a change of the
             // directory's contents.
             Ok(_) => None,
-            Err(err) => Some(Err(err)),
+            Err(err) => Some(Err(NodeError::storage(err))),
+        }))
+    }
+
+    /// One item per successful sync session of this directory: the device
+    /// it ran with — only the identity's own devices hold the directory —
+    /// and when it finished. Like [`changes`](Self::changes), the fork's
+    /// event vocabulary stays behind this layer. An `Err` item reports the
+    /// subscription failing; the stream ends when the node shuts down.
+    pub async fn syncs(
+        &self,
+    ) -> Result<
+        impl Stream<Item = Result<(NodeId, SystemTime), NodeError>> + Send + Unpin + 'static,
+        NodeError,
+    > {
+        let events = self.events().await?;
+        Ok(events.filter_map(|event| match event {
+            Ok(LiveEvent::SyncFinished(sync)) if sync.result.is_ok() => Some(Ok((
+                NodeId::from_bytes(*sync.peer.as_bytes()),
+                sync.finished,
+            ))),
+            Ok(_) => None,
+            Err(err) => Some(Err(NodeError::storage(err))),
         }))
     }
 
@@ -264,7 +611,11 @@ impl PrivateMetadataStore {
     /// replica already starts its first session and enrols it in the
     /// periodic reconcile pass, so a failed first exchange is re-dialed
     /// within this wait's own budget.
-    pub async fn wait_caught_up(&self, since: SystemTime, timeout: Duration) -> Result<()> {
+    pub async fn wait_caught_up(
+        &self,
+        since: SystemTime,
+        timeout: Duration,
+    ) -> Result<(), NodeError> {
         let mut events = self.events().await?;
         let deadline = Instant::now() + timeout;
         loop {
@@ -291,7 +642,7 @@ impl PrivateMetadataStore {
     /// what routing the identity's devices can discover here — and what
     /// must not appear (no tickets to another identity's data stores; those
     /// live in connection metadata stores).
-    pub async fn list_ticket_kinds(&self) -> Result<Vec<String>> {
+    pub async fn list_ticket_kinds(&self) -> Result<Vec<String>, NodeError> {
         let query = Query::single_latest_per_key().key_prefix(TICKETS_PREFIX.as_bytes());
         let mut stream = std::pin::pin!(self.doc.get_many(query).await?);
         let mut kinds = Vec::new();
@@ -315,12 +666,104 @@ impl PrivateMetadataStore {
     /// implementation's own bug. The counterparty-written grants of
     /// [`ConnectionMetadataStore::read_grant`](crate::ConnectionMetadataStore::read_grant)
     /// deliberately read the other way.
-    pub async fn get_ticket(&self, kind: &str) -> Result<Option<DocTicket>> {
+    pub async fn get_ticket(&self, kind: &str) -> Result<Option<DocTicket>, NodeError> {
         let Some(bytes) = read_payload(&self.doc, &self.blobs, ticket_key(kind).as_bytes()).await?
         else {
             return Ok(None);
         };
-        let ticket = std::str::from_utf8(&bytes)?.parse::<DocTicket>()?;
+        let ticket = std::str::from_utf8(&bytes)
+            .map_err(NodeError::storage)?
+            .parse::<DocTicket>()
+            .map_err(NodeError::storage)?;
         Ok(Some(ticket))
     }
 }
+
+/// The encoded key events under `prefix` in `doc`, per sequence number in
+/// sequence order, from sequence number 0 up to the first one with no
+/// readable record — the one reading of the directory's log, and of the
+/// logs a connection metadata store carries.
+///
+/// Every author's record is read, not only the latest per key: a later
+/// write at a sequence number would otherwise shadow an earlier one, and
+/// a log forked by two devices' concurrent appends would read as whichever
+/// landed last. The records at a position come ordered by content hash, so
+/// every replica holding the same ones reads them alike.
+pub(crate) async fn read_key_events(
+    doc: &Doc,
+    blobs: &iroh_blobs::api::Store,
+    prefix: &str,
+) -> Result<Vec<Vec<Vec<u8>>>> {
+    let query = Query::all().key_prefix(prefix.as_bytes());
+    let mut recorded: BTreeMap<u64, BTreeSet<Hash>> = BTreeMap::new();
+    {
+        let mut stream = std::pin::pin!(doc.get_many(query).await?);
+        while let Some(entry) = stream.next().await {
+            let entry = entry?;
+            // An empty record is a tombstone, not an event.
+            if entry.content_len() == 0 {
+                continue;
+            }
+            if let Some(sn) = key_event_sn_of(prefix, entry.key()) {
+                recorded.entry(sn).or_default().insert(entry.content_hash());
+            }
+        }
+    }
+    let mut events = Vec::new();
+    for (expected, (sn, hashes)) in (0u64..).zip(recorded) {
+        if sn != expected {
+            break;
+        }
+        let mut readable = Vec::new();
+        for hash in hashes {
+            if blobs.has(hash).await? {
+                readable.push(blobs.get_bytes(hash).await?.to_vec());
+            }
+        }
+        if readable.is_empty() {
+            break;
+        }
+        events.push(readable);
+    }
+    Ok(events)
+}
+
+/// Record `successor` in the retired replica `doc`, under its own key.
+pub(crate) async fn put_successor_in(
+    doc: &Doc,
+    author: AuthorId,
+    successor: &Successor,
+) -> Result<(), NodeError> {
+    doc.set_bytes(
+        author,
+        successor_key(&successor.namespace).into_bytes(),
+        serde_json::to_vec(successor).map_err(NodeError::storage)?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// The successor records in `doc` whose payloads have arrived and decode.
+pub(crate) async fn read_successors(
+    doc: &Doc,
+    blobs: &iroh_blobs::api::Store,
+) -> Result<Vec<Successor>> {
+    let query = Query::single_latest_per_key().key_prefix(SUCCESSOR_PREFIX.as_bytes());
+    let mut keys = Vec::new();
+    {
+        let mut stream = std::pin::pin!(doc.get_many(query).await?);
+        while let Some(entry) = stream.next().await {
+            keys.push(entry?.key().to_vec());
+        }
+    }
+    let mut successors = Vec::new();
+    for key in keys {
+        let Some(bytes) = read_payload(doc, blobs, &key).await? else {
+            continue;
+        };
+        if let Ok(successor) = serde_json::from_slice(&bytes) {
+            successors.push(successor);
+        }
+    }
+    Ok(successors)
+}
@@ -13,9 +13,9 @@ use std::time::Duration;
 use anyhow::Result;
 use data_layer::{
     claim_id_of, AddrInfoOptions, ConnectionMetadataStore, DocTicket, PrivateMetadataStore,
-    ReadGrant, ShareMode, SpawnOptions, SyncNode,
+    ReadGrant, ShareMode, SpawnOptions, Successor, SyncNode,
 };
-use pdn_types::{EntryPath, NonEmpty, PdnId};
+use pdn_types::{EntryPath, NodeId, NonEmpty, OperationalKey, PdnId, PdnIdentityProof};
 use test_utils::{eventually, ids, wait_entry_is};
 
 /// The reconcile cadence the sibling-serving scenario runs at. A grantee
@@ -27,18 +27,22 @@ const RECONCILE: Duration = Duration::from_millis(500);
 
 /// Spawn a node with the sibling-serving scenario's short reconcile cadence.
 async fn spawn_node() -> Result<SyncNode> {
-    SyncNode::spawn_with_options(SpawnOptions {
+    let node = SyncNode::spawn_with_options(SpawnOptions {
         reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
     })
-    .await
+    .await?;
+    Ok(node)
 }
 
 /// Create a data namespace for `issuer` on `node` and return its read
 /// ticket — a real whole-store ticket for grants to carry.
 async fn data_ticket(node: &mut SyncNode, issuer: PdnId) -> Result<DocTicket> {
     node.create_namespace(issuer).await?;
-    node.share_ticket(issuer, ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
-        .await
+    let ticket = node
+        .share_ticket(issuer, ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
+        .await?;
+    Ok(ticket)
 }
 
 /// The nominal claim these store-level scenarios grant on: the store
@@ -51,6 +55,7 @@ fn nominal_grant(issuer: PdnId, audience: PdnId) -> ReadGrant {
         audience,
         claims: NonEmpty::new(claim_id_of(&issuer, &path)),
         write: false,
+        window: None,
     }
 }
 
@@ -309,6 +314,7 @@ async fn one_grant_record_replaces_and_withdraws_atomically() -> Result<()> {
         audience: ids::BOB,
         claims: NonEmpty::new(claim_id_of(&ids::ALICE, &email)),
         write: false,
+        window: None,
     };
 
     // Published: the counterparty reads the capability and its ticket.
@@ -413,6 +419,53 @@ async fn a_withdrawn_device_record_is_not_resurrected_by_pair_opening() -> Resul
     Ok(())
 }
 
+/// A revocation's re-keying of an own store: the fresh replica carries the
+/// grants, the mirrored key event log and the live device records — not
+/// the withdrawn device's, and not the successor records the retired
+/// replica is left. Those stay readable on the retired replica, one record
+/// per successor claimed.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_rekeyed_store_leaves_the_withdrawn_device_and_successors_behind() -> Result<()> {
+    let mut alice = SyncNode::spawn().await?;
+    let own = ConnectionMetadataStore::create(&alice).await?;
+    let kept = alice.node_id();
+    let revoked = NodeId::from_bytes([0x5e; 32]);
+    own.publish_device(kept).await?;
+    own.publish_device(revoked).await?;
+    own.put_key_event(0, b"inception").await?;
+    let ticket = data_ticket(&mut alice, ids::ALICE).await?;
+    own.publish_grant(&nominal_grant(ids::ALICE, ids::BOB), &ticket)
+        .await?;
+    own.withdraw_device(revoked).await?;
+
+    let moved = own.rekeyed(&alice).await?;
+    assert_ne!(moved.namespace(), own.namespace());
+    assert_eq!(moved.published_devices().await?, vec![kept]);
+    assert_eq!(moved.key_events().await?, vec![vec![b"inception".to_vec()]]);
+    assert!(moved.read_grant(ids::ALICE).await?.is_some());
+
+    // Two claims side by side: neither shadows the other.
+    let claim = |namespace| Successor {
+        namespace,
+        via: kept,
+        proof: PdnIdentityProof {
+            key: OperationalKey::from_bytes([0x0c; 32]),
+            signature: vec![0; 64],
+        },
+    };
+    own.put_successor(&claim(moved.namespace())).await?;
+    own.put_successor(&claim(own.namespace())).await?;
+    let mut successors = own.successors().await?;
+    successors.sort_by_key(|successor| successor.namespace.to_string());
+    let mut expected = vec![claim(moved.namespace()), claim(own.namespace())];
+    expected.sort_by_key(|successor| successor.namespace.to_string());
+    assert_eq!(successors, expected);
+    assert!(own.rekeyed(&alice).await?.successors().await?.is_empty());
+
+    alice.shutdown().await?;
+    Ok(())
+}
+
 /// The access pairs of Invariant 3, each allowed path with its tightest
 /// denial. Write: the issuer's second device writes via the directory's
 /// write ticket ⟷ the counterparty, holding only the read ticket, cannot
@@ -578,6 +631,7 @@ async fn a_sibling_session_keeps_scope_withdrawal_and_audience() -> Result<()> {
         audience: ids::ALICE,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
         write: false,
+        window: None,
     };
     b_own.publish_grant(&grant, &data_read).await?;
 
@@ -0,0 +1,157 @@
+//! The node-wide entry feed and the watches narrowing it: a write shows up
+//! on the writing node's feed and, once replicated, on the importing
+//! device's — under the issuer the replica is bound to, as an insert, then
+//! an update, each followed by its payload's readiness — a deletion
+//! reports its own path alone, and a watch sees its own issuer and prefix
+//! alone.
+
+use anyhow::{Context, Result};
+use data_layer::{AddrInfoOptions, NodeError, ShareMode, SyncNode};
+use futures_lite::StreamExt;
+use pdn_types::{EntryEvent, EntryEventKind, EntryOrigin, EntryPath};
+use test_utils::{ids, TIMEOUT};
+use tokio::sync::broadcast;
+
+/// The next event on `feed` at `path` of `kind`, skipping any other.
+async fn next_at(
+    feed: &mut broadcast::Receiver<EntryEvent>,
+    path: &EntryPath,
+    kind: EntryEventKind,
+) -> Result<EntryEvent> {
+    let wait = async {
+        loop {
+            let event = feed.recv().await?;
+            if event.info.path == *path && event.kind == kind {
+                return Ok(event);
+            }
+        }
+    };
+    tokio::time::timeout(TIMEOUT, wait).await?
+}
+
+/// The next change on `feed` at `path`, payload readiness skipped.
+async fn next_change_at(
+    feed: &mut broadcast::Receiver<EntryEvent>,
+    path: &EntryPath,
+) -> Result<EntryEvent> {
+    let wait = async {
+        loop {
+            let event = feed.recv().await?;
+            if event.info.path == *path && event.kind != EntryEventKind::PayloadReady {
+                return Ok(event);
+            }
+        }
+    };
+    tokio::time::timeout(TIMEOUT, wait).await?
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_reach_the_entry_feed_locally_and_by_sync() -> Result<()> {
+    let phone = SyncNode::spawn().await?;
+    let laptop = SyncNode::spawn().await?;
+    let mut phone_feed = phone.subscribe_entries();
+    let mut laptop_feed = laptop.subscribe_entries();
+
+    let author = phone.create_author().await?;
+    phone.create_namespace(ids::ALICE).await?;
+    let ticket = phone
+        .share_ticket(
+            ids::ALICE,
+            ShareMode::Write,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+    laptop.import_namespace(ids::ALICE, ticket).await?;
+
+    let name = EntryPath::new("contact/name")?;
+    phone.write(ids::ALICE, author, &name, b"Alice").await?;
+
+    let local = next_at(&mut phone_feed, &name, EntryEventKind::Inserted).await?;
+    assert_eq!(local.info.issuer, ids::ALICE);
+    assert_eq!(local.info.payload_len, 5);
+    assert_eq!(local.origin, EntryOrigin::Local);
+    next_at(&mut phone_feed, &name, EntryEventKind::PayloadReady).await?;
+
+    let remote = next_at(&mut laptop_feed, &name, EntryEventKind::Inserted).await?;
+    assert_eq!(remote.info, local.info);
+    assert_eq!(remote.origin, EntryOrigin::Remote(phone.node_id()));
+    next_at(&mut laptop_feed, &name, EntryEventKind::PayloadReady).await?;
+    assert_eq!(
+        laptop.read(ids::ALICE, &name).await?,
+        Some(b"Alice".to_vec())
+    );
+
+    phone.write(ids::ALICE, author, &name, b"Alice A.").await?;
+    let updated = next_at(&mut laptop_feed, &name, EntryEventKind::Updated).await?;
+    assert_eq!(updated.info.payload_len, 8);
+
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_deletion_reports_its_own_path_alone() -> Result<()> {
+    let node = SyncNode::spawn().await?;
+    let author = node.create_author().await?;
+    node.create_namespace(ids::ALICE).await?;
+    let mut feed = node.subscribe_entries();
+
+    // `contacts/ab` shares bytes with `contacts/a`, and `contacts/a/x`
+    // sits below it: the store's sweep takes both, the deletion neither.
+    let deleted = EntryPath::new("contacts/a")?;
+    let sibling = EntryPath::new("contacts/ab")?;
+    let below = EntryPath::new("contacts/a/x")?;
+    for path in [&deleted, &sibling, &below] {
+        node.write(ids::ALICE, author, path, b"v").await?;
+        next_at(&mut feed, path, EntryEventKind::Inserted).await?;
+    }
+    assert!(node.delete(ids::ALICE, author, &deleted).await?);
+    next_at(&mut feed, &deleted, EntryEventKind::Deleted).await?;
+
+    // Never reported gone: written back as updates, and still held, so
+    // the next write at either is an update too, not an insert.
+    for path in [&sibling, &below] {
+        let written_back = next_change_at(&mut feed, path).await?;
+        assert_eq!(written_back.kind, EntryEventKind::Updated);
+        node.write(ids::ALICE, author, path, b"w").await?;
+        let event = next_change_at(&mut feed, path).await?;
+        assert_eq!(event.kind, EntryEventKind::Updated);
+        assert_eq!(node.read(ids::ALICE, path).await?, Some(b"w".to_vec()));
+    }
+    assert_eq!(node.read(ids::ALICE, &deleted).await?, None);
+
+    node.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_watch_sees_its_issuer_and_prefix_alone() -> Result<()> {
+    let node = SyncNode::spawn().await?;
+    let author = node.create_author().await?;
+    node.create_namespace(ids::ALICE).await?;
+    node.create_namespace(ids::BOB).await?;
+
+    let refused = node.watch_entries(ids::DAVE, None);
+    let err = refused.err().context("an unbound issuer was watched")?;
+    assert!(matches!(err, NodeError::UnknownIssuer(_)));
+
+    let contacts = EntryPath::new("contacts")?;
+    let mut watch = node.watch_entries(ids::ALICE, Some(&contacts))?;
+    let outside = EntryPath::new("contactsx/c")?;
+    let elsewhere = EntryPath::new("contacts/b")?;
+    let wanted = EntryPath::new("contacts/a")?;
+    node.write(ids::ALICE, author, &outside, b"no").await?;
+    node.write(ids::BOB, author, &elsewhere, b"no").await?;
+    node.write(ids::ALICE, author, &wanted, b"yes").await?;
+
+    let first = tokio::time::timeout(TIMEOUT, watch.next())
+        .await?
+        .context("the watch ended")??;
+    assert_eq!((first.info.issuer, first.info.path), (ids::ALICE, wanted));
+    assert_eq!(first.kind, EntryEventKind::Inserted);
+
+    drop(watch);
+    node.shutdown().await?;
+    Ok(())
+}
@@ -15,8 +15,8 @@ use std::sync::{Arc, Mutex, PoisonError};
 
 use anyhow::Result;
 use data_layer::{
-    AcceptError, AddrInfoOptions, AlpnTaken, Connection, ProtocolHandler, ShareMode, SyncNode,
-    BUILT_IN_ALPNS,
+    AcceptError, AddrInfoOptions, AlpnTaken, Connection, NodeError, ProtocolHandler, ShareMode,
+    SyncNode, BUILT_IN_ALPNS,
 };
 use pdn_types::{EntryPath, NodeId};
 use test_utils::{ids, wait_entry_is};
@@ -197,8 +197,10 @@ async fn alpn_collisions_are_refused_at_spawn() -> Result<()> {
         )])
         .await
         .expect_err("a built-in ALPN must be refused at spawn");
-        let taken: &AlpnTaken = err.downcast_ref().expect("typed AlpnTaken error");
-        assert_eq!(taken.alpn, reserved);
+        assert!(
+            matches!(&err, NodeError::AlpnTaken(AlpnTaken { alpn }) if alpn == reserved),
+            "expected the typed AlpnTaken error, got: {err}"
+        );
     }
 
     let err = SyncNode::spawn_with_protocols(vec![
@@ -207,7 +209,9 @@ async fn alpn_collisions_are_refused_at_spawn() -> Result<()> {
     ])
     .await
     .expect_err("a duplicate extra ALPN must be refused at spawn");
-    let taken: &AlpnTaken = err.downcast_ref().expect("typed AlpnTaken error");
-    assert_eq!(taken.alpn, ECHO_ALPN);
+    assert!(
+        matches!(&err, NodeError::AlpnTaken(AlpnTaken { alpn }) if alpn == ECHO_ALPN),
+        "expected the typed AlpnTaken error, got: {err}"
+    );
     Ok(())
 }
@@ -3,10 +3,12 @@
 //! Single-node scenarios: listing yields exactly the written paths as
 //! metadata, the prefix filter matches whole components, and — the paired
 //! deny — listing an issuer with no data store on this node fails with
-//! `UnknownIssuer`.
+//! `UnknownIssuer`. The same holds through the node's `DataLayer` handle,
+//! whose listing streams.
 
 use anyhow::Result;
-use data_layer::{SyncNode, UnknownIssuer};
+use data_layer::{DataLayer as _, DataLayerError, NodeError, SyncNode};
+use futures_lite::StreamExt as _;
 use pdn_types::{EntryInfo, EntryPath};
 use test_utils::ids;
 
@@ -62,7 +64,56 @@ async fn listing_yields_written_paths_and_prefix_matches_whole_components() -> R
     // Paired deny: an issuer with no data store on this node is refused as
     // specifically unknown, not a generic failure.
     let err = node.list(ids::BOB, None).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(err, NodeError::UnknownIssuer(_)));
+
+    node.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_node_serves_the_data_layer_contract() -> Result<()> {
+    let node = SyncNode::spawn().await?;
+    let author = node.create_author().await?;
+    node.create_namespace(ids::ALICE).await?;
+    let layer = node.data_layer(author);
+
+    let email = EntryPath::new("contact/email")?;
+    let phone = EntryPath::new("contacts/phone")?;
+    layer
+        .insert_entry(ids::ALICE, &email, b"a@example.org")
+        .await?;
+    layer.insert_entry(ids::ALICE, &phone, b"+1").await?;
+    assert_eq!(
+        layer.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"a@example.org".as_ref())
+    );
+
+    // The listing streams, under the same whole-component prefix rule.
+    let mut listing = layer
+        .list_entries(ids::ALICE, Some(&EntryPath::new("contact")?))
+        .await?;
+    let mut listed = Vec::new();
+    while let Some(entry) = listing.next().await {
+        listed.push(entry?);
+    }
+    assert_eq!(
+        listed,
+        [EntryInfo {
+            issuer: ids::ALICE,
+            path: email.clone(),
+            payload_len: 13,
+        }]
+    );
+
+    assert!(layer.delete_entry(ids::ALICE, &email).await?);
+    assert_eq!(layer.get_entry(ids::ALICE, &email).await?, None);
+
+    // Paired deny: the unknown issuer is a variant, not a string.
+    let refused = layer.get_entry(ids::BOB, &email).await;
+    assert!(matches!(
+        refused,
+        Err(DataLayerError::UnknownIssuer { issuer }) if issuer == ids::BOB
+    ));
 
     node.shutdown().await?;
     Ok(())
@@ -11,7 +11,7 @@
 
 use anyhow::Result;
 use data_layer::{
-    AddrInfoOptions, AuthorId, DocTicket, PrivateMetadataStore, ShareMode, SyncNode, UnknownIssuer,
+    AddrInfoOptions, AuthorId, DocTicket, NodeError, PrivateMetadataStore, ShareMode, SyncNode,
 };
 use pdn_types::{EntryPath, PdnId};
 use test_utils::{ids, wait_connected, wait_devices, wait_entry_is};
@@ -87,7 +87,7 @@ async fn multi_identity_two_devices() -> Result<()> {
     // here (specifically unknown, not just any error).
     assert!(!laptop_work_dir.is_connected(ids::CAROL).await?);
     let err = laptop.read(ids::ALICE_AT_LEISURE, &path).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(err, NodeError::UnknownIssuer(_)));
 
     // Second identity joins the already-joined node by its own act.
     let laptop_leisure_dir = PrivateMetadataStore::import(&laptop, leisure_ticket).await?;
@@ -182,21 +182,21 @@ async fn forgetting_a_namespace_unregisters_its_issuer() -> Result<()> {
     laptop.forget_namespace(ids::ALICE_AT_WORK).await?;
     let author = laptop.create_author().await?;
     let read_err = laptop.read(ids::ALICE_AT_WORK, &path).await.unwrap_err();
-    assert!(read_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(read_err, NodeError::UnknownIssuer(_)));
     let write_err = laptop
         .write(ids::ALICE_AT_WORK, author, &path, b"residue")
         .await
         .unwrap_err();
-    assert!(write_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(write_err, NodeError::UnknownIssuer(_)));
     let list_err = laptop.list(ids::ALICE_AT_WORK, None).await.unwrap_err();
-    assert!(list_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(list_err, NodeError::UnknownIssuer(_)));
 
     // Forgetting an issuer that is (now) unknown refuses the same way.
     let again = laptop
         .forget_namespace(ids::ALICE_AT_WORK)
         .await
         .unwrap_err();
-    assert!(again.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(again, NodeError::UnknownIssuer(_)));
 
     // The co-hosted issuer is untouched: its entries remain readable.
     assert!(
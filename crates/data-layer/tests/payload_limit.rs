@@ -0,0 +1,137 @@
+//! The payload ceiling: a node refuses a local write over its
+//! `max_payload` with the typed error, and a larger record arriving by sync
+//! from a node with a higher ceiling is refused at ingest — the entry it
+//! would have replaced stays as it was.
+
+use anyhow::{Context, Result};
+use data_layer::{
+    AddrInfoOptions, DataLayer as _, DataLayerError, NodeError, ShareMode, SpawnOptions, SyncNode,
+};
+use pdn_types::{EntryEventKind, EntryPath};
+use test_utils::{ids, wait_entry_is};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oversized_payloads_are_refused_locally_and_at_ingest() -> Result<()> {
+    let phone = SyncNode::spawn().await?;
+    let laptop = SyncNode::spawn_with_options(SpawnOptions {
+        max_payload: 8,
+        ..SpawnOptions::default()
+    })
+    .await?;
+    let mut laptop_feed = laptop.subscribe_entries();
+
+    let author = phone.create_author().await?;
+    phone.create_namespace(ids::ALICE).await?;
+    let ticket = phone
+        .share_ticket(
+            ids::ALICE,
+            ShareMode::Write,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+    laptop.import_namespace(ids::ALICE, ticket).await?;
+
+    // Local: refused before anything is written, by the node and by its
+    // data layer alike.
+    let note = EntryPath::new("notes/a")?;
+    let laptop_author = laptop.create_author().await?;
+    let err = laptop
+        .write(ids::ALICE, laptop_author, &note, b"nine byte")
+        .await
+        .err()
+        .context("an oversized write was accepted")?;
+    assert!(matches!(
+        err,
+        NodeError::PayloadTooLarge { size: 9, max: 8 }
+    ));
+    assert!(matches!(
+        laptop
+            .data_layer(laptop_author)
+            .insert_entry(ids::ALICE, &note, b"nine byte")
+            .await,
+        Err(DataLayerError::PayloadTooLarge { size: 9, max: 8 })
+    ));
+    assert_eq!(laptop.read(ids::ALICE, &note).await?, None);
+
+    // By sync: a record within the laptop's ceiling arrives as usual...
+    phone.write(ids::ALICE, author, &note, b"short").await?;
+    assert!(
+        wait_entry_is(&laptop, ids::ALICE, &note, b"short").await?,
+        "laptop did not receive the short note"
+    );
+
+    // ...and one over it, accepted by the phone, is refused at ingest: the
+    // short note stays. A later record behind it shows the sync went by.
+    phone
+        .write(ids::ALICE, author, &note, b"far too long for the laptop")
+        .await?;
+    let later = EntryPath::new("notes/b")?;
+    phone.write(ids::ALICE, author, &later, b"later").await?;
+    assert!(
+        wait_entry_is(&laptop, ids::ALICE, &later, b"later").await?,
+        "laptop did not receive the later note"
+    );
+    assert_eq!(
+        laptop.read(ids::ALICE, &note).await?.as_deref(),
+        Some(&b"short"[..]),
+        "the oversized note displaced the one the laptop held"
+    );
+    assert_eq!(laptop.list(ids::ALICE, None).await?.len(), 2);
+    let displaced = std::iter::from_fn(|| laptop_feed.try_recv().ok())
+        .any(|event| event.info.path == note && event.kind == EntryEventKind::Deleted);
+    assert!(!displaced, "the oversized note reported as a deletion");
+
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
+
+/// A record a replica already held when its node came back under a lower
+/// ceiling is quarantined — it neither reads nor lists — yet deleting by
+/// prefix clears it, as deleting its path does, and leaves the record
+/// beside it whose key it prefixes.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_quarantined_record_is_deleted_by_prefix_as_by_path() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let node = SyncNode::spawn_with_options(SpawnOptions {
+        storage: Some(root.path().to_path_buf()),
+        ..SpawnOptions::default()
+    })
+    .await?;
+    let author = node.default_author().await?;
+    node.create_namespace(ids::ALICE).await?;
+    let namespace = node
+        .share_ticket(ids::ALICE, ShareMode::Read, AddrInfoOptions::Id)
+        .await?
+        .capability
+        .id();
+    let long = EntryPath::new("notes/a")?;
+    let beside = EntryPath::new("notes/ab")?;
+    node.write(ids::ALICE, author, &long, b"nine byte").await?;
+    node.write(ids::ALICE, author, &beside, b"short").await?;
+    node.shutdown().await?;
+
+    let node = SyncNode::spawn_with_options(SpawnOptions {
+        storage: Some(root.path().to_path_buf()),
+        max_payload: 8,
+        ..SpawnOptions::default()
+    })
+    .await?;
+    let author = node.default_author().await?;
+    node.open_namespace(ids::ALICE, namespace).await?;
+    assert_eq!(node.read(ids::ALICE, &long).await?, None);
+    assert_eq!(node.list(ids::ALICE, None).await?.len(), 1);
+
+    let deleted = node
+        .delete_prefix(ids::ALICE, author, &EntryPath::new("notes/a")?)
+        .await?;
+    assert_eq!(deleted, 1);
+    assert!(!node.delete(ids::ALICE, author, &long).await?);
+    assert_eq!(
+        node.read(ids::ALICE, &beside).await?.as_deref(),
+        Some(&b"short"[..])
+    );
+
+    node.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! On-disk storage: a node spawned on a storage root survives a restart.
+//!
+//! Single-node scenarios: a respawn on the same root answers to the same
+//! endpoint id and reopens the data namespace and the directory it held,
+//! with every record intact. The paired deny — a node spawned without a
+//! root comes back empty, as a different node, and refuses the reopen.
+
+use anyhow::Result;
+use data_layer::{
+    AddrInfoOptions, NodeError, PrivateMetadataStore, ShareMode, SpawnOptions, SyncNode,
+};
+use pdn_types::EntryPath;
+use test_utils::ids;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn respawn_on_the_same_storage_root_keeps_identity_and_replicas() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = SpawnOptions {
+        storage: Some(root.path().to_path_buf()),
+        ..SpawnOptions::default()
+    };
+
+    let node = SyncNode::spawn_with_options(options.clone()).await?;
+    let node_id = node.node_id();
+    let author = node.create_author().await?;
+    node.create_namespace(ids::ALICE).await?;
+    let data_namespace = node
+        .share_ticket(ids::ALICE, ShareMode::Read, AddrInfoOptions::Id)
+        .await?
+        .capability
+        .id();
+    let email = EntryPath::new("contact/email")?;
+    let phone = EntryPath::new("contact/phone")?;
+    node.write(ids::ALICE, author, &email, b"alice@example.org")
+        .await?;
+    node.write(ids::ALICE, author, &phone, b"+1 555 0100")
+        .await?;
+    let directory = PrivateMetadataStore::create(&node).await?;
+    directory.add_device(node_id).await?;
+    directory.connect(ids::BOB).await?;
+    let directory_namespace = directory.namespace();
+    node.shutdown().await?;
+
+    // The endpoint key under the root is readable by its owner alone.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let key = std::fs::metadata(root.path().join("endpoint.key"))?;
+        assert_eq!(key.permissions().mode() & 0o777, 0o600);
+    }
+
+    let node = SyncNode::spawn_with_options(options).await?;
+    assert_eq!(node.node_id(), node_id);
+
+    // Registrations do not survive — the data namespace is unbound until
+    // reopened — but the replica does.
+    let err = node.read(ids::ALICE, &email).await.unwrap_err();
+    assert!(matches!(err, NodeError::UnknownIssuer(_)));
+    node.open_namespace(ids::ALICE, data_namespace).await?;
+    assert_eq!(
+        node.read(ids::ALICE, &email).await?.as_deref(),
+        Some(&b"alice@example.org"[..])
+    );
+    assert_eq!(
+        node.read(ids::ALICE, &phone).await?.as_deref(),
+        Some(&b"+1 555 0100"[..])
+    );
+    assert_eq!(node.list(ids::ALICE, None).await?.len(), 2);
+
+    let directory = PrivateMetadataStore::open(&node, directory_namespace).await?;
+    assert_eq!(directory.list_devices().await?, vec![node_id]);
+    assert!(directory.is_connected(ids::BOB).await?);
+
+    node.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn in_memory_node_comes_back_empty() -> Result<()> {
+    let node = SyncNode::spawn().await?;
+    let node_id = node.node_id();
+    node.create_namespace(ids::ALICE).await?;
+    let data_namespace = node
+        .share_ticket(ids::ALICE, ShareMode::Read, AddrInfoOptions::Id)
+        .await?
+        .capability
+        .id();
+    node.shutdown().await?;
+
+    let node = SyncNode::spawn().await?;
+    assert_ne!(node.node_id(), node_id);
+    assert!(node
+        .open_namespace(ids::ALICE, data_namespace)
+        .await
+        .is_err());
+
+    node.shutdown().await?;
+    Ok(())
+}
@@ -35,10 +35,12 @@ const RECONCILE: Duration = Duration::from_millis(500);
 
 /// Spawn a node with the test's short reconcile cadence.
 async fn spawn_node() -> Result<SyncNode> {
-    SyncNode::spawn_with_options(SpawnOptions {
+    let node = SyncNode::spawn_with_options(SpawnOptions {
         reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
     })
-    .await
+    .await?;
+    Ok(node)
 }
 
 /// Assemble the serving side: Bob's node hosting his identity — directory
@@ -142,6 +144,7 @@ async fn read_restricted_peer_receives_exactly_the_granted_subset() -> Result<()
         audience: ids::ALICE,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
         write: false,
+        window: None,
     };
     let data_read_ticket = bob
         .share_ticket(
@@ -292,6 +295,7 @@ async fn a_grant_addressed_to_another_identity_serves_nobody() -> Result<()> {
         audience: ids::CAROL,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
         write: false,
+        window: None,
     };
     let data_read_ticket = bob
         .share_ticket(
@@ -345,7 +349,7 @@ async fn a_grant_addressed_to_another_identity_serves_nobody() -> Result<()> {
 /// Denied (the read side is still scoped): Bob's other entries never reach
 /// Alice, proven after her own write demonstrably round-tripped.
 ///
-/// KNOWN GAP, pinned: with no ingest hook installed (ADR-0008), write
+/// KNOWN GAP, pinned: with no write check at ingest (ADR-0008), write
 /// authority is the namespace secret — effectively whole-store — so
 /// Alice's write *outside* her granted claim is accepted, and the test
 /// asserts exactly that undesired behaviour. A red run here means
@@ -376,6 +380,7 @@ async fn write_grant_round_trips_while_reads_stay_scoped() -> Result<()> {
         audience: ids::ALICE,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &note)),
         write: true,
+        window: None,
     };
     let data_write_ticket = bob
         .share_ticket(
@@ -496,6 +501,7 @@ async fn withdrawn_grant_refuses_the_next_session_but_keeps_delivered_data() ->
         audience: ids::ALICE,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
         write: false,
+        window: None,
     };
     let ticket = bob
         .share_ticket(
@@ -625,6 +631,7 @@ async fn swarm_membership_does_not_bypass_the_access_book() -> Result<()> {
         audience: ids::DAVE,
         claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
         write: false,
+        window: None,
     };
     serving
         .own_toward_peer
@@ -671,6 +678,68 @@ async fn swarm_membership_does_not_bypass_the_access_book() -> Result<()> {
     Ok(())
 }
 
+/// A deleted claim reaches its grantee as a tombstone: Bob deletes the
+/// granted entry, and Alice — who received it under the grant — reads it
+/// as gone and no longer lists it. The tombstone sits at the claim's own
+/// key, so the egress filter passes it exactly where it passed the claim.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_deleted_granted_claim_reaches_the_grantee_as_gone() -> Result<()> {
+    let bob = spawn_node().await?;
+    let alice = spawn_node().await?;
+
+    let alice_own = ConnectionMetadataStore::create(&alice).await?;
+    alice_own.publish_device(alice.node_id()).await?;
+    let serving = serving_side(&bob, ids::ALICE, &alice_own).await?;
+    bob.create_namespace(ids::BOB).await?;
+    write_bobs_entries(&bob).await?;
+
+    let email = EntryPath::new(GRANTED)?;
+    let grant = ReadGrant {
+        issuer: ids::BOB,
+        audience: ids::ALICE,
+        claims: NonEmpty::new(claim_id_of(&ids::BOB, &email)),
+        write: false,
+        window: None,
+    };
+    let data_read_ticket = bob
+        .share_ticket(
+            ids::BOB,
+            ShareMode::Read,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+    serving
+        .own_toward_peer
+        .publish_grant(&grant, &data_read_ticket)
+        .await?;
+    let alice_peer =
+        ConnectionMetadataStore::import(&alice, serving.own_read_ticket.clone()).await?;
+    alice.host_connection(ids::ALICE, ids::BOB, &alice_own, &alice_peer)?;
+    let (_grant, received_ticket) = eventually_scoped_grant(&alice_peer, ids::BOB).await?;
+    alice
+        .import_namespace_scoped(ids::BOB, received_ticket)
+        .await?;
+    assert!(
+        eventually(|| async { Ok(alice.read(ids::BOB, &email).await?.is_some()) }).await?,
+        "the granted entry did not reach the granted peer"
+    );
+
+    let author = bob.create_author().await?;
+    assert!(bob.delete(ids::BOB, author, &email).await?);
+    assert!(
+        eventually(|| async {
+            Ok(alice.read(ids::BOB, &email).await?.is_none()
+                && alice.list(ids::BOB, None).await?.is_empty())
+        })
+        .await?,
+        "the grantee did not receive the granted claim's tombstone"
+    );
+
+    bob.shutdown().await?;
+    alice.shutdown().await?;
+    Ok(())
+}
+
 /// Poll the peer store until the scoped grant for `issuer` is readable
 /// (record and payloads arrived), then return it.
 async fn eventually_scoped_grant(
@@ -11,7 +11,9 @@
 use std::time::{Duration, SystemTime};
 
 use anyhow::Result;
-use data_layer::{AddrInfoOptions, CatchUpTimeout, PrivateMetadataStore, ShareMode, SyncNode};
+use data_layer::{
+    AddrInfoOptions, CatchUpTimeout, NodeError, PrivateMetadataStore, ShareMode, SyncNode,
+};
 use pdn_types::EntryPath;
 use test_utils::{eventually, ids, wait_connected, wait_entry_is, TIMEOUT};
 
@@ -153,6 +155,77 @@ async fn concurrent_writes_converge() -> Result<()> {
     Ok(())
 }
 
+/// Deletions replicate as tombstones, whichever device wrote the entry:
+/// the laptop deletes the phone's entries — one by exact path, the rest by
+/// component prefix — and both devices read them as gone, while entries
+/// that merely share a byte prefix with a deleted path survive, on the
+/// deleting author's side too.
+#[tokio::test(flavor = "multi_thread")]
+async fn deletions_replicate_as_tombstones() -> Result<()> {
+    let phone = SyncNode::spawn().await?;
+    let laptop = SyncNode::spawn().await?;
+
+    let phone_author = phone.create_author().await?;
+    let laptop_author = laptop.create_author().await?;
+    phone.create_namespace(ids::ALICE).await?;
+    let ticket = phone
+        .share_ticket(
+            ids::ALICE,
+            ShareMode::Write,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+    laptop.import_namespace(ids::ALICE, ticket).await?;
+
+    let name = EntryPath::new("contact/name")?;
+    let diary = EntryPath::new("notes/diary")?;
+    let todo = EntryPath::new("notes/todo")?;
+    let sibling = EntryPath::new("notesx/kept")?;
+    for path in [&name, &diary, &todo, &sibling] {
+        phone.write(ids::ALICE, phone_author, path, b"v").await?;
+        assert!(
+            wait_entry_is(&laptop, ids::ALICE, path, b"v").await?,
+            "laptop did not receive {path}"
+        );
+    }
+    // The laptop's own record sharing the deleted path's bytes.
+    let nameplate = EntryPath::new("contact/nameplate")?;
+    laptop
+        .write(ids::ALICE, laptop_author, &nameplate, b"kept")
+        .await?;
+
+    assert!(laptop.delete(ids::ALICE, laptop_author, &name).await?);
+    assert!(!laptop.delete(ids::ALICE, laptop_author, &name).await?);
+    let notes = EntryPath::new("notes")?;
+    assert_eq!(
+        laptop
+            .delete_prefix(ids::ALICE, laptop_author, &notes)
+            .await?,
+        2
+    );
+
+    for node in [&laptop, &phone] {
+        let gone = eventually(|| async {
+            Ok(node.read(ids::ALICE, &name).await?.is_none()
+                && node.list(ids::ALICE, Some(&notes)).await?.is_empty())
+        })
+        .await?;
+        assert!(gone, "the deletions did not take effect on every device");
+    }
+    assert!(
+        wait_entry_is(&phone, ids::ALICE, &nameplate, b"kept").await?,
+        "a byte-prefix sibling of a deleted path was swept"
+    );
+    assert_eq!(
+        laptop.read(ids::ALICE, &sibling).await?.as_deref(),
+        Some(b"v".as_ref())
+    );
+
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
+
 /// The directory carries tickets of any kind: published on one device, a
 /// ticket becomes readable on another once its payload arrives (`get_ticket`
 /// is `None` on the record alone). `data` is the kind creation actually
@@ -249,7 +322,7 @@ async fn directory_wait_times_out_without_a_reachable_peer() -> Result<()> {
         .await
         .unwrap_err();
     assert!(
-        err.downcast_ref::<CatchUpTimeout>().is_some(),
+        matches!(err, NodeError::Timeout(CatchUpTimeout)),
         "expected the typed catch-up timeout, got: {err:#}"
     );
 
@@ -257,6 +330,53 @@ async fn directory_wait_times_out_without_a_reachable_peer() -> Result<()> {
     Ok(())
 }
 
+/// Two devices appending at one position of the key event log with no
+/// coordination both keep their record: once synced, each device reads
+/// both at that position, in the same order, for the layer above to settle
+/// — neither write shadows the other, and neither refuses the read.
+#[tokio::test(flavor = "multi_thread")]
+async fn key_event_appends_made_apart_both_survive_sync() -> Result<()> {
+    let phone = SyncNode::spawn().await?;
+    let laptop = SyncNode::spawn().await?;
+
+    let phone_dir = PrivateMetadataStore::create(&phone).await?;
+    phone_dir.put_key_event(0, b"inception").await?;
+    let ticket = phone_dir
+        .share_ticket(ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
+        .await?;
+    let laptop_dir = PrivateMetadataStore::import(&laptop, ticket).await?;
+    assert!(
+        eventually(|| async { Ok(laptop_dir.key_events().await?.len() == 1) }).await?,
+        "the inception did not reach laptop"
+    );
+
+    // Each device appends at sn 1 on the log it has read, neither waiting
+    // for the other's.
+    phone_dir.put_key_event(1, b"from-phone").await?;
+    laptop_dir.put_key_event(1, b"from-laptop").await?;
+
+    let mut reads = Vec::new();
+    for dir in [&phone_dir, &laptop_dir] {
+        let forked = eventually(|| async {
+            Ok(dir
+                .key_events()
+                .await?
+                .get(1)
+                .is_some_and(|at| at.len() == 2))
+        })
+        .await?;
+        assert!(forked, "a device did not read both appends at sn 1");
+        reads.push(dir.key_events().await?);
+    }
+    assert_eq!(reads[0], reads[1]);
+    let at = &reads[0][1];
+    assert!(at.contains(&b"from-phone".to_vec()) && at.contains(&b"from-laptop".to_vec()));
+
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
+
 /// An empty payload is not a storable value: zero-length entries are the
 /// underlying deletion marker, and writing one is rejected — it neither
 /// stores an "empty file" nor deletes the previous value.
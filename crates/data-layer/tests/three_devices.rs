@@ -11,7 +11,7 @@
 
 use anyhow::Result;
 use data_layer::{
-    AddrInfoOptions, DocTicket, PrivateMetadataStore, ShareMode, SyncNode, UnknownIssuer,
+    AddrInfoOptions, DocTicket, NodeError, PrivateMetadataStore, ShareMode, SyncNode,
 };
 use pdn_types::{EntryPath, PdnId};
 use test_utils::{ids, wait_connected, wait_devices, wait_entry_is};
@@ -147,7 +147,7 @@ async fn three_devices_two_identities() -> Result<()> {
 
     // And the tablet knows nothing of leisure: the namespace is unknown there.
     let err = tablet.read(ids::ALICE_AT_LEISURE, &path).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(err, NodeError::UnknownIssuer(_)));
 
     phone.shutdown().await?;
     laptop.shutdown().await?;
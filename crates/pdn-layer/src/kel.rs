@@ -0,0 +1,1202 @@
+//! Key event log: the KERI-style history behind an identity's keys.
+//!
+//! An identity is incepted by an ed25519 key whose public half is its
+//! [`Aid`] — and, byte for byte, its [`PdnId`]. The inception event also
+//! commits to the *next* key by digest (pre-rotation), so the key that may
+//! one day replace the current one is fixed before the current one can be
+//! stolen. Every later event is appended in sequence, chained to its
+//! predecessor by digest and signed by the key current after it — or, for
+//! a device addition, by a key already authorized to add devices;
+//! verification replays the log from inception and yields the resulting
+//! [`KeyState`].
+//!
+//! Devices other than the incepting one never hold the identity's keys.
+//! Each generates its own device key, and the log authorizes it with an
+//! [`AddDevice`](KeyEventKind::AddDevice) event, so which devices joined
+//! the identity is part of its verifiable history — as is which ones left
+//! it: a [`RemoveDevice`](KeyEventKind::RemoveDevice) event takes a device
+//! key's authority away for good.
+//!
+//! Pure, like the rest of this crate: key material comes in as seeds from
+//! the caller's own randomness, and where the log is stored is the node
+//! runtime's business (the identity's private-metadata directory).
+
+use ed25519_dalek::{Signer as _, SigningKey, VerifyingKey};
+use pdn_types::{Aid, OperationalKey, PdnId, PdnIdentityProof};
+use serde::{Deserialize, Serialize};
+
+/// Domain separation of the bytes an event's signature and digest cover.
+const EVENT_DOMAIN: &[u8] = b"pdn.kel.event.v0";
+
+/// Domain separation of the bytes an identity proof's signature covers, so
+/// no proof doubles as a key event signature or the other way round.
+const PROOF_DOMAIN: &[u8] = b"pdn.kel.proof.v0";
+
+/// Key-derivation context of the next-key commitment.
+const NEXT_KEY_CONTEXT: &str = "pdn.kel.next-key.v0";
+
+/// An ed25519 signing key, rebuilt from its 32-byte seed. The seed is the
+/// whole secret: keep it where the device keeps its secrets, and nowhere a
+/// replica carries.
+#[derive(Clone)]
+pub struct KeyPair {
+    signing: SigningKey,
+}
+
+impl KeyPair {
+    /// The key pair of `seed`.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            signing: SigningKey::from_bytes(&seed),
+        }
+    }
+
+    /// The seed this key pair was built from — for the device keystore.
+    pub fn seed(&self) -> [u8; 32] {
+        self.signing.to_bytes()
+    }
+
+    /// The public half, as the operational key verifiers know it by.
+    pub fn public(&self) -> OperationalKey {
+        OperationalKey::from_bytes(self.signing.verifying_key().to_bytes())
+    }
+
+    /// Sign `message`.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(self.signing.sign(message).to_bytes())
+    }
+
+    /// Prove that the identity this key speaks for issued `statement` —
+    /// checked by [`KeyState::verify_proof`] against the identity's log.
+    pub fn prove(&self, statement: &[u8]) -> PdnIdentityProof {
+        PdnIdentityProof {
+            key: self.public(),
+            signature: self.sign(&proof_bytes(statement)).0.to_vec(),
+        }
+    }
+}
+
+/// The bytes a proof over `statement` signs: the domain, then the statement.
+fn proof_bytes(statement: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PROOF_DOMAIN.len() + statement.len());
+    bytes.extend_from_slice(PROOF_DOMAIN);
+    bytes.extend_from_slice(statement);
+    bytes
+}
+
+impl std::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The secret never reaches a log line.
+        write!(f, "KeyPair({:?})", self.public())
+    }
+}
+
+/// An ed25519 signature.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature(#[serde(with = "serde_bytes")] [u8; 64]);
+
+impl Signature {
+    /// Create from raw bytes.
+    pub const fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+
+    /// View as raw bytes.
+    pub const fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Signature(")?;
+        for byte in self.0.iter().take(4) {
+            write!(f, "{byte:02x}")?;
+        }
+        write!(f, "...)")
+    }
+}
+
+/// Whether `signature` is `key`'s signature over `message`. Strict
+/// verification: a malleated signature or a weak key never verifies.
+pub fn verify_signature(key: &OperationalKey, message: &[u8], signature: &Signature) -> bool {
+    let Ok(verifying) = VerifyingKey::from_bytes(key.as_bytes()) else {
+        return false;
+    };
+    verifying
+        .verify_strict(message, &ed25519_dalek::Signature::from_bytes(&signature.0))
+        .is_ok()
+}
+
+pdn_types::define_byte_id! {
+    /// Commitment to a future operational key: the digest an event names
+    /// its successor key by, before that key signs anything.
+    pub struct KeyDigest;
+}
+
+pdn_types::define_byte_id! {
+    /// Digest of one key event — how the next event names its
+    /// predecessor.
+    pub struct EventDigest;
+}
+
+/// The commitment to `key` that a pre-rotating event carries.
+pub fn commit_to(key: &OperationalKey) -> KeyDigest {
+    KeyDigest::from_bytes(blake3::derive_key(NEXT_KEY_CONTEXT, key.as_bytes()))
+}
+
+/// The PDN identity of the identifier `aid`: the same 32 bytes, read at
+/// the domain layer.
+pub fn pdn_id_of(aid: &Aid) -> PdnId {
+    PdnId::from_bytes(*aid.as_bytes())
+}
+
+/// What one key event does.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KeyEventKind {
+    /// The identity's first event: `key` becomes current — and is the
+    /// identifier — and `next` commits to its successor.
+    Inception {
+        key: OperationalKey,
+        next: KeyDigest,
+    },
+    /// Pre-rotation: `key` — the one the previous event committed to —
+    /// replaces the current key, and `next` commits to its own successor.
+    /// With `compromised`, the replaced key is known stolen: nothing it
+    /// signed is accepted any more, whatever state it claims to have
+    /// signed under.
+    Rotation {
+        key: OperationalKey,
+        next: KeyDigest,
+        compromised: bool,
+    },
+    /// Device authorization: `key`, a device's own key, joins the keys
+    /// the identity acknowledges. Signed by `signer` — the current key or
+    /// a device key an earlier event added — rather than by `key` itself.
+    AddDevice {
+        key: OperationalKey,
+        signer: OperationalKey,
+    },
+    /// Device revocation: `key`, a device key an earlier event added,
+    /// stops being one. Signed by `signer`, a key that may add devices —
+    /// the removed key itself included. A removed key is never added back.
+    RemoveDevice {
+        key: OperationalKey,
+        signer: OperationalKey,
+    },
+}
+
+/// One event of an identity's key history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEvent {
+    /// The identity whose history this is.
+    pub aid: Aid,
+    /// Position in the log: 0 for the inception, then one more per event.
+    pub sn: u64,
+    /// Digest of the event at `sn - 1`; `None` for the inception alone.
+    pub prior: Option<EventDigest>,
+    pub kind: KeyEventKind,
+}
+
+impl KeyEvent {
+    /// The bytes the event's signature and digest cover — a fixed-layout
+    /// encoding, so no serializer's choices can make two verifiers
+    /// disagree about them.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(160);
+        bytes.extend_from_slice(EVENT_DOMAIN);
+        bytes.extend_from_slice(self.aid.as_bytes());
+        bytes.extend_from_slice(&self.sn.to_be_bytes());
+        match &self.prior {
+            None => bytes.push(0),
+            Some(prior) => {
+                bytes.push(1);
+                bytes.extend_from_slice(prior.as_bytes());
+            }
+        }
+        match &self.kind {
+            KeyEventKind::Inception { key, next } => {
+                bytes.push(0);
+                bytes.extend_from_slice(key.as_bytes());
+                bytes.extend_from_slice(next.as_bytes());
+            }
+            KeyEventKind::Rotation {
+                key,
+                next,
+                compromised,
+            } => {
+                bytes.push(1);
+                bytes.extend_from_slice(key.as_bytes());
+                bytes.extend_from_slice(next.as_bytes());
+                bytes.push(u8::from(*compromised));
+            }
+            KeyEventKind::AddDevice { key, signer } => {
+                bytes.push(2);
+                bytes.extend_from_slice(key.as_bytes());
+                bytes.extend_from_slice(signer.as_bytes());
+            }
+            KeyEventKind::RemoveDevice { key, signer } => {
+                bytes.push(3);
+                bytes.extend_from_slice(key.as_bytes());
+                bytes.extend_from_slice(signer.as_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// The event's digest.
+    pub fn digest(&self) -> EventDigest {
+        EventDigest::from_bytes(*blake3::hash(&self.signing_bytes()).as_bytes())
+    }
+}
+
+/// A key event with the signature of the key it leaves current — or, for a
+/// device addition or removal, of the key it names as signer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedKeyEvent {
+    pub event: KeyEvent,
+    pub signature: Signature,
+}
+
+impl SignedKeyEvent {
+    /// Sign `event` with `signer`.
+    pub fn sign(event: KeyEvent, signer: &KeyPair) -> Self {
+        let signature = signer.sign(&event.signing_bytes());
+        Self { event, signature }
+    }
+}
+
+/// Where a key stands in a verified log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// The current key.
+    Active,
+    /// Replaced in an orderly rotation at sequence number `at`.
+    RotatedOut { at: u64 },
+    /// Replaced at `at` by a rotation that declared it stolen.
+    Compromised { at: u64 },
+}
+
+/// A key the log has rotated away from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetiredKey {
+    pub key: OperationalKey,
+    /// Sequence number of the rotation that replaced it.
+    pub at: u64,
+    /// Whether that rotation declared it compromised.
+    pub compromised: bool,
+}
+
+/// Where a verified log leaves the identity's keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyState {
+    pub aid: Aid,
+    /// Sequence number of the last event.
+    pub sn: u64,
+    /// The key current after the last event.
+    pub current: OperationalKey,
+    /// The commitment to the key that may replace `current`.
+    pub next: KeyDigest,
+    /// Digest of the last event, which the next one must name.
+    pub last: EventDigest,
+    /// Every key rotated away from, oldest first.
+    pub retired: Vec<RetiredKey>,
+    /// Every device key the log has added and not removed, oldest first.
+    pub devices: Vec<OperationalKey>,
+    /// Every device key the log has removed, oldest first.
+    pub removed: Vec<OperationalKey>,
+}
+
+impl KeyState {
+    /// The identity this state belongs to, at the domain layer.
+    pub fn pdn_id(&self) -> PdnId {
+        pdn_id_of(&self.aid)
+    }
+
+    /// Where `key` stands, or `None` for a key this log never named.
+    pub fn status_of(&self, key: &OperationalKey) -> Option<KeyStatus> {
+        if *key == self.current {
+            return Some(KeyStatus::Active);
+        }
+        self.retired
+            .iter()
+            .find(|retired| retired.key == *key)
+            .map(|retired| {
+                if retired.compromised {
+                    KeyStatus::Compromised { at: retired.at }
+                } else {
+                    KeyStatus::RotatedOut { at: retired.at }
+                }
+            })
+    }
+
+    /// Whether `key` is a device key this log has added.
+    pub fn has_device(&self, key: &OperationalKey) -> bool {
+        self.devices.contains(key)
+    }
+
+    /// Whether `key` may sign a device addition: the current key, or a
+    /// device key the log has added.
+    pub fn authorizes_devices(&self, key: &OperationalKey) -> bool {
+        *key == self.current || self.has_device(key)
+    }
+
+    /// Whether `proof` shows one of this identity's devices issued
+    /// `statement` now: signed by a key that may add devices — the current
+    /// key, or a device key the log added and has not removed.
+    pub fn verify_device_proof(&self, statement: &[u8], proof: &PdnIdentityProof) -> bool {
+        let Ok(signature) = <[u8; 64]>::try_from(proof.signature.as_slice()) else {
+            return false;
+        };
+        self.authorizes_devices(&proof.key)
+            && verify_signature(&proof.key, &proof_bytes(statement), &Signature(signature))
+    }
+
+    /// Whether `proof` shows this identity issued `statement` now: signed by
+    /// the current key. A proof by a key the log has rotated away from —
+    /// compromised or not — is refused; proofs are fresh evidence, not
+    /// archived statements. What a key signed to last beyond a rotation
+    /// names the state it was signed under, and is checked with
+    /// [`accepts`](Self::accepts).
+    pub fn verify_proof(&self, statement: &[u8], proof: &PdnIdentityProof) -> bool {
+        let Ok(signature) = <[u8; 64]>::try_from(proof.signature.as_slice()) else {
+            return false;
+        };
+        proof.key == self.current
+            && verify_signature(&proof.key, &proof_bytes(statement), &Signature(signature))
+    }
+
+    /// Whether a statement signed by `key` under the key state at sequence
+    /// number `anchored_at` is acceptable — `anchored_at` being bound into
+    /// the signed bytes, so only the signer chose it. A key rotated out in
+    /// an orderly rotation at `at` stays good for statements anchored
+    /// before `at`, and the current key for those anchored since it became
+    /// current. A key a rotation declared compromised is good for none,
+    /// whatever anchor it claims: its thief picks the anchor along with
+    /// the signature. A device key speaks for the identity at any anchor
+    /// while the log holds it, and at none once removed. A statement
+    /// anchored past the log's end is from a state this log has not
+    /// reached, and is not accepted either.
+    pub fn accepts(&self, key: &OperationalKey, anchored_at: u64) -> bool {
+        if anchored_at > self.sn {
+            return false;
+        }
+        if self.has_device(key) {
+            return true;
+        }
+        let mut since = 0;
+        for retired in &self.retired {
+            if retired.key == *key {
+                return !retired.compromised && (since..retired.at).contains(&anchored_at);
+            }
+            since = retired.at;
+        }
+        *key == self.current && anchored_at >= since
+    }
+}
+
+/// Why a key event log does not verify — the first failure met in
+/// sequence order.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KelError {
+    /// The log holds no events.
+    #[error("key event log is empty")]
+    Empty,
+    /// The event at a position carries another sequence number.
+    #[error("event at position {expected} carries sn {found}")]
+    OutOfSequence { expected: u64, found: u64 },
+    /// An inception that is not self-certifying: its identifier is not its
+    /// key, or it names a predecessor.
+    #[error("inception is not self-certifying")]
+    MalformedInception,
+    /// An inception after the first event.
+    #[error("inception at sn {sn} after the log began")]
+    UnexpectedInception { sn: u64 },
+    /// A non-inception event before any inception.
+    #[error("event {sn} precedes the inception")]
+    NotIncepted { sn: u64 },
+    /// An event that names another identity than the log's.
+    #[error("event {sn} belongs to another identity")]
+    ForeignAid { sn: u64 },
+    /// An event that does not name its predecessor's digest.
+    #[error("event {sn} does not chain to its predecessor")]
+    BrokenChain { sn: u64 },
+    /// A rotation to a key the previous event did not commit to.
+    #[error("rotation {sn} installs a key that was not pre-committed")]
+    UncommittedKey { sn: u64 },
+    /// A signature that does not verify under the key it must be from.
+    #[error("signature of event {sn} does not verify")]
+    BadSignature { sn: u64 },
+    /// A device addition signed by a key not authorized to add devices.
+    #[error("device addition {sn} is signed by a key that cannot add devices")]
+    UnauthorizedSigner { sn: u64 },
+    /// A device addition of a key the log already names, or once removed.
+    #[error("device addition {sn} names a key the log already holds")]
+    DuplicateDevice { sn: u64 },
+    /// A device removal of a key that is not a device key of the log.
+    #[error("device removal {sn} names a key that is not a device of the log")]
+    UnknownDevice { sn: u64 },
+}
+
+/// An identity's key event log, in sequence order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEventLog {
+    events: Vec<SignedKeyEvent>,
+}
+
+impl KeyEventLog {
+    /// Incept an identity: `current` becomes its first key and identifier,
+    /// and `next` is committed to as the successor.
+    pub fn incept(current: &KeyPair, next: &OperationalKey) -> Self {
+        let key = current.public();
+        let event = KeyEvent {
+            aid: Aid::from_bytes(*key.as_bytes()),
+            sn: 0,
+            prior: None,
+            kind: KeyEventKind::Inception {
+                key,
+                next: commit_to(next),
+            },
+        };
+        Self {
+            events: vec![SignedKeyEvent::sign(event, current)],
+        }
+    }
+
+    /// A log of `events` as stored — unverified until [`verify`](Self::verify).
+    pub fn from_events(events: Vec<SignedKeyEvent>) -> Self {
+        Self { events }
+    }
+
+    /// The log of `identity` that a replica's records resolve to, given
+    /// every event recorded at each sequence number in sequence order —
+    /// verified, or empty when no inception of `identity` is among them.
+    ///
+    /// A record that is not a signed extension of the log so far is
+    /// ignored: an event of another identity or position, a broken chain or
+    /// signature, or one its signer may not make — so garbage, or an append
+    /// by a removed device, cannot stop the log. Two valid events at one
+    /// position are a fork from devices appending while apart, and every
+    /// replica holding both picks the same one: a device removal first, so
+    /// no revocation is lost, then a rotation, whose keys the rotating
+    /// device already holds, then the lower event digest. The other side
+    /// and anything built on it drop out, and their author appends again.
+    /// The log ends at the first position with no valid event.
+    pub fn resolve(
+        identity: PdnId,
+        candidates: impl IntoIterator<Item = Vec<SignedKeyEvent>>,
+    ) -> Self {
+        let mut state: Option<KeyState> = None;
+        let mut events = Vec::new();
+        for (sn, recorded) in (0u64..).zip(candidates) {
+            let chosen = recorded
+                .into_iter()
+                .filter(|signed| signed.event.sn == sn && pdn_id_of(&signed.event.aid) == identity)
+                .filter_map(|signed| Some((apply(state.clone(), &signed).ok()?, signed)))
+                .min_by_key(|(_, signed)| precedence(&signed.event));
+            let Some((next, signed)) = chosen else {
+                break;
+            };
+            state = Some(next);
+            events.push(signed);
+        }
+        Self { events }
+    }
+
+    /// The events, in sequence order.
+    pub fn events(&self) -> &[SignedKeyEvent] {
+        &self.events
+    }
+
+    /// Rotate to `current` — the key the log's last event committed to —
+    /// committing to `next` as its successor; with `compromised`, the key
+    /// replaced is declared stolen. The new event is signed by `current`
+    /// and is the log's last afterwards. Refused, with the log unchanged,
+    /// when the log does not verify or `current` is not the committed key.
+    pub fn rotate(
+        &mut self,
+        current: &KeyPair,
+        next: &OperationalKey,
+        compromised: bool,
+    ) -> Result<KeyState, KelError> {
+        let state = self.verify()?;
+        let sn = state.sn + 1;
+        let key = current.public();
+        if commit_to(&key) != state.next {
+            return Err(KelError::UncommittedKey { sn });
+        }
+        let event = KeyEvent {
+            aid: state.aid,
+            sn,
+            prior: Some(state.last),
+            kind: KeyEventKind::Rotation {
+                key,
+                next: commit_to(next),
+                compromised,
+            },
+        };
+        let signed = SignedKeyEvent::sign(event, current);
+        let state = apply(Some(state), &signed)?;
+        self.events.push(signed);
+        Ok(state)
+    }
+
+    /// Authorize the device key `key`, signed by `signer` — the current key
+    /// or a device key already added. The new event is the log's last
+    /// afterwards. Refused, with the log unchanged, when the log does not
+    /// verify, `signer` may not add devices, or the log already names
+    /// `key`.
+    pub fn add_device(
+        &mut self,
+        signer: &KeyPair,
+        key: &OperationalKey,
+    ) -> Result<KeyState, KelError> {
+        let state = self.verify()?;
+        let event = KeyEvent {
+            aid: state.aid,
+            sn: state.sn + 1,
+            prior: Some(state.last),
+            kind: KeyEventKind::AddDevice {
+                key: *key,
+                signer: signer.public(),
+            },
+        };
+        let addition = SignedKeyEvent::sign(event, signer);
+        let state = apply(Some(state), &addition)?;
+        self.events.push(addition);
+        Ok(state)
+    }
+
+    /// Revoke the device key `key`, signed by `signer` — the current key or
+    /// a device key still authorized. The new event is the log's last
+    /// afterwards. Refused, with the log unchanged, when the log does not
+    /// verify, `signer` may not add devices, or `key` is not a device key
+    /// of the log.
+    pub fn remove_device(
+        &mut self,
+        signer: &KeyPair,
+        key: &OperationalKey,
+    ) -> Result<KeyState, KelError> {
+        let state = self.verify()?;
+        let event = KeyEvent {
+            aid: state.aid,
+            sn: state.sn + 1,
+            prior: Some(state.last),
+            kind: KeyEventKind::RemoveDevice {
+                key: *key,
+                signer: signer.public(),
+            },
+        };
+        let removal = SignedKeyEvent::sign(event, signer);
+        let state = apply(Some(state), &removal)?;
+        self.events.push(removal);
+        Ok(state)
+    }
+
+    /// Replay the log from inception: every event in sequence, chained,
+    /// and signed by the key it must be signed by.
+    pub fn verify(&self) -> Result<KeyState, KelError> {
+        let mut state: Option<KeyState> = None;
+        for (expected, signed) in (0u64..).zip(&self.events) {
+            let event = &signed.event;
+            if event.sn != expected {
+                return Err(KelError::OutOfSequence {
+                    expected,
+                    found: event.sn,
+                });
+            }
+            state = Some(apply(state, signed)?);
+        }
+        state.ok_or(KelError::Empty)
+    }
+}
+
+/// Apply one sequence-checked event to the state before it.
+fn apply(state: Option<KeyState>, signed: &SignedKeyEvent) -> Result<KeyState, KelError> {
+    let event = &signed.event;
+    let sn = event.sn;
+    match &event.kind {
+        KeyEventKind::Inception { key, next } => {
+            if state.is_some() {
+                return Err(KelError::UnexpectedInception { sn });
+            }
+            if event.prior.is_some() || event.aid.as_bytes() != key.as_bytes() {
+                return Err(KelError::MalformedInception);
+            }
+            if !verify_signature(key, &event.signing_bytes(), &signed.signature) {
+                return Err(KelError::BadSignature { sn });
+            }
+            Ok(KeyState {
+                aid: event.aid,
+                sn,
+                current: *key,
+                next: *next,
+                last: event.digest(),
+                retired: Vec::new(),
+                devices: Vec::new(),
+                removed: Vec::new(),
+            })
+        }
+        KeyEventKind::Rotation {
+            key,
+            next,
+            compromised,
+        } => {
+            let mut state = state.ok_or(KelError::NotIncepted { sn })?;
+            chain(&state, event)?;
+            if commit_to(key) != state.next {
+                return Err(KelError::UncommittedKey { sn });
+            }
+            if !verify_signature(key, &event.signing_bytes(), &signed.signature) {
+                return Err(KelError::BadSignature { sn });
+            }
+            state.retired.push(RetiredKey {
+                key: state.current,
+                at: sn,
+                compromised: *compromised,
+            });
+            state.sn = sn;
+            state.current = *key;
+            state.next = *next;
+            state.last = event.digest();
+            Ok(state)
+        }
+        KeyEventKind::AddDevice { key, signer } => {
+            let state = state.ok_or(KelError::NotIncepted { sn })?;
+            change_devices(state, signed, signer, |state| {
+                if state.has_device(key)
+                    || state.removed.contains(key)
+                    || state.status_of(key).is_some()
+                {
+                    return Err(KelError::DuplicateDevice { sn });
+                }
+                state.devices.push(*key);
+                Ok(())
+            })
+        }
+        KeyEventKind::RemoveDevice { key, signer } => {
+            let state = state.ok_or(KelError::NotIncepted { sn })?;
+            change_devices(state, signed, signer, |state| {
+                if !state.has_device(key) {
+                    return Err(KelError::UnknownDevice { sn });
+                }
+                state.devices.retain(|device| device != key);
+                state.removed.push(*key);
+                Ok(())
+            })
+        }
+    }
+}
+
+/// Where `event` ranks among valid events at one position, lowest first —
+/// the order [`KeyEventLog::resolve`] settles a fork by.
+fn precedence(event: &KeyEvent) -> (u8, [u8; 32]) {
+    let rank = match event.kind {
+        KeyEventKind::RemoveDevice { .. } => 0,
+        KeyEventKind::Rotation { .. } => 1,
+        KeyEventKind::Inception { .. } | KeyEventKind::AddDevice { .. } => 2,
+    };
+    (rank, *event.digest().as_bytes())
+}
+
+/// Apply a device addition or removal signed by `signer`: chained, signed
+/// by a key that may change the device set, and `change` applied to the
+/// set — which refuses what the event may not do.
+fn change_devices(
+    mut state: KeyState,
+    signed_event: &SignedKeyEvent,
+    signer: &OperationalKey,
+    change: impl FnOnce(&mut KeyState) -> Result<(), KelError>,
+) -> Result<KeyState, KelError> {
+    let event = &signed_event.event;
+    let sn = event.sn;
+    chain(&state, event)?;
+    if !state.authorizes_devices(signer) {
+        return Err(KelError::UnauthorizedSigner { sn });
+    }
+    change(&mut state)?;
+    if !verify_signature(signer, &event.signing_bytes(), &signed_event.signature) {
+        return Err(KelError::BadSignature { sn });
+    }
+    state.sn = sn;
+    state.last = event.digest();
+    Ok(state)
+}
+
+/// Check that a non-inception `event` continues `state`'s log: the same
+/// identity, and the digest of the event before it.
+fn chain(state: &KeyState, event: &KeyEvent) -> Result<(), KelError> {
+    if event.aid != state.aid {
+        return Err(KelError::ForeignAid { sn: event.sn });
+    }
+    if event.prior != Some(state.last) {
+        return Err(KelError::BrokenChain { sn: event.sn });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> KeyPair {
+        KeyPair::from_seed([byte; 32])
+    }
+
+    #[test]
+    fn a_proof_verifies_only_under_the_current_key() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.verify().unwrap();
+        let proof = key(1).prove(b"statement");
+        assert!(state.verify_proof(b"statement", &proof));
+        assert!(!state.verify_proof(b"another statement", &proof));
+
+        // A key the log never named, claiming to be the current one.
+        let forged = PdnIdentityProof {
+            key: key(1).public(),
+            ..key(7).prove(b"statement")
+        };
+        assert!(!state.verify_proof(b"statement", &forged));
+        assert!(!state.verify_proof(b"statement", &key(7).prove(b"statement")));
+
+        // Rotated away from: the old key's proofs no longer count.
+        let rotated = log.rotate(&key(2), &key(3).public(), false).unwrap();
+        assert!(!rotated.verify_proof(b"statement", &proof));
+        assert!(rotated.verify_proof(b"statement", &key(2).prove(b"statement")));
+    }
+
+    #[test]
+    fn inception_is_self_certifying() {
+        let current = key(1);
+        let next = key(2);
+        let log = KeyEventLog::incept(&current, &next.public());
+        let state = log.verify().unwrap();
+        assert_eq!(state.aid.as_bytes(), current.public().as_bytes());
+        assert_eq!(
+            state.pdn_id(),
+            PdnId::from_bytes(*current.public().as_bytes())
+        );
+        assert_eq!(state.current, current.public());
+        assert_eq!(state.next, commit_to(&next.public()));
+        assert_eq!(state.sn, 0);
+    }
+
+    #[test]
+    fn tampered_inception_is_refused() {
+        let log = KeyEventLog::incept(&key(1), &key(2).public());
+        let mut events = log.events().to_vec();
+        // Swapping the committed next key invalidates the signature.
+        if let Some(first) = events.first_mut() {
+            first.event.kind = KeyEventKind::Inception {
+                key: key(1).public(),
+                next: commit_to(&key(3).public()),
+            };
+        }
+        assert_eq!(
+            KeyEventLog::from_events(events).verify(),
+            Err(KelError::BadSignature { sn: 0 })
+        );
+    }
+
+    #[test]
+    fn inception_under_a_foreign_identifier_is_refused() {
+        let current = key(1);
+        let event = KeyEvent {
+            aid: Aid::from_bytes([9; 32]),
+            sn: 0,
+            prior: None,
+            kind: KeyEventKind::Inception {
+                key: current.public(),
+                next: commit_to(&key(2).public()),
+            },
+        };
+        let log = KeyEventLog::from_events(vec![SignedKeyEvent::sign(event, &current)]);
+        assert_eq!(log.verify(), Err(KelError::MalformedInception));
+    }
+
+    #[test]
+    fn a_second_inception_and_gaps_are_refused() {
+        let log = KeyEventLog::incept(&key(1), &key(2).public());
+        let first = log.events().to_vec();
+
+        // A gap: the next position carries another sequence number.
+        let mut gapped = first.clone();
+        gapped.extend(
+            KeyEventLog::incept(&key(3), &key(4).public())
+                .events()
+                .to_vec(),
+        );
+        assert_eq!(
+            KeyEventLog::from_events(gapped).verify(),
+            Err(KelError::OutOfSequence {
+                expected: 1,
+                found: 0
+            })
+        );
+
+        // In sequence, but an inception all the same.
+        let second = KeyEvent {
+            aid: Aid::from_bytes(*key(3).public().as_bytes()),
+            sn: 1,
+            prior: None,
+            kind: KeyEventKind::Inception {
+                key: key(3).public(),
+                next: commit_to(&key(4).public()),
+            },
+        };
+        let mut reincepted = first;
+        reincepted.push(SignedKeyEvent::sign(second, &key(3)));
+        assert_eq!(
+            KeyEventLog::from_events(reincepted).verify(),
+            Err(KelError::UnexpectedInception { sn: 1 })
+        );
+
+        assert_eq!(KeyEventLog::default().verify(), Err(KelError::Empty));
+    }
+
+    #[test]
+    fn rotation_installs_the_committed_key() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.rotate(&key(2), &key(3).public(), false).unwrap();
+        assert_eq!(state.current, key(2).public());
+        assert_eq!(state.next, commit_to(&key(3).public()));
+        assert_eq!(state.sn, 1);
+        assert_eq!(state.aid.as_bytes(), key(1).public().as_bytes());
+        assert_eq!(log.verify().unwrap(), state);
+        assert_eq!(
+            state.status_of(&key(1).public()),
+            Some(KeyStatus::RotatedOut { at: 1 })
+        );
+        assert_eq!(state.status_of(&key(2).public()), Some(KeyStatus::Active));
+        assert_eq!(state.status_of(&key(3).public()), None);
+
+        // A second rotation chains on the first.
+        let state = log.rotate(&key(3), &key(4).public(), false).unwrap();
+        assert_eq!(state.sn, 2);
+        assert_eq!(state.retired.len(), 2);
+    }
+
+    #[test]
+    fn rotation_to_an_uncommitted_key_is_refused() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        // The thief of key 1 cannot rotate: it never held key 2.
+        assert_eq!(
+            log.rotate(&key(9), &key(3).public(), false),
+            Err(KelError::UncommittedKey { sn: 1 })
+        );
+        assert_eq!(log.events().len(), 1);
+
+        // Nor can it smuggle the event in past `rotate`.
+        let forged = KeyEvent {
+            aid: log.verify().unwrap().aid,
+            sn: 1,
+            prior: Some(log.verify().unwrap().last),
+            kind: KeyEventKind::Rotation {
+                key: key(9).public(),
+                next: commit_to(&key(3).public()),
+                compromised: true,
+            },
+        };
+        let mut events = log.events().to_vec();
+        events.push(SignedKeyEvent::sign(forged, &key(9)));
+        assert_eq!(
+            KeyEventLog::from_events(events).verify(),
+            Err(KelError::UncommittedKey { sn: 1 })
+        );
+    }
+
+    #[test]
+    fn rotation_must_chain_to_its_predecessor() {
+        let log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.verify().unwrap();
+        let unchained = KeyEvent {
+            aid: state.aid,
+            sn: 1,
+            prior: Some(EventDigest::from_bytes([0; 32])),
+            kind: KeyEventKind::Rotation {
+                key: key(2).public(),
+                next: commit_to(&key(3).public()),
+                compromised: false,
+            },
+        };
+        let foreign = KeyEvent {
+            aid: Aid::from_bytes([9; 32]),
+            prior: Some(state.last),
+            ..unchained.clone()
+        };
+        for (event, refusal) in [
+            (unchained, KelError::BrokenChain { sn: 1 }),
+            (foreign, KelError::ForeignAid { sn: 1 }),
+        ] {
+            let mut events = log.events().to_vec();
+            events.push(SignedKeyEvent::sign(event, &key(2)));
+            assert_eq!(KeyEventLog::from_events(events).verify(), Err(refusal));
+        }
+
+        // A rotation cannot open a log.
+        let orphan = KeyEvent {
+            aid: state.aid,
+            sn: 0,
+            prior: None,
+            kind: KeyEventKind::Rotation {
+                key: key(2).public(),
+                next: commit_to(&key(3).public()),
+                compromised: false,
+            },
+        };
+        assert_eq!(
+            KeyEventLog::from_events(vec![SignedKeyEvent::sign(orphan, &key(2))]).verify(),
+            Err(KelError::NotIncepted { sn: 0 })
+        );
+    }
+
+    #[test]
+    fn a_compromised_key_is_refused_from_the_rotation_point() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.rotate(&key(2), &key(3).public(), true).unwrap();
+        let stolen = key(1).public();
+        assert_eq!(
+            state.status_of(&stolen),
+            Some(KeyStatus::Compromised { at: 1 })
+        );
+        // Nothing it signed counts, whatever anchor it names: the thief
+        // names one as easily as the owner did.
+        assert!(!state.accepts(&stolen, 0));
+        assert!(!state.accepts(&stolen, 1));
+        assert!(state.accepts(&key(2).public(), 1));
+        assert!(!state.accepts(&key(2).public(), 0));
+        // No statement anchors past the log's end.
+        assert!(!state.accepts(&key(2).public(), 2));
+    }
+
+    #[test]
+    fn an_orderly_rotation_keeps_what_the_old_key_signed() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        log.add_device(&key(1), &key(10).public()).unwrap();
+        let state = log.rotate(&key(2), &key(3).public(), false).unwrap();
+        let old = key(1).public();
+        assert!(state.accepts(&old, 0));
+        assert!(state.accepts(&old, 1));
+        assert!(!state.accepts(&old, 2));
+        // A device key is good at any anchor the log has reached, until
+        // the log removes it.
+        assert!(state.accepts(&key(10).public(), 0));
+        assert!(state.accepts(&key(10).public(), 2));
+        let removed = log.remove_device(&key(2), &key(10).public()).unwrap();
+        assert!(!removed.accepts(&key(10).public(), 0));
+        assert!(!removed.accepts(&key(10).public(), 3));
+    }
+
+    #[test]
+    fn signatures_verify_only_under_their_key() {
+        let signer = key(1);
+        let signature = signer.sign(b"message");
+        assert!(verify_signature(&signer.public(), b"message", &signature));
+        assert!(!verify_signature(&signer.public(), b"other", &signature));
+        assert!(!verify_signature(&key(2).public(), b"message", &signature));
+    }
+
+    #[test]
+    fn signed_event_serde_round_trips() {
+        let log = KeyEventLog::incept(&key(1), &key(2).public());
+        let json = serde_json::to_string(&log).unwrap();
+        let back: KeyEventLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, log);
+        assert!(back.verify().is_ok());
+    }
+
+    #[test]
+    fn device_keys_are_added_by_authorized_keys_only() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        let laptop = log.add_device(&key(1), &key(10).public()).unwrap();
+        assert!(laptop.has_device(&key(10).public()));
+        assert_eq!(laptop.current, key(1).public());
+        assert_eq!(laptop.next, commit_to(&key(2).public()));
+
+        // A device key adds further devices; the log still verifies.
+        let tablet = log.add_device(&key(10), &key(11).public()).unwrap();
+        assert_eq!(tablet.devices, vec![key(10).public(), key(11).public()]);
+        assert_eq!(log.verify(), Ok(tablet));
+
+        // A key the log never authorized adds nothing.
+        assert_eq!(
+            log.add_device(&key(7), &key(12).public()),
+            Err(KelError::UnauthorizedSigner { sn: 3 })
+        );
+        assert_eq!(
+            log.add_device(&key(1), &key(10).public()),
+            Err(KelError::DuplicateDevice { sn: 3 })
+        );
+        assert_eq!(log.events().len(), 3);
+
+        // Device keys survive a rotation of the identity's key.
+        let rotated = log.rotate(&key(2), &key(3).public(), false).unwrap();
+        assert!(rotated.has_device(&key(11).public()));
+        assert!(!rotated.authorizes_devices(&key(1).public()));
+    }
+
+    #[test]
+    fn a_removed_device_key_loses_its_authority_for_good() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        log.add_device(&key(1), &key(10).public()).unwrap();
+        log.add_device(&key(10), &key(11).public()).unwrap();
+        let before = log.verify().unwrap();
+        assert!(before.verify_device_proof(b"statement", &key(11).prove(b"statement")));
+
+        // A sibling device revokes the phone.
+        let after = log.remove_device(&key(10), &key(11).public()).unwrap();
+        assert_eq!(after.devices, vec![key(10).public()]);
+        assert_eq!(after.removed, vec![key(11).public()]);
+        assert_eq!(log.verify(), Ok(after.clone()));
+        assert!(!after.authorizes_devices(&key(11).public()));
+        assert!(!after.verify_device_proof(b"statement", &key(11).prove(b"statement")));
+        assert!(after.verify_device_proof(b"statement", &key(10).prove(b"statement")));
+        assert!(after.verify_device_proof(b"statement", &key(1).prove(b"statement")));
+
+        // The removed key signs nothing more, and is never added back.
+        assert_eq!(
+            log.add_device(&key(11), &key(12).public()),
+            Err(KelError::UnauthorizedSigner { sn: 4 })
+        );
+        assert_eq!(
+            log.add_device(&key(10), &key(11).public()),
+            Err(KelError::DuplicateDevice { sn: 4 })
+        );
+        // Nor removed twice, nor the identity's own key removed as a device.
+        assert_eq!(
+            log.remove_device(&key(10), &key(11).public()),
+            Err(KelError::UnknownDevice { sn: 4 })
+        );
+        assert_eq!(
+            log.remove_device(&key(10), &key(1).public()),
+            Err(KelError::UnknownDevice { sn: 4 })
+        );
+        assert_eq!(log.events().len(), 4);
+    }
+
+    #[test]
+    fn a_device_removal_signed_by_a_removed_key_is_refused() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        log.add_device(&key(1), &key(10).public()).unwrap();
+        log.add_device(&key(1), &key(11).public()).unwrap();
+        let state = log.remove_device(&key(1), &key(11).public()).unwrap();
+        // The revoked device answers by revoking its sibling, signed with
+        // the key it still holds.
+        let retaliation = KeyEvent {
+            aid: state.aid,
+            sn: 4,
+            prior: Some(state.last),
+            kind: KeyEventKind::RemoveDevice {
+                key: key(10).public(),
+                signer: key(11).public(),
+            },
+        };
+        let mut events = log.events().to_vec();
+        events.push(SignedKeyEvent::sign(retaliation, &key(11)));
+        assert_eq!(
+            KeyEventLog::from_events(events).verify(),
+            Err(KelError::UnauthorizedSigner { sn: 4 })
+        );
+    }
+
+    #[test]
+    fn a_device_addition_signed_by_another_key_is_refused() {
+        let log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.verify().unwrap();
+        let claimed = KeyEvent {
+            aid: state.aid,
+            sn: 1,
+            prior: Some(state.last),
+            kind: KeyEventKind::AddDevice {
+                key: key(10).public(),
+                signer: key(1).public(),
+            },
+        };
+        let mut events = log.events().to_vec();
+        events.push(SignedKeyEvent::sign(claimed, &key(10)));
+        assert_eq!(
+            KeyEventLog::from_events(events).verify(),
+            Err(KelError::BadSignature { sn: 1 })
+        );
+    }
+
+    #[test]
+    fn a_fork_resolves_alike_on_every_replica() {
+        let mut base = KeyEventLog::incept(&key(1), &key(2).public());
+        base.add_device(&key(1), &key(10).public()).unwrap();
+        // Apart, the phone and the laptop each add a device at sn 2, and
+        // the laptop builds on its own addition.
+        let mut phone = base.clone();
+        phone.add_device(&key(1), &key(11).public()).unwrap();
+        let mut laptop = base.clone();
+        laptop.add_device(&key(10), &key(12).public()).unwrap();
+        laptop.add_device(&key(10), &key(13).public()).unwrap();
+        let identity = base.verify().unwrap().pdn_id();
+        let at = |sn: usize| {
+            let mut recorded = vec![phone.events().get(sn), laptop.events().get(sn)];
+            recorded.dedup();
+            recorded.into_iter().flatten().cloned().collect::<Vec<_>>()
+        };
+        let forward = KeyEventLog::resolve(identity, (0..3).map(at));
+        let backward = KeyEventLog::resolve(
+            identity,
+            (0..3).map(|sn| at(sn).into_iter().rev().collect::<Vec<_>>()),
+        );
+        assert_eq!(forward, backward);
+        let phone_wins = phone.events()[2].event.digest().as_bytes()
+            < laptop.events()[2].event.digest().as_bytes();
+        assert_eq!(forward, if phone_wins { phone } else { laptop });
+        assert!(forward.verify().is_ok());
+    }
+
+    #[test]
+    fn a_fork_keeps_the_removal_and_ignores_invalid_records() {
+        let mut base = KeyEventLog::incept(&key(1), &key(2).public());
+        base.add_device(&key(1), &key(10).public()).unwrap();
+        let identity = base.verify().unwrap().pdn_id();
+        let mut revoking = base.clone();
+        revoking.remove_device(&key(1), &key(10).public()).unwrap();
+        let mut adding = base.clone();
+        adding.add_device(&key(10), &key(11).public()).unwrap();
+        // Beside them: an event of another identity, and one signed by a
+        // key the log never authorized.
+        let foreign = KeyEventLog::incept(&key(7), &key(8).public());
+        let state = base.verify().unwrap();
+        let unauthorized = SignedKeyEvent::sign(
+            KeyEvent {
+                aid: state.aid,
+                sn: 2,
+                prior: Some(state.last),
+                kind: KeyEventKind::AddDevice {
+                    key: key(12).public(),
+                    signer: key(7).public(),
+                },
+            },
+            &key(7),
+        );
+        let resolved = KeyEventLog::resolve(
+            identity,
+            vec![
+                vec![foreign.events()[0].clone(), base.events()[0].clone()],
+                vec![base.events()[1].clone()],
+                vec![
+                    unauthorized,
+                    adding.events()[2].clone(),
+                    revoking.events()[2].clone(),
+                ],
+            ],
+        );
+        assert_eq!(resolved, revoking);
+        // A position with no valid event ends the log there.
+        assert_eq!(
+            KeyEventLog::resolve(identity, vec![base.events().to_vec(), vec![]]),
+            KeyEventLog::from_events(base.events()[..1].to_vec())
+        );
+        assert!(
+            KeyEventLog::resolve(identity, vec![foreign.events().to_vec()])
+                .events()
+                .is_empty()
+        );
+    }
+}
@@ -1,14 +1,15 @@
 //! The PDN layer: the platform surface products consume.
 //!
 //! Pure domain — no transport, no storage backend. The domain model
-//! (claims, connections, delegation), the operation AST ([`PdnOp`]), and
-//! the [`uwill`] capability-token module live here; executing operations
-//! over a data layer is the node runtime's job.
+//! (claims, connections, delegation), the operation AST ([`PdnOp`]), the
+//! [`kel`] key event log, and the [`uwill`] capability-token module live
+//! here; executing operations over a data layer is the node runtime's job.
 
 use pdn_types::{ClaimId, OperationalKey, PdnId, PdnIdentityProof};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+pub mod kel;
 pub mod uwill;
 
 // ---------------------------------------------------------------------------
@@ -54,7 +55,7 @@ pub enum AttributeValue {
 }
 
 /// Named, typed property holding a single piece of data.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
     pub name: String,
     pub value: AttributeValue,
@@ -66,7 +67,7 @@ pub enum AccessMode {
     Write,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Capability {
     pub holders: Vec<PdnId>,
     pub access: AccessMode,
@@ -76,7 +77,7 @@ pub struct Capability {
 
 /// An assertion about a Subject by a Subject. Inseparable bundle of
 /// data (`Attribute`) and access semantics (`Capability`).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Claim {
     pub about: PdnId,
     pub issued_by: PdnId,
@@ -86,9 +87,13 @@ pub struct Claim {
 }
 
 /// A `Claim` conditionally shared into another Identity Context.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DelegatedClaim {
     pub source: ClaimId,
+    /// The claim's issuer: whose data namespace holds it.
+    pub issuer: PdnId,
+    /// The identity that shared it on, holding a grant on it.
+    pub delegator: PdnId,
     pub conditions: Capability,
 }
 
@@ -112,7 +117,8 @@ pub enum PdnOp {
     /// Authorize a new device under the current `PdnId`. -> ()
     ///
     /// The new device has already generated its keypair; only the public
-    /// part is passed here.
+    /// part is passed here. Recorded as a [`kel::KeyEventKind::AddDevice`]
+    /// event, signed by a key of a device already in the identity.
     AddDevice { new_key: OperationalKey },
 
     /// Revoke a device. -> ()
@@ -165,14 +171,18 @@ pub enum PdnOp {
     // --- Delegation ------------------------------------------------------
     /// Delegate a claim into another Identity Context under conditions.
     /// -> `DelegatedClaim`
+    ///
+    /// The claim is `issuer`'s; delegating another identity's claim needs
+    /// a grant on it that carries [`uwill::Command::Delegate`].
     DelegateClaim {
+        issuer: PdnId,
         claim_id: ClaimId,
         capability: Capability,
     },
 
     /// List my outgoing delegations to a specific identity context.
     /// -> Vec<DelegatedClaim>
-    ListDelegationsTo,
+    ListDelegationsTo { to: PdnId },
 
     /// List delegations others have made into my contexts.
     /// -> Vec<DelegatedClaim>
@@ -11,10 +11,55 @@
 //! checks) belongs here; the node runtime resolves an entry to the
 //! relevant chain and calls into this module for the verdict.
 //!
+//! Tokens travel in canonical DAG-CBOR, signed with Ed25519 by an
+//! operational key of the issuer, and are referred to by the CID of their
+//! signed envelope ([`CapabilityCid`]). The encoding is exact: decoding
+//! refuses any byte string its own re-encoding would not reproduce, so one
+//! token has one encoding and one CID on every build.
+//!
+//! The format is a PDN profile of UCAN v1.0.0-rc.1, not a conforming
+//! UCAN: the envelope, the varsig header and the payload's DAG-CBOR layout
+//! follow the spec, but the payloads depart from it —
+//!
+//! - `cmd` is an array of command paths, where UCAN has one command;
+//! - `res` names the granted claim, a field UCAN does not have;
+//! - `nbf` and `exp` are unix milliseconds, where UCAN counts seconds;
+//! - a revocation's `revoke` is the CID's raw bytes, not a CID link.
+//!
+//! The payloads are tagged with the profile's own version
+//! ([`DELEGATION_TAG`], [`REVOCATION_TAG`]), so no UCAN implementation
+//! takes a token for one of its own, and a later profile can be told apart.
+//!
 //! See `components/pdn-node/uwill.md` for the full specification.
 
-use pdn_types::{ClaimId, PdnId};
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+
+pub use pdn_types::{CapabilityCid, ValidityWindow};
+use pdn_types::{ClaimId, OperationalKey, PdnId};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use crate::kel::{verify_signature, KeyPair, KeyState, Signature};
+
+/// The envelope tag of a delegation payload, naming the version of the
+/// PDN profile the encoding follows.
+pub const DELEGATION_TAG: &str = "pdn/dlg@2";
+
+/// The envelope tag of a revocation payload.
+pub const REVOCATION_TAG: &str = "pdn/r@2";
+
+/// The envelope's varsig header: an Ed25519 signature over the DAG-CBOR
+/// encoding of the signature payload.
+pub const VARSIG_HEADER: [u8; 8] = [0x34, 0x01, 0xed, 0x01, 0xed, 0x01, 0x13, 0x71];
+
+/// The method prefix of the DIDs a token names its principals by: a
+/// [`PdnId`] in lowercase hex follows.
+pub const DID_PREFIX: &str = "did:pdn:";
+
+/// The prefix of a [`CapabilityCid`] as a binary CID: version 1, the
+/// DAG-CBOR codec, and a 32-byte SHA-256 multihash.
+pub const CID_PREFIX: [u8; 4] = CapabilityCid::PREFIX;
 
 /// Commands that a `UWill` capability can grant.
 ///
@@ -28,12 +73,31 @@ pub enum Command {
     Delegate,
 }
 
+impl Command {
+    /// The command path the token encoding names this command by.
+    pub const fn path(self) -> &'static str {
+        match self {
+            Self::Read => "/pdn/read",
+            Self::Write => "/pdn/write",
+            Self::Delete => "/pdn/delete",
+            Self::Delegate => "/pdn/delegate",
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        [Self::Read, Self::Write, Self::Delete, Self::Delegate]
+            .into_iter()
+            .find(|command| command.path() == path)
+    }
+}
+
 /// `UWill` delegation token: UCAN envelope with a single-claim resource and DID principals.
 ///
-/// Field names follow the UCAN v1.0.0-rc.1 Delegation spec.
+/// Field names follow the UCAN v1.0.0-rc.1 Delegation spec; their
+/// encoding follows the PDN profile (see the module docs).
 /// Storage-level addressing (namespace, path) is NOT exposed here;
 /// resolving `res` to a concrete storage location is the runtime's job.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UwillCapability {
     /// Delegator's PdnId-backed DID.
     pub iss: PdnId,
@@ -53,17 +117,1302 @@ pub struct UwillCapability {
     pub nonce: [u8; 12],
 }
 
-pdn_types::define_byte_id! {
-    /// CID of a `UWill` delegation — used for revocation references.
-    pub struct CapabilityCid;
+impl UwillCapability {
+    /// Sign this capability with `key`, an operational key of the issuer,
+    /// under the issuer's key state at sequence number `anchor` — the last
+    /// event of the log the signer holds.
+    pub fn sign(self, key: &KeyPair, anchor: u64) -> SignedUwill {
+        let signer = Signer {
+            key: key.public(),
+            anchor,
+        };
+        let signature = key.sign(&delegation_payload(&self, &signer));
+        SignedUwill {
+            capability: self,
+            signer,
+            signature,
+        }
+    }
 }
 
-/// Wall-clock validity window for a `UWill` capability.
-///
-/// Both bounds are absolute unix-ms timestamps, matching the wire format
-/// of `nbf` / `exp` in [`UwillCapability`].
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct ValidityWindow {
-    pub nbf: u64,
-    pub exp: u64,
+/// The key a token or revocation is signed by, and the sequence number of
+/// its issuer's key event log it was signed under. Both travel in the
+/// payload's `meta` map — a [`PdnId`]-backed DID names an identity, not a
+/// key — and are covered by the signature, so the anchor is the signer's
+/// own claim: [`KeyState::accepts`] decides which claims stand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signer {
+    /// The signing operational key.
+    pub key: OperationalKey,
+    /// The sequence number of the issuer's log the key signed under.
+    pub anchor: u64,
+}
+
+/// A signed `UWill` delegation: the capability, its [`Signer`], and the
+/// signature. Which keys count as the issuer's, under which state, is its
+/// key event log's to say ([`verify`](Self::verify)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedUwill {
+    pub capability: UwillCapability,
+    pub signer: Signer,
+    pub signature: Signature,
+}
+
+impl SignedUwill {
+    /// The token's envelope in canonical DAG-CBOR: the signature, then the
+    /// signed payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        envelope(
+            &self.signature,
+            delegation_payload(&self.capability, &self.signer),
+        )
+    }
+
+    /// Decode an envelope [`to_bytes`](Self::to_bytes) produced. Anything
+    /// else — another shape, another tag or header, or the same token in a
+    /// non-canonical encoding — is refused; the signature is not checked.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UwillError> {
+        let mut reader = cbor::Reader::new(bytes);
+        let signature = open_envelope(&mut reader, DELEGATION_TAG)?;
+        let (capability, signer) = decode_payload(&mut reader)?;
+        close_envelope(&reader)?;
+        let token = Self {
+            capability,
+            signer,
+            signature,
+        };
+        if token.to_bytes() != bytes {
+            return Err(UwillError::NonCanonical);
+        }
+        Ok(token)
+    }
+
+    /// The token's CID: over its whole envelope, signature included.
+    pub fn cid(&self) -> CapabilityCid {
+        CapabilityCid::from_bytes(Sha256::digest(self.to_bytes()).into())
+    }
+
+    /// Check the token was signed by its issuer: `issuer` is the key state
+    /// of `iss`, its log [accepts](KeyState::accepts) the signing key at
+    /// the anchor the token names — a key current then and rotated out in
+    /// order since stays good, a compromised or removed one does not — and
+    /// the signature verifies under it.
+    pub fn verify(&self, issuer: &KeyState) -> Result<(), UwillError> {
+        check_signer(self.capability.iss, &self.signer, issuer)?;
+        self.verify_signature()
+    }
+
+    /// Check the signature under the named signer, nothing more.
+    pub fn verify_signature(&self) -> Result<(), UwillError> {
+        let payload = delegation_payload(&self.capability, &self.signer);
+        check_signature(&self.signer.key, &payload, &self.signature)
+    }
+}
+
+/// A revocation of a `UWill` delegation: `iss` withdraws the token
+/// `revoke`, and with it every chain through that token. Only an issuer of
+/// the revoked link or of a link above it may revoke
+/// ([`SignedRevocation::applies_to`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Revocation {
+    /// The revoking identity.
+    pub iss: PdnId,
+    /// The revoked token.
+    pub revoke: CapabilityCid,
+}
+
+impl Revocation {
+    /// Sign this revocation with `key`, an operational key of the revoker,
+    /// under the revoker's key state at sequence number `anchor`.
+    pub fn sign(self, key: &KeyPair, anchor: u64) -> SignedRevocation {
+        let signer = Signer {
+            key: key.public(),
+            anchor,
+        };
+        let signature = key.sign(&revocation_payload(&self, &signer));
+        SignedRevocation {
+            revocation: self,
+            signer,
+            signature,
+        }
+    }
+}
+
+/// A signed revocation, in the envelope a delegation travels in: the
+/// revoker's [`Signer`] named in `meta`, as for [`SignedUwill`]. This is
+/// the record a revocation is published as — a chain holder checks it on
+/// its own, without trusting whoever relayed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedRevocation {
+    pub revocation: Revocation,
+    pub signer: Signer,
+    pub signature: Signature,
+}
+
+impl SignedRevocation {
+    /// The revocation's envelope in canonical DAG-CBOR.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        envelope(
+            &self.signature,
+            revocation_payload(&self.revocation, &self.signer),
+        )
+    }
+
+    /// Decode an envelope [`to_bytes`](Self::to_bytes) produced, refusing
+    /// anything else as [`SignedUwill::from_bytes`] does.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UwillError> {
+        let mut reader = cbor::Reader::new(bytes);
+        let signature = open_envelope(&mut reader, REVOCATION_TAG)?;
+        let (revocation, signer) = decode_revocation(&mut reader)?;
+        close_envelope(&reader)?;
+        let record = Self {
+            revocation,
+            signer,
+            signature,
+        };
+        if record.to_bytes() != bytes {
+            return Err(UwillError::NonCanonical);
+        }
+        Ok(record)
+    }
+
+    /// Check the revocation was signed by its revoker, as
+    /// [`SignedUwill::verify`] checks a token.
+    pub fn verify(&self, revoker: &KeyState) -> Result<(), UwillError> {
+        check_signer(self.revocation.iss, &self.signer, revoker)?;
+        self.verify_signature()
+    }
+
+    /// Check the signature under the named signer, nothing more.
+    pub fn verify_signature(&self) -> Result<(), UwillError> {
+        let payload = revocation_payload(&self.revocation, &self.signer);
+        check_signature(&self.signer.key, &payload, &self.signature)
+    }
+
+    /// Whether this revocation takes effect on `chain`: the chain holds the
+    /// revoked token, and the revoker issued it or a link above it.
+    pub fn applies_to(&self, chain: &[SignedUwill]) -> bool {
+        let Some(revoked) = chain
+            .iter()
+            .position(|token| token.cid() == self.revocation.revoke)
+        else {
+            return false;
+        };
+        chain
+            .iter()
+            .take(revoked.saturating_add(1))
+            .any(|token| token.capability.iss == self.revocation.iss)
+    }
+}
+
+/// Check `signer` speaks for `iss`: `state` is the key state of `iss`, and
+/// its log accepts the key at the anchor the signer names.
+fn check_signer(iss: PdnId, signer: &Signer, state: &KeyState) -> Result<(), UwillError> {
+    if state.pdn_id() != iss {
+        return Err(UwillError::ForeignIssuer);
+    }
+    if !state.accepts(&signer.key, signer.anchor) {
+        return Err(UwillError::UnauthorizedSigner);
+    }
+    Ok(())
+}
+
+fn check_signature(
+    signer: &OperationalKey,
+    payload: &[u8],
+    signature: &Signature,
+) -> Result<(), UwillError> {
+    if verify_signature(signer, payload, signature) {
+        Ok(())
+    } else {
+        Err(UwillError::BadSignature)
+    }
+}
+
+/// Why a token does not decode or verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum UwillError {
+    /// Bytes that are not a `UWill` envelope.
+    #[error("malformed UWill token: {reason}")]
+    Malformed { reason: &'static str },
+    /// A token in an encoding other than its canonical one.
+    #[error("UWill token is not canonically encoded")]
+    NonCanonical,
+    /// A token verified against another identity than its issuer.
+    #[error("UWill token is not issued by the identity it was checked against")]
+    ForeignIssuer,
+    /// A token signed by a key the issuer's log does not accept at the
+    /// anchor the token names.
+    #[error("UWill token is signed by a key its issuer does not authorize")]
+    UnauthorizedSigner,
+    /// A signature that does not verify under the named signer.
+    #[error("UWill token signature does not verify")]
+    BadSignature,
+}
+
+impl UwillCapability {
+    /// The capability's validity window.
+    pub fn window(&self) -> ValidityWindow {
+        ValidityWindow {
+            nbf: self.nbf,
+            exp: self.exp,
+        }
+    }
+}
+
+/// Where revoked tokens are looked up during chain validation. The
+/// runtime gathers the revocations it holds before validating; the check
+/// itself stays pure.
+pub trait RevocationSource {
+    /// Whether the token `cid` has been revoked.
+    fn is_revoked(&self, cid: &CapabilityCid) -> bool;
+}
+
+impl<S: BuildHasher> RevocationSource for HashSet<CapabilityCid, S> {
+    fn is_revoked(&self, cid: &CapabilityCid) -> bool {
+        self.contains(cid)
+    }
+}
+
+/// Where the key states of a chain's issuers are looked up during chain
+/// validation. The runtime verifies the key event logs it holds before
+/// validating, as it gathers revocations; an issuer without one here
+/// grants nothing.
+pub trait KeyStateSource {
+    /// The key state of `issuer`, if its log is at hand.
+    fn key_state(&self, issuer: &PdnId) -> Option<&KeyState>;
+}
+
+impl<S: BuildHasher> KeyStateSource for HashMap<PdnId, KeyState, S> {
+    fn key_state(&self, issuer: &PdnId) -> Option<&KeyState> {
+        self.get(issuer)
+    }
+}
+
+/// What a valid chain grants: its last link's audience may exercise `cmd`
+/// on `res` in `sub`'s namespace, within `window`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verdict {
+    /// The namespace owner, who issued the chain's first link.
+    pub sub: PdnId,
+    /// The holder: the last link's audience.
+    pub aud: PdnId,
+    /// The claim granted.
+    pub res: ClaimId,
+    /// The commands granted: the last link's, which every link before it
+    /// covers.
+    pub cmd: Vec<Command>,
+    /// The window the grant holds in: the last link's, which every link
+    /// before it covers.
+    pub window: ValidityWindow,
+    /// The CID of each link, first link first.
+    pub cids: Vec<CapabilityCid>,
+    /// Each link's issuer and the key that signed it, first link first:
+    /// a key the issuer's log accepts at the link's anchor, in the key
+    /// state validation resolved ([`SignedUwill::verify`]).
+    pub signers: Vec<(PdnId, OperationalKey)>,
+}
+
+/// Why a chain grants nothing — the first failure met, first link first.
+/// `link` is the index of the failing link in the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ChainError {
+    /// The chain holds no links.
+    #[error("capability chain is empty")]
+    Empty,
+    /// The first link is not issued by the namespace owner.
+    #[error("the first link is not issued by its subject")]
+    RootNotIssuedBySubject,
+    /// A link whose issuer has no key state at hand.
+    #[error("link {link}: its issuer's key state is unknown")]
+    UnknownIssuer { link: usize },
+    /// A link signed by a key its issuer's log does not authorize.
+    #[error("link {link}: signed by a key its issuer does not authorize")]
+    UnauthorizedSigner { link: usize },
+    /// A signature that does not verify under its link's signer.
+    #[error("link {link}: signature does not verify")]
+    BadSignature { link: usize },
+    /// A link that does not grant `Read`.
+    #[error("link {link}: does not grant read")]
+    MissingRead { link: usize },
+    /// A link whose window ends before it begins.
+    #[error("link {link}: validity window is empty")]
+    EmptyWindow { link: usize },
+    /// A link about another namespace than the first link's.
+    #[error("link {link}: subject differs from the chain's")]
+    SubjectChanged { link: usize },
+    /// A link granting another claim than the first link's.
+    #[error("link {link}: resource differs from the chain's")]
+    ResourceChanged { link: usize },
+    /// A link not issued by the previous link's audience.
+    #[error("link {link}: not issued by the previous link's audience")]
+    BrokenLinkage { link: usize },
+    /// A link issued under a parent that does not grant `Delegate`.
+    #[error("link {link}: the previous link does not grant delegation")]
+    NotDelegable { link: usize },
+    /// A link granting a command its parent does not.
+    #[error("link {link}: grants a command the previous link does not")]
+    CommandEscalated { link: usize },
+    /// A link whose window reaches outside its parent's.
+    #[error("link {link}: validity window exceeds the previous link's")]
+    WindowEscalated { link: usize },
+    /// A link not valid yet at the time of validation.
+    #[error("link {link}: not valid yet")]
+    NotYetValid { link: usize },
+    /// A link expired at the time of validation.
+    #[error("link {link}: expired")]
+    Expired { link: usize },
+    /// A revoked link — which revokes every link after it.
+    #[error("link {link}: revoked")]
+    Revoked { link: usize, cid: CapabilityCid },
+}
+
+/// Validate a delegation chain at `now` (unix ms): `chain[0]` is issued by
+/// the namespace owner, and each later link by the audience of the one
+/// before it, attenuating it — the same subject and resource, a subset of
+/// its commands under a parent that grants `Delegate`, and a window inside
+/// its window. Every link must grant `Read`, be signed by a key its
+/// issuer's key state in `issuers` authorizes, be valid at `now`, and not
+/// be revoked in `revocations`.
+pub fn validate_chain(
+    chain: &[SignedUwill],
+    now: u64,
+    revocations: &dyn RevocationSource,
+    issuers: &dyn KeyStateSource,
+) -> Result<Verdict, ChainError> {
+    let (root, leaf) = match (chain.first(), chain.last()) {
+        (Some(root), Some(leaf)) => (&root.capability, &leaf.capability),
+        _ => return Err(ChainError::Empty),
+    };
+    if root.iss != root.sub {
+        return Err(ChainError::RootNotIssuedBySubject);
+    }
+    let mut cids = Vec::with_capacity(chain.len());
+    let mut parent: Option<&UwillCapability> = None;
+    for (link, token) in chain.iter().enumerate() {
+        check_link(link, token, root, issuers)?;
+        if let Some(parent) = parent {
+            check_attenuation(link, &token.capability, parent)?;
+        }
+        let window = token.capability.window();
+        if now < window.nbf {
+            return Err(ChainError::NotYetValid { link });
+        }
+        if !window.contains(now) {
+            return Err(ChainError::Expired { link });
+        }
+        let cid = token.cid();
+        if revocations.is_revoked(&cid) {
+            return Err(ChainError::Revoked { link, cid });
+        }
+        cids.push(cid);
+        parent = Some(&token.capability);
+    }
+    Ok(Verdict {
+        sub: root.sub,
+        aud: leaf.aud,
+        res: root.res,
+        cmd: leaf.cmd.clone(),
+        window: leaf.window(),
+        cids,
+        signers: chain
+            .iter()
+            .map(|token| (token.capability.iss, token.signer.key))
+            .collect(),
+    })
+}
+
+/// The checks a link passes on its own, and against the chain's first.
+fn check_link(
+    link: usize,
+    token: &SignedUwill,
+    root: &UwillCapability,
+    issuers: &dyn KeyStateSource,
+) -> Result<(), ChainError> {
+    let capability = &token.capability;
+    let Some(issuer) = issuers.key_state(&capability.iss) else {
+        return Err(ChainError::UnknownIssuer { link });
+    };
+    match token.verify(issuer) {
+        Ok(()) => {}
+        Err(UwillError::BadSignature) => return Err(ChainError::BadSignature { link }),
+        Err(_foreign_or_unauthorized) => return Err(ChainError::UnauthorizedSigner { link }),
+    }
+    if !capability.cmd.contains(&Command::Read) {
+        return Err(ChainError::MissingRead { link });
+    }
+    if capability.exp <= capability.nbf {
+        return Err(ChainError::EmptyWindow { link });
+    }
+    if capability.sub != root.sub {
+        return Err(ChainError::SubjectChanged { link });
+    }
+    if capability.res != root.res {
+        return Err(ChainError::ResourceChanged { link });
+    }
+    Ok(())
+}
+
+/// The checks a link passes against the link before it.
+fn check_attenuation(
+    link: usize,
+    capability: &UwillCapability,
+    parent: &UwillCapability,
+) -> Result<(), ChainError> {
+    if capability.iss != parent.aud {
+        return Err(ChainError::BrokenLinkage { link });
+    }
+    if !parent.cmd.contains(&Command::Delegate) {
+        return Err(ChainError::NotDelegable { link });
+    }
+    if !capability
+        .cmd
+        .iter()
+        .all(|command| parent.cmd.contains(command))
+    {
+        return Err(ChainError::CommandEscalated { link });
+    }
+    if !capability.window().within(&parent.window()) {
+        return Err(ChainError::WindowEscalated { link });
+    }
+    Ok(())
+}
+
+/// An envelope: the signature, then the signed payload.
+fn envelope(signature: &Signature, signed: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::new();
+    cbor::head(&mut out, cbor::ARRAY, 2);
+    cbor::bytes(&mut out, signature.as_bytes());
+    out.extend(signed);
+    out
+}
+
+/// The signed half of an envelope: `{"h": header, tag: payload}`, its map
+/// keys in DAG-CBOR order (shorter first, then bytewise).
+fn signature_payload(tag: &str, payload: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut out = Vec::new();
+    cbor::head(&mut out, cbor::MAP, 2);
+    cbor::text(&mut out, "h");
+    cbor::bytes(&mut out, &VARSIG_HEADER);
+    cbor::text(&mut out, tag);
+    payload(&mut out);
+    out
+}
+
+fn delegation_payload(capability: &UwillCapability, signer: &Signer) -> Vec<u8> {
+    signature_payload(DELEGATION_TAG, |out| {
+        encode_payload(out, capability, signer);
+    })
+}
+
+/// The revocation payload: `{"iss", "meta": {"sn", "key"}, "revoke"}`, the
+/// revoked token named by its binary CID.
+fn revocation_payload(revocation: &Revocation, signer: &Signer) -> Vec<u8> {
+    signature_payload(REVOCATION_TAG, |out| {
+        cbor::head(out, cbor::MAP, 3);
+        cbor::text(out, "iss");
+        cbor::text(out, &did(&revocation.iss));
+        encode_meta(out, signer);
+        cbor::text(out, "revoke");
+        cbor::bytes(out, &revocation.revoke.to_cid_bytes());
+    })
+}
+
+/// The `meta` entry naming the signer: its log anchor, then its key.
+fn encode_meta(out: &mut Vec<u8>, signer: &Signer) {
+    cbor::text(out, "meta");
+    cbor::head(out, cbor::MAP, 2);
+    cbor::text(out, "sn");
+    cbor::head(out, cbor::UINT, signer.anchor);
+    cbor::text(out, "key");
+    cbor::bytes(out, signer.key.as_bytes());
+}
+
+/// The delegation payload, keys in DAG-CBOR order. `pol` is the UCAN
+/// policy, always empty: the resource and commands say all a token grants.
+fn encode_payload(out: &mut Vec<u8>, capability: &UwillCapability, signer: &Signer) {
+    cbor::head(out, cbor::MAP, 10);
+    cbor::text(out, "aud");
+    cbor::text(out, &did(&capability.aud));
+    cbor::text(out, "cmd");
+    cbor::head(out, cbor::ARRAY, cbor::len(capability.cmd.len()));
+    for command in &capability.cmd {
+        cbor::text(out, command.path());
+    }
+    cbor::text(out, "exp");
+    cbor::head(out, cbor::UINT, capability.exp);
+    cbor::text(out, "iss");
+    cbor::text(out, &did(&capability.iss));
+    cbor::text(out, "nbf");
+    cbor::head(out, cbor::UINT, capability.nbf);
+    cbor::text(out, "pol");
+    cbor::head(out, cbor::ARRAY, 0);
+    cbor::text(out, "res");
+    cbor::bytes(out, capability.res.as_bytes());
+    cbor::text(out, "sub");
+    cbor::text(out, &did(&capability.sub));
+    encode_meta(out, signer);
+    cbor::text(out, "nonce");
+    cbor::bytes(out, &capability.nonce);
+}
+
+/// Read an envelope up to its payload, which must be tagged `tag`; return
+/// the signature.
+fn open_envelope(reader: &mut cbor::Reader<'_>, tag: &str) -> Result<Signature, UwillError> {
+    if reader.array()? != 2 {
+        return Err(malformed("the envelope is not a pair"));
+    }
+    let signature = Signature::from_bytes(reader.fixed_bytes()?);
+    if reader.map()? != 2 || reader.text()? != "h" {
+        return Err(malformed("the signed payload has no varsig header"));
+    }
+    if reader.bytes()? != VARSIG_HEADER {
+        return Err(malformed("the varsig header is not Ed25519 over DAG-CBOR"));
+    }
+    if reader.text()? != tag {
+        return Err(malformed(
+            "the payload is not of the expected kind and version",
+        ));
+    }
+    Ok(signature)
+}
+
+/// Refuse bytes after the envelope's payload.
+fn close_envelope(reader: &cbor::Reader<'_>) -> Result<(), UwillError> {
+    if reader.is_empty() {
+        Ok(())
+    } else {
+        Err(malformed("bytes trail the envelope"))
+    }
+}
+
+/// Decode the `meta` map's value: the signer, its fields in any order.
+fn decode_meta(reader: &mut cbor::Reader<'_>) -> Result<Signer, UwillError> {
+    let unnamed = || malformed("the meta map does not name the signer");
+    if reader.map()? != 2 {
+        return Err(unnamed());
+    }
+    let (mut key, mut anchor) = (None, None);
+    for _ in 0..2 {
+        match reader.text()? {
+            "key" => key = Some(OperationalKey::from_bytes(reader.fixed_bytes()?)),
+            "sn" => anchor = Some(reader.uint()?),
+            _ => return Err(unnamed()),
+        }
+    }
+    Ok(Signer {
+        key: key.ok_or_else(unnamed)?,
+        anchor: anchor.ok_or_else(unnamed)?,
+    })
+}
+
+/// Decode a revocation payload, fields in any order as for
+/// [`decode_payload`].
+fn decode_revocation(reader: &mut cbor::Reader<'_>) -> Result<(Revocation, Signer), UwillError> {
+    let (mut iss, mut signer, mut revoke) = (None, None, None);
+    for _ in 0..reader.map()? {
+        match reader.text()? {
+            "iss" => iss = Some(parse_did(reader.text()?)?),
+            "meta" => signer = Some(decode_meta(reader)?),
+            "revoke" => {
+                let digest = reader
+                    .bytes()?
+                    .strip_prefix(&CID_PREFIX)
+                    .and_then(|digest| <[u8; 32]>::try_from(digest).ok())
+                    .ok_or(malformed("a revoked CID of another kind"))?;
+                revoke = Some(CapabilityCid::from_bytes(digest));
+            }
+            _ => return Err(malformed("an unknown payload field")),
+        }
+    }
+    let missing = || malformed("a payload field is missing");
+    let revocation = Revocation {
+        iss: iss.ok_or_else(missing)?,
+        revoke: revoke.ok_or_else(missing)?,
+    };
+    Ok((revocation, signer.ok_or_else(missing)?))
+}
+
+/// Decode the payload map. Fields are taken in any order and a missing or
+/// unknown one is refused; the canonical order is the caller's check.
+fn decode_payload(reader: &mut cbor::Reader<'_>) -> Result<(UwillCapability, Signer), UwillError> {
+    let (mut aud, mut cmd, mut exp, mut iss, mut nbf) = (None, None, None, None, None);
+    let (mut res, mut sub, mut signer, mut nonce) = (None, None, None, None);
+    for _ in 0..reader.map()? {
+        match reader.text()? {
+            "aud" => aud = Some(parse_did(reader.text()?)?),
+            "cmd" => {
+                let count = reader.array()?;
+                let mut commands = Vec::new();
+                for _ in 0..count {
+                    let path = reader.text()?;
+                    commands.push(Command::from_path(path).ok_or(malformed("an unknown command"))?);
+                }
+                cmd = Some(commands);
+            }
+            "exp" => exp = Some(reader.uint()?),
+            "iss" => iss = Some(parse_did(reader.text()?)?),
+            "nbf" => nbf = Some(reader.uint()?),
+            "pol" => {
+                if reader.array()? != 0 {
+                    return Err(malformed("a policy other than the empty one"));
+                }
+            }
+            "res" => res = Some(ClaimId::from_bytes(reader.fixed_bytes()?)),
+            "sub" => sub = Some(parse_did(reader.text()?)?),
+            "meta" => signer = Some(decode_meta(reader)?),
+            "nonce" => nonce = Some(reader.fixed_bytes()?),
+            _ => return Err(malformed("an unknown payload field")),
+        }
+    }
+    let missing = || malformed("a payload field is missing");
+    let capability = UwillCapability {
+        iss: iss.ok_or_else(missing)?,
+        aud: aud.ok_or_else(missing)?,
+        sub: sub.ok_or_else(missing)?,
+        cmd: cmd.ok_or_else(missing)?,
+        res: res.ok_or_else(missing)?,
+        nbf: nbf.ok_or_else(missing)?,
+        exp: exp.ok_or_else(missing)?,
+        nonce: nonce.ok_or_else(missing)?,
+    };
+    Ok((capability, signer.ok_or_else(missing)?))
+}
+
+/// The DID a token names `id` by.
+fn did(id: &PdnId) -> String {
+    format!("{DID_PREFIX}{id}")
+}
+
+fn parse_did(did: &str) -> Result<PdnId, UwillError> {
+    did.strip_prefix(DID_PREFIX)
+        .filter(|hex| hex.bytes().all(|byte| !byte.is_ascii_uppercase()))
+        .and_then(|hex| hex.parse().ok())
+        .ok_or(malformed("a principal that is not a PdnId-backed DID"))
+}
+
+fn malformed(reason: &'static str) -> UwillError {
+    UwillError::Malformed { reason }
+}
+
+/// The slice of DAG-CBOR the envelope uses: unsigned integers, byte and
+/// text strings, arrays and maps, every head in its shortest form.
+mod cbor {
+    use super::{malformed, UwillError};
+
+    pub(super) const UINT: u8 = 0;
+    const BYTES: u8 = 2;
+    const TEXT: u8 = 3;
+    pub(super) const ARRAY: u8 = 4;
+    pub(super) const MAP: u8 = 5;
+
+    /// The additional-information values of a head whose argument follows
+    /// in 1, 2, 4 or 8 bytes.
+    const ARG_U8: u8 = 0x18;
+    const ARG_U16: u8 = 0x19;
+    const ARG_U32: u8 = 0x1a;
+    const ARG_U64: u8 = 0x1b;
+
+    /// A length as a head argument.
+    pub(super) fn len(len: usize) -> u64 {
+        u64::try_from(len).unwrap_or(u64::MAX)
+    }
+
+    /// Write the head of an item of `major` type with argument `n`.
+    pub(super) fn head(out: &mut Vec<u8>, major: u8, n: u64) {
+        let major = major << 5;
+        if let Ok(n) = u8::try_from(n) {
+            if n < ARG_U8 {
+                out.push(major | n);
+            } else {
+                out.extend([major | ARG_U8, n]);
+            }
+        } else if let Ok(n) = u16::try_from(n) {
+            out.push(major | ARG_U16);
+            out.extend(n.to_be_bytes());
+        } else if let Ok(n) = u32::try_from(n) {
+            out.push(major | ARG_U32);
+            out.extend(n.to_be_bytes());
+        } else {
+            out.push(major | ARG_U64);
+            out.extend(n.to_be_bytes());
+        }
+    }
+
+    pub(super) fn bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+        head(out, BYTES, len(bytes.len()));
+        out.extend_from_slice(bytes);
+    }
+
+    pub(super) fn text(out: &mut Vec<u8>, text: &str) {
+        head(out, TEXT, len(text.len()));
+        out.extend_from_slice(text.as_bytes());
+    }
+
+    /// A cursor over encoded bytes.
+    pub(super) struct Reader<'a> {
+        rest: &'a [u8],
+    }
+
+    impl<'a> Reader<'a> {
+        pub(super) fn new(bytes: &'a [u8]) -> Self {
+            Self { rest: bytes }
+        }
+
+        pub(super) fn is_empty(&self) -> bool {
+            self.rest.is_empty()
+        }
+
+        fn take(&mut self, n: usize) -> Result<&'a [u8], UwillError> {
+            let (taken, rest) = self
+                .rest
+                .split_at_checked(n)
+                .ok_or(malformed("the encoding ends early"))?;
+            self.rest = rest;
+            Ok(taken)
+        }
+
+        fn array_of<const N: usize>(&mut self) -> Result<[u8; N], UwillError> {
+            self.take(N)?
+                .try_into()
+                .map_err(|_| malformed("the encoding ends early"))
+        }
+
+        /// Read a head of `major` type; return its argument.
+        fn head(&mut self, major: u8) -> Result<u64, UwillError> {
+            let [initial] = self.array_of::<1>()?;
+            if initial >> 5 != major {
+                return Err(malformed("an item of an unexpected type"));
+            }
+            match initial & 0x1f {
+                info if info < ARG_U8 => Ok(u64::from(info)),
+                ARG_U8 => Ok(u64::from(u8::from_be_bytes(self.array_of()?))),
+                ARG_U16 => Ok(u64::from(u16::from_be_bytes(self.array_of()?))),
+                ARG_U32 => Ok(u64::from(u32::from_be_bytes(self.array_of()?))),
+                ARG_U64 => Ok(u64::from_be_bytes(self.array_of()?)),
+                _ => Err(malformed("an indefinite or reserved length")),
+            }
+        }
+
+        fn length(&mut self, major: u8) -> Result<usize, UwillError> {
+            usize::try_from(self.head(major)?).map_err(|_| malformed("a length out of range"))
+        }
+
+        pub(super) fn uint(&mut self) -> Result<u64, UwillError> {
+            self.head(UINT)
+        }
+
+        pub(super) fn bytes(&mut self) -> Result<&'a [u8], UwillError> {
+            let len = self.length(BYTES)?;
+            self.take(len)
+        }
+
+        /// A byte string of exactly `N` bytes.
+        pub(super) fn fixed_bytes<const N: usize>(&mut self) -> Result<[u8; N], UwillError> {
+            self.bytes()?
+                .try_into()
+                .map_err(|_| malformed("a byte string of the wrong length"))
+        }
+
+        pub(super) fn text(&mut self) -> Result<&'a str, UwillError> {
+            let len = self.length(TEXT)?;
+            std::str::from_utf8(self.take(len)?).map_err(|_| malformed("text that is not UTF-8"))
+        }
+
+        pub(super) fn array(&mut self) -> Result<usize, UwillError> {
+            self.length(ARRAY)
+        }
+
+        pub(super) fn map(&mut self) -> Result<usize, UwillError> {
+            self.length(MAP)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::fmt::Write as _;
+
+    use super::*;
+    use crate::kel::KeyEventLog;
+
+    fn key(byte: u8) -> KeyPair {
+        KeyPair::from_seed([byte; 32])
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+
+    fn capability(iss: PdnId) -> UwillCapability {
+        UwillCapability {
+            iss,
+            aud: PdnId::from_bytes([0xb0; 32]),
+            sub: iss,
+            cmd: vec![Command::Read, Command::Write],
+            res: ClaimId::from_bytes([0x5c; 32]),
+            nbf: 1_700_000_000_000,
+            exp: 1_700_086_400_000,
+            nonce: [7; 12],
+        }
+    }
+
+    /// The envelope of `capability(0xa1…)` signed by the key of seed
+    /// `[1; 32]` under its log's inception, recorded from this encoder when the profile's version
+    /// was fixed. A change here breaks every token minted so far, and
+    /// needs a new profile version.
+    const VECTOR: &str = concat!(
+        "82584012127bed9a52ad8b8454a0634650a585112db4804cbb0a807798e361b5",
+        "c605827b95281ac31ae31eff84467fee43d905b017cf95241e431ca7d36baf2d",
+        "2ce006a26168483401ed01ed0113716970646e2f646c674032aa636175647848",
+        "6469643a70646e3a623062306230623062306230623062306230623062306230",
+        "6230623062306230623062306230623062306230623062306230623062306230",
+        "623062306230623063636d6482692f70646e2f726561646a2f70646e2f777269",
+        "7465636578701b0000018bd50bc4006369737378486469643a70646e3a613161",
+        "3161316131613161316131613161316131613161316131613161316131613161",
+        "3161316131613161316131613161316131613161316131613161316131636e62",
+        "661b0000018bcfe5680063706f6c806372657358205c5c5c5c5c5c5c5c5c5c5c",
+        "5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c5c6373756278486469643a70",
+        "646e3a6131613161316131613161316131613161316131613161316131613161",
+        "3161316131613161316131613161316131613161316131613161316131613161",
+        "316131646d657461a262736e00636b657958208a88e3dd7409f195fd52db2d3c",
+        "ba5d72ca6709bf1d94121bf3748801b40f6f5c656e6f6e63654c070707070707",
+        "070707070707",
+    );
+
+    /// SHA-256 of [`VECTOR`].
+    const VECTOR_CID: &str = "709b8874541689973453ea9341bb209058038509005ebbf0f8f5eb35a0547bce";
+
+    #[test]
+    fn a_token_encodes_to_its_vector_and_back() {
+        let token = capability(PdnId::from_bytes([0xa1; 32])).sign(&key(1), 0);
+        let bytes = token.to_bytes();
+        assert_eq!(hex(&bytes), VECTOR);
+        assert_eq!(hex(token.cid().as_bytes()), VECTOR_CID);
+        assert_eq!(
+            hex(&token.cid().to_cid_bytes()),
+            format!("01711220{VECTOR_CID}")
+        );
+        assert_eq!(SignedUwill::from_bytes(&bytes), Ok(token.clone()));
+        assert_eq!(token.verify_signature(), Ok(()));
+    }
+
+    #[test]
+    fn a_token_verifies_only_under_a_key_of_its_issuer() {
+        let mut log = KeyEventLog::incept(&key(1), &key(2).public());
+        let state = log.verify().unwrap();
+        let token = capability(state.pdn_id()).sign(&key(1), 0);
+        assert_eq!(token.verify(&state), Ok(()));
+
+        // Another identity's key state.
+        let stranger = KeyEventLog::incept(&key(5), &key(6).public())
+            .verify()
+            .unwrap();
+        assert_eq!(token.verify(&stranger), Err(UwillError::ForeignIssuer));
+
+        // A key the issuer's log never named, and one naming a state the
+        // log has not reached.
+        let forged = capability(state.pdn_id()).sign(&key(9), 0);
+        assert_eq!(forged.verify(&state), Err(UwillError::UnauthorizedSigner));
+        let ahead = capability(state.pdn_id()).sign(&key(1), 1);
+        assert_eq!(ahead.verify(&state), Err(UwillError::UnauthorizedSigner));
+
+        // An orderly rotation keeps what the old key signed while current,
+        // and nothing it signs naming a later state.
+        let rotated = log.rotate(&key(2), &key(3).public(), false).unwrap();
+        assert_eq!(token.verify(&rotated), Ok(()));
+        let late = capability(state.pdn_id()).sign(&key(1), 1);
+        assert_eq!(late.verify(&rotated), Err(UwillError::UnauthorizedSigner));
+
+        // A payload changed after signing.
+        let mut tampered = token;
+        tampered.capability.cmd.push(Command::Delegate);
+        assert_eq!(tampered.verify(&state), Err(UwillError::BadSignature));
+    }
+
+    #[test]
+    fn only_the_canonical_envelope_decodes() {
+        let bytes = capability(PdnId::from_bytes([0xa1; 32]))
+            .sign(&key(1), 0)
+            .to_bytes();
+
+        // The empty policy with its length in a one-byte argument.
+        let wide = hex(&bytes).replace("63706f6c80", "63706f6c9800");
+        let wide: Vec<u8> = (0..wide.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&wide[i..i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(
+            SignedUwill::from_bytes(&wide),
+            Err(UwillError::NonCanonical)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            SignedUwill::from_bytes(&trailing),
+            Err(UwillError::Malformed { .. })
+        ));
+        assert!(matches!(
+            SignedUwill::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(UwillError::Malformed { .. })
+        ));
+    }
+
+    /// The identity incepted by `key(byte)`.
+    fn id(byte: u8) -> PdnId {
+        PdnId::from_bytes(*key(byte).public().as_bytes())
+    }
+
+    /// A link in Alice's namespace (key 1) from `iss` to `aud`.
+    fn link(iss: u8, aud: u8, cmd: &[Command], nbf: u64, exp: u64) -> (u8, UwillCapability) {
+        let capability = UwillCapability {
+            iss: id(iss),
+            aud: id(aud),
+            sub: id(1),
+            cmd: cmd.to_vec(),
+            res: ClaimId::from_bytes([0x5c; 32]),
+            nbf,
+            exp,
+            nonce: [iss; 12],
+        };
+        (iss, capability)
+    }
+
+    /// Alice grants Bob (key 2) read, write and delegation over
+    /// 1000..5000; Bob passes read on to Carol (key 3) over 2000..4000.
+    fn links() -> Vec<(u8, UwillCapability)> {
+        use Command::{Delegate, Read, Write};
+        vec![
+            link(1, 2, &[Read, Write, Delegate], 1000, 5000),
+            link(2, 3, &[Read], 2000, 4000),
+        ]
+    }
+
+    /// Each link signed by the key its signer byte names.
+    fn signed(links: Vec<(u8, UwillCapability)>) -> Vec<SignedUwill> {
+        links
+            .into_iter()
+            .map(|(signer, capability)| capability.sign(&key(signer), 0))
+            .collect()
+    }
+
+    /// The key states of Alice, Bob and Carol, each as incepted.
+    fn issuers() -> HashMap<PdnId, KeyState> {
+        (1..=3)
+            .map(|byte| {
+                let log = KeyEventLog::incept(&key(byte), &key(byte + 10).public());
+                (id(byte), log.verify().unwrap())
+            })
+            .collect()
+    }
+
+    const NOW: u64 = 3000;
+
+    /// `links()` with `change` applied to the link at `at`, signed after.
+    fn validate_changed(at: usize, change: impl FnOnce(&mut UwillCapability)) -> ChainError {
+        let mut links = links();
+        change(&mut links[at].1);
+        validate_chain(&signed(links), NOW, &HashSet::new(), &issuers()).unwrap_err()
+    }
+
+    #[test]
+    fn a_valid_chain_grants_what_its_last_link_grants() {
+        let chain = signed(links());
+        let verdict = validate_chain(&chain, NOW, &HashSet::new(), &issuers()).unwrap();
+        assert_eq!(verdict.sub, id(1));
+        assert_eq!(verdict.aud, id(3));
+        assert_eq!(verdict.res, ClaimId::from_bytes([0x5c; 32]));
+        assert_eq!(verdict.cmd, [Command::Read]);
+        assert_eq!(
+            verdict.window,
+            ValidityWindow {
+                nbf: 2000,
+                exp: 4000
+            }
+        );
+        assert_eq!(verdict.cids, [chain[0].cid(), chain[1].cid()]);
+        assert_eq!(
+            verdict.signers,
+            [(id(1), key(1).public()), (id(2), key(2).public())]
+        );
+
+        // The first link alone is a chain too.
+        let root = validate_chain(&chain[..1], NOW, &HashSet::new(), &issuers()).unwrap();
+        assert_eq!(root.aud, id(2));
+        assert_eq!(root.cmd.len(), 3);
+    }
+
+    #[test]
+    fn a_chain_must_start_at_its_subject() {
+        assert_eq!(
+            validate_chain(&[], NOW, &HashSet::new(), &issuers()),
+            Err(ChainError::Empty)
+        );
+        let mut links = links();
+        links.remove(0);
+        assert_eq!(
+            validate_chain(&signed(links), NOW, &HashSet::new(), &issuers()),
+            Err(ChainError::RootNotIssuedBySubject)
+        );
+    }
+
+    #[test]
+    fn every_link_must_be_well_formed_on_its_own() {
+        let mut chain = signed(links());
+        chain[1].capability.nonce = [0; 12];
+        assert_eq!(
+            validate_chain(&chain, NOW, &HashSet::new(), &issuers()),
+            Err(ChainError::BadSignature { link: 1 })
+        );
+        assert_eq!(
+            validate_changed(0, |root| root.cmd.retain(|&c| c != Command::Read)),
+            ChainError::MissingRead { link: 0 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.cmd = vec![Command::Write]),
+            ChainError::MissingRead { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.exp = leaf.nbf),
+            ChainError::EmptyWindow { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.sub = id(2)),
+            ChainError::SubjectChanged { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.res = ClaimId::from_bytes([0; 32])),
+            ChainError::ResourceChanged { link: 1 }
+        );
+    }
+
+    #[test]
+    fn every_link_must_be_signed_by_its_issuer() {
+        // Bob's link signed by a key Bob's log never named.
+        let root = signed(links()).remove(0);
+        let (_bob, leaf) = links().remove(1);
+        let forged = vec![root, leaf.sign(&key(4), 0)];
+        assert_eq!(
+            validate_chain(&forged, NOW, &HashSet::new(), &issuers()),
+            Err(ChainError::UnauthorizedSigner { link: 1 })
+        );
+
+        // Bob's link, with no key state of Bob's at hand.
+        let mut known = issuers();
+        known.remove(&id(2));
+        assert_eq!(
+            validate_chain(&signed(links()), NOW, &HashSet::new(), &known),
+            Err(ChainError::UnknownIssuer { link: 1 })
+        );
+
+        // The same key speaks for Bob once his log adds it as a device key.
+        let mut bob = KeyEventLog::incept(&key(2), &key(12).public());
+        let mut known = issuers();
+        known.insert(id(2), bob.add_device(&key(2), &key(4).public()).unwrap());
+        assert!(validate_chain(&forged, NOW, &HashSet::new(), &known).is_ok());
+    }
+
+    #[test]
+    fn a_compromised_key_signs_nothing_whatever_its_anchor() {
+        // Alice's first key is stolen; she rotates away from it declaring
+        // so. The thief's link names the state the key was current in.
+        let mut alice = KeyEventLog::incept(&key(1), &key(11).public());
+        let mut known = issuers();
+        known.insert(
+            id(1),
+            alice.rotate(&key(11), &key(21).public(), true).unwrap(),
+        );
+        let chain = signed(links());
+        assert_eq!(chain[0].signer.anchor, 0);
+        assert_eq!(
+            validate_chain(&chain, NOW, &HashSet::new(), &known),
+            Err(ChainError::UnauthorizedSigner { link: 0 })
+        );
+        let revocation = Revocation {
+            iss: id(1),
+            revoke: chain[1].cid(),
+        };
+        assert_eq!(
+            revocation.sign(&key(1), 0).verify(&known[&id(1)]),
+            Err(UwillError::UnauthorizedSigner)
+        );
+        // The key she rotated to signs under the state it is current in.
+        assert_eq!(revocation.sign(&key(11), 1).verify(&known[&id(1)]), Ok(()));
+    }
+
+    #[test]
+    fn a_delegation_survives_orderly_rotations_of_its_signers() {
+        // Alice's grant to Bob and Bob's link to Carol were signed under
+        // their inceptions; both rotate in the ordinary course since.
+        let chain = signed(links());
+        let mut alice = KeyEventLog::incept(&key(1), &key(11).public());
+        let mut bob = KeyEventLog::incept(&key(2), &key(12).public());
+        let mut known = issuers();
+        known.insert(
+            id(1),
+            alice.rotate(&key(11), &key(21).public(), false).unwrap(),
+        );
+        known.insert(
+            id(2),
+            bob.rotate(&key(12), &key(22).public(), false).unwrap(),
+        );
+        let verdict = validate_chain(&chain, NOW, &HashSet::new(), &known).unwrap();
+        assert_eq!(verdict.aud, id(3));
+
+        // Bob's revocation, made with his old key before he rotated, still
+        // counts; one made with it after does not.
+        let revocation = Revocation {
+            iss: id(2),
+            revoke: chain[1].cid(),
+        };
+        let before = revocation.sign(&key(2), 0);
+        assert_eq!(before.verify(&known[&id(2)]), Ok(()));
+        assert!(before.applies_to(&chain));
+        assert_eq!(
+            revocation.sign(&key(2), 1).verify(&known[&id(2)]),
+            Err(UwillError::UnauthorizedSigner)
+        );
+        let revoked = HashSet::from([before.revocation.revoke]);
+        assert_eq!(
+            validate_chain(&chain, NOW, &revoked, &known),
+            Err(ChainError::Revoked {
+                link: 1,
+                cid: chain[1].cid(),
+            })
+        );
+    }
+
+    #[test]
+    fn every_link_must_attenuate_the_one_before() {
+        // Carol re-issuing Bob's grant to herself.
+        let mut links = links();
+        links[1] = link(3, 3, &[Command::Read], 2000, 4000);
+        assert_eq!(
+            validate_chain(&signed(links), NOW, &HashSet::new(), &issuers()),
+            Err(ChainError::BrokenLinkage { link: 1 })
+        );
+        assert_eq!(
+            validate_changed(0, |root| root.cmd.retain(|&c| c != Command::Delegate)),
+            ChainError::NotDelegable { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.cmd.push(Command::Delete)),
+            ChainError::CommandEscalated { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.exp = 6000),
+            ChainError::WindowEscalated { link: 1 }
+        );
+        assert_eq!(
+            validate_changed(1, |leaf| leaf.nbf = 500),
+            ChainError::WindowEscalated { link: 1 }
+        );
+    }
+
+    #[test]
+    fn every_link_must_be_valid_now_and_unrevoked() {
+        let chain = signed(links());
+        let none = HashSet::new();
+        assert_eq!(
+            validate_chain(&chain, 500, &none, &issuers()),
+            Err(ChainError::NotYetValid { link: 0 })
+        );
+        assert_eq!(
+            validate_chain(&chain, 1500, &none, &issuers()),
+            Err(ChainError::NotYetValid { link: 1 })
+        );
+        assert_eq!(
+            validate_chain(&chain, 4000, &none, &issuers()),
+            Err(ChainError::Expired { link: 1 })
+        );
+        assert_eq!(
+            validate_chain(&chain, 5000, &none, &issuers()),
+            Err(ChainError::Expired { link: 0 })
+        );
+
+        // Revoking a link revokes the chain through it.
+        for (at, token) in chain.iter().enumerate() {
+            let revoked = HashSet::from([token.cid()]);
+            assert_eq!(
+                validate_chain(&chain, NOW, &revoked, &issuers()),
+                Err(ChainError::Revoked {
+                    link: at,
+                    cid: token.cid()
+                })
+            );
+        }
+    }
+
+    /// The revocation of [`VECTOR`]'s token by its issuer, signed by the
+    /// same key, recorded as [`VECTOR`] was.
+    const REVOCATION_VECTOR: &str = concat!(
+        "825840383cce3827f1f6cfb246ce8c555803954585157b909a9dbb374d0ab416",
+        "33546d24ec6357980fd6ea95c29062c811e59b57e3647c776e57610daaa229a4",
+        "2fb401a26168483401ed01ed0113716770646e2f724032a36369737378486469",
+        "643a70646e3a6131613161316131613161316131613161316131613161316131",
+        "6131613161316131613161316131613161316131613161316131613161316131",
+        "613161316131646d657461a262736e00636b657958208a88e3dd7409f195fd52",
+        "db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c667265766f6b65582401",
+        "711220709b8874541689973453ea9341bb209058038509005ebbf0f8f5eb35a0",
+        "547bce",
+    );
+
+    #[test]
+    fn a_revocation_encodes_to_its_vector_and_back() {
+        let token = capability(PdnId::from_bytes([0xa1; 32])).sign(&key(1), 0);
+        let revocation = Revocation {
+            iss: token.capability.iss,
+            revoke: token.cid(),
+        }
+        .sign(&key(1), 0);
+        let bytes = revocation.to_bytes();
+        assert_eq!(hex(&bytes), REVOCATION_VECTOR);
+        assert_eq!(SignedRevocation::from_bytes(&bytes), Ok(revocation.clone()));
+        assert_eq!(revocation.verify_signature(), Ok(()));
+
+        // Neither envelope decodes as the other.
+        assert!(matches!(
+            SignedUwill::from_bytes(&bytes),
+            Err(UwillError::Malformed { .. })
+        ));
+        assert!(matches!(
+            SignedRevocation::from_bytes(&token.to_bytes()),
+            Err(UwillError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn a_revocation_applies_from_the_revoked_link_up() {
+        let chain = signed(links());
+        let revoke = |by: u8, at: usize| {
+            Revocation {
+                iss: id(by),
+                revoke: chain[at].cid(),
+            }
+            .sign(&key(by), 0)
+        };
+        // Alice may revoke her own link and Bob's below it; Bob only his.
+        assert!(revoke(1, 0).applies_to(&chain));
+        assert!(revoke(1, 1).applies_to(&chain));
+        assert!(revoke(2, 1).applies_to(&chain));
+        assert!(!revoke(2, 0).applies_to(&chain));
+        // Carol, the holder, issued nothing in the chain.
+        assert!(!revoke(3, 1).applies_to(&chain));
+        // A token the chain does not hold.
+        assert!(!revoke(1, 0).applies_to(&chain[1..]));
+
+        let state = KeyEventLog::incept(&key(1), &key(2).public())
+            .verify()
+            .unwrap();
+        assert_eq!(revoke(1, 0).verify(&state), Ok(()));
+        assert_eq!(revoke(2, 0).verify(&state), Err(UwillError::ForeignIssuer));
+        let mut tampered = revoke(1, 0);
+        tampered.revocation.revoke = chain[1].cid();
+        assert_eq!(tampered.verify(&state), Err(UwillError::BadSignature));
+    }
 }
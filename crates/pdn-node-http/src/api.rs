@@ -0,0 +1,413 @@
+//! The versioned `/v1` surface: the runtime's four services over JSON.
+//!
+//! Routes map one-to-one onto service operations and add no semantics of
+//! their own. Ids travel as the hex the types print, entry paths as the
+//! route's tail, payload bytes as standard base64, and the ceremonies'
+//! payloads as their `pdn-invite:` / `pdn-link:` URIs. Failures answer as
+//! [`ApiError`] describes.
+//!
+//! | Route | Operation |
+//! |---|---|
+//! | `GET /v1/node` | the node id |
+//! | `POST /v1/tokens` | mint an access token (see the `auth` module) |
+//! | `GET, POST /v1/identities` | hosted identities; create one |
+//! | `POST /v1/identities/link` | link this node into an identity |
+//! | `POST /v1/identities/{identity}/linking-invites` | mint a linking invite |
+//! | `POST /v1/identities/{identity}/invites` | mint a connection invite |
+//! | `GET, POST /v1/identities/{identity}/connections` | list; establish from an invite |
+//! | `GET /v1/identities/{identity}/connections/{peer}/grants` | the peer's grants toward us |
+//! | `PUT, DELETE /v1/identities/{identity}/connections/{peer}/grants/{issuer}` | publish, bounded by an optional `window`; withdraw |
+//! | `GET, DELETE /v1/data/{issuer}/entries` | list entries, `?prefix=` narrowing; delete those under `?prefix=` |
+//! | `GET, PUT, DELETE /v1/data/{issuer}/entries/{path}` | read; write; delete one entry |
+//! | `GET /v1/events` | the change feed as SSE, `?identity=`, `?issuer=`, `?prefix=` narrowing |
+//! | `GET /v1/identities/{identity}/events` | one identity's share of the feed |
+//!
+//! The two feeds are the `events` module's.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use data_encoding::BASE64;
+use pdn_node::{
+    ClaimId, ConnectionsService as _, DataService as _, EntryInfo, EntryPath, IdentityService as _,
+    InvitePayload, LinkingPayload, NodeId, NonEmpty, PdnId, ReadGrant, Runtime, SyncService as _,
+    ValidityWindow,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+use crate::events;
+
+/// How long `link` waits for the first directory sync unless the request
+/// says otherwise.
+const DEFAULT_LINK_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// The `/v1` routes, over the embedded runtime.
+pub(crate) fn routes() -> Router<Arc<Runtime>> {
+    Router::new()
+        .route("/v1/node", get(node))
+        .route("/v1/identities", get(list_identities).post(create_identity))
+        .route("/v1/identities/link", post(link_identity))
+        .route(
+            "/v1/identities/{identity}/linking-invites",
+            post(linking_invite),
+        )
+        .route("/v1/identities/{identity}/invites", post(invite))
+        .route(
+            "/v1/identities/{identity}/connections",
+            get(list_connections).post(establish),
+        )
+        .route(
+            "/v1/identities/{identity}/connections/{peer}/grants",
+            get(read_grants),
+        )
+        .route(
+            "/v1/identities/{identity}/connections/{peer}/grants/{issuer}",
+            put(publish_grant).delete(withdraw_grant),
+        )
+        .route("/v1/events", get(events::all_events))
+        .route(
+            "/v1/identities/{identity}/events",
+            get(events::identity_events),
+        )
+        .route(
+            "/v1/data/{issuer}/entries",
+            get(list_entries).delete(delete_entries),
+        )
+        .route(
+            "/v1/data/{issuer}/entries/{*path}",
+            get(read_entry).put(write_entry).delete(delete_entry),
+        )
+}
+
+/// Parse an id from its route segment.
+pub(crate) fn id(segment: &str) -> ApiResult<PdnId> {
+    segment
+        .parse()
+        .map_err(|err| ApiError::bad_request(format!("invalid id {segment:?}: {err}")))
+}
+
+/// Parse an entry path from the route's tail.
+pub(crate) fn entry_path(tail: &str) -> ApiResult<EntryPath> {
+    EntryPath::new(tail).map_err(|err| ApiError::bad_request(format!("invalid path: {err}")))
+}
+
+/// Parse a JSON request body. An empty body reads as `{}`, so a request
+/// whose fields are all optional may send none.
+pub(crate) fn body<T: DeserializeOwned>(bytes: &Bytes) -> ApiResult<T> {
+    let bytes: &[u8] = if bytes.is_empty() { b"{}" } else { bytes };
+    serde_json::from_slice(bytes)
+        .map_err(|err| ApiError::bad_request(format!("invalid request body: {err}")))
+}
+
+/// A 201 answering with `body`.
+fn created<T: Serialize>(body: T) -> Response {
+    (StatusCode::CREATED, Json(body)).into_response()
+}
+
+#[derive(Serialize)]
+struct NodeBody {
+    node_id: NodeId,
+}
+
+async fn node(State(runtime): State<Arc<Runtime>>) -> Json<NodeBody> {
+    Json(NodeBody {
+        node_id: runtime.sync().node_id(),
+    })
+}
+
+#[derive(Serialize)]
+struct IdentitiesBody {
+    identities: Vec<PdnId>,
+}
+
+async fn list_identities(State(runtime): State<Arc<Runtime>>) -> ApiResult<Json<IdentitiesBody>> {
+    let identities = runtime.sync().hosted_identities().await?;
+    Ok(Json(IdentitiesBody { identities }))
+}
+
+#[derive(Serialize)]
+struct IdentityBody {
+    identity: PdnId,
+}
+
+async fn create_identity(State(runtime): State<Arc<Runtime>>) -> ApiResult<Response> {
+    let identity = runtime.identity().create().await?;
+    Ok(created(IdentityBody { identity }))
+}
+
+#[derive(Deserialize)]
+struct LinkRequest {
+    payload: String,
+    label: Option<String>,
+    timeout_ms: Option<u64>,
+}
+
+async fn link_identity(State(runtime): State<Arc<Runtime>>, bytes: Bytes) -> ApiResult<Response> {
+    let request: LinkRequest = body(&bytes)?;
+    let payload: LinkingPayload = request.payload.parse()?;
+    let identity = payload.identity;
+    let timeout = request
+        .timeout_ms
+        .map_or(DEFAULT_LINK_TIMEOUT, Duration::from_millis);
+    runtime
+        .identity()
+        .link(payload, request.label.as_deref(), timeout)
+        .await?;
+    Ok(created(IdentityBody { identity }))
+}
+
+#[derive(Deserialize)]
+struct InviteRequest {
+    lifetime_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct LinkingInviteBody {
+    payload: String,
+}
+
+async fn linking_invite(
+    State(runtime): State<Arc<Runtime>>,
+    Path(identity): Path<String>,
+    bytes: Bytes,
+) -> ApiResult<Response> {
+    let request: InviteRequest = body(&bytes)?;
+    let payload = runtime
+        .identity()
+        .linking_invite(
+            id(&identity)?,
+            request.lifetime_secs.map(Duration::from_secs),
+        )
+        .await?;
+    Ok(created(LinkingInviteBody {
+        payload: payload.to_string(),
+    }))
+}
+
+#[derive(Serialize)]
+struct InviteBody {
+    invite: String,
+}
+
+async fn invite(
+    State(runtime): State<Arc<Runtime>>,
+    Path(identity): Path<String>,
+    bytes: Bytes,
+) -> ApiResult<Response> {
+    let request: InviteRequest = body(&bytes)?;
+    let payload = runtime
+        .connections()
+        .invite(
+            id(&identity)?,
+            request.lifetime_secs.map(Duration::from_secs),
+        )
+        .await?;
+    Ok(created(InviteBody {
+        invite: payload.to_string(),
+    }))
+}
+
+#[derive(Serialize)]
+struct ConnectionsBody {
+    connections: Vec<PdnId>,
+}
+
+async fn list_connections(
+    State(runtime): State<Arc<Runtime>>,
+    Path(identity): Path<String>,
+) -> ApiResult<Json<ConnectionsBody>> {
+    let connections = runtime.connections().list(id(&identity)?).await?;
+    Ok(Json(ConnectionsBody { connections }))
+}
+
+#[derive(Deserialize)]
+struct EstablishRequest {
+    invite: String,
+}
+
+#[derive(Serialize)]
+struct PeerBody {
+    peer: PdnId,
+}
+
+async fn establish(
+    State(runtime): State<Arc<Runtime>>,
+    Path(identity): Path<String>,
+    bytes: Bytes,
+) -> ApiResult<Response> {
+    let identity = id(&identity)?;
+    let request: EstablishRequest = body(&bytes)?;
+    let invite: InvitePayload = request.invite.parse()?;
+    let peer = invite.inviter;
+    runtime.connections().establish(identity, invite).await?;
+    Ok(created(PeerBody { peer }))
+}
+
+#[derive(Serialize)]
+struct GrantBody {
+    grant: ReadGrant,
+    ticket: String,
+}
+
+#[derive(Serialize)]
+struct GrantsBody {
+    grants: Vec<GrantBody>,
+}
+
+async fn read_grants(
+    State(runtime): State<Arc<Runtime>>,
+    Path((identity, peer)): Path<(String, String)>,
+) -> ApiResult<Json<GrantsBody>> {
+    let grants = runtime
+        .connections()
+        .read_grants(id(&identity)?, id(&peer)?)
+        .await?
+        .into_iter()
+        .map(|granted| GrantBody {
+            grant: granted.grant,
+            ticket: granted.ticket.to_string(),
+        })
+        .collect();
+    Ok(Json(GrantsBody { grants }))
+}
+
+#[derive(Deserialize)]
+struct PublishGrantRequest {
+    claims: Vec<ClaimId>,
+    #[serde(default)]
+    write: bool,
+    #[serde(default)]
+    window: Option<ValidityWindow>,
+}
+
+async fn publish_grant(
+    State(runtime): State<Arc<Runtime>>,
+    Path((identity, peer, issuer)): Path<(String, String, String)>,
+    bytes: Bytes,
+) -> ApiResult<StatusCode> {
+    let (identity, peer, issuer) = (id(&identity)?, id(&peer)?, id(&issuer)?);
+    let request: PublishGrantRequest = body(&bytes)?;
+    let claims = NonEmpty::from_vec(request.claims)
+        .ok_or_else(|| ApiError::bad_request("a grant names at least one claim"))?;
+    let connections = runtime.connections();
+    let _tokens = match request.window {
+        Some(window) => {
+            connections
+                .publish_grant_within(identity, peer, issuer, claims, request.write, window)
+                .await?
+        }
+        None => {
+            connections
+                .publish_grant(identity, peer, issuer, claims, request.write)
+                .await?
+        }
+    };
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn withdraw_grant(
+    State(runtime): State<Arc<Runtime>>,
+    Path((identity, peer, issuer)): Path<(String, String, String)>,
+) -> ApiResult<StatusCode> {
+    runtime
+        .connections()
+        .withdraw_grant(id(&identity)?, id(&peer)?, id(&issuer)?)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    prefix: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EntriesBody {
+    entries: Vec<EntryInfo>,
+}
+
+async fn list_entries(
+    State(runtime): State<Arc<Runtime>>,
+    Path(issuer): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult<Json<EntriesBody>> {
+    let prefix = query.prefix.as_deref().map(entry_path).transpose()?;
+    let entries = runtime.data().list(id(&issuer)?, prefix.as_ref()).await?;
+    Ok(Json(EntriesBody { entries }))
+}
+
+#[derive(Serialize)]
+struct DeletedBody {
+    deleted: usize,
+}
+
+/// Deleting under no prefix would empty the whole store; it has to be
+/// asked for by name.
+async fn delete_entries(
+    State(runtime): State<Arc<Runtime>>,
+    Path(issuer): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult<Json<DeletedBody>> {
+    let prefix = query
+        .prefix
+        .as_deref()
+        .ok_or_else(|| ApiError::bad_request("deleting entries takes a ?prefix="))?;
+    let prefix = entry_path(prefix)?;
+    let deleted = runtime.data().delete_prefix(id(&issuer)?, &prefix).await?;
+    Ok(Json(DeletedBody { deleted }))
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryBody {
+    payload: String,
+}
+
+async fn read_entry(
+    State(runtime): State<Arc<Runtime>>,
+    Path((issuer, path)): Path<(String, String)>,
+) -> ApiResult<Json<EntryBody>> {
+    let path = entry_path(&path)?;
+    let payload = runtime
+        .data()
+        .read(id(&issuer)?, &path)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("no entry at {path}")))?;
+    Ok(Json(EntryBody {
+        payload: BASE64.encode(&payload),
+    }))
+}
+
+async fn write_entry(
+    State(runtime): State<Arc<Runtime>>,
+    Path((issuer, path)): Path<(String, String)>,
+    bytes: Bytes,
+) -> ApiResult<StatusCode> {
+    let (issuer, path) = (id(&issuer)?, entry_path(&path)?);
+    let request: EntryBody = body(&bytes)?;
+    let payload = BASE64
+        .decode(request.payload.as_bytes())
+        .map_err(|err| ApiError::bad_request(format!("payload is not base64: {err}")))?;
+    runtime.data().write(issuer, &path, &payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_entry(
+    State(runtime): State<Arc<Runtime>>,
+    Path((issuer, path)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    let path = entry_path(&path)?;
+    if !runtime.data().delete(id(&issuer)?, &path).await? {
+        return Err(ApiError::not_found(format!("no entry at {path}")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,299 @@
+//! The host's local authentication: bearer tokens, each either the
+//! operator's — the whole host — or scoped to one hosted identity, so a
+//! client app handed a scoped token acts for that `PdnId` alone.
+//!
+//! Every request but `GET /live` presents `Authorization: Bearer <token>`;
+//! a request without a known token is refused `401` before any route runs.
+//! A scoped token reaches only the routes addressing its identity —
+//! `/v1/identities/{identity}/…` and its own data under
+//! `/v1/data/{identity}/…` — plus `GET /v1/node`; everything else
+//! (creating or linking identities, listing them, minting tokens, the debug
+//! scaffolding) is the operator's, and a scoped token is refused `403`
+//! there.
+//!
+//! Tokens live in a JSON token file, written owner-only. The first start
+//! over a missing file generates it with one operator token, which the
+//! operator reads out of the file; further tokens are minted over
+//! `POST /v1/tokens` and written through. A token can also be configured
+//! from the environment instead, held in memory only.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::{Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use data_encoding::HEXLOWER;
+use pdn_node::PdnId;
+use serde::{Deserialize, Serialize};
+
+use crate::api::body;
+use crate::error::ApiError;
+
+/// What a token may act for: the whole host, or one hosted identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The operator: every route.
+    Operator,
+    /// A client app acting for one identity.
+    Identity(PdnId),
+}
+
+/// One token as the token file records it; `identity` absent for an
+/// operator token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TokenRecord {
+    token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identity: Option<PdnId>,
+}
+
+impl TokenRecord {
+    fn scope(&self) -> Scope {
+        self.identity.map_or(Scope::Operator, Scope::Identity)
+    }
+}
+
+/// The token file's contents.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenFile {
+    tokens: Vec<TokenRecord>,
+}
+
+/// The host's token set — cheap to clone, shared by the middleware and the
+/// token route.
+#[derive(Debug, Clone, Default)]
+pub struct Auth {
+    inner: Arc<Mutex<Tokens>>,
+}
+
+#[derive(Debug, Default)]
+struct Tokens {
+    records: Vec<TokenRecord>,
+    /// Where minted tokens are written through; `None` holds them in memory.
+    file: Option<PathBuf>,
+}
+
+impl Auth {
+    /// An empty token set held in memory: nothing authenticates until a
+    /// token is issued.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// The token set of the token file at `path`. A missing file is created
+    /// holding one freshly generated operator token.
+    pub fn load_or_init(path: &Path) -> Result<Self> {
+        let records = match std::fs::read(path) {
+            Ok(bytes) => {
+                serde_json::from_slice::<TokenFile>(&bytes)
+                    .with_context(|| format!("unreadable token file {}", path.display()))?
+                    .tokens
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let file = TokenFile {
+                    tokens: vec![TokenRecord {
+                        token: generate(),
+                        identity: None,
+                    }],
+                };
+                replace_file(path, &serde_json::to_vec_pretty(&file)?)?;
+                file.tokens
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("cannot read {}", path.display()));
+            }
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Tokens {
+                records,
+                file: Some(path.to_owned()),
+            })),
+        })
+    }
+
+    /// Accept `token` with `scope` for this process's lifetime, without
+    /// writing it to the token file — for a token configured from the
+    /// environment.
+    pub fn allow(&self, token: &str, scope: Scope) -> Result<()> {
+        let mut tokens = self.lock()?;
+        tokens.records.push(TokenRecord {
+            token: token.to_owned(),
+            identity: scope_identity(scope),
+        });
+        Ok(())
+    }
+
+    /// Mint a fresh token with `scope`, written through to the token file
+    /// when there is one.
+    pub fn issue(&self, scope: Scope) -> Result<String> {
+        let mut tokens = self.lock()?;
+        let record = TokenRecord {
+            token: generate(),
+            identity: scope_identity(scope),
+        };
+        if let Some(path) = &tokens.file {
+            let mut persisted = match std::fs::read(path) {
+                Ok(bytes) => serde_json::from_slice::<TokenFile>(&bytes)?,
+                Err(err) if err.kind() == ErrorKind::NotFound => TokenFile::default(),
+                Err(err) => return Err(err.into()),
+            };
+            persisted.tokens.push(record.clone());
+            replace_file(path, &serde_json::to_vec_pretty(&persisted)?)?;
+        }
+        let token = record.token.clone();
+        tokens.records.push(record);
+        Ok(token)
+    }
+
+    /// The scope of `presented`, if it is a known token. Every known token
+    /// is compared in full, so the time taken does not tell how much of a
+    /// guess matched.
+    fn scope_of(&self, presented: &str) -> Option<Scope> {
+        let tokens = self.inner.lock().ok()?;
+        tokens.records.iter().fold(None, |found, record| {
+            if constant_time_eq(record.token.as_bytes(), presented.as_bytes()) {
+                Some(record.scope())
+            } else {
+                found
+            }
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Tokens>> {
+        self.inner
+            .lock()
+            .map_err(|_poisoned| anyhow::anyhow!("token set poisoned"))
+    }
+}
+
+fn scope_identity(scope: Scope) -> Option<PdnId> {
+    match scope {
+        Scope::Operator => None,
+        Scope::Identity(identity) => Some(identity),
+    }
+}
+
+/// A fresh token: 32 random bytes, hex.
+fn generate() -> String {
+    HEXLOWER.encode(&rand::random::<[u8; 32]>())
+}
+
+/// Byte equality whose duration depends on the lengths alone.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Who may call a route.
+enum Required {
+    /// Nobody needs a token.
+    Open,
+    /// Any known token.
+    Any,
+    /// A token scoped to this identity, or the operator's; the segment is
+    /// compared as the hex the route carries.
+    Identity(String),
+    /// The operator's token.
+    Operator,
+}
+
+/// What `path` requires, read off its segments.
+fn required(path: &str) -> Required {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["live"] => Required::Open,
+        ["v1", "node"] => Required::Any,
+        ["v1", "identities", identity, _, ..] | ["v1", "data", identity, ..] => {
+            Required::Identity((*identity).to_owned())
+        }
+        _ => Required::Operator,
+    }
+}
+
+/// The middleware: authenticate the bearer token, then hold it to the
+/// route's scope.
+pub(crate) async fn require_token(
+    State(auth): State<Auth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let required = required(request.uri().path());
+    if matches!(required, Required::Open) {
+        return next.run(request).await;
+    }
+    let presented = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let Some(scope) = presented.and_then(|token| auth.scope_of(token.trim())) else {
+        return ApiError::unauthenticated().into_response();
+    };
+    let allowed = match (scope, required) {
+        (Scope::Operator, _) | (_, Required::Open | Required::Any) => true,
+        (Scope::Identity(own), Required::Identity(addressed)) => addressed
+            .parse::<PdnId>()
+            .is_ok_and(|addressed| addressed == own),
+        (Scope::Identity(_), Required::Operator) => false,
+    };
+    if allowed {
+        next.run(request).await
+    } else {
+        ApiError::forbidden().into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct IssueRequest {
+    identity: Option<PdnId>,
+}
+
+#[derive(Serialize)]
+struct IssuedBody {
+    token: String,
+}
+
+/// `POST /v1/tokens`: mint a token — scoped to `identity` when the body
+/// names one, the operator's otherwise. Operator-only, like every route the
+/// middleware does not hand to a scope.
+pub(crate) async fn issue_token(
+    State(auth): State<Auth>,
+    bytes: Bytes,
+) -> Result<(StatusCode, Json<IssuedBody>), ApiError> {
+    let request: IssueRequest = body(&bytes)?;
+    let scope = request.identity.map_or(Scope::Operator, Scope::Identity);
+    let token = auth.issue(scope)?;
+    Ok((StatusCode::CREATED, Json(IssuedBody { token })))
+}
+
+/// Replace the file at `path` with `bytes`, whole and owner-only: staged
+/// and renamed into place, so a crash mid-write leaves the previous token
+/// file rather than a torn one. A staged file a crash left behind is
+/// removed first — created anew, it takes the owner-only mode.
+fn replace_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let staged = path.with_extension("staged");
+    match std::fs::remove_file(&staged) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("cannot remove {}", staged.display()));
+        }
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&staged)
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::io::Write::write_all(&mut file, bytes)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::fs::rename(&staged, path).with_context(|| format!("cannot write {}", path.display()))
+}
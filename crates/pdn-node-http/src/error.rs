@@ -0,0 +1,136 @@
+//! How `/v1` failures answer: a status chosen from the error's type, and a
+//! JSON body naming it — `{"error": <code>, "message": <text>}`.
+//!
+//! The runtime's services report their refusals as [`ServiceError`]
+//! variants; each a client can act on gets its own status, and anything
+//! else is the host's or the network's failure — a 500, its message passed
+//! through.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use pdn_node::{PayloadParseError, ServiceError};
+use serde::Serialize;
+
+/// A failed `/v1` request.
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+/// The body every failure answers with.
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+    message: &'a str,
+}
+
+impl ApiError {
+    /// A request the host could not even hand to the runtime: an id, path,
+    /// payload or body that does not parse.
+    pub(crate) fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "bad_request",
+            message: message.into(),
+        }
+    }
+
+    /// No known bearer token came with the request.
+    pub(crate) fn unauthenticated() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "unauthenticated",
+            message: "a bearer token is required".to_owned(),
+        }
+    }
+
+    /// The token is known but scoped to an identity this route does not
+    /// address.
+    pub(crate) fn forbidden() -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            code: "forbidden",
+            message: "the token is not scoped for this route".to_owned(),
+        }
+    }
+
+    /// Nothing at the addressed place — an entry not written, or not synced
+    /// yet.
+    pub(crate) fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "not_found",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ServiceError> for ApiError {
+    fn from(err: ServiceError) -> Self {
+        let (status, code) = match &err {
+            ServiceError::NotHosted(_) => (StatusCode::NOT_FOUND, "unknown_identity"),
+            ServiceError::UnknownIssuer(_) => (StatusCode::UNPROCESSABLE_ENTITY, "unknown_issuer"),
+            ServiceError::UnknownCapability(_) => (StatusCode::NOT_FOUND, "unknown_capability"),
+            ServiceError::NotDelegable(_) => (StatusCode::FORBIDDEN, "not_delegable"),
+            ServiceError::UnsupportedInviteVersion(_)
+            | ServiceError::UnsupportedLinkingVersion(_)
+            | ServiceError::UnsupportedClaimVersion(_) => {
+                (StatusCode::BAD_REQUEST, "unsupported_version")
+            }
+            ServiceError::InvalidAttributeName(_) => {
+                (StatusCode::BAD_REQUEST, "invalid_attribute_name")
+            }
+            ServiceError::NoSigningKeys(_) => (StatusCode::CONFLICT, "no_signing_keys"),
+            ServiceError::PayloadTooLarge { .. } => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large")
+            }
+            ServiceError::NotAuthorizedToWrite { .. } => {
+                (StatusCode::FORBIDDEN, "not_authorized_to_write")
+            }
+            ServiceError::ProtocolRefused(_) => (StatusCode::BAD_GATEWAY, "refused"),
+            ServiceError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "timeout"),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        };
+        Self {
+            status,
+            code,
+            message: format!("{err:#}"),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    /// The host's own failures, sorted as the services sort theirs.
+    fn from(err: anyhow::Error) -> Self {
+        ServiceError::from(err).into()
+    }
+}
+
+impl From<PayloadParseError> for ApiError {
+    /// A payload of a version this runtime does not speak is refused as the
+    /// service would refuse it; every other parse failure is a bad request.
+    fn from(err: PayloadParseError) -> Self {
+        let code = match err {
+            PayloadParseError::UnsupportedVersion { .. } => "unsupported_version",
+            _ => "bad_request",
+        };
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: self.code,
+            message: &self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,127 @@
+//! The runtime's change feed as server-sent events, so a UI updates live
+//! instead of polling.
+//!
+//! Each event is one SSE message named by its type (`entry_changed`,
+//! `connection_added`, …) whose data is the [`RuntimeEvent`] as JSON, the
+//! type repeated under `"type"`. A `lagged` message means the stream fell
+//! behind and dropped events: re-read what the client mirrors. Idle streams
+//! carry keep-alive comments.
+
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_lite::{stream, Stream};
+use pdn_node::{
+    EntryEvent, EntryPath, PdnId, Runtime, RuntimeEvent, ServiceError, SyncService as _,
+    UnknownIdentity,
+};
+use serde::Deserialize;
+
+use crate::api::{entry_path, id};
+use crate::error::ApiError;
+
+/// Which events one stream carries; each field narrows its own kind only.
+struct Filter {
+    /// Connection, grant and device events of this identity alone.
+    identity: Option<PdnId>,
+    /// Entry events under this issuer alone.
+    issuer: Option<PdnId>,
+    /// Entry events under this path prefix alone, by whole components.
+    prefix: Option<EntryPath>,
+}
+
+impl Filter {
+    fn admits(&self, event: &RuntimeEvent) -> bool {
+        match event {
+            RuntimeEvent::Lagged { .. } => true,
+            RuntimeEvent::EntryChanged(EntryEvent { info, .. }) => {
+                self.issuer.is_none_or(|issuer| info.issuer == issuer)
+                    && self
+                        .prefix
+                        .as_ref()
+                        .is_none_or(|prefix| info.path.starts_with(prefix))
+            }
+            other => self
+                .identity
+                .is_none_or(|identity| other.identity() == Some(identity)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct FeedQuery {
+    identity: Option<String>,
+    issuer: Option<String>,
+    prefix: Option<String>,
+}
+
+type Messages = Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>;
+type EventStream = Sse<Messages>;
+
+/// `GET /v1/events`: everything the runtime reports, optionally narrowed by
+/// `?identity=`, `?issuer=` and `?prefix=`.
+pub(crate) async fn all_events(
+    State(runtime): State<Arc<Runtime>>,
+    Query(query): Query<FeedQuery>,
+) -> Result<EventStream, ApiError> {
+    let filter = Filter {
+        identity: query.identity.as_deref().map(id).transpose()?,
+        issuer: query.issuer.as_deref().map(id).transpose()?,
+        prefix: query.prefix.as_deref().map(entry_path).transpose()?,
+    };
+    Ok(feed(&runtime, filter))
+}
+
+/// `GET /v1/identities/{identity}/events`: what concerns one hosted
+/// identity — its connections, grants and devices, and the entries of its
+/// own data, `?prefix=` narrowing those.
+pub(crate) async fn identity_events(
+    State(runtime): State<Arc<Runtime>>,
+    Path(identity): Path<String>,
+    Query(query): Query<FeedQuery>,
+) -> Result<EventStream, ApiError> {
+    let identity = id(&identity)?;
+    if !runtime
+        .sync()
+        .hosted_identities()
+        .await?
+        .contains(&identity)
+    {
+        return Err(ServiceError::from(UnknownIdentity { identity }).into());
+    }
+    let filter = Filter {
+        identity: Some(identity),
+        issuer: Some(identity),
+        prefix: query.prefix.as_deref().map(entry_path).transpose()?,
+    };
+    Ok(feed(&runtime, filter))
+}
+
+/// Subscribe now — before the response starts, so nothing after the
+/// request is missed — and stream what `filter` admits until the runtime
+/// goes away or the client does.
+fn feed(runtime: &Runtime, filter: Filter) -> EventStream {
+    let events = runtime.sync().events();
+    let messages: Messages = Box::pin(stream::unfold(
+        (events, filter),
+        |(mut events, filter)| async move {
+            loop {
+                let event = events.next().await?;
+                if filter.admits(&event) {
+                    return Some((Ok(message(&event)), (events, filter)));
+                }
+            }
+        },
+    ));
+    Sse::new(messages).keep_alive(KeepAlive::default())
+}
+
+/// One SSE message: named by the event's type, its JSON as the data.
+fn message(event: &RuntimeEvent) -> Event {
+    let json = serde_json::to_value(event).unwrap_or_default();
+    let name = json["type"].as_str().unwrap_or("event").to_owned();
+    Event::default().event(name).data(json.to_string())
+}
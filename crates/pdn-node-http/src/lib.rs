@@ -3,11 +3,15 @@
 //!
 //! One process, one embedded runtime. The HTTP surface is a host over the
 //! core, not the platform API, and the runtime itself stays host-free. The
-//! host adds no authorization of its own: access control is the embedded
-//! runtime's.
+//! host authenticates its local clients — bearer tokens, the operator's or
+//! scoped to one identity (see [`Auth`]) — and leaves every decision about
+//! data access to the embedded runtime.
 //!
-//! `GET /live` is the one always-on route, probed by container harnesses
-//! and the demo stand. Everything under `/debug/` is demo scaffolding:
+//! `GET /live` is probed by container harnesses and the demo stand, and is
+//! the one route answered without a token. Under
+//! `/v1/` the runtime's services are served over JSON, one route per
+//! operation — a pinned, versioned shape (see the `api` module). Everything
+//! under `/debug/` is demo scaffolding:
 //! absent unless `PDN_DEBUG=1` is set at startup, shape deliberately
 //! unpinned and free to change without a spec change.
 
@@ -15,21 +19,34 @@ use std::sync::Arc;
 
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::routing::get;
-use axum::Router;
+use axum::routing::{get, post};
+use axum::{middleware, Router};
 use pdn_node::{Runtime, SyncService as _};
 
-/// Build the host's router over the embedded runtime. Debug scaffolding
-/// routes exist only when `debug` is set: off means absent, so requests
-/// under `/debug/` fall through to 404.
-pub fn router(runtime: Arc<Runtime>, debug: bool) -> Router {
-    let app = Router::new().route("/live", get(live));
+mod api;
+mod auth;
+mod error;
+mod events;
+
+pub use auth::{Auth, Scope};
+
+/// Build the host's router over the embedded runtime, every route but
+/// `/live` behind `auth`. Debug scaffolding routes exist only when `debug`
+/// is set: off means absent, so requests under `/debug/` fall through to
+/// 404.
+pub fn router(runtime: Arc<Runtime>, auth: Auth, debug: bool) -> Router {
+    let app = Router::new().route("/live", get(live)).merge(api::routes());
     let app = if debug {
         app.route("/debug/status", get(debug_status))
     } else {
         app
     };
+    let tokens = Router::new()
+        .route("/v1/tokens", post(auth::issue_token))
+        .with_state(auth.clone());
     app.with_state(runtime)
+        .merge(tokens)
+        .layer(middleware::from_fn_with_state(auth, auth::require_token))
 }
 
 /// Liveness: the process is up with its embedded runtime.
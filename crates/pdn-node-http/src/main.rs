@@ -1,22 +1,56 @@
 //! Entry point: serve one embedded runtime over HTTP.
 //!
 //! Environment: `PDN_HOST` (default `127.0.0.1`), `PDN_PORT` (default
-//! `3011`), and `PDN_DEBUG=1` to mount the demo-scaffolding `/debug/`
-//! routes (absent otherwise). The binary is glue only — assembly and
-//! authorization posture live in `pdn-node` (see the library crate docs).
+//! `3011`), `PDN_STORAGE` (a directory holding the node's state across
+//! restarts; unset, the node is in-memory), and `PDN_DEBUG=1` to mount the
+//! demo-scaffolding `/debug/` routes (absent otherwise). The binary is glue
+//! only — assembly and authorization posture live in `pdn-node` (see the
+//! library crate docs).
+//!
+//! Authentication: `PDN_TOKEN_FILE` names the token file, defaulting to
+//! `http-tokens.json` inside `PDN_STORAGE` and to `pdn-http-tokens.json` in
+//! the working directory without one; a missing file is generated with one
+//! operator token on first start. `PDN_TOKEN`, when set, is accepted as an
+//! operator token as well, without being written anywhere.
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+
+use pdn_node::{Runtime, SpawnOptions};
+use pdn_node_http::{router, Auth, Scope};
+
+/// The token file's name when `PDN_TOKEN_FILE` does not give one.
+const TOKEN_FILE: &str = "http-tokens.json";
 
-use pdn_node::Runtime;
-use pdn_node_http::router;
+/// How long a shutdown waits for open connections to finish.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let runtime = Arc::new(Runtime::spawn().await?);
+    let options = SpawnOptions {
+        storage: std::env::var_os("PDN_STORAGE").map(Into::into),
+        ..SpawnOptions::default()
+    };
+    let token_file = std::env::var_os("PDN_TOKEN_FILE")
+        .map(PathBuf::from)
+        .or_else(|| options.storage.as_ref().map(|dir| dir.join(TOKEN_FILE)))
+        .unwrap_or_else(|| PathBuf::from(format!("pdn-{TOKEN_FILE}")));
+    if let Some(dir) = token_file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    let auth = Auth::load_or_init(&token_file)?;
+    if let Ok(token) = std::env::var("PDN_TOKEN") {
+        auth.allow(&token, Scope::Operator)?;
+    }
+    let runtime = Arc::new(Runtime::spawn_with(options).await?);
 
     let debug = std::env::var("PDN_DEBUG").is_ok_and(|v| v == "1" || v == "true");
-    let app = router(Arc::clone(&runtime), debug);
+    let app = router(Arc::clone(&runtime), auth, debug);
 
     let host = std::env::var("PDN_HOST").unwrap_or_else(|_| "127.0.0.1".into());
     let port: u16 = std::env::var("PDN_PORT")
@@ -25,14 +59,22 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or(3011);
     let addr: SocketAddr = format!("{host}:{port}").parse()?;
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async {
+    let serve = axum::serve(listener, app).with_graceful_shutdown(async {
+        let _ = tokio::signal::ctrl_c().await;
+    });
+    // An open `/v1/events` stream never finishes by itself, so the graceful
+    // drain is bounded: past the grace period the remaining connections are
+    // abandoned, and the runtime with them if one still holds it.
+    tokio::select! {
+        served = std::future::IntoFuture::into_future(serve) => served?,
+        () = async {
             let _ = tokio::signal::ctrl_c().await;
-        })
-        .await?;
+            tokio::time::sleep(SHUTDOWN_GRACE).await;
+        } => {}
+    }
 
-    // Serve has returned and every handler with it, so the runtime is ours
-    // again; close the endpoint cleanly.
+    // Once serve has returned every handler went with it, so the runtime is
+    // ours again; close the endpoint cleanly.
     if let Ok(runtime) = Arc::try_unwrap(runtime) {
         runtime.shutdown().await?;
     }
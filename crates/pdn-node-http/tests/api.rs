@@ -0,0 +1,399 @@
+//! The `/v1` surface end to end over two embedded runtimes: identities,
+//! an establishment from a minted invite, a grant published, read and
+//! withdrawn, and entries written, read and listed; the change feed
+//! streaming as server-sent events — and the runtime's typed refusals each
+//! answering with its own status and error code.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt as _;
+use pdn_node::{claim_id_of, EntryPath, InvitePayload, Runtime, SpawnOptions};
+use pdn_node_http::{router, Auth, Scope};
+use serde_json::{json, Value};
+use test_utils::{eventually, ids, TIMEOUT};
+use tower::ServiceExt as _;
+
+/// A router over `runtime` and the operator token it accepts.
+struct Host {
+    app: Router,
+    token: String,
+}
+
+fn host(runtime: &Arc<Runtime>) -> Result<Host> {
+    let auth = Auth::in_memory();
+    let token = auth.issue(Scope::Operator)?;
+    Ok(Host {
+        app: router(Arc::clone(runtime), auth, false),
+        token,
+    })
+}
+
+/// Send one request to `host` as its operator, with `body` as JSON when
+/// given, and answer the status and the decoded JSON body (`Null` when
+/// empty).
+async fn call(
+    host: &Host,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", host.token))
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(serde_json::to_vec(&body)?))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = host.app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = response.into_body().collect().await?.to_bytes();
+    let value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes)?
+    };
+    Ok((status, value))
+}
+
+/// Shut the runtime down once the routers holding it are dropped.
+async fn shutdown(runtime: Arc<Runtime>) -> Result<()> {
+    if let Ok(runtime) = Arc::try_unwrap(runtime) {
+        runtime.shutdown().await?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_services_are_served_under_v1() -> Result<()> {
+    let rt_a = Arc::new(Runtime::spawn().await?);
+    let rt_b = Arc::new(Runtime::spawn().await?);
+    let app_a = host(&rt_a)?;
+    let app_b = host(&rt_b)?;
+
+    let (status, created) = call(&app_a, Method::POST, "/v1/identities", None).await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let x = created["identity"].as_str().unwrap_or_default().to_owned();
+    let (_, created) = call(&app_b, Method::POST, "/v1/identities", None).await?;
+    let y = created["identity"].as_str().unwrap_or_default().to_owned();
+    let (status, listed) = call(&app_a, Method::GET, "/v1/identities", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed, json!({ "identities": [x] }));
+
+    // An invite minted on one host establishes from the other.
+    let (status, minted) = call(
+        &app_a,
+        Method::POST,
+        &format!("/v1/identities/{x}/invites"),
+        Some(json!({ "lifetime_secs": 60 })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let invite = minted["invite"].clone();
+    assert!(invite
+        .as_str()
+        .is_some_and(|uri| uri.starts_with("pdn-invite:")));
+    let (status, established) = call(
+        &app_b,
+        Method::POST,
+        &format!("/v1/identities/{y}/connections"),
+        Some(json!({ "invite": invite })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(established, json!({ "peer": x }));
+    let (_, connections) = call(
+        &app_a,
+        Method::GET,
+        &format!("/v1/identities/{x}/connections"),
+        None,
+    )
+    .await?;
+    assert_eq!(connections, json!({ "connections": [y] }));
+
+    // Entries by issuer and path, payloads as base64.
+    let (status, _) = call(
+        &app_a,
+        Method::PUT,
+        &format!("/v1/data/{x}/entries/contact/email"),
+        Some(json!({ "payload": "YUBleGFtcGxlLm9yZw==" })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, read) = call(
+        &app_a,
+        Method::GET,
+        &format!("/v1/data/{x}/entries/contact/email"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(read, json!({ "payload": "YUBleGFtcGxlLm9yZw==" }));
+    let (_, listed) = call(
+        &app_a,
+        Method::GET,
+        &format!("/v1/data/{x}/entries?prefix=contact"),
+        None,
+    )
+    .await?;
+    assert_eq!(listed["entries"][0]["path"], json!("contact/email"));
+    assert_eq!(listed["entries"][0]["payload_len"], json!(13));
+
+    // A grant published on one side is read on the other, then withdrawn.
+    let issuer: pdn_node::PdnId = x.parse()?;
+    let claim = claim_id_of(&issuer, &EntryPath::new("contact/email")?);
+    let (status, _) = call(
+        &app_a,
+        Method::PUT,
+        &format!("/v1/identities/{x}/connections/{y}/grants/{x}"),
+        Some(json!({ "claims": [claim] })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let grants_uri = format!("/v1/identities/{y}/connections/{x}/grants");
+    assert!(
+        eventually(|| async {
+            let (_, grants) = call(&app_b, Method::GET, &grants_uri, None).await?;
+            Ok(grants["grants"][0]["grant"]["claims"] == json!([claim]))
+        })
+        .await?
+    );
+    let (status, _) = call(
+        &app_a,
+        Method::DELETE,
+        &format!("/v1/identities/{x}/connections/{y}/grants/{x}"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // Deleting one entry, then what lies under a prefix.
+    let entry_uri = format!("/v1/data/{x}/entries/contact/email");
+    let (status, _) = call(&app_a, Method::DELETE, &entry_uri, None).await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = call(&app_a, Method::DELETE, &entry_uri, None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    for path in ["notes/a", "notes/b"] {
+        call(
+            &app_a,
+            Method::PUT,
+            &format!("/v1/data/{x}/entries/{path}"),
+            Some(json!({ "payload": "YQ==" })),
+        )
+        .await?;
+    }
+    let entries_uri = format!("/v1/data/{x}/entries");
+    let (status, _) = call(&app_a, Method::DELETE, &entries_uri, None).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, deleted) = call(
+        &app_a,
+        Method::DELETE,
+        &format!("{entries_uri}?prefix=notes"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(deleted, json!({ "deleted": 2 }));
+    let (_, listed) = call(
+        &app_a,
+        Method::GET,
+        &format!("{entries_uri}?prefix=notes"),
+        None,
+    )
+    .await?;
+    assert_eq!(listed, json!({ "entries": [] }));
+
+    drop((app_a, app_b));
+    shutdown(rt_a).await?;
+    shutdown(rt_b).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_change_feed_streams_as_server_sent_events() -> Result<()> {
+    let runtime = Arc::new(Runtime::spawn().await?);
+    let app = host(&runtime)?;
+    let (_, created) = call(&app, Method::POST, "/v1/identities", None).await?;
+    let x = created["identity"].as_str().unwrap_or_default().to_owned();
+
+    let request = Request::get(format!("/v1/identities/{x}/events?prefix=contact"))
+        .header("authorization", format!("Bearer {}", app.token))
+        .body(Body::empty())?;
+    let response = app.app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok()),
+        Some("text/event-stream")
+    );
+    let mut body = response.into_body();
+
+    // Outside the prefix, then inside it: only the second is streamed.
+    for path in ["notes/a", "contact/email"] {
+        let (status, _) = call(
+            &app,
+            Method::PUT,
+            &format!("/v1/data/{x}/entries/{path}"),
+            Some(json!({ "payload": "YUBleGFtcGxlLm9yZw==" })),
+        )
+        .await?;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    // The first message, past any keep-alive comment.
+    let mut streamed = String::new();
+    let message = loop {
+        let from_event = streamed
+            .find("event: ")
+            .and_then(|start| streamed.get(start..));
+        if let Some((message, _rest)) = from_event.and_then(|rest| rest.split_once("\n\n")) {
+            break message.to_owned();
+        }
+        let frame = tokio::time::timeout(TIMEOUT, body.frame())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("the stream ended"))??;
+        if let Ok(data) = frame.into_data() {
+            streamed.push_str(std::str::from_utf8(&data)?);
+        }
+    };
+    assert!(message.starts_with("event: entry_changed\n"), "{message}");
+    let data = message
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap_or_default();
+    let event: Value = serde_json::from_str(data)?;
+    assert_eq!(
+        event,
+        json!({
+            "type": "entry_changed",
+            "issuer": x,
+            "path": "contact/email",
+            "payload_len": 13,
+            "kind": "inserted",
+            "origin": "local",
+        })
+    );
+
+    drop((body, app));
+    shutdown(runtime).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refusals_answer_with_distinct_statuses() -> Result<()> {
+    let runtime = Arc::new(
+        Runtime::spawn_with(SpawnOptions {
+            max_payload: 8,
+            ..SpawnOptions::default()
+        })
+        .await?,
+    );
+    let app = host(&runtime)?;
+    let (_, created) = call(&app, Method::POST, "/v1/identities", None).await?;
+    let x = created["identity"].as_str().unwrap_or_default().to_owned();
+    let stranger = ids::DAVE;
+
+    let expect = |(status, body): (StatusCode, Value), want: StatusCode, code: &str| {
+        assert_eq!(status, want, "{body}");
+        assert_eq!(body["error"], json!(code), "{body}");
+    };
+
+    // An identity this runtime does not host.
+    expect(
+        call(
+            &app,
+            Method::GET,
+            &format!("/v1/identities/{stranger}/connections"),
+            None,
+        )
+        .await?,
+        StatusCode::NOT_FOUND,
+        "unknown_identity",
+    );
+    // A data namespace this runtime does not hold.
+    expect(
+        call(
+            &app,
+            Method::PUT,
+            &format!("/v1/data/{stranger}/entries/a"),
+            Some(json!({ "payload": "" })),
+        )
+        .await?,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "unknown_issuer",
+    );
+    // A payload over the node's maximum: nine bytes against eight.
+    expect(
+        call(
+            &app,
+            Method::PUT,
+            &format!("/v1/data/{x}/entries/a"),
+            Some(json!({ "payload": "AAAAAAAAAAAA" })),
+        )
+        .await?,
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload_too_large",
+    );
+    // Delegating another identity's data without a delegable grant.
+    expect(
+        call(
+            &app,
+            Method::PUT,
+            &format!("/v1/identities/{x}/connections/{stranger}/grants/{stranger}"),
+            Some(json!({ "claims": [ids::ALICE] })),
+        )
+        .await?,
+        StatusCode::FORBIDDEN,
+        "not_delegable",
+    );
+    // An invite of a format version this runtime does not speak.
+    let (_, minted) = call(
+        &app,
+        Method::POST,
+        &format!("/v1/identities/{x}/invites"),
+        None,
+    )
+    .await?;
+    let invite: InvitePayload = minted["invite"].as_str().unwrap_or_default().parse()?;
+    let unversioned = InvitePayload {
+        version: 99,
+        ..invite
+    };
+    expect(
+        call(
+            &app,
+            Method::POST,
+            &format!("/v1/identities/{x}/connections"),
+            Some(json!({ "invite": unversioned.to_string() })),
+        )
+        .await?,
+        StatusCode::BAD_REQUEST,
+        "unsupported_version",
+    );
+
+    // What never reaches the runtime: a malformed id, a missing entry.
+    expect(
+        call(&app, Method::GET, "/v1/identities/xyz/connections", None).await?,
+        StatusCode::BAD_REQUEST,
+        "bad_request",
+    );
+    expect(
+        call(
+            &app,
+            Method::GET,
+            &format!("/v1/data/{x}/entries/never/written"),
+            None,
+        )
+        .await?,
+        StatusCode::NOT_FOUND,
+        "not_found",
+    );
+
+    drop(app);
+    shutdown(runtime).await
+}
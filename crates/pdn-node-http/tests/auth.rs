@@ -0,0 +1,167 @@
+//! The host's authentication: nothing but `/live` answers without a known
+//! token, a token scoped to one identity reaches that identity's routes
+//! and no other, only the operator mints tokens, and the token file is
+//! generated on first start and read back on the next.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt as _;
+use pdn_node::{IdentityService as _, Runtime};
+use pdn_node_http::{router, Auth, Scope};
+use serde_json::{json, Value};
+use test_utils::ids;
+use tower::ServiceExt as _;
+
+/// Send one request to `app`, presenting `token` when given, and answer the
+/// status and the decoded JSON body (`Null` when empty).
+async fn call(
+    app: &Router,
+    token: Option<&str>,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    let request = match body {
+        Some(body) => request.body(Body::from(serde_json::to_vec(&body)?))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = response.into_body().collect().await?.to_bytes();
+    let value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null)
+    };
+    Ok((status, value))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tokens_are_required_and_held_to_their_scope() -> Result<()> {
+    let runtime = Arc::new(Runtime::spawn().await?);
+    let x = runtime.identity().create().await?;
+    let auth = Auth::in_memory();
+    let operator = auth.issue(Scope::Operator)?;
+    let app = router(Arc::clone(&runtime), auth, false);
+
+    // Liveness alone is open; everything else, a route or not, is refused
+    // without a known token.
+    let (status, _) = call(&app, None, Method::GET, "/live", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    for token in [None, Some("not-a-token")] {
+        for uri in ["/v1/node", "/v1/identities", "/debug/status", "/nowhere"] {
+            let (status, body) = call(&app, token, Method::GET, uri, None).await?;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri}");
+            assert_eq!(body["error"], json!("unauthenticated"));
+        }
+    }
+
+    // The operator mints a token scoped to one identity.
+    let (status, minted) = call(
+        &app,
+        Some(&operator),
+        Method::POST,
+        "/v1/tokens",
+        Some(json!({ "identity": x })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    let scoped = minted["token"].as_str().unwrap_or_default().to_owned();
+
+    // The scoped token reaches its own identity and data, and the node id.
+    for uri in [
+        "/v1/node".to_owned(),
+        format!("/v1/identities/{x}/connections"),
+        format!("/v1/data/{x}/entries"),
+    ] {
+        let (status, _) = call(&app, Some(&scoped), Method::GET, &uri, None).await?;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+    }
+    // Another identity, and the operator's routes, are refused.
+    let stranger = ids::DAVE;
+    for (method, uri) in [
+        (
+            Method::GET,
+            format!("/v1/identities/{stranger}/connections"),
+        ),
+        (Method::GET, format!("/v1/data/{stranger}/entries")),
+        (Method::GET, "/v1/identities".to_owned()),
+        (Method::POST, "/v1/identities".to_owned()),
+        (Method::POST, "/v1/tokens".to_owned()),
+    ] {
+        let (status, body) = call(&app, Some(&scoped), method, &uri, None).await?;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+        assert_eq!(body["error"], json!("forbidden"));
+    }
+    // The operator reaches any identity's routes.
+    let (status, _) = call(
+        &app,
+        Some(&operator),
+        Method::GET,
+        &format!("/v1/data/{x}/entries"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    drop(app);
+    if let Ok(runtime) = Arc::try_unwrap(runtime) {
+        runtime.shutdown().await?;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_token_file_is_generated_then_reused() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tokens.json");
+
+    let auth = Auth::load_or_init(&path)?;
+    let written: Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+    let operator = written["tokens"][0]["token"]
+        .as_str()
+        .unwrap_or_default()
+        .to_owned();
+    assert_eq!(operator.len(), 64);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // A minted token is written through, and both survive a reload.
+    let scoped = auth.issue(Scope::Identity(ids::ALICE))?;
+    let reloaded = Auth::load_or_init(&path)?;
+    let runtime = Arc::new(Runtime::spawn().await?);
+    let app = router(Arc::clone(&runtime), reloaded, false);
+    let (status, _) = call(&app, Some(&operator), Method::GET, "/v1/identities", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(
+        &app,
+        Some(&scoped),
+        Method::GET,
+        &format!("/v1/data/{}/entries", ids::ALICE),
+        None,
+    )
+    .await?;
+    assert_ne!(status, StatusCode::UNAUTHORIZED);
+    assert_ne!(status, StatusCode::FORBIDDEN);
+
+    drop(app);
+    if let Ok(runtime) = Arc::try_unwrap(runtime) {
+        runtime.shutdown().await?;
+    }
+    Ok(())
+}
@@ -9,13 +9,15 @@ use anyhow::Result;
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use pdn_node::Runtime;
-use pdn_node_http::router;
+use pdn_node_http::{router, Auth, Scope};
 use tower::ServiceExt as _;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn live_is_200_and_debug_is_absent_without_the_flag() -> Result<()> {
     let runtime = Arc::new(Runtime::spawn().await?);
-    let app = router(Arc::clone(&runtime), false);
+    let auth = Auth::in_memory();
+    let token = auth.issue(Scope::Operator)?;
+    let app = router(Arc::clone(&runtime), auth, false);
 
     let live = app
         .clone()
@@ -25,7 +27,11 @@ async fn live_is_200_and_debug_is_absent_without_the_flag() -> Result<()> {
 
     // Paired deny: without the flag no `/debug/` route exists at all.
     let debug = app
-        .oneshot(Request::get("/debug/status").body(Body::empty())?)
+        .oneshot(
+            Request::get("/debug/status")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::empty())?,
+        )
         .await?;
     assert_eq!(debug.status(), StatusCode::NOT_FOUND);
 
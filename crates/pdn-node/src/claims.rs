@@ -0,0 +1,227 @@
+//! The claims service: `pdn_layer` claims as entries of their issuer's data
+//! namespace — `PdnOp::WriteClaim`, `GetClaim`, `ListClaimsAbout` and
+//! `ListMyClaims` executed over a [`DataLayer`] backend.
+//!
+//! A claim lives at a canonical path derived from what it is about and the
+//! name of its attribute — `claims/<about>/<attribute name>` — so its
+//! [`ClaimId`] is [`claim_id_of`] that path, the same id grants name: one
+//! claim per subject and attribute, rewritten in place, and granted by the
+//! id a write returns. Its payload is the claim as JSON inside a versioned
+//! envelope ([`CLAIM_FORMAT_VERSION`]).
+
+use data_layer::{claim_id_of, DataLayer};
+use futures_lite::StreamExt as _;
+use pdn_layer::Claim;
+use pdn_types::{ClaimId, EntryPath, PdnId};
+use serde::{Deserialize, Serialize};
+
+use crate::data::RuntimeEntries;
+use crate::error::ServiceError;
+use crate::runtime::Runtime;
+
+/// The claim payload format this runtime speaks. A stored claim of any
+/// other version is refused on read ([`UnsupportedClaimVersion`]) rather
+/// than guessed at.
+pub const CLAIM_FORMAT_VERSION: u8 = 1;
+
+/// The first component of every claim path.
+const CLAIMS_ROOT: &str = "claims";
+
+/// A claim's attribute name cannot be a path component: empty, longer than
+/// a component may be, or holding a `/`. Carried by
+/// [`ServiceError::InvalidAttributeName`] from [`ClaimsService::write`] and
+/// [`ClaimsService::get`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("attribute name {name:?} is not a valid claim path component")]
+pub struct InvalidAttributeName {
+    /// The refused name.
+    pub name: String,
+}
+
+/// A stored claim carried a payload format version this runtime does not
+/// speak. Carried by [`ServiceError::UnsupportedClaimVersion`] from
+/// [`ClaimsService::get`].
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("unsupported claim payload version: {version}")]
+pub struct UnsupportedClaimVersion {
+    /// The version the payload carried.
+    pub version: u8,
+}
+
+/// The stored form of a claim: the format version, then the claim.
+#[derive(Serialize)]
+struct ClaimRecord<'a> {
+    version: u8,
+    claim: &'a Claim,
+}
+
+/// A stored claim as read back: its version decoded first, so a payload of
+/// another version is refused by its number and not by its shape.
+#[derive(Deserialize)]
+struct StoredVersion {
+    version: u8,
+}
+
+#[derive(Deserialize)]
+struct StoredClaim {
+    claim: Claim,
+}
+
+/// The canonical path of a claim about `about` with attribute `name`.
+pub fn claim_path(about: &PdnId, name: &str) -> Result<EntryPath, InvalidAttributeName> {
+    let invalid = || InvalidAttributeName {
+        name: name.to_owned(),
+    };
+    if name.contains('/') {
+        return Err(invalid());
+    }
+    EntryPath::new(format!("{CLAIMS_ROOT}/{about}/{name}")).map_err(|_| invalid())
+}
+
+/// Whether `path` is a claim path: `claims/<about>/<name>`, exactly.
+fn is_claim_path(path: &EntryPath) -> bool {
+    let mut components = path.components();
+    components.next() == Some(CLAIMS_ROOT)
+        && components
+            .next()
+            .is_some_and(|about| about.parse::<PdnId>().is_ok())
+        && components.next().is_some()
+        && components.next().is_none()
+}
+
+/// Encode `claim` as its stored payload.
+fn encode(claim: &Claim) -> Result<Vec<u8>, ServiceError> {
+    serde_json::to_vec(&ClaimRecord {
+        version: CLAIM_FORMAT_VERSION,
+        claim,
+    })
+    .map_err(ServiceError::storage)
+}
+
+/// Decode the payload stored at `path` in `issuer`'s namespace, refusing a
+/// claim that does not belong where it was found: issued by another
+/// identity, or about another subject or attribute than its path names.
+fn decode(issuer: PdnId, path: &EntryPath, payload: &[u8]) -> Result<Claim, ServiceError> {
+    let StoredVersion { version } =
+        serde_json::from_slice(payload).map_err(ServiceError::storage)?;
+    if version != CLAIM_FORMAT_VERSION {
+        return Err(UnsupportedClaimVersion { version }.into());
+    }
+    let StoredClaim { claim } = serde_json::from_slice(payload).map_err(ServiceError::storage)?;
+    let placed = claim_path(&claim.about, &claim.attribute.name).ok();
+    if claim.issued_by != issuer || placed.as_ref() != Some(path) {
+        return Err(ServiceError::storage(anyhow::anyhow!(
+            "the claim stored at {path} under {issuer} does not belong there"
+        )));
+    }
+    Ok(claim)
+}
+
+/// Writing and reading `pdn_layer` claims by issuer. Claims are stored as
+/// entries, so everything the data service says of entries holds of them:
+/// they replicate to the issuer's devices, reach a grantee whose grant
+/// names their [`ClaimId`], and read as absent until their payload syncs.
+///
+/// The service stores a claim as given: its `proof_of_issued_by` travels
+/// with it for readers to check against the issuer's key event log.
+#[allow(async_fn_in_trait)]
+pub trait ClaimsService {
+    /// Write `claim` into the data namespace of its `issued_by`, at its
+    /// canonical path (`PdnOp::WriteClaim`), replacing the claim about the
+    /// same subject with the same attribute name. Returns the claim's id.
+    /// An attribute name that cannot be a path component is refused
+    /// ([`InvalidAttributeName`]).
+    async fn write(&self, claim: &Claim) -> Result<ClaimId, ServiceError>;
+
+    /// The claim `issuer` made about `about` with attribute `name`
+    /// (`PdnOp::GetClaim`): `Ok(None)` when there is none, and while its
+    /// payload has not synced. Addressed by what its [`ClaimId`] derives
+    /// from — the id is a one-way hash of the canonical path, so a read by
+    /// id alone would have to hash every claim path the namespace holds.
+    async fn get(
+        &self,
+        issuer: PdnId,
+        about: PdnId,
+        name: &str,
+    ) -> Result<Option<Claim>, ServiceError>;
+
+    /// The ids of the claims `issuer` made about `about`
+    /// (`PdnOp::ListClaimsAbout`), as far as this node holds them.
+    async fn list_about(&self, issuer: PdnId, about: PdnId) -> Result<Vec<ClaimId>, ServiceError>;
+
+    /// The ids of every claim `issuer` made (`PdnOp::ListMyClaims` for a
+    /// hosted identity), as far as this node holds them.
+    async fn list_mine(&self, issuer: PdnId) -> Result<Vec<ClaimId>, ServiceError>;
+}
+
+/// The production [`ClaimsService`]: claims through a [`DataLayer`] backend
+/// — by default the runtime's own node ([`RuntimeEntries`]), as for the
+/// data service.
+#[derive(Clone, Copy)]
+pub struct RuntimeClaimsService<L> {
+    entries: L,
+}
+
+impl<'rt> RuntimeClaimsService<RuntimeEntries<'rt>> {
+    pub(crate) fn new(runtime: &'rt Runtime) -> Self {
+        Self::with_entries(RuntimeEntries::new(runtime))
+    }
+}
+
+impl<L: DataLayer> RuntimeClaimsService<L> {
+    /// The claims service over another entries backend.
+    pub fn with_entries(entries: L) -> Self {
+        Self { entries }
+    }
+
+    /// The ids of the claims stored under `prefix` in `issuer`'s namespace.
+    async fn ids_under(
+        &self,
+        issuer: PdnId,
+        prefix: &EntryPath,
+    ) -> Result<Vec<ClaimId>, ServiceError> {
+        let mut listing = self.entries.list_entries(issuer, Some(prefix)).await?;
+        let mut ids = Vec::new();
+        while let Some(entry) = listing.next().await {
+            let entry = entry?;
+            if is_claim_path(&entry.path) {
+                ids.push(claim_id_of(&issuer, &entry.path));
+            }
+        }
+        Ok(ids)
+    }
+}
+
+impl<L: DataLayer> ClaimsService for RuntimeClaimsService<L> {
+    async fn write(&self, claim: &Claim) -> Result<ClaimId, ServiceError> {
+        let path = claim_path(&claim.about, &claim.attribute.name)?;
+        self.entries
+            .insert_entry(claim.issued_by, &path, &encode(claim)?)
+            .await?;
+        Ok(claim_id_of(&claim.issued_by, &path))
+    }
+
+    async fn get(
+        &self,
+        issuer: PdnId,
+        about: PdnId,
+        name: &str,
+    ) -> Result<Option<Claim>, ServiceError> {
+        let path = claim_path(&about, name)?;
+        match self.entries.get_entry(issuer, &path).await? {
+            Some(payload) => decode(issuer, &path, &payload).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_about(&self, issuer: PdnId, about: PdnId) -> Result<Vec<ClaimId>, ServiceError> {
+        let prefix =
+            EntryPath::new(format!("{CLAIMS_ROOT}/{about}")).map_err(ServiceError::storage)?;
+        self.ids_under(issuer, &prefix).await
+    }
+
+    async fn list_mine(&self, issuer: PdnId) -> Result<Vec<ClaimId>, ServiceError> {
+        let root = EntryPath::new(CLAIMS_ROOT).map_err(ServiceError::storage)?;
+        self.ids_under(issuer, &root).await
+    }
+}
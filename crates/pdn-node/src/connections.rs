@@ -2,40 +2,46 @@
 //! list them, and carry grants over the connections' metadata pairs.
 
 use std::sync::Weak;
-use std::time::{Duration, Instant};
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use data_layer::{
-    AddrInfoOptions, ConnectionMetadata, ConnectionMetadataStore, DocTicket, EndpointAddr,
-    EndpointId, ReadGrant, ShareMode,
+    unix_ms, AddrInfoOptions, ConnectionMetadata, ConnectionMetadataStore, DocTicket, EndpointAddr,
+    EndpointId, NamespaceId, NodeError, ReadGrant, ShareMode,
 };
 use futures_lite::{Stream, StreamExt};
-use pdn_types::{ClaimId, NonEmpty, PdnId};
+use pdn_layer::uwill::{Revocation, SignedUwill};
+use pdn_layer::{AccessMode, Capability, DelegatedClaim};
+use pdn_types::{CapabilityCid, ClaimId, NonEmpty, PdnId, ValidityWindow};
 use tokio::sync::Mutex;
 
+use crate::delegation::{self, NotDelegable, TokenSigner};
+use crate::error::ServiceError;
+use crate::events::RuntimeEvent;
+use crate::identity::{mirror_key_events, read_key_event_log, NoSigningKeys};
+use crate::mailbox::{
+    collect_mailboxes, deposit_request, open_mailbox_invite, DEFAULT_MAILBOX_LIFETIME,
+};
 use crate::pairing::{
-    establish_via_dialogue, InvitePayload, UnsupportedInviteVersion, DEFAULT_INVITE_LIFETIME,
-    INVITE_FORMAT_VERSION,
+    establish_via_dialogue, mint_invite, InvitePayload, UnsupportedInviteVersion,
+    DEFAULT_INVITE_LIFETIME, INVITE_FORMAT_VERSION,
 };
 use crate::runtime::{Runtime, State};
 
-/// A grant publication named a data issuer other than the granting
-/// identity itself — refused: granting another identity's data is
-/// delegation, and delegation is not expressible. The classifier scans the
-/// connections of the *data issuer's* identity, so a grant recorded under
-/// a different granting identity could never be honored — the publish
-/// would succeed, replicate, and enforce as nothing, a silent no-op on
-/// both sides.
+/// How long an armer whose directory was retired waits before it tries its
+/// successor again, when no change of the directory prompts it sooner.
+const FOLLOW_RETRY: Duration = Duration::from_secs(1);
+
+/// A revocation named a token no grant of the revoking identity is made
+/// under — refused rather than published into no store: a revocation
+/// nobody holds the token of would revoke nothing.
 #[derive(Debug, Clone, Copy, thiserror::Error)]
-#[error(
-    "identity {identity} cannot grant data issued by {issuer}: granting another identity's data \
-     is delegation, which is not expressible"
-)]
-pub struct DelegationUnsupported {
-    /// The identity attempting the grant.
+#[error("no grant of {identity} is made under the capability {cid}")]
+pub struct UnknownCapability {
+    /// The revoking identity.
     pub identity: PdnId,
-    /// The foreign data issuer it tried to grant.
-    pub issuer: PdnId,
+    /// The token it named.
+    pub cid: CapabilityCid,
 }
 
 /// A grant read from a connected peer's metadata store: the capability
@@ -65,40 +71,100 @@ pub struct PeerGrant {
 /// grant record exists per granted issuer — every grant is scoped by an
 /// exact claim set — so a republication replaces the previous record and a
 /// withdrawal is one act.
+///
+/// A device holding a signing key for the identity makes each grant under
+/// `UWill` tokens, one per granted claim, and records their CIDs with it;
+/// [`revoke`](Self::revoke) voids every grant made under a token, on both
+/// sides of the connection, as soon as the revocation's record arrives.
 #[allow(async_fn_in_trait)]
 pub trait ConnectionsService {
     /// Mint an invite for hosted `identity`: a one-time secret pending on
     /// this runtime (default lifetime, unless `lifetime` overrides it) and
     /// the self-contained payload to show the counterparty. The payload
-    /// carries no bearer material — no tickets, no identity proof.
-    async fn invite(&self, identity: PdnId, lifetime: Option<Duration>) -> Result<InvitePayload>;
+    /// carries no bearer material — no tickets — only the identity's proof
+    /// over the invite, so a device holding neither the identity's keys
+    /// nor a device key its log authorizes cannot invite
+    /// ([`NoSigningKeys`](crate::NoSigningKeys)).
+    async fn invite(
+        &self,
+        identity: PdnId,
+        lifetime: Option<Duration>,
+    ) -> Result<InvitePayload, ServiceError>;
+
+    /// [`invite`](Self::invite) for a counterparty that may open the
+    /// payload long after this device went offline: the identity's sealed
+    /// handshake package is deposited with the mailbox `relay` — any node
+    /// serving [`MAILBOX_ALPN`](crate::mailbox::MAILBOX_ALPN) — and the
+    /// payload names it. The invite lives a day unless `lifetime` overrides
+    /// it; [`collect_mailboxes`](Self::collect_mailboxes) finishes it.
+    async fn invite_via_mailbox(
+        &self,
+        identity: PdnId,
+        relay: EndpointAddr,
+        lifetime: Option<Duration>,
+    ) -> Result<InvitePayload, ServiceError>;
 
     /// Establish a connection for hosted `identity` from a scanned invite
     /// payload: dial the payload's address on the pairing ALPN and run the
-    /// establishment dialogue. A payload version this runtime does not
-    /// speak is refused before dialing ([`UnsupportedInviteVersion`]).
-    async fn establish(&self, identity: PdnId, invite: InvitePayload) -> Result<()>;
+    /// establishment dialogue, in which each side proves its identity to
+    /// the other. A payload version this runtime does not speak is refused
+    /// before dialing ([`UnsupportedInviteVersion`]), and so is a device
+    /// without a key to sign for the identity
+    /// ([`NoSigningKeys`](crate::NoSigningKeys));
+    /// an inviter whose proofs do not verify fails the establishment with
+    /// nothing committed on this side.
+    ///
+    /// A mailbox payload is not dialed: this side checks the inviter's
+    /// package and deposits its half with the relay, and returns with the
+    /// connection still pending — a later
+    /// [`collect_mailboxes`](Self::collect_mailboxes) completes it once the
+    /// inviter has answered.
+    async fn establish(&self, identity: PdnId, invite: InvitePayload) -> Result<(), ServiceError>;
+
+    /// Take one step in every mailbox establishment pending on this
+    /// runtime: as inviter, answer a scanner's deposited half; as scanner,
+    /// complete from the inviter's deposited answer. Answers how many
+    /// connections this call established. An unreachable relay leaves its
+    /// ceremonies for the next call; a deposit that fails to verify ends
+    /// its ceremony with nothing committed, as a refused dialogue does.
+    ///
+    /// The runtime never calls this itself: a mailbox ceremony advances
+    /// only when the host collects, so a host that offers mailbox invites
+    /// schedules the calls — on start, on a timer, on coming to the
+    /// foreground.
+    async fn collect_mailboxes(&self) -> Result<usize, ServiceError>;
 
     /// List the current connections of hosted `identity`.
-    async fn list(&self, identity: PdnId) -> Result<Vec<PdnId>>;
+    async fn list(&self, identity: PdnId) -> Result<Vec<PdnId>, ServiceError>;
 
     /// Withdraw the grant of `issuer`'s data store toward `peer` — one
-    /// tombstone over the single record. The issuer
-    /// must be the granting identity itself, as for publishing. The grantee
+    /// tombstone over the single record, whether the grant is of the
+    /// identity's own data or a delegation of another's. The grantee
     /// side drops the namespace once the tombstone replicates: what its
     /// grant binder imported it also forgets, so withdrawal reaches the
     /// bytes and not only the classification.
-    async fn withdraw_grant(&self, identity: PdnId, peer: PdnId, issuer: PdnId) -> Result<()>;
+    async fn withdraw_grant(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+    ) -> Result<(), ServiceError>;
 
     /// Publish a grant: `identity` grants `peer` read — and, with `write`,
-    /// write — on exactly `claims` of `issuer`'s data store. The issuer must
-    /// be the granting identity itself — anything else is refused
-    /// ([`DelegationUnsupported`]). Capability and ticket travel as one
+    /// write — on exactly `claims` of `issuer`'s data store. An issuer other
+    /// than the granting identity makes the grant a delegation: read-only,
+    /// and only of claims the identity holds a delegable grant on
+    /// ([`NotDelegable`]), as [`delegate`](Self::delegate) makes them.
+    /// Capability and ticket travel as one
     /// record (replacing any previous grant for this issuer); the ticket
     /// carries exactly the granted authority: read-only → a read ticket (no
     /// namespace secret — the grantee cannot write at all), with `write` →
-    /// a write ticket (the namespace secret carries write authority — no
-    /// ingest hook is installed, ADR-0008).
+    /// a write ticket (the namespace secret carries write authority — the
+    /// ingest hook checks none, ADR-0008).
+    ///
+    /// Returns the CIDs of the `UWill` tokens the grant is made under — the
+    /// handles [`revoke`](Self::revoke) takes — or none on a device without
+    /// the identity's signing keys, whose grant is a plain scoped one.
     async fn publish_grant(
         &self,
         identity: PdnId,
@@ -106,13 +172,80 @@ pub trait ConnectionsService {
         issuer: PdnId,
         claims: NonEmpty<ClaimId>,
         write: bool,
-    ) -> Result<()>;
+    ) -> Result<Vec<CapabilityCid>, ServiceError>;
+
+    /// [`publish_grant`](Self::publish_grant), bounded to `window`: the
+    /// grant serves `claims` from `window.nbf` until `window.exp` (unix ms)
+    /// and at no other time, and the grantee's grant binder forgets what it
+    /// imported once the window has closed. Its tokens are valid in the
+    /// same window. A delegation of another identity's data takes the
+    /// window's expiry.
+    async fn publish_grant_within(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+        claims: NonEmpty<ClaimId>,
+        write: bool,
+        window: ValidityWindow,
+    ) -> Result<Vec<CapabilityCid>, ServiceError>;
+
+    /// Revoke the `UWill` token `cid` issued by hosted `identity`: a
+    /// revocation signed on the identity's behalf, published into every
+    /// connection store of the identity whose grant is made under the
+    /// token. The grant reads as no grant, and classifies no session, from
+    /// the moment the revocation's record is present — on the grantee's
+    /// devices as on the identity's own — and the grantee's grant binder
+    /// forgets what it imported, as on a withdrawal. Revocations are final; granting
+    /// again mints new tokens. A device without the identity's signing
+    /// keys cannot revoke ([`NoSigningKeys`]), and a token no grant is made
+    /// under is refused ([`UnknownCapability`]).
+    async fn revoke(&self, identity: PdnId, cid: CapabilityCid) -> Result<(), ServiceError>;
+
+    /// Delegate `claim` of `issuer`'s data from hosted `identity` to the
+    /// holders `conditions` names, each a connection of the identity
+    /// (`PdnOp::DelegateClaim`). Of the identity's own data, the claim joins
+    /// its grant toward each holder under a token the holder may delegate
+    /// on; of another identity's data, the identity extends its own
+    /// delegable grant on the claim by one read-only link, and re-serves the
+    /// claim to the holders from its replica for as long as the issuer's
+    /// grant to it lasts. Write access, and a claim the identity holds no
+    /// delegable grant on, are refused ([`NotDelegable`]); a device without
+    /// the identity's signing keys cannot delegate ([`NoSigningKeys`]).
+    async fn delegate(
+        &self,
+        identity: PdnId,
+        issuer: PdnId,
+        claim: ClaimId,
+        conditions: Capability,
+    ) -> Result<DelegatedClaim, ServiceError>;
+
+    /// The delegations hosted `identity` made toward `to`
+    /// (`PdnOp::ListDelegationsTo`): one per claim it granted `to` under a
+    /// token, of its own data or shared on.
+    async fn delegations_to(
+        &self,
+        identity: PdnId,
+        to: PdnId,
+    ) -> Result<Vec<DelegatedClaim>, ServiceError>;
+
+    /// The delegations hosted `identity`'s peers made to it
+    /// (`PdnOp::ListIncomingDelegations`), as far as their records and
+    /// tokens have replicated here.
+    async fn incoming_delegations(
+        &self,
+        identity: PdnId,
+    ) -> Result<Vec<DelegatedClaim>, ServiceError>;
 
     /// Read the grants `peer` has published toward hosted `identity` —
     /// capability and ticket together, with the same
     /// payload-waiting and poll-friendly contract as
     /// [`read_grants`](Self::read_grants).
-    async fn read_grants(&self, identity: PdnId, peer: PdnId) -> Result<Vec<PeerGrant>>;
+    async fn read_grants(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+    ) -> Result<Vec<PeerGrant>, ServiceError>;
 }
 
 /// The production [`ConnectionsService`], backed by the runtime's
@@ -126,26 +259,119 @@ impl<'rt> RuntimeConnectionsService<'rt> {
     pub(crate) fn new(runtime: &'rt Runtime) -> Self {
         Self { runtime }
     }
+
+    /// [`ConnectionsService::publish_grant`], bounded to `window` when one
+    /// is given.
+    async fn publish(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+        claims: NonEmpty<ClaimId>,
+        write: bool,
+        window: Option<ValidityWindow>,
+    ) -> Result<Vec<CapabilityCid>, ServiceError> {
+        let mut state = self.runtime.state.lock().await;
+        state.hosted(identity)?;
+        if identity != issuer {
+            // Another identity's data: one delegation per claim.
+            if write {
+                return Err(NotDelegable {
+                    identity,
+                    issuer,
+                    claim: claims.head,
+                }
+                .into());
+            }
+            let conditions = Capability {
+                holders: vec![peer],
+                access: AccessMode::Read,
+                expires_at: window.map(|window| window.exp),
+            };
+            let mut minted = Vec::with_capacity(claims.len());
+            for claim in claims {
+                let (_delegation, cids) =
+                    delegation::delegate(&mut state, identity, issuer, claim, conditions.clone())
+                        .await?;
+                minted.extend(cids);
+            }
+            return Ok(minted);
+        }
+        let pair = open_pair(&mut state, identity, peer)
+            .await?
+            .with_context(|| format!("no connection metadata pair toward {peer}"))?;
+        // The ticket carries exactly the granted authority.
+        let mode = if write {
+            ShareMode::Write
+        } else {
+            ShareMode::Read
+        };
+        let ticket = state
+            .node
+            .share_ticket(issuer, mode, AddrInfoOptions::RelayAndAddresses)
+            .await?;
+        let grant = ReadGrant {
+            issuer,
+            audience: peer,
+            claims,
+            write,
+            window,
+        };
+        // One token per granted claim, valid in the grant's window — or
+        // open-ended: such a grant lasts until withdrawn or revoked.
+        let token_window = delegation::token_window(&grant);
+        let signer = TokenSigner::of(&state, identity).await?;
+        let Some(minted) = signer.map(|signer| {
+            grant
+                .claims
+                .iter()
+                .map(|&claim| delegation::root_token(&signer, &grant, claim, false, token_window))
+                .collect::<Vec<_>>()
+        }) else {
+            pair.own.publish_grant(&grant, &ticket).await?;
+            return Ok(Vec::new());
+        };
+        let chain = NonEmpty::from_vec(minted.iter().map(SignedUwill::cid).collect())
+            .context("a grant names at least one claim")?;
+        let log = read_key_event_log(&state.hosted(identity)?.directory, identity).await?;
+        delegation::publish_chained(&pair.own, &log, &grant, &chain, &minted, &ticket).await?;
+        Ok(chain.into_vec())
+    }
 }
 
 impl ConnectionsService for RuntimeConnectionsService<'_> {
-    async fn invite(&self, identity: PdnId, lifetime: Option<Duration>) -> Result<InvitePayload> {
+    async fn invite(
+        &self,
+        identity: PdnId,
+        lifetime: Option<Duration>,
+    ) -> Result<InvitePayload, ServiceError> {
         let mut state = self.runtime.state.lock().await;
-        state.hosted(identity)?;
-        let secret = state.pending_invites.mint(
+        let (payload, _kel) = mint_invite(
+            &mut state,
             identity,
             lifetime.unwrap_or(DEFAULT_INVITE_LIFETIME),
-            Instant::now(),
-        )?;
-        Ok(InvitePayload {
-            version: INVITE_FORMAT_VERSION,
-            inviter_addr: state.node.dial_handle().addr(),
-            secret,
-            inviter: identity,
-        })
+        )
+        .await?;
+        Ok(payload)
     }
 
-    async fn establish(&self, identity: PdnId, invite: InvitePayload) -> Result<()> {
+    async fn invite_via_mailbox(
+        &self,
+        identity: PdnId,
+        relay: EndpointAddr,
+        lifetime: Option<Duration>,
+    ) -> Result<InvitePayload, ServiceError> {
+        let payload = open_mailbox_invite(
+            &self.runtime.state,
+            identity,
+            relay,
+            lifetime.unwrap_or(DEFAULT_MAILBOX_LIFETIME),
+        )
+        .await?;
+        Ok(payload)
+    }
+
+    async fn establish(&self, identity: PdnId, invite: InvitePayload) -> Result<(), ServiceError> {
         // The version refusal precedes the dial. The hosted check and every
         // other step run inside the dialogue, which takes the runtime lock
         // per phase and never holds it across the network round-trip — see
@@ -156,24 +382,35 @@ impl ConnectionsService for RuntimeConnectionsService<'_> {
             }
             .into());
         }
-        establish_via_dialogue(&self.runtime.state, identity, &invite).await
+        match &invite.mailbox {
+            Some(relay) => deposit_request(&self.runtime.state, identity, &invite, relay).await?,
+            None => establish_via_dialogue(&self.runtime.state, identity, &invite).await?,
+        }
+        Ok(())
+    }
+
+    async fn collect_mailboxes(&self) -> Result<usize, ServiceError> {
+        Ok(collect_mailboxes(&self.runtime.state).await?)
     }
 
-    async fn list(&self, identity: PdnId) -> Result<Vec<PdnId>> {
+    async fn list(&self, identity: PdnId) -> Result<Vec<PdnId>, ServiceError> {
         let state = self.runtime.state.lock().await;
-        state.hosted(identity)?.directory.list_connections().await
+        Ok(state.hosted(identity)?.directory.list_connections().await?)
     }
 
-    async fn withdraw_grant(&self, identity: PdnId, peer: PdnId, issuer: PdnId) -> Result<()> {
+    async fn withdraw_grant(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+    ) -> Result<(), ServiceError> {
         let mut state = self.runtime.state.lock().await;
         state.hosted(identity)?;
-        if identity != issuer {
-            return Err(DelegationUnsupported { identity, issuer }.into());
-        }
         let pair = open_pair(&mut state, identity, peer)
             .await?
             .with_context(|| format!("no connection metadata pair toward {peer}"))?;
-        pair.own.withdraw_grant(issuer).await
+        pair.own.withdraw_grant(issuer).await?;
+        Ok(())
     }
 
     async fn publish_grant(
@@ -183,35 +420,90 @@ impl ConnectionsService for RuntimeConnectionsService<'_> {
         issuer: PdnId,
         claims: NonEmpty<ClaimId>,
         write: bool,
-    ) -> Result<()> {
+    ) -> Result<Vec<CapabilityCid>, ServiceError> {
+        self.publish(identity, peer, issuer, claims, write, None)
+            .await
+    }
+
+    async fn publish_grant_within(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+        claims: NonEmpty<ClaimId>,
+        write: bool,
+        window: ValidityWindow,
+    ) -> Result<Vec<CapabilityCid>, ServiceError> {
+        self.publish(identity, peer, issuer, claims, write, Some(window))
+            .await
+    }
+
+    async fn revoke(&self, identity: PdnId, cid: CapabilityCid) -> Result<(), ServiceError> {
         let mut state = self.runtime.state.lock().await;
-        state.hosted(identity)?;
-        if identity != issuer {
-            return Err(DelegationUnsupported { identity, issuer }.into());
-        }
-        let pair = open_pair(&mut state, identity, peer)
+        let peers = state.hosted(identity)?.directory.list_connections().await?;
+        let revocation = TokenSigner::of(&state, identity)
             .await?
-            .with_context(|| format!("no connection metadata pair toward {peer}"))?;
-        // The ticket carries exactly the granted authority.
-        let mode = if write {
-            ShareMode::Write
-        } else {
-            ShareMode::Read
-        };
-        let ticket = state
-            .node
-            .share_ticket(issuer, mode, AddrInfoOptions::RelayAndAddresses)
-            .await?;
-        let grant = ReadGrant {
-            issuer,
-            audience: peer,
-            claims,
-            write,
-        };
-        pair.own.publish_grant(&grant, &ticket).await
+            .ok_or(NoSigningKeys { identity })?
+            .revoke(Revocation {
+                iss: identity,
+                revoke: cid,
+            })
+            .to_bytes();
+        let mut published = false;
+        for peer in peers {
+            let Some(pair) = open_pair(&mut state, identity, peer).await? else {
+                continue;
+            };
+            // Of its own data or a delegation of another's, alike.
+            for issuer in pair.own.list_grants().await? {
+                if pair.own.grant_chain(issuer).await?.contains(&cid) {
+                    pair.own.publish_revocation(&cid, &revocation).await?;
+                    published = true;
+                    break;
+                }
+            }
+        }
+        if !published {
+            return Err(UnknownCapability { identity, cid }.into());
+        }
+        Ok(())
     }
 
-    async fn read_grants(&self, identity: PdnId, peer: PdnId) -> Result<Vec<PeerGrant>> {
+    async fn delegate(
+        &self,
+        identity: PdnId,
+        issuer: PdnId,
+        claim: ClaimId,
+        conditions: Capability,
+    ) -> Result<DelegatedClaim, ServiceError> {
+        let mut state = self.runtime.state.lock().await;
+        let (delegation, _minted) =
+            delegation::delegate(&mut state, identity, issuer, claim, conditions).await?;
+        Ok(delegation)
+    }
+
+    async fn delegations_to(
+        &self,
+        identity: PdnId,
+        to: PdnId,
+    ) -> Result<Vec<DelegatedClaim>, ServiceError> {
+        let mut state = self.runtime.state.lock().await;
+        Ok(delegation::delegations_to(&mut state, identity, to).await?)
+    }
+
+    async fn incoming_delegations(
+        &self,
+        identity: PdnId,
+    ) -> Result<Vec<DelegatedClaim>, ServiceError> {
+        let mut state = self.runtime.state.lock().await;
+        Ok(delegation::incoming_delegations(&mut state, identity).await?)
+    }
+
+    async fn read_grants(
+        &self,
+        identity: PdnId,
+        peer: PdnId,
+    ) -> Result<Vec<PeerGrant>, ServiceError> {
         // Assembly under the lock, polling outside it, exactly as
         // `read_grants`.
         let pair = {
@@ -241,6 +533,13 @@ impl ConnectionsService for RuntimeConnectionsService<'_> {
 /// its first grant read, and stay invisible to the counterparty
 /// ([`open_pair`] is also what publishes the device record).
 ///
+/// The armer watches one directory, `directory`, and ends when the
+/// identity is hosted on another one. A sweep that finds successor records
+/// in the directory follows the first that proves itself
+/// ([`follow_successor`](crate::revocation::follow_successor)) outside the
+/// lock; a failed attempt — no record proven yet among them — is retried
+/// on the next change, or after [`FOLLOW_RETRY`] when none comes.
+///
 /// The task holds the runtime state weakly and upgrades per sweep, so
 /// shutdown's sole-ownership wait is never blocked for longer than one
 /// sweep's local acts; it exits when the runtime is gone or the directory's
@@ -248,19 +547,48 @@ impl ConnectionsService for RuntimeConnectionsService<'_> {
 pub(crate) fn spawn_connection_armer(
     state: Weak<Mutex<State>>,
     identity: PdnId,
-    changes: impl Stream<Item = Result<()>> + Send + Unpin + 'static,
+    directory: NamespaceId,
+    changes: impl Stream<Item = Result<(), NodeError>> + Send + Unpin + 'static,
 ) {
     let mut changes = changes;
     let _detached = tokio::spawn(async move {
         loop {
-            {
+            let retired = {
                 let Some(strong) = state.upgrade() else {
                     return;
                 };
                 let mut guard = strong.lock().await;
+                match guard.hosted(identity) {
+                    Ok(hosted) if hosted.directory.namespace() == directory => {}
+                    _ => return,
+                }
                 arm_connections(&mut guard, identity, &state).await;
-            }
-            match changes.next().await {
+                crate::events::observe_directory(&mut guard, identity).await;
+                match guard.hosted(identity) {
+                    Ok(hosted) => hosted
+                        .directory
+                        .successors()
+                        .await
+                        .is_ok_and(|successors| !successors.is_empty()),
+                    Err(_unhosted) => return,
+                }
+            };
+            let next = if retired {
+                if crate::revocation::follow_successor(&state, identity)
+                    .await
+                    .is_ok()
+                {
+                    // The successor's own armer takes over from here.
+                    return;
+                }
+                match tokio::time::timeout(FOLLOW_RETRY, changes.next()).await {
+                    Ok(next) => next,
+                    Err(_quiet) => Some(Ok(())),
+                }
+            } else {
+                changes.next().await
+            };
+            match next {
                 Some(Ok(())) => {}
                 // A failed subscription or a stream ended by shutdown both
                 // end the armer; the grant surface's on-demand open remains
@@ -281,8 +609,11 @@ pub(crate) fn spawn_connection_armer(
 /// The binder owns exactly what it imported ([`State::bound_grants`]): a
 /// grant that appears is imported, a grant whose ticket comes to name a
 /// different replica is re-imported onto it, and a grant that disappears is
-/// forgotten again. A namespace that arrived any other way is never
-/// touched, so an out-of-band import is not dropped from under its owner.
+/// forgotten again. A time-bounded grant is imported when its window opens
+/// and forgotten when it closes: the binder also sweeps at the next window
+/// edge of the grants it reads, which no change of the replica announces.
+/// A namespace that arrived any other way is never touched, so an
+/// out-of-band import is not dropped from under its owner.
 ///
 /// Like the connection armer the task holds the state weakly and upgrades
 /// per sweep. It exits when the runtime is gone, when the event stream ends
@@ -301,12 +632,14 @@ pub(crate) fn spawn_grant_binder(
             Err(_unsubscribable) => return release_binder(&state, identity, peer).await,
         };
         loop {
-            {
+            let edge = {
                 let Some(strong) = state.upgrade() else {
                     return;
                 };
                 let mut guard = strong.lock().await;
-                if !bind_grants(&mut guard, identity, peer, &peer_store).await {
+                let Sweep::Watch { edge } =
+                    bind_grants(&mut guard, identity, peer, &peer_store).await
+                else {
                     // Hand the pair over, bookkeeping included: the successor
                     // starts against a replica that has not synced yet, and
                     // an inherited record of what was imported would read as
@@ -320,11 +653,23 @@ pub(crate) fn spawn_grant_binder(
                     );
                     guard.grant_binders.remove(&(identity, peer));
                     return;
+                };
+                edge
+            };
+            // A window opening or closing changes no record: the sweep
+            // that follows it is timed, not prompted.
+            let next = match edge {
+                Some(at) => {
+                    let wait = Duration::from_millis(at.saturating_sub(unix_ms(SystemTime::now())));
+                    tokio::time::timeout(wait, changes.next()).await
+                }
+                None => Ok(changes.next().await),
+            };
+            match next {
+                Ok(Some(Ok(()))) | Err(_window_edge) => {}
+                Ok(Some(Err(_)) | None) => {
+                    return release_binder(&state, identity, peer).await;
                 }
-            }
-            match changes.next().await {
-                Some(Ok(())) => {}
-                Some(Err(_)) | None => return release_binder(&state, identity, peer).await,
             }
         }
     });
@@ -338,9 +683,19 @@ async fn release_binder(state: &Weak<Mutex<State>>, identity: PdnId, peer: PdnId
     strong.lock().await.grant_binders.remove(&(identity, peer));
 }
 
+/// What a sweep leaves its binder to do.
+enum Sweep {
+    /// The pair is gone or re-opened onto a fresh replica: the task ends.
+    Superseded,
+    /// Watch the replica for its next change — and, when a grant's window
+    /// opens or closes at `edge` (unix ms), sweep again then at the latest.
+    Watch { edge: Option<u64> },
+}
+
 /// One grant-arming sweep over the counterparty's replica. Answers whether
-/// this binder is still the right one to watch it: `false` once the pair is
-/// gone or re-opened onto a fresh replica, which ends the task.
+/// this binder is still the right one to watch it, and until when at the
+/// latest: [`Sweep::Superseded`] once the pair is gone or re-opened onto a
+/// fresh replica, which ends the task.
 ///
 /// A sweep never fails as a whole — a read that fails leaves that grant for
 /// the next change rather than abandoning the ones beside it.
@@ -349,21 +704,70 @@ async fn bind_grants(
     identity: PdnId,
     peer: PdnId,
     peer_store: &ConnectionMetadataStore,
-) -> bool {
+) -> Sweep {
     match state.metadata_pairs.get(&(identity, peer)) {
         Some(pair) if pair.peer.namespace() == peer_store.namespace() => {}
-        _ => return false,
+        _ => return Sweep::Superseded,
     }
-    let Ok(granted) = peer_store.list_grants().await else {
-        return true;
+    // A store the peer's revocation retired hands over to its successor
+    // once that proves itself; until then its grants stand as they are.
+    if matches!(
+        crate::revocation::follow_peer_successor(state, identity, peer, peer_store).await,
+        Ok(true)
+    ) {
+        return Sweep::Superseded;
+    }
+    let Ok(listed) = peer_store.list_grants().await else {
+        return Sweep::Watch { edge: None };
     };
+    // A grant made under a revoked token is gone for good, as a withdrawn
+    // one is: what it imported is forgotten alike — and so is a grant
+    // outside its window, until it opens or for good once it has closed.
+    // A delegation of a third identity's data is live only while its chain
+    // checks — issued by the data issuer, extended by the peer, valid now
+    // — and until its tokens have arrived. A probe that fails, and a
+    // record not readable yet, keep the grant for the next change, so a
+    // republished grant keeps its binding.
+    let now = unix_ms(SystemTime::now());
+    let mut edge: Option<u64> = None;
+    let mut granted = Vec::with_capacity(listed.len());
+    for issuer in listed {
+        if matches!(peer_store.grant_revoked(issuer).await, Ok(true)) {
+            continue;
+        }
+        let Ok(Some((cap, _ticket))) = peer_store.read_grant(issuer).await else {
+            granted.push(issuer);
+            continue;
+        };
+        if let Some(window) = cap.window {
+            if let Some(at) = window.next_edge(now) {
+                edge = Some(edge.map_or(at, |earlier| earlier.min(at)));
+            }
+            if !window.contains(now) {
+                continue;
+            }
+        }
+        if issuer != peer
+            && matches!(
+                delegation::delegation_holds(peer_store, identity, peer, &cap).await,
+                Ok(false)
+            )
+        {
+            continue;
+        }
+        granted.push(issuer);
+    }
+    // The peer's revocations of tokens this identity delegated on reach
+    // the delegates too; a relay that fails retries on the next change.
+    let _relayed_or_next_change =
+        delegation::relay_revocations(state, identity, peer, peer_store).await;
     for issuer in &granted {
         let _cold_until_next_change = bind_one_grant(state, identity, peer, *issuer, peer_store)
             .await
             .is_ok();
     }
     unbind_withdrawn(state, identity, peer, &granted).await;
-    true
+    Sweep::Watch { edge }
 }
 
 /// Import the data namespace behind one live grant, unless this binder
@@ -387,6 +791,14 @@ async fn bind_one_grant(
     if state.bound_grants.get(&bound) != Some(&namespace) {
         let _displaced = state.node.import_namespace_scoped(issuer, ticket).await?;
         state.bound_grants.insert(bound, namespace);
+        crate::events::emit(
+            state,
+            RuntimeEvent::GrantReceived {
+                identity,
+                peer,
+                issuer,
+            },
+        );
     }
     // Contacts are refreshed even when the binding is unchanged: the
     // audience's device set moves independently of the grant.
@@ -417,10 +829,12 @@ async fn refresh_sibling_contacts(state: &mut State, identity: PdnId, issuer: Pd
     if contacts.is_empty() {
         return Ok(());
     }
-    state.node.add_namespace_contacts(issuer, contacts)
+    state.node.add_namespace_contacts(issuer, contacts)?;
+    Ok(())
 }
 
-/// Forget the namespaces whose grant this pair no longer carries — the
+/// Forget the namespaces whose grant this pair no longer carries —
+/// withdrawn, made under a revoked token, or outside its window — the
 /// counterpart of the import above, bounded to what this binder brought in.
 /// A republished grant imports afresh, so dropping the replica is not a
 /// one-way door for the connection, only for the bytes held under a grant
@@ -437,11 +851,21 @@ async fn unbind_withdrawn(state: &mut State, identity: PdnId, peer: PdnId, live:
     for issuer in withdrawn {
         let _gone_or_already_gone = state.node.forget_namespace(issuer).await;
         state.bound_grants.remove(&(identity, peer, issuer));
+        crate::events::emit(
+            state,
+            RuntimeEvent::GrantWithdrawn {
+                identity,
+                peer,
+                issuer,
+            },
+        );
     }
 }
 
 /// One arming sweep: open every pair `identity`'s directory lists that is
-/// not in the cache yet, and put a grant binder on every pair that is open.
+/// not in the cache yet, mirror the identity's key event log into every
+/// open pair's own store, and put a grant binder on every pair that is
+/// open.
 /// A pair that cannot open — its tickets still payload-waiting, or a
 /// transient store failure — stays cold until the next sweep; a sweep never
 /// fails as a whole.
@@ -450,12 +874,15 @@ async fn unbind_withdrawn(state: &mut State, identity: PdnId, peer: PdnId, live:
 /// opening, because establishment fills the cache directly: a pair this
 /// device paired itself would otherwise never be watched for grants.
 async fn arm_connections(state: &mut State, identity: PdnId, runtime: &Weak<Mutex<State>>) {
-    let peers = {
+    let (peers, log) = {
         let Ok(hosted) = state.hosted(identity) else {
             return;
         };
         match hosted.directory.list_connections().await {
-            Ok(peers) => peers,
+            Ok(peers) => (
+                peers,
+                read_key_event_log(&hosted.directory, identity).await.ok(),
+            ),
             Err(_directory_unreadable) => return,
         }
     };
@@ -466,6 +893,9 @@ async fn arm_connections(state: &mut State, identity: PdnId, runtime: &Weak<Mute
         let Some(pair) = state.metadata_pairs.get(&(identity, peer)) else {
             continue;
         };
+        if let Some(log) = &log {
+            let _mirrored_or_next_sweep = mirror_key_events(log, &pair.own).await;
+        }
         let peer_store = pair.peer.clone();
         if state.grant_binders.insert((identity, peer)) {
             spawn_grant_binder(runtime.clone(), identity, peer, peer_store);
@@ -486,7 +916,7 @@ async fn arm_connections(state: &mut State, identity: PdnId, runtime: &Weak<Mute
 /// established on the identity's other devices. `None` when the directory
 /// has no complete pair for `peer` (not connected, or the tickets have not
 /// synced here yet) and nothing is cached.
-async fn open_pair(
+pub(crate) async fn open_pair(
     state: &mut State,
     identity: PdnId,
     peer: PdnId,
@@ -1,13 +1,19 @@
 //! The data service: entries in data namespaces hosted on this node, plus
 //! the namespace ticket handover.
 
-use anyhow::Result;
-use data_layer::{AddrInfoOptions, DocTicket, ShareMode};
-use pdn_types::{EntryInfo, EntryPath, PdnId};
+use data_layer::{AddrInfoOptions, DataLayer, DataLayerError, DocTicket, ShareMode};
+use futures_lite::{stream, StreamExt as _};
+use pdn_types::{EntryEvent, EntryInfo, EntryPath, PdnId};
 
+use crate::error::ServiceError;
 use crate::runtime::Runtime;
 
-/// Writing, reading, and listing entries by issuer and path, and the
+/// The stream [`DataService::watch`] hands back: entry events, and an
+/// [`EntriesMissed`](ServiceError::EntriesMissed) error item each time the
+/// watch fell behind.
+pub type DataWatch = stream::Boxed<Result<EntryEvent, ServiceError>>;
+
+/// Writing, reading, listing, and deleting entries by issuer and path, and the
 /// namespace-ticket handover: share a namespace hosted here, import a
 /// peer's.
 ///
@@ -25,22 +31,58 @@ use crate::runtime::Runtime;
 /// at all.
 #[allow(async_fn_in_trait)]
 pub trait DataService {
-    /// Write `payload` at `path` in the data namespace of `issuer`.
-    async fn write(&self, issuer: PdnId, path: &EntryPath, payload: &[u8]) -> Result<()>;
+    /// Write `payload` at `path` in the data namespace of `issuer`. A
+    /// payload over the backend's maximum is refused with
+    /// [`ServiceError::PayloadTooLarge`].
+    async fn write(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+        payload: &[u8],
+    ) -> Result<(), ServiceError>;
 
     /// Read the latest payload at `path` under `issuer`. Returns `Ok(None)`
     /// both when no entry exists and when its record is stored but the
     /// payload has not synced yet (record-first reads); poll to observe
     /// convergence.
-    async fn read(&self, issuer: PdnId, path: &EntryPath) -> Result<Option<Vec<u8>>>;
+    async fn read(&self, issuer: PdnId, path: &EntryPath) -> Result<Option<Vec<u8>>, ServiceError>;
 
     /// List entry metadata under `issuer` — no payload bytes — optionally
     /// narrowed to paths under `path_prefix`, matching whole components.
-    async fn list(&self, issuer: PdnId, path_prefix: Option<&EntryPath>) -> Result<Vec<EntryInfo>>;
+    async fn list(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Vec<EntryInfo>, ServiceError>;
+
+    /// Delete the entry at `path` under `issuer`; answers whether one was
+    /// there. The deletion replicates as a tombstone — to the issuer's
+    /// other devices, and to each grantee whose grant covers `path`, which
+    /// then reads the claim as gone. A later write brings it back.
+    async fn delete(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, ServiceError>;
+
+    /// Delete every entry under `prefix` by whole components, `prefix`
+    /// itself included; answers how many were deleted. Each replicates as
+    /// [`delete`](Self::delete)'s does.
+    async fn delete_prefix(&self, issuer: PdnId, prefix: &EntryPath)
+        -> Result<usize, ServiceError>;
+
+    /// Watch the entries under `issuer` from now on, optionally narrowed to
+    /// paths under `path_prefix`: each insert, update and deletion, local
+    /// or arrived by sync, and a `PayloadReady` once each written payload
+    /// can be [`read`](Self::read) — the push counterpart of polling.
+    /// Falling behind yields an
+    /// [`EntriesMissed`](ServiceError::EntriesMissed) error and the watch
+    /// carries on; it ends with the runtime.
+    async fn watch(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<DataWatch, ServiceError>;
 
     /// Share the data namespace of `issuer` as a ticket a peer runtime can
     /// import: the namespace handover.
-    async fn share(&self, issuer: PdnId, mode: ShareMode) -> Result<DocTicket>;
+    async fn share(&self, issuer: PdnId, mode: ShareMode) -> Result<DocTicket, ServiceError>;
 
     /// Import a peer's data namespace from a `ticket` obtained **out of
     /// band**, registering it under `issuer` (named by the caller — the
@@ -55,49 +97,108 @@ pub trait DataService {
     /// replicated grant record. What the session actually delivers is the
     /// issuer's grant record to decide, not the import: with no record
     /// behind it this ticket delivers nothing.
-    async fn import(&self, issuer: PdnId, ticket: DocTicket) -> Result<()>;
+    async fn import(&self, issuer: PdnId, ticket: DocTicket) -> Result<(), ServiceError>;
 
     /// [`import`](Self::import) for a ticket that arrived with a grant —
     /// same registration, same stance. Reads nudge a reconciliation and are
     /// served from the local replica.
-    async fn import_scoped(&self, issuer: PdnId, ticket: DocTicket) -> Result<()>;
+    async fn import_scoped(&self, issuer: PdnId, ticket: DocTicket) -> Result<(), ServiceError>;
 }
 
-/// The production [`DataService`], backed by the runtime's `data-layer`
-/// stack.
+/// The production [`DataService`]: entries through a [`DataLayer`] backend
+/// — by default the runtime's own node ([`RuntimeEntries`]) — and the
+/// namespace handover through the runtime's node, which no backend
+/// replaces: tickets are the sync stack's vocabulary, not the trait's.
 #[derive(Clone, Copy)]
-pub struct RuntimeDataService<'rt> {
+pub struct RuntimeDataService<'rt, L = RuntimeEntries<'rt>> {
     runtime: &'rt Runtime,
+    entries: L,
 }
 
 impl<'rt> RuntimeDataService<'rt> {
     pub(crate) fn new(runtime: &'rt Runtime) -> Self {
-        Self { runtime }
+        Self {
+            runtime,
+            entries: RuntimeEntries::new(runtime),
+        }
     }
 }
 
-impl DataService for RuntimeDataService<'_> {
-    async fn write(&self, issuer: PdnId, path: &EntryPath, payload: &[u8]) -> Result<()> {
-        let state = self.runtime.state.lock().await;
-        state.node.write(issuer, state.author, path, payload).await
+impl<'rt, L: DataLayer> RuntimeDataService<'rt, L> {
+    /// The data service of `runtime` with its entries served by another
+    /// backend.
+    pub fn with_entries(runtime: &'rt Runtime, entries: L) -> Self {
+        Self { runtime, entries }
+    }
+}
+
+impl<L: DataLayer> DataService for RuntimeDataService<'_, L> {
+    async fn write(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+        payload: &[u8],
+    ) -> Result<(), ServiceError> {
+        self.entries
+            .insert_entry(issuer, path, payload)
+            .await
+            .map_err(ServiceError::from)
     }
 
-    async fn read(&self, issuer: PdnId, path: &EntryPath) -> Result<Option<Vec<u8>>> {
-        let state = self.runtime.state.lock().await;
-        state.node.read(issuer, path).await
+    async fn read(&self, issuer: PdnId, path: &EntryPath) -> Result<Option<Vec<u8>>, ServiceError> {
+        self.entries
+            .get_entry(issuer, path)
+            .await
+            .map_err(ServiceError::from)
     }
 
-    async fn list(&self, issuer: PdnId, path_prefix: Option<&EntryPath>) -> Result<Vec<EntryInfo>> {
-        let state = self.runtime.state.lock().await;
-        state.node.list(issuer, path_prefix).await
+    async fn list(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Vec<EntryInfo>, ServiceError> {
+        self.entries
+            .list_entries(issuer, path_prefix)
+            .await?
+            .map(|entry| entry.map_err(ServiceError::from))
+            .try_collect()
+            .await
     }
 
-    async fn share(&self, issuer: PdnId, mode: ShareMode) -> Result<DocTicket> {
+    async fn delete(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, ServiceError> {
+        self.entries
+            .delete_entry(issuer, path)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn delete_prefix(
+        &self,
+        issuer: PdnId,
+        prefix: &EntryPath,
+    ) -> Result<usize, ServiceError> {
+        self.entries
+            .delete_entries(issuer, prefix)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    async fn watch(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<DataWatch, ServiceError> {
+        let watch = self.entries.watch_entries(issuer, path_prefix).await?;
+        Ok(watch.map(|event| event.map_err(ServiceError::from)).boxed())
+    }
+
+    async fn share(&self, issuer: PdnId, mode: ShareMode) -> Result<DocTicket, ServiceError> {
         let state = self.runtime.state.lock().await;
-        state
+        let ticket = state
             .node
             .share_ticket(issuer, mode, AddrInfoOptions::RelayAndAddresses)
-            .await
+            .await?;
+        Ok(ticket)
     }
 
     /// The foreign ticket is registered into the node and persisted nowhere
@@ -105,7 +206,7 @@ impl DataService for RuntimeDataService<'_> {
     /// stores, where a copy would spread to every device and outlive the
     /// grant it came from. The runtime's registry is a cache, not the
     /// ticket's durable home.
-    async fn import(&self, issuer: PdnId, ticket: DocTicket) -> Result<()> {
+    async fn import(&self, issuer: PdnId, ticket: DocTicket) -> Result<(), ServiceError> {
         let state = self.runtime.state.lock().await;
         // Importing an issuer this runtime already knows rebinds it, and the
         // displaced binding is dropped knowingly: with one namespace per
@@ -117,7 +218,7 @@ impl DataService for RuntimeDataService<'_> {
         Ok(())
     }
 
-    async fn import_scoped(&self, issuer: PdnId, ticket: DocTicket) -> Result<()> {
+    async fn import_scoped(&self, issuer: PdnId, ticket: DocTicket) -> Result<(), ServiceError> {
         let state = self.runtime.state.lock().await;
         // Same rebinding contract as `import` — and the same grantee stance
         // below it (contacts-only sync, audience-device re-serving); the
@@ -126,3 +227,80 @@ impl DataService for RuntimeDataService<'_> {
         Ok(())
     }
 }
+
+/// The runtime's own entries backend: its node as a [`DataLayer`], writing
+/// as the runtime's author. Each operation takes the state lock only to
+/// reach the node.
+#[derive(Clone, Copy)]
+pub struct RuntimeEntries<'rt> {
+    runtime: &'rt Runtime,
+}
+
+impl<'rt> RuntimeEntries<'rt> {
+    pub(crate) fn new(runtime: &'rt Runtime) -> Self {
+        Self { runtime }
+    }
+}
+
+impl DataLayer for RuntimeEntries<'_> {
+    async fn insert_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+        payload: &[u8],
+    ) -> Result<(), DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.insert_entry(issuer, path, payload).await
+    }
+
+    async fn get_entry(
+        &self,
+        issuer: PdnId,
+        path: &EntryPath,
+    ) -> Result<Option<Vec<u8>>, DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.get_entry(issuer, path).await
+    }
+
+    type EntryStream = stream::Boxed<Result<EntryInfo, DataLayerError>>;
+
+    async fn list_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EntryStream, DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.list_entries(issuer, path_prefix).await
+    }
+
+    async fn delete_entry(&self, issuer: PdnId, path: &EntryPath) -> Result<bool, DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.delete_entry(issuer, path).await
+    }
+
+    async fn delete_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: &EntryPath,
+    ) -> Result<usize, DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.delete_entries(issuer, path_prefix).await
+    }
+
+    type EventStream = stream::Boxed<Result<EntryEvent, DataLayerError>>;
+
+    async fn watch_entries(
+        &self,
+        issuer: PdnId,
+        path_prefix: Option<&EntryPath>,
+    ) -> Result<Self::EventStream, DataLayerError> {
+        let state = self.runtime.state.lock().await;
+        let layer = state.node.data_layer(state.author);
+        layer.watch_entries(issuer, path_prefix).await
+    }
+}
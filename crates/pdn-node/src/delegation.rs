@@ -0,0 +1,861 @@
+//! Delegation over connections: grants made under `UWill` tokens, and
+//! shared on by their grantees — `PdnOp::DelegateClaim`,
+//! `ListDelegationsTo` and `ListIncomingDelegations` executed over the
+//! connections' metadata pairs.
+//!
+//! An identity granting its own data makes each claim of the grant under a
+//! token of its own, published beside the grant record in its store toward
+//! the grantee. A token carrying [`Command::Delegate`] lets the grantee
+//! share that claim on: it extends the issuer's token by a link of its own
+//! toward one of its peers, checks the whole chain, and records the result
+//! in its store toward that peer as a delegated grant — tokens included,
+//! so the delegate can check the chain in turn. The grantee re-serves the
+//! delegated claims from its own replica of the issuer's data: the issuer
+//! never hears of the delegate, and the grantee's access book serves the
+//! delegate only what the issuer's grant to it still covers.
+//!
+//! A delegation is read-only, and one link deep: the links a grantee adds
+//! carry no [`Command::Delegate`] of their own.
+
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+
+use anyhow::{bail, ensure, Context, Result};
+use data_layer::{
+    unix_ms, AddrInfoOptions, ConnectionMetadataStore, DocTicket, ReadGrant, ShareMode,
+};
+use pdn_layer::kel::{KeyEventLog, KeyPair, KeyState};
+use pdn_layer::uwill::{
+    validate_chain, Command, Revocation, SignedRevocation, SignedUwill, UwillCapability, Verdict,
+};
+use pdn_layer::{AccessMode, Capability, DelegatedClaim};
+use pdn_types::{CapabilityCid, ClaimId, NonEmpty, PdnId, ValidityWindow};
+
+use crate::connections::open_pair;
+use crate::identity::{
+    mirror_key_events, read_key_event_log, relay_key_events, verified_copy, verified_key_event_log,
+    NoSigningKeys,
+};
+use crate::runtime::State;
+
+/// A delegation `identity` cannot make: it holds no live grant on `claim`
+/// of `issuer`'s data whose token carries [`Command::Delegate`] — or it
+/// asked for write, which no delegation carries. Carried by
+/// [`ServiceError::NotDelegable`](crate::ServiceError::NotDelegable).
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("identity {identity} holds no delegable read grant on claim {claim} of {issuer}")]
+pub struct NotDelegable {
+    /// The identity attempting the delegation.
+    pub identity: PdnId,
+    /// The issuer of the claim.
+    pub issuer: PdnId,
+    /// The claim it tried to delegate.
+    pub claim: ClaimId,
+}
+
+/// The key this device signs an identity's tokens and revocations with,
+/// and the sequence number of the identity's key event log it signs under:
+/// the log's last event as the directory holds it — the state a verifier
+/// checks the key in ([`KeyState::accepts`]), so a token outlives an
+/// orderly rotation of the key that signed it.
+pub(crate) struct TokenSigner {
+    key: KeyPair,
+    anchor: u64,
+}
+
+impl TokenSigner {
+    /// The signer of hosted `identity` on this device, `None` where the
+    /// device holds no key of the identity's; refused while the identity's
+    /// log does not verify here.
+    pub(crate) async fn of(state: &State, identity: PdnId) -> Result<Option<Self>> {
+        let Some(key) = state.keys.device_signer(identity).cloned() else {
+            return Ok(None);
+        };
+        let directory = &state.hosted(identity)?.directory;
+        let (_log, key_state) = verified_key_event_log(directory, identity).await?;
+        Ok(Some(Self {
+            key,
+            anchor: key_state.sn,
+        }))
+    }
+
+    /// Sign `capability`.
+    pub(crate) fn sign(&self, capability: UwillCapability) -> SignedUwill {
+        capability.sign(&self.key, self.anchor)
+    }
+
+    /// Sign `revocation`.
+    pub(crate) fn revoke(&self, revocation: Revocation) -> SignedRevocation {
+        revocation.sign(&self.key, self.anchor)
+    }
+}
+
+/// The token a grant of `grant.issuer`'s own data makes `claim` under:
+/// issued by the issuer, its own subject, to the grant's audience, for the
+/// grant's commands — plus [`Command::Delegate`] when `delegable` — valid
+/// in `window`.
+pub(crate) fn root_token(
+    signer: &TokenSigner,
+    grant: &ReadGrant,
+    claim: ClaimId,
+    delegable: bool,
+    window: ValidityWindow,
+) -> SignedUwill {
+    let mut cmd = vec![Command::Read];
+    if grant.write {
+        cmd.push(Command::Write);
+    }
+    if delegable {
+        cmd.push(Command::Delegate);
+    }
+    signer.sign(UwillCapability {
+        iss: grant.issuer,
+        aud: grant.audience,
+        sub: grant.issuer,
+        cmd,
+        res: claim,
+        nbf: window.nbf,
+        exp: window.exp,
+        nonce: rand::random(),
+    })
+}
+
+/// The window a token of `grant` is valid in: the grant's own, or from
+/// now on, open-ended, for a grant without one.
+pub(crate) fn token_window(grant: &ReadGrant) -> ValidityWindow {
+    grant.window.unwrap_or(ValidityWindow {
+        nbf: unix_ms(SystemTime::now()),
+        exp: u64::MAX,
+    })
+}
+
+/// Publish `grant` of the store writer's own data into `store`, each claim
+/// under its token — `chain`, in claim order — and the tokens in `minted`
+/// beside it. The writer's key event `log` and the tokens first: a grantee
+/// that has the grant record then finds what it checks the chain by
+/// sooner.
+pub(crate) async fn publish_chained(
+    store: &ConnectionMetadataStore,
+    log: &KeyEventLog,
+    grant: &ReadGrant,
+    chain: &NonEmpty<CapabilityCid>,
+    minted: &[SignedUwill],
+    ticket: &DocTicket,
+) -> Result<()> {
+    mirror_key_events(log, store).await?;
+    for token in minted {
+        store.publish_token(&token.cid(), &token.to_bytes()).await?;
+    }
+    store.publish_chained_grant(grant, chain, ticket).await?;
+    Ok(())
+}
+
+/// Re-sign what `identity` signed in its own `store` that its log no
+/// longer accepts, and rewrite the grants made under it. An orderly
+/// rotation voids nothing — a token is checked at the anchor it was
+/// signed under — but a removal event voids everything the removed
+/// device's key signed, and a recovery rotation everything the retired
+/// key signed, at any anchor. Each such token is signed again by this
+/// device's [`TokenSigner`], its capability unchanged, and every grant made under it is
+/// rewritten under the new token's CID; each such revocation is signed
+/// again, so a token it revoked stays revoked. `data`, where given, is the
+/// ticket the grant of `identity`'s own data is republished with even if
+/// none of its tokens changed — the re-keyed data's.
+///
+/// The identity's log is mirrored into `store` first, so a grantee finds
+/// the anchor the new signatures are made under. What this cannot reach
+/// is a grantee's delegation on a re-signed token: its chain names the
+/// token the issuer replaced, and it holds again once the grantee shares
+/// the claim on anew.
+pub(crate) async fn reissue_grants(
+    state: &State,
+    identity: PdnId,
+    store: &ConnectionMetadataStore,
+    data: Option<&DocTicket>,
+) -> Result<()> {
+    let signer = TokenSigner::of(state, identity)
+        .await?
+        .ok_or(NoSigningKeys { identity })?;
+    let directory = &state.hosted(identity)?.directory;
+    let (log, key_state) = verified_key_event_log(directory, identity).await?;
+    mirror_key_events(&log, store).await?;
+    for issuer in store.list_grants().await? {
+        let Some((grant, ticket)) = store.read_grant(issuer).await? else {
+            continue;
+        };
+        let mut changed = false;
+        let mut chains = Vec::new();
+        for chain in store.grant_chains(issuer).await? {
+            let mut cids = Vec::with_capacity(chain.len());
+            for cid in chain {
+                match reissue_token(store, identity, &signer, &key_state, cid).await? {
+                    Some(reissued) => {
+                        changed = true;
+                        cids.push(reissued);
+                    }
+                    None => cids.push(cid),
+                }
+            }
+            chains.push(NonEmpty::from_vec(cids).context("a chain holds at least one token")?);
+        }
+        if issuer == identity {
+            let Some(ticket) = data.cloned().or(changed.then_some(ticket)) else {
+                continue;
+            };
+            match NonEmpty::from_vec(chains.into_iter().flatten().collect()) {
+                Some(chain) => store.publish_chained_grant(&grant, &chain, &ticket).await?,
+                None => store.publish_grant(&grant, &ticket).await?,
+            }
+        } else if changed {
+            let chains = NonEmpty::from_vec(chains).context("a delegation names a claim")?;
+            store
+                .publish_delegated_grant(&grant, &chains, &ticket)
+                .await?;
+        }
+    }
+    for cid in store.list_revocations().await? {
+        let Some(record) = store.read_revocation(&cid).await? else {
+            continue;
+        };
+        let Ok(signed) = SignedRevocation::from_bytes(&record) else {
+            continue;
+        };
+        if signed.revocation.iss == identity && signed.verify(&key_state).is_err() {
+            let resigned = signer.revoke(signed.revocation);
+            store.publish_revocation(&cid, &resigned.to_bytes()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// The CID of token `cid` signed again by `signer` and published in
+/// `store`, where `identity` issued it and `key_state` no longer accepts
+/// its signature; `None` for a token that still holds, another issuer's,
+/// or one whose payload is not here.
+async fn reissue_token(
+    store: &ConnectionMetadataStore,
+    identity: PdnId,
+    signer: &TokenSigner,
+    key_state: &KeyState,
+    cid: CapabilityCid,
+) -> Result<Option<CapabilityCid>> {
+    let Some(bytes) = store.read_token(&cid).await? else {
+        return Ok(None);
+    };
+    let token = SignedUwill::from_bytes(&bytes)
+        .with_context(|| format!("undecodable capability token {cid}"))?;
+    if token.capability.iss != identity || token.verify(key_state).is_ok() {
+        return Ok(None);
+    }
+    let resigned = signer.sign(token.capability);
+    store
+        .publish_token(&resigned.cid(), &resigned.to_bytes())
+        .await?;
+    Ok(Some(resigned.cid()))
+}
+
+/// The tokens of `chain` that `store` revokes — the chain check's
+/// revocation source. A revocation counts once it holds under the key
+/// states of the chain's issuers in `key_states`, and from its record
+/// alone while its payload has not arrived: the chain is void until the
+/// revocation is checked. One that does not hold is ignored.
+async fn revocations(
+    store: &ConnectionMetadataStore,
+    chain: &[SignedUwill],
+    key_states: &HashMap<PdnId, KeyState>,
+) -> Result<HashSet<CapabilityCid>> {
+    let mut revoked = HashSet::new();
+    for cid in store.list_revocations().await? {
+        if !chain.iter().any(|token| token.cid() == cid) {
+            continue;
+        }
+        let holds = match store.read_revocation(&cid).await? {
+            Some(record) => revocation_holds(&record, cid, key_states, chain),
+            None => true,
+        };
+        if holds {
+            revoked.insert(cid);
+        }
+    }
+    Ok(revoked)
+}
+
+/// The signed tokens of `chain`, as `store` holds them: `None` while any
+/// token's payload has not arrived, an error for one that does not decode.
+async fn read_chain(
+    store: &ConnectionMetadataStore,
+    chain: &NonEmpty<CapabilityCid>,
+) -> Result<Option<Vec<SignedUwill>>> {
+    let mut tokens = Vec::with_capacity(chain.len());
+    for cid in chain {
+        let Some(bytes) = store.read_token(cid).await? else {
+            return Ok(None);
+        };
+        let token = SignedUwill::from_bytes(&bytes)
+            .with_context(|| format!("undecodable capability token {cid}"))?;
+        ensure!(token.cid() == *cid, "the token stored as {cid} is another");
+        tokens.push(token);
+    }
+    Ok(Some(tokens))
+}
+
+/// Check a chain at `now` against the revocations of the store it came
+/// from and the key states of its issuers in `key_states` — logs the
+/// caller verified — and that it grants `claim` of `issuer`'s data to
+/// `holder`.
+fn check_chain(
+    tokens: &[SignedUwill],
+    now: u64,
+    revoked: &HashSet<CapabilityCid>,
+    key_states: &HashMap<PdnId, KeyState>,
+    issuer: PdnId,
+    holder: PdnId,
+    claim: ClaimId,
+) -> Option<Verdict> {
+    validate_chain(tokens, now, revoked, key_states)
+        .ok()
+        .filter(|verdict| verdict.sub == issuer && verdict.aud == holder && verdict.res == claim)
+}
+
+/// A chain an identity may extend: its tokens, and the data issuer's key
+/// event log their signers were checked under — relayed beside each
+/// delegation made on the chain.
+struct Delegable {
+    tokens: Vec<SignedUwill>,
+    issuer_log: KeyEventLog,
+    issuer_state: KeyState,
+}
+
+/// Delegate `claim` of `issuer`'s data from hosted `identity` to the
+/// holders `conditions` names, each a connection of the identity
+/// (`PdnOp::DelegateClaim`). Answers the delegation and the CIDs of the
+/// tokens it minted, one per holder.
+///
+/// For the identity's own data this adds the claim to its grant toward
+/// each holder, under a token carrying [`Command::Delegate`]. For another
+/// identity's data it extends the identity's own delegable grant on the
+/// claim toward each holder, recorded as a delegated grant the identity
+/// re-serves ([`NotDelegable`] without one).
+pub(crate) async fn delegate(
+    state: &mut State,
+    identity: PdnId,
+    issuer: PdnId,
+    claim: ClaimId,
+    conditions: Capability,
+) -> Result<(DelegatedClaim, Vec<CapabilityCid>)> {
+    state.hosted(identity)?;
+    let not_delegable = NotDelegable {
+        identity,
+        issuer,
+        claim,
+    };
+    if conditions.access != AccessMode::Read {
+        return Err(not_delegable.into());
+    }
+    ensure!(
+        !conditions.holders.is_empty(),
+        "a delegation names at least one holder"
+    );
+    // Without a delegable grant the claim is refused as such, signing keys
+    // or not.
+    let parents = if identity == issuer {
+        None
+    } else {
+        Some(
+            delegable_chain(state, identity, issuer, claim)
+                .await?
+                .ok_or(not_delegable)?,
+        )
+    };
+    let signer = TokenSigner::of(state, identity)
+        .await?
+        .ok_or(NoSigningKeys { identity })?;
+    let exp = conditions.expires_at.unwrap_or(u64::MAX);
+    let mut minted = Vec::with_capacity(conditions.holders.len());
+    if let Some(parents) = parents {
+        for &holder in &conditions.holders {
+            minted
+                .push(delegate_on(state, &signer, identity, issuer, holder, &parents, exp).await?);
+        }
+    } else {
+        for &holder in &conditions.holders {
+            minted.push(delegate_own(state, &signer, identity, holder, claim, exp).await?);
+        }
+    }
+    let delegation = DelegatedClaim {
+        source: claim,
+        issuer,
+        delegator: identity,
+        conditions,
+    };
+    Ok((delegation, minted))
+}
+
+/// Add `claim` to `identity`'s grant of its own data toward `holder`,
+/// under a fresh delegable token; the grant's other claims keep their
+/// tokens — a claim granted before tokens were gets one minted now — and
+/// the grant keeps its window, which bounds the new token too.
+async fn delegate_own(
+    state: &mut State,
+    signer: &TokenSigner,
+    identity: PdnId,
+    holder: PdnId,
+    claim: ClaimId,
+    exp: u64,
+) -> Result<CapabilityCid> {
+    let pair = open_pair(state, identity, holder)
+        .await?
+        .with_context(|| format!("no connection metadata pair toward {holder}"))?;
+    let (kept, write, window, ticket) = match pair.own.read_grant(identity).await? {
+        Some((existing, ticket)) => {
+            let chains = pair.own.grant_chains(identity).await?;
+            (
+                by_claim(existing.claims, chains),
+                existing.write,
+                existing.window,
+                ticket,
+            )
+        }
+        None => {
+            let ticket = state
+                .node
+                .share_ticket(
+                    identity,
+                    ShareMode::Read,
+                    AddrInfoOptions::RelayAndAddresses,
+                )
+                .await?;
+            (Vec::new(), false, None, ticket)
+        }
+    };
+    let mut claims: Vec<ClaimId> = kept.iter().map(|(granted, _chain)| *granted).collect();
+    claims.retain(|granted| *granted != claim);
+    claims.push(claim);
+    let grant = ReadGrant {
+        issuer: identity,
+        audience: holder,
+        claims: NonEmpty::from_vec(claims).context("a grant names at least one claim")?,
+        write,
+        window,
+    };
+    let open = token_window(&grant);
+    let mut minted = Vec::new();
+    let mut chain = Vec::with_capacity(grant.claims.len());
+    for &granted in &grant.claims {
+        let kept_cid = kept
+            .iter()
+            .find(|(kept_claim, _chain)| *kept_claim == granted && granted != claim)
+            .map(|(_claim, chain)| *chain.first());
+        if let Some(cid) = kept_cid {
+            chain.push(cid);
+            continue;
+        }
+        let delegable = granted == claim;
+        let window = if delegable {
+            ValidityWindow {
+                exp: exp.min(open.exp),
+                ..open
+            }
+        } else {
+            open
+        };
+        let token = root_token(signer, &grant, granted, delegable, window);
+        chain.push(token.cid());
+        minted.push(token);
+    }
+    let chain = NonEmpty::from_vec(chain).context("a grant names at least one claim")?;
+    let cid = *chain.last();
+    let log = read_key_event_log(&state.hosted(identity)?.directory, identity).await?;
+    publish_chained(&pair.own, &log, &grant, &chain, &minted, &ticket).await?;
+    Ok(cid)
+}
+
+/// A grant's claims paired with their token chains — none when the grant
+/// is not made under one chain per claim.
+fn by_claim(
+    claims: NonEmpty<ClaimId>,
+    chains: Vec<NonEmpty<CapabilityCid>>,
+) -> Vec<(ClaimId, NonEmpty<CapabilityCid>)> {
+    if chains.len() != claims.len() {
+        return Vec::new();
+    }
+    claims.into_iter().zip(chains).collect()
+}
+
+/// The chain behind `identity`'s live grant on `claim` of `issuer`'s data,
+/// checked under the issuer's log as its store toward the identity mirrors
+/// it, and delegable: `None` when there is no such grant, its tokens or
+/// the issuer's log have not all arrived, or its last link does not carry
+/// [`Command::Delegate`].
+async fn delegable_chain(
+    state: &mut State,
+    identity: PdnId,
+    issuer: PdnId,
+    claim: ClaimId,
+) -> Result<Option<Delegable>> {
+    let Some(upstream) = open_pair(state, identity, issuer).await? else {
+        return Ok(None);
+    };
+    let Some((grant, _ticket)) = upstream.peer.read_grant(issuer).await? else {
+        return Ok(None);
+    };
+    let Some(index) = grant.claims.iter().position(|granted| *granted == claim) else {
+        return Ok(None);
+    };
+    let chains = upstream.peer.grant_chains(issuer).await?;
+    let Some(chain) = chains.get(index) else {
+        return Ok(None);
+    };
+    let Some(tokens) = read_chain(&upstream.peer, chain).await? else {
+        return Ok(None);
+    };
+    let Some((issuer_log, issuer_state)) = verified_copy(upstream.peer.key_events().await?, issuer)
+    else {
+        return Ok(None);
+    };
+    let key_states = HashMap::from([(issuer, issuer_state.clone())]);
+    let revoked = revocations(&upstream.peer, &tokens, &key_states).await?;
+    let now = unix_ms(SystemTime::now());
+    Ok(
+        check_chain(&tokens, now, &revoked, &key_states, issuer, identity, claim)
+            .filter(|verdict| verdict.cmd.contains(&Command::Delegate))
+            .map(|_verdict| Delegable {
+                tokens,
+                issuer_log,
+                issuer_state,
+            }),
+    )
+}
+
+/// Extend `parents` — `identity`'s delegable chain on `claim` of
+/// `issuer`'s data — by a read-only link to `holder`, inside the chain's
+/// window and ending no later than `exp`, and record it in the identity's
+/// store toward the holder: the delegated grant for the issuer gains the
+/// claim, every token of the chain and the issuer's log beside it, and
+/// holds in the narrowest window of its chains.
+async fn delegate_on(
+    state: &mut State,
+    signer: &TokenSigner,
+    identity: PdnId,
+    issuer: PdnId,
+    holder: PdnId,
+    parents: &Delegable,
+    exp: u64,
+) -> Result<CapabilityCid> {
+    let Some(parent) = parents.tokens.last() else {
+        bail!("a delegation extends a chain of at least one token");
+    };
+    let claim = parent.capability.res;
+    let now = unix_ms(SystemTime::now());
+    let link = signer.sign(UwillCapability {
+        iss: identity,
+        aud: holder,
+        sub: issuer,
+        cmd: vec![Command::Read],
+        res: claim,
+        nbf: now.max(parent.capability.nbf),
+        exp: exp.min(parent.capability.exp),
+        nonce: rand::random(),
+    });
+    let mut tokens = parents.tokens.clone();
+    tokens.push(link.clone());
+    let (log, key_state) =
+        verified_key_event_log(&state.hosted(identity)?.directory, identity).await?;
+    let key_states = HashMap::from([
+        (issuer, parents.issuer_state.clone()),
+        (identity, key_state),
+    ]);
+    check_chain(
+        &tokens,
+        now,
+        &HashSet::new(),
+        &key_states,
+        issuer,
+        holder,
+        claim,
+    )
+    .with_context(|| format!("the delegation of {claim} to {holder} does not validate"))?;
+
+    let pair = open_pair(state, identity, holder)
+        .await?
+        .with_context(|| format!("no connection metadata pair toward {holder}"))?;
+    let mut delegated = kept_delegations(&pair.own, issuer, claim, now).await?;
+    let chain = NonEmpty::from_vec(tokens.iter().map(SignedUwill::cid).collect())
+        .context("a chain holds at least one token")?;
+    delegated.push((claim, chain, link.capability.window()));
+    // One record carries one window for all its claims: the narrowest, so
+    // the delegator's access book serves no claim past its chain.
+    let window = delegated
+        .iter()
+        .map(|(_claim, _chain, window)| *window)
+        .reduce(|narrowest, window| ValidityWindow {
+            nbf: narrowest.nbf.max(window.nbf),
+            exp: narrowest.exp.min(window.exp),
+        });
+    let (claims, chains): (Vec<_>, Vec<_>) = delegated
+        .into_iter()
+        .map(|(claim, chain, _window)| (claim, chain))
+        .unzip();
+    let grant = ReadGrant {
+        issuer,
+        audience: holder,
+        claims: NonEmpty::from_vec(claims).context("a grant names at least one claim")?,
+        write: false,
+        window,
+    };
+    let chains = NonEmpty::from_vec(chains).context("a grant names at least one claim")?;
+
+    let ticket = state
+        .node
+        .share_ticket(issuer, ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
+        .await?;
+    mirror_key_events(&log, &pair.own).await?;
+    relay_key_events(issuer, &parents.issuer_log, &pair.own).await?;
+    for token in &tokens {
+        pair.own
+            .publish_token(&token.cid(), &token.to_bytes())
+            .await?;
+    }
+    pair.own
+        .publish_delegated_grant(&grant, &chains, &ticket)
+        .await?;
+    Ok(link.cid())
+}
+
+/// The delegations on `issuer`'s data `store` records toward its audience,
+/// besides the one of `claim`, still in force at `now` — each claim with
+/// its chain and the window the chain holds in: its last token's, which
+/// validation kept inside every link above it.
+async fn kept_delegations(
+    store: &ConnectionMetadataStore,
+    issuer: PdnId,
+    claim: ClaimId,
+    now: u64,
+) -> Result<Vec<(ClaimId, NonEmpty<CapabilityCid>, ValidityWindow)>> {
+    let Some((existing, _ticket)) = store.read_grant(issuer).await? else {
+        return Ok(Vec::new());
+    };
+    let mut kept = Vec::new();
+    for (granted, chain) in by_claim(existing.claims, store.grant_chains(issuer).await?) {
+        if granted == claim {
+            continue;
+        }
+        let leaf = chain.last();
+        let bytes = store
+            .read_token(leaf)
+            .await?
+            .with_context(|| format!("capability token {leaf} is missing"))?;
+        let window = SignedUwill::from_bytes(&bytes)
+            .with_context(|| format!("undecodable capability token {leaf}"))?
+            .capability
+            .window();
+        if now < window.exp {
+            kept.push((granted, chain, window));
+        }
+    }
+    Ok(kept)
+}
+
+/// The delegations `store` records, by `delegator`: one per claim of each
+/// readable grant made under tokens, its conditions read off the claim's
+/// last token — the one naming the holder. A claim whose token has not
+/// arrived yet is left out until it has; `to` narrows to grants toward one
+/// audience.
+async fn delegations_in(
+    store: &ConnectionMetadataStore,
+    delegator: PdnId,
+    to: Option<PdnId>,
+) -> Result<Vec<DelegatedClaim>> {
+    let mut delegations = Vec::new();
+    for issuer in store.list_grants().await? {
+        let Some((grant, _ticket)) = store.read_grant(issuer).await? else {
+            continue;
+        };
+        if to.is_some_and(|audience| audience != grant.audience) {
+            continue;
+        }
+        for (claim, chain) in by_claim(grant.claims, store.grant_chains(issuer).await?) {
+            let Some(leaf) = store.read_token(chain.last()).await? else {
+                continue;
+            };
+            let leaf = SignedUwill::from_bytes(&leaf)
+                .with_context(|| format!("undecodable capability token {}", chain.last()))?;
+            delegations.push(DelegatedClaim {
+                source: claim,
+                issuer,
+                delegator,
+                conditions: conditions_of(&leaf.capability),
+            });
+        }
+    }
+    Ok(delegations)
+}
+
+/// A token's terms, as a delegation's conditions.
+fn conditions_of(token: &UwillCapability) -> Capability {
+    Capability {
+        holders: vec![token.aud],
+        access: if token.cmd.contains(&Command::Write) {
+            AccessMode::Write
+        } else {
+            AccessMode::Read
+        },
+        expires_at: (token.exp != u64::MAX).then_some(token.exp),
+    }
+}
+
+/// Hosted `identity`'s delegations toward `to` (`PdnOp::ListDelegationsTo`):
+/// every claim it granted `to` under a token, of its own data or shared
+/// on.
+pub(crate) async fn delegations_to(
+    state: &mut State,
+    identity: PdnId,
+    to: PdnId,
+) -> Result<Vec<DelegatedClaim>> {
+    state.hosted(identity)?;
+    let Some(pair) = open_pair(state, identity, to).await? else {
+        return Ok(Vec::new());
+    };
+    delegations_in(&pair.own, identity, Some(to)).await
+}
+
+/// The delegations hosted `identity`'s peers made to it
+/// (`PdnOp::ListIncomingDelegations`), as far as their records have
+/// replicated here.
+pub(crate) async fn incoming_delegations(
+    state: &mut State,
+    identity: PdnId,
+) -> Result<Vec<DelegatedClaim>> {
+    let peers = state.hosted(identity)?.directory.list_connections().await?;
+    let mut delegations = Vec::new();
+    for peer in peers {
+        let Some(pair) = open_pair(state, identity, peer).await? else {
+            continue;
+        };
+        delegations.extend(delegations_in(&pair.peer, peer, Some(identity)).await?);
+    }
+    Ok(delegations)
+}
+
+/// Whether the delegated grant `grant` that `peer` recorded in `store`
+/// toward `identity` holds: one chain per claim, every token arrived, and
+/// each chain valid now — issued by the data issuer, extended by `peer`,
+/// granting its claim to `identity`, and each link signed by a key of its
+/// issuer under the logs `store` carries: the issuer's as `peer` relays
+/// it, and `peer`'s own mirror. A revocation in `store` voids a chain once
+/// it holds under the same logs. What the grant binder checks before
+/// importing a delegation's namespace.
+pub(crate) async fn delegation_holds(
+    store: &ConnectionMetadataStore,
+    identity: PdnId,
+    peer: PdnId,
+    grant: &ReadGrant,
+) -> Result<bool> {
+    let chains = store.grant_chains(grant.issuer).await?;
+    if grant.write || chains.len() != grant.claims.len() {
+        return Ok(false);
+    }
+    let issuer_events = store.issuer_key_events(grant.issuer).await?;
+    let peer_events = store.key_events().await?;
+    let (Some((_issuer_log, issuer_state)), Some((_peer_log, peer_state))) = (
+        verified_copy(issuer_events, grant.issuer),
+        verified_copy(peer_events, peer),
+    ) else {
+        return Ok(false);
+    };
+    let key_states = HashMap::from([(grant.issuer, issuer_state), (peer, peer_state)]);
+    let now = unix_ms(SystemTime::now());
+    for (&claim, chain) in grant.claims.iter().zip(&chains) {
+        let Some(tokens) = read_chain(store, chain).await? else {
+            return Ok(false);
+        };
+        let revoked = revocations(store, &tokens, &key_states).await?;
+        let extended_by_peer = tokens
+            .last()
+            .is_some_and(|leaf| leaf.capability.iss == peer);
+        if !extended_by_peer
+            || check_chain(
+                &tokens,
+                now,
+                &revoked,
+                &key_states,
+                grant.issuer,
+                identity,
+                claim,
+            )
+            .is_none()
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Relay the revocations `issuer` published toward hosted `identity` into
+/// every store of the identity whose delegated grant on the issuer's data
+/// is made under a revoked token — the delegates hold the same chain, and
+/// their binders drop it once the revocation reaches them. Only a
+/// revocation that holds is relayed: signed by a key of the issuer under
+/// the log `issuer_store` mirrors, and taking effect on the chain.
+pub(crate) async fn relay_revocations(
+    state: &State,
+    identity: PdnId,
+    issuer: PdnId,
+    issuer_store: &ConnectionMetadataStore,
+) -> Result<()> {
+    let revoked = issuer_store.list_revocations().await?;
+    if revoked.is_empty() {
+        return Ok(());
+    }
+    let Some((_log, issuer_state)) = verified_copy(issuer_store.key_events().await?, issuer) else {
+        return Ok(());
+    };
+    let key_states = HashMap::from([(issuer, issuer_state)]);
+    let delegate_stores: Vec<ConnectionMetadataStore> = state
+        .metadata_pairs
+        .iter()
+        .filter(|((holder, peer), _pair)| *holder == identity && *peer != issuer)
+        .map(|(_key, pair)| pair.own.clone())
+        .collect();
+    for store in delegate_stores {
+        for chain in store.grant_chains(issuer).await? {
+            let Some(tokens) = read_chain(&store, &chain).await? else {
+                continue;
+            };
+            for cid in revoked
+                .iter()
+                .filter(|cid| chain.iter().any(|held| held == *cid))
+            {
+                if store.is_revoked(cid).await? {
+                    continue;
+                }
+                let Some(record) = issuer_store.read_revocation(cid).await? else {
+                    continue;
+                };
+                if revocation_holds(&record, *cid, &key_states, &tokens) {
+                    store.publish_revocation(cid, &record).await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `record` is a signed revocation of `cid` that takes effect on
+/// `chain`: signed by a key its revoker's log — the key state in
+/// `key_states` — authorizes, and issued by an issuer of the revoked link
+/// or of one above it.
+fn revocation_holds(
+    record: &[u8],
+    cid: CapabilityCid,
+    key_states: &HashMap<PdnId, KeyState>,
+    chain: &[SignedUwill],
+) -> bool {
+    SignedRevocation::from_bytes(record).is_ok_and(|signed| {
+        signed.revocation.revoke == cid
+            && key_states
+                .get(&signed.revocation.iss)
+                .is_some_and(|revoker| signed.verify(revoker).is_ok())
+            && signed.applies_to(chain)
+    })
+}
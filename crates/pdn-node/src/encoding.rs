@@ -0,0 +1,234 @@
+//! The canonical text form of the ceremonies' payloads — what a host shows,
+//! sends by message, or renders as a QR code, so every host reads what any
+//! other host wrote.
+//!
+//! A payload is a URI: `pdn-invite:` for an [`InvitePayload`], `pdn-link:`
+//! for a [`LinkingPayload`], then the unpadded URL-safe base64 of the
+//! payload's postcard encoding followed by a four-byte checksum. The
+//! encoding leads with the payload's format version, so a payload from a
+//! newer or older runtime is recognized as such rather than misread; the
+//! checksum — a truncated blake3 over the scheme and the encoding — catches
+//! a mistyped or truncated string, and binds it to its scheme, so a linking
+//! payload never parses as an invite. It is no authentication: the
+//! payloads' own secrets and proofs are what the ceremonies check.
+//!
+//! With the `qr` feature, either payload also renders its URI as a QR code
+//! ([`QrMatrix`], or SVG markup).
+
+use std::fmt;
+use std::str::FromStr;
+
+use data_encoding::BASE64URL_NOPAD;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::linking::{LinkingPayload, LINKING_FORMAT_VERSION};
+use crate::pairing::{InvitePayload, INVITE_FORMAT_VERSION};
+
+/// The URI scheme of an invite payload's text form.
+pub const INVITE_URI_SCHEME: &str = "pdn-invite";
+
+/// The URI scheme of a linking payload's text form.
+pub const LINK_URI_SCHEME: &str = "pdn-link";
+
+/// Bytes of checksum trailing the encoded payload.
+const CHECKSUM_LEN: usize = 4;
+
+/// Why a string is not a payload of the kind asked for. The returned error
+/// of parsing either payload ([`FromStr`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PayloadParseError {
+    /// The string does not start with the payload's scheme — another kind
+    /// of payload, or not a payload at all.
+    #[error("not a {expected}: URI")]
+    WrongScheme {
+        /// The scheme the parse expected.
+        expected: &'static str,
+    },
+    /// The body is not base64, or too short to carry a checksum.
+    #[error("malformed payload encoding")]
+    Malformed,
+    /// The checksum does not match: the string was altered or cut short.
+    #[error("payload checksum mismatch")]
+    ChecksumMismatch,
+    /// The payload is intact but in a format version this runtime does not
+    /// speak.
+    #[error("unsupported payload version: {version}")]
+    UnsupportedVersion {
+        /// The version the payload carried.
+        version: u8,
+    },
+}
+
+/// A payload with a canonical text form: its scheme and the format version
+/// this runtime speaks for it.
+trait TextPayload: Serialize + DeserializeOwned {
+    const SCHEME: &'static str;
+    const VERSION: u8;
+}
+
+impl TextPayload for InvitePayload {
+    const SCHEME: &'static str = INVITE_URI_SCHEME;
+    const VERSION: u8 = INVITE_FORMAT_VERSION;
+}
+
+impl TextPayload for LinkingPayload {
+    const SCHEME: &'static str = LINK_URI_SCHEME;
+    const VERSION: u8 = LINKING_FORMAT_VERSION;
+}
+
+/// The checksum of `bytes` under `scheme`.
+fn checksum(scheme: &str, bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(scheme.as_bytes());
+    hasher.update(bytes);
+    let [a, b, c, d, ..] = *hasher.finalize().as_bytes();
+    [a, b, c, d]
+}
+
+/// Write `payload`'s URI.
+fn write_uri<P: TextPayload>(payload: &P, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut bytes = postcard::to_stdvec(payload).map_err(|_| fmt::Error)?;
+    let sum = checksum(P::SCHEME, &bytes);
+    bytes.extend_from_slice(&sum);
+    write!(f, "{}:{}", P::SCHEME, BASE64URL_NOPAD.encode(&bytes))
+}
+
+/// Parse a URI [`write_uri`] wrote. The scheme matches case-insensitively,
+/// as URI schemes do; the body is case-sensitive base64.
+fn parse_uri<P: TextPayload>(text: &str) -> Result<P, PayloadParseError> {
+    let wrong_scheme = PayloadParseError::WrongScheme {
+        expected: P::SCHEME,
+    };
+    let (scheme, body) = text.trim().split_once(':').ok_or(wrong_scheme)?;
+    if !scheme.eq_ignore_ascii_case(P::SCHEME) {
+        return Err(wrong_scheme);
+    }
+    let bytes = BASE64URL_NOPAD
+        .decode(body.as_bytes())
+        .map_err(|_| PayloadParseError::Malformed)?;
+    let split = bytes
+        .len()
+        .checked_sub(CHECKSUM_LEN)
+        .ok_or(PayloadParseError::Malformed)?;
+    let (encoded, sum) = bytes.split_at(split);
+    if checksum(P::SCHEME, encoded) != sum {
+        return Err(PayloadParseError::ChecksumMismatch);
+    }
+    let version = *encoded.first().ok_or(PayloadParseError::Malformed)?;
+    if version != P::VERSION {
+        return Err(PayloadParseError::UnsupportedVersion { version });
+    }
+    postcard::from_bytes(encoded).map_err(|_| PayloadParseError::Malformed)
+}
+
+impl fmt::Display for InvitePayload {
+    /// The payload's `pdn-invite:` URI.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_uri(self, f)
+    }
+}
+
+impl FromStr for InvitePayload {
+    type Err = PayloadParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_uri(text)
+    }
+}
+
+impl fmt::Display for LinkingPayload {
+    /// The payload's `pdn-link:` URI.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_uri(self, f)
+    }
+}
+
+impl FromStr for LinkingPayload {
+    type Err = PayloadParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_uri(text)
+    }
+}
+
+#[cfg(feature = "qr")]
+pub use qr::QrMatrix;
+
+#[cfg(feature = "qr")]
+mod qr {
+    use qrcode::render::svg;
+    use qrcode::{Color, QrCode, QrResult};
+
+    use super::{InvitePayload, LinkingPayload};
+
+    /// A payload's URI as a QR code: a square of modules, row by row,
+    /// `true` for dark — for hosts that draw the code themselves.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct QrMatrix {
+        /// Modules per side, quiet zone excluded.
+        pub width: usize,
+        /// `width * width` modules, row-major; `true` is dark.
+        pub modules: Vec<bool>,
+    }
+
+    impl QrMatrix {
+        /// Whether the module at (`x`, `y`) is dark; `false` outside the
+        /// code.
+        pub fn is_dark(&self, x: usize, y: usize) -> bool {
+            x < self.width
+                && self
+                    .modules
+                    .get(y * self.width + x)
+                    .copied()
+                    .unwrap_or(false)
+        }
+    }
+
+    fn encode(uri: &str) -> QrResult<QrCode> {
+        QrCode::new(uri.as_bytes())
+    }
+
+    fn matrix(uri: &str) -> QrResult<QrMatrix> {
+        let code = encode(uri)?;
+        Ok(QrMatrix {
+            width: code.width(),
+            modules: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    fn svg(uri: &str) -> QrResult<String> {
+        Ok(encode(uri)?.render::<svg::Color<'_>>().build())
+    }
+
+    impl InvitePayload {
+        /// The payload's URI as a QR matrix. Fails only for a payload too
+        /// large for any QR version.
+        pub fn to_qr_matrix(&self) -> QrResult<QrMatrix> {
+            matrix(&self.to_string())
+        }
+
+        /// The payload's URI as a QR code in SVG markup, quiet zone
+        /// included.
+        pub fn to_qr_svg(&self) -> QrResult<String> {
+            svg(&self.to_string())
+        }
+    }
+
+    impl LinkingPayload {
+        /// The payload's URI as a QR matrix. Fails only for a payload too
+        /// large for any QR version.
+        pub fn to_qr_matrix(&self) -> QrResult<QrMatrix> {
+            matrix(&self.to_string())
+        }
+
+        /// The payload's URI as a QR code in SVG markup, quiet zone
+        /// included.
+        pub fn to_qr_svg(&self) -> QrResult<String> {
+            svg(&self.to_string())
+        }
+    }
+}
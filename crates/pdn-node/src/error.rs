@@ -0,0 +1,180 @@
+//! [`ServiceError`]: what the runtime's services fail with — each refusal a
+//! host can act on as its own variant, so it maps failures to answers
+//! without reading messages; everything else is the store's or the
+//! network's failure.
+
+use data_layer::{CatchUpTimeout, DataLayerError, EntriesMissed, NodeError, UnknownIssuer};
+use pdn_types::PdnId;
+
+use crate::claims::{InvalidAttributeName, UnsupportedClaimVersion};
+use crate::connections::UnknownCapability;
+use crate::delegation::NotDelegable;
+use crate::identity::NoSigningKeys;
+use crate::linking::UnsupportedLinkingVersion;
+use crate::pairing::UnsupportedInviteVersion;
+use crate::runtime::UnknownIdentity;
+
+/// A dialogue the counterpart closed without an answer. Refusals are
+/// uniform by design — the responder never says why — so this names only
+/// which dialogue was refused, and by whom.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("{dialogue} refused by {by}")]
+pub struct DialogueRefused {
+    /// The refused dialogue.
+    pub dialogue: &'static str,
+    /// The side that refused it.
+    pub by: &'static str,
+}
+
+/// Error returned by the [`IdentityService`](crate::IdentityService),
+/// [`ConnectionsService`](crate::ConnectionsService),
+/// [`DataService`](crate::DataService),
+/// [`ClaimsService`](crate::ClaimsService) and
+/// [`SyncService`](crate::SyncService) operations.
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    /// The operation addressed an identity this runtime does not host.
+    #[error(transparent)]
+    NotHosted(#[from] UnknownIdentity),
+
+    /// The operation addressed a data namespace not bound on this node.
+    #[error(transparent)]
+    UnknownIssuer(#[from] UnknownIssuer),
+
+    /// A bounded catch-up wait elapsed with no sync session completed.
+    #[error(transparent)]
+    Timeout(#[from] CatchUpTimeout),
+
+    /// The counterpart of a dialogue, or a mailbox relay, refused it.
+    #[error(transparent)]
+    ProtocolRefused(#[from] DialogueRefused),
+
+    /// An invite payload of a version this runtime does not speak.
+    #[error(transparent)]
+    UnsupportedInviteVersion(#[from] UnsupportedInviteVersion),
+
+    /// A linking payload of a version this runtime does not speak.
+    #[error(transparent)]
+    UnsupportedLinkingVersion(#[from] UnsupportedLinkingVersion),
+
+    /// A stored claim of a payload version this runtime does not speak.
+    #[error(transparent)]
+    UnsupportedClaimVersion(#[from] UnsupportedClaimVersion),
+
+    /// A claim whose attribute name cannot be a path component.
+    #[error(transparent)]
+    InvalidAttributeName(#[from] InvalidAttributeName),
+
+    /// The act needs the identity's signing keys, which this device lacks.
+    #[error(transparent)]
+    NoSigningKeys(#[from] NoSigningKeys),
+
+    /// A delegation the identity holds no delegable grant for.
+    #[error(transparent)]
+    NotDelegable(#[from] NotDelegable),
+
+    /// A revocation of a token no grant of the identity is made under.
+    #[error(transparent)]
+    UnknownCapability(#[from] UnknownCapability),
+
+    /// The data backend holds no authority to write as `issued_by`.
+    #[error("local node is not authorized to write as {issued_by}")]
+    NotAuthorizedToWrite { issued_by: PdnId },
+
+    /// A payload over the data backend's maximum.
+    #[error("payload too large: {size} bytes (max {max})")]
+    PayloadTooLarge { size: usize, max: usize },
+
+    /// An entry watch fell behind; it carries on.
+    #[error(transparent)]
+    EntriesMissed(#[from] EntriesMissed),
+
+    /// The store, the network, or anything else the caller cannot act on
+    /// by kind.
+    #[error(transparent)]
+    Storage(anyhow::Error),
+}
+
+impl ServiceError {
+    /// A failure beneath a service operation — sorted as any other, so a
+    /// refusal it carries keeps its variant.
+    pub(crate) fn storage(err: impl Into<anyhow::Error>) -> Self {
+        Self::from(err.into())
+    }
+}
+
+impl From<NodeError> for ServiceError {
+    fn from(err: NodeError) -> Self {
+        match err {
+            NodeError::UnknownIssuer(refusal) => refusal.into(),
+            NodeError::Timeout(refusal) => refusal.into(),
+            NodeError::PayloadTooLarge { size, max } => Self::PayloadTooLarge { size, max },
+            NodeError::EntriesMissed(missed) => missed.into(),
+            NodeError::Storage(err) => Self::Storage(err),
+            NodeError::AlpnTaken(taken) => Self::Storage(taken.into()),
+        }
+    }
+}
+
+impl From<DataLayerError> for ServiceError {
+    fn from(err: DataLayerError) -> Self {
+        match err {
+            DataLayerError::NotAuthorizedToWrite { issued_by } => {
+                Self::NotAuthorizedToWrite { issued_by }
+            }
+            DataLayerError::UnknownIssuer { issuer } => UnknownIssuer { issuer }.into(),
+            DataLayerError::PayloadTooLarge { size, max } => Self::PayloadTooLarge { size, max },
+            DataLayerError::Lagged { missed } => EntriesMissed { missed }.into(),
+            DataLayerError::Storage(source) => Self::Storage(anyhow::Error::from_boxed(source)),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ServiceError {
+    /// Sort an internal failure: a refusal raised anywhere beneath the
+    /// service — by the runtime or by its node — keeps its variant, even
+    /// under added context; anything else is [`Storage`](Self::Storage),
+    /// its context intact.
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Self>() {
+            Ok(service) => return service,
+            Err(err) => err,
+        };
+        if let Some(&refusal) = err.downcast_ref::<UnknownIdentity>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<UnknownIssuer>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<CatchUpTimeout>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<DialogueRefused>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<UnsupportedInviteVersion>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<UnsupportedLinkingVersion>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<UnsupportedClaimVersion>() {
+            refusal.into()
+        } else if let Some(refusal) = err.downcast_ref::<InvalidAttributeName>() {
+            refusal.clone().into()
+        } else if let Some(&refusal) = err.downcast_ref::<NoSigningKeys>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<NotDelegable>() {
+            refusal.into()
+        } else if let Some(&refusal) = err.downcast_ref::<UnknownCapability>() {
+            refusal.into()
+        } else if let Some(&missed) = err.downcast_ref::<EntriesMissed>() {
+            missed.into()
+        } else if matches!(
+            err.downcast_ref::<NodeError>(),
+            Some(node) if !matches!(node, NodeError::Storage(_))
+        ) {
+            err.downcast::<NodeError>()
+                .map_or_else(Self::Storage, Self::from)
+        } else if err.is::<DataLayerError>() {
+            err.downcast::<DataLayerError>()
+                .map_or_else(Self::Storage, Self::from)
+        } else {
+            Self::Storage(err)
+        }
+    }
+}
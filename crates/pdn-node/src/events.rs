@@ -0,0 +1,183 @@
+//! The runtime's change feed: typed events for what changes under the
+//! hosted identities, so a host can push updates instead of being polled.
+//!
+//! Nothing here observes on its own account. Entry events relay data-layer's
+//! node-wide entry feed; connection and device events are the connection
+//! armer's sweep diffing each directory against what it last saw; grant
+//! events are the grant binder importing and forgetting granted namespaces.
+//! The feed is a broadcast — best-effort, in memory, from the moment of
+//! subscribing — and a subscriber that falls behind is told how much it
+//! missed ([`RuntimeEvent::Lagged`]) rather than silently skipped over.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use data_layer::PrivateMetadataStore;
+use pdn_types::{EntryEvent, NodeId, PdnId};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::runtime::State;
+
+/// How many events the feed buffers per subscriber before a slow one lags.
+pub(crate) const EVENT_FEED_CAPACITY: usize = 1024;
+
+/// One change observed by the runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuntimeEvent {
+    /// An entry changed in a data namespace bound here — by this runtime or
+    /// arrived by sync — or its payload became readable
+    /// ([`EntryEventKind`](pdn_types::EntryEventKind)).
+    EntryChanged(EntryEvent),
+    /// Hosted `identity` is now connected to `peer` — established here or
+    /// on another of its devices.
+    ConnectionAdded { identity: PdnId, peer: PdnId },
+    /// Hosted `identity` is no longer connected to `peer`.
+    ConnectionRemoved { identity: PdnId, peer: PdnId },
+    /// `peer` granted hosted `identity` access to `issuer`'s data, and the
+    /// granted namespace is bound here — also when a grant moves onto a
+    /// fresh replica, and for each grant bound again after a restart.
+    GrantReceived {
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+    },
+    /// `peer` withdrew its grant of `issuer`'s data to hosted `identity`.
+    GrantWithdrawn {
+        identity: PdnId,
+        peer: PdnId,
+        issuer: PdnId,
+    },
+    /// `device` joined hosted `identity`'s device set.
+    DeviceLinked { identity: PdnId, device: NodeId },
+    /// This subscriber fell behind and `missed` events were dropped for it;
+    /// re-read whatever it mirrors.
+    Lagged { missed: u64 },
+}
+
+impl RuntimeEvent {
+    /// The hosted identity the event concerns — `None` for entry events,
+    /// which are addressed by issuer, and for [`Lagged`](Self::Lagged).
+    pub fn identity(&self) -> Option<PdnId> {
+        match self {
+            Self::ConnectionAdded { identity, .. }
+            | Self::ConnectionRemoved { identity, .. }
+            | Self::GrantReceived { identity, .. }
+            | Self::GrantWithdrawn { identity, .. }
+            | Self::DeviceLinked { identity, .. } => Some(*identity),
+            Self::EntryChanged(_) | Self::Lagged { .. } => None,
+        }
+    }
+}
+
+/// A subscription to the runtime's change feed
+/// ([`SyncService::events`](crate::SyncService::events)).
+#[derive(Debug)]
+pub struct RuntimeEvents {
+    receiver: broadcast::Receiver<RuntimeEvent>,
+}
+
+impl RuntimeEvents {
+    pub(crate) fn new(receiver: broadcast::Receiver<RuntimeEvent>) -> Self {
+        Self { receiver }
+    }
+
+    /// The next event, waiting for one; `None` once the runtime is gone.
+    pub async fn next(&mut self) -> Option<RuntimeEvent> {
+        match self.receiver.recv().await {
+            Ok(event) => Some(event),
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                Some(RuntimeEvent::Lagged { missed })
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+/// What the connection armer last saw in one hosted identity's directory,
+/// keyed by identity in [`State::observed`] — the baseline its next sweep
+/// diffs against.
+#[derive(Debug, Default)]
+pub(crate) struct Observed {
+    connections: HashSet<PdnId>,
+    devices: HashSet<NodeId>,
+}
+
+impl Observed {
+    /// The baseline of `directory` as it stands: taken when an identity
+    /// comes to be hosted, so what it already held is not reported as new.
+    pub(crate) async fn read(directory: &PrivateMetadataStore) -> Result<Self> {
+        Ok(Self {
+            connections: directory.list_connections().await?.into_iter().collect(),
+            devices: directory.list_devices().await?.into_iter().collect(),
+        })
+    }
+}
+
+/// Publish `event` to whoever is subscribed; nobody listening is fine.
+pub(crate) fn emit(state: &State, event: RuntimeEvent) {
+    let _unobserved = state.events.send(event);
+}
+
+/// Diff `identity`'s directory against its baseline and report what
+/// changed, then make the directory as it stands the new baseline. A
+/// directory that cannot be read is left for the next sweep.
+pub(crate) async fn observe_directory(state: &mut State, identity: PdnId) {
+    let Ok(hosted) = state.hosted(identity) else {
+        return;
+    };
+    let Ok(now) = Observed::read(&hosted.directory).await else {
+        return;
+    };
+    let before = state.observed.remove(&identity).unwrap_or_default();
+    for peer in now.connections.difference(&before.connections) {
+        emit(
+            state,
+            RuntimeEvent::ConnectionAdded {
+                identity,
+                peer: *peer,
+            },
+        );
+    }
+    for peer in before.connections.difference(&now.connections) {
+        emit(
+            state,
+            RuntimeEvent::ConnectionRemoved {
+                identity,
+                peer: *peer,
+            },
+        );
+    }
+    for device in now.devices.difference(&before.devices) {
+        emit(
+            state,
+            RuntimeEvent::DeviceLinked {
+                identity,
+                device: *device,
+            },
+        );
+    }
+    state.observed.insert(identity, now);
+}
+
+/// Relay data-layer's entry feed into the runtime's, until the node's feed
+/// closes at shutdown. Holds neither the node nor the state, so it keeps
+/// neither alive.
+pub(crate) fn spawn_entry_relay(
+    mut entries: broadcast::Receiver<EntryEvent>,
+    events: broadcast::Sender<RuntimeEvent>,
+) {
+    let _detached = tokio::spawn(async move {
+        loop {
+            let event = match entries.recv().await {
+                Ok(entry) => RuntimeEvent::EntryChanged(entry),
+                // Entry events were lost before reaching the runtime's
+                // feed; every subscriber missed them.
+                Err(broadcast::error::RecvError::Lagged(missed)) => RuntimeEvent::Lagged { missed },
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            let _unobserved = events.send(event);
+        }
+    });
+}
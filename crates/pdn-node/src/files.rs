@@ -0,0 +1,34 @@
+//! The runtime's own files under a storage root — the keystore and the
+//! hosted-identity manifest — and the one way they are written.
+
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Replace the file at `path` with `bytes`, whole and owner-only: staged
+/// beside it, synced, and renamed into place, so a crash mid-write leaves
+/// the previous file rather than a torn one — the keystore holds the
+/// device's secret keys. A staged file left by an earlier crash is removed
+/// before the new one is created, since the mode is set on create alone.
+pub(crate) fn replace_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let staged = path.with_extension("staged");
+    match std::fs::remove_file(&staged) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("cannot remove {}", staged.display()));
+        }
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&staged)
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::io::Write::write_all(&mut file, bytes)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("cannot write {}", staged.display()))?;
+    std::fs::rename(&staged, path).with_context(|| format!("cannot write {}", path.display()))
+}
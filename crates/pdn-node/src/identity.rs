@@ -1,58 +1,177 @@
 //! The identity service: create an identity on its first device, link every
 //! further device over the linking dialogue.
 
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::Result;
-use data_layer::{AddrInfoOptions, PrivateMetadataStore, ShareMode};
-use pdn_types::PdnId;
+use anyhow::{anyhow, ensure, Context, Result};
+use data_layer::{
+    unix_ms, AddrInfoOptions, ConnectionMetadataStore, DeviceRecord, NodeError,
+    PrivateMetadataStore, ShareMode,
+};
+use futures_lite::{Stream, StreamExt};
+use pdn_layer::kel::{commit_to, KeyEventLog, KeyPair, KeyState, SignedKeyEvent};
+use pdn_types::{NodeId, NonEmpty, OperationalKey, PdnId, PdnIdentityProof};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 
+use crate::connections::open_pair;
+use crate::delegation::reissue_grants;
+use crate::error::ServiceError;
+use crate::events::Observed;
+use crate::keystore::IdentityKeys;
 use crate::linking::{
     link_via_dialogue, LinkingPayload, UnsupportedLinkingVersion, LINKING_FORMAT_VERSION,
 };
 use crate::pairing::DEFAULT_INVITE_LIFETIME;
-use crate::runtime::{HostedIdentity, Runtime};
+use crate::runtime::{HostedIdentity, Runtime, State};
 
 /// The private-metadata directory kind under which an identity's own
 /// data-namespace ticket is published at creation — the flat bootstrap
 /// model's durable record. Nothing in the linking critical path reads it:
-/// the dialogue's reply hands the bootstrap tickets over directly.
-const DATA_TICKET_KIND: &str = "data";
+/// the dialogue's reply hands the bootstrap tickets over directly; a
+/// restart on a storage root reads it to reopen the data namespace.
+pub(crate) const DATA_TICKET_KIND: &str = "data";
+
+/// How old a device's recorded sighting of another gets before a later
+/// one replaces it, in ms: the grain of a last sync reported across a
+/// restart.
+const SIGHTING_INTERVAL_MS: u64 = 60_000;
+
+/// A signing act addressed an identity whose keys this device does not
+/// hold: only the device that incepted an identity holds its key pair.
+/// Carried by [`ServiceError::NoSigningKeys`] from
+/// [`IdentityService::rotate_key`] — and, on a device holding not even a
+/// device key, from [`IdentityService::linking_invite`] and the
+/// connections service's `invite` and `establish`, whose dialogue proves
+/// the identity.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("this device holds no signing keys for {identity}")]
+pub struct NoSigningKeys {
+    /// The identity the act addressed.
+    pub identity: PdnId,
+}
+
+/// One device of an identity, as [`IdentityService::devices`] reports it:
+/// the directory's device record, and what this runtime observed of the
+/// device. Descriptive only — which devices are the identity's is decided
+/// by the records' presence, never by these fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// The device's node id.
+    pub node: NodeId,
+    /// The human label the device chose when it was linked, if any.
+    pub label: Option<String>,
+    /// When the device joined the identity, unix ms — `None` while its
+    /// record's payload has not arrived, and for a record written before
+    /// devices were described.
+    pub linked_at: Option<u64>,
+    /// The operational key the device signs with: the identity's current
+    /// key on the device holding the identity's keys, the device key the
+    /// identity's log authorized on a linked one.
+    pub key: Option<OperationalKey>,
+    /// When this device last finished a successful sync with the device,
+    /// unix ms — `None` for this device itself, and for one it never synced
+    /// with. Recorded in the identity's directory at a coarser grain, so a
+    /// restart on a storage root reports the last recorded sighting until
+    /// the next sync.
+    pub last_sync: Option<u64>,
+}
 
 /// Creating and linking identities on a runtime. The production
-/// implementation mints placeholder identifiers with no key material
-/// behind them.
+/// implementation incepts every identity with a fresh ed25519 key
+/// ([`pdn_layer::kel`]): the identity *is* its inception key, and its key
+/// event log lives in its directory, where every linked device replicates
+/// it.
 #[allow(async_fn_in_trait)]
 pub trait IdentityService {
-    /// Create an identity on this runtime — its first device: mint a fresh
-    /// placeholder [`PdnId`] (a random identifier, no key material) and
+    /// Create an identity on this runtime — its first device: generate the
+    /// inception key and the pre-rotated next key (held in this device's
+    /// keystore), derive the [`PdnId`] from the inception event, and
     /// provision its store set — the private-metadata directory with this
-    /// device registered, and the data namespace, whose ticket is published
-    /// in the directory under the `data` kind.
-    async fn create(&self) -> Result<PdnId>;
+    /// device registered and the key event log recorded, and the data
+    /// namespace, whose ticket is published in the directory under the
+    /// `data` kind.
+    async fn create(&self) -> Result<PdnId, ServiceError>;
+
+    /// The key state of hosted `identity`: its key event log as this
+    /// runtime's directory replica holds it, verified from inception — a
+    /// fork settled as every replica settles it. Fails while a linked
+    /// device's replica has not yet received the log's first event.
+    async fn key_state(&self, identity: PdnId) -> Result<KeyState, ServiceError>;
+
+    /// Rotate hosted `identity`'s key (`PdnOp::RotateKey`): the pre-rotated
+    /// next key becomes current, a fresh next key is committed to, and the
+    /// rotation event is appended to the key event log in the directory,
+    /// from where it replicates to every linked device. With `compromised`
+    /// this is a recovery rotation: the replaced key is marked compromised,
+    /// and verifiers reject whatever it signed, at any anchor
+    /// ([`KeyState::accepts`]); the grants made under its tokens are
+    /// republished under the tokens signed again. Returns the new current
+    /// key. A rotation that dropped out of the log, losing a fork to a
+    /// device removal appended while apart, is made again rather than a
+    /// new one. Refused with [`NoSigningKeys`] on a device that does not
+    /// hold the identity's keys.
+    async fn rotate_key(
+        &self,
+        identity: PdnId,
+        compromised: bool,
+    ) -> Result<OperationalKey, ServiceError>;
+
+    /// The devices of hosted `identity` (`PdnOp::ActiveDevices`), as this
+    /// runtime's directory replica lists them — this device among them —
+    /// each described by its record and by the last successful sync this
+    /// runtime saw with it.
+    async fn devices(&self, identity: PdnId) -> Result<NonEmpty<DeviceInfo>, ServiceError>;
+
+    /// Label this device within hosted `identity`, replacing the label it
+    /// was linked with; replicates to the identity's other devices.
+    async fn set_device_label(&self, identity: PdnId, label: &str) -> Result<(), ServiceError>;
+
+    /// Revoke `device` from hosted `identity` (`PdnOp::RevokeDevice`): its
+    /// device record is tombstoned and a revocation record written, it is
+    /// withdrawn from the device set every connection publishes, and the
+    /// directory and data namespace it could write are re-keyed onto fresh
+    /// replicas, which the identity's remaining devices move to on their
+    /// own. From then on the revoked device is refused by every device that
+    /// has seen the revocation and receives no new entries. Refuses this
+    /// device itself and a node that is not a device of the identity; see
+    /// the `revocation` module for what re-keying leaves within reach.
+    async fn revoke_device(&self, identity: PdnId, device: NodeId) -> Result<(), ServiceError>;
 
     /// Mint a linking invite for hosted `identity`: a one-time secret with
     /// a short lifetime (a default unless `lifetime` overrides it), pending
     /// on this runtime, and the self-contained payload the new device
     /// consumes. The payload carries no bearer material — no tickets and no
-    /// identity proof; the bootstrap tickets ride the dialogue's reply.
+    /// identity proof; the bootstrap tickets ride the dialogue's reply,
+    /// with the key event log authorizing the new device's key. Refused
+    /// with [`NoSigningKeys`] on a device holding no key to authorize it
+    /// with — one linked before device keys existed.
     async fn linking_invite(
         &self,
         identity: PdnId,
         lifetime: Option<Duration>,
-    ) -> Result<LinkingPayload>;
+    ) -> Result<LinkingPayload, ServiceError>;
 
     /// Link this runtime as a device of the payload's identity, one
     /// explicit act per identity: dial the payload's address on the linking
-    /// ALPN, present the secret, and import the directory and data
-    /// namespace from the reply. Does not return success until the imported
-    /// directory has completed one successful sync exchange — bounded by
-    /// `timeout`, after which the attempt fails and leaves nothing behind
+    /// ALPN, present the secret and a device key generated for the
+    /// identity, and — once the reply's key event log authorizes that key
+    /// under the payload's identity — import the directory and data
+    /// namespace from the reply. `label` names this device to the
+    /// identity's other devices ([`devices`](Self::devices)). Does not
+    /// return success until the imported directory has completed one
+    /// successful sync exchange — bounded by `timeout`, after which the
+    /// attempt fails ([`ServiceError::Timeout`]) and leaves nothing behind
     /// on this runtime. A payload version this runtime does not speak
     /// ([`UnsupportedLinkingVersion`]) and an identity it already hosts are
     /// refused before dialing.
-    async fn link(&self, payload: LinkingPayload, timeout: Duration) -> Result<()>;
+    async fn link(
+        &self,
+        payload: LinkingPayload,
+        label: Option<&str>,
+        timeout: Duration,
+    ) -> Result<(), ServiceError>;
 }
 
 /// The production [`IdentityService`], backed by the runtime's `data-layer`
@@ -69,14 +188,31 @@ impl<'rt> RuntimeIdentityService<'rt> {
 }
 
 impl IdentityService for RuntimeIdentityService<'_> {
-    async fn create(&self) -> Result<PdnId> {
-        let identity = PdnId::from_bytes(rand::random());
+    async fn create(&self) -> Result<PdnId, ServiceError> {
+        // The inception: the identity is the first key's public half, and
+        // the next key is committed to before the first one signs anything.
+        let keys = IdentityKeys {
+            current: KeyPair::from_seed(rand::random()),
+            next: KeyPair::from_seed(rand::random()),
+        };
+        let log = KeyEventLog::incept(&keys.current, &keys.next.public());
+        let identity = log.verify().map_err(ServiceError::storage)?.pdn_id();
         let mut state = self.runtime.state.lock().await;
-        // The directory, with this device registered. Registration is
-        // immediate — the store is fresh, there is no first sync for the
-        // local write to race.
+        // The directory, with this device registered and the log recorded.
+        // Registration is immediate — the store is fresh, there is no first
+        // sync for the local write to race.
         let directory = PrivateMetadataStore::create(&state.node).await?;
-        directory.add_device(state.node.node_id()).await?;
+        let record = DeviceRecord {
+            key: Some(keys.current.public()),
+            ..DeviceRecord::linked_now(None)
+        };
+        directory.put_device(state.node.node_id(), &record).await?;
+        for event in log.events() {
+            append_key_event(&directory, event).await?;
+        }
+        // The secrets are held before the identity is: an identity hosted
+        // here without its keys could never sign again.
+        state.keys.insert(identity, keys)?;
         // The data namespace, its ticket published as the directory's
         // durable record (the reply of a later linking hands over a fresh
         // one instead of reading this entry).
@@ -96,27 +232,157 @@ impl IdentityService for RuntimeIdentityService<'_> {
         // before the handle moves into the hosted set; connections this
         // identity establishes or learns of by replication then register
         // as their records arrive, not as a side effect of the first grant
-        // read.
+        // read. The sync observer's subscription likewise, so the first
+        // sync of a device linked later is recorded.
         let changes = directory.changes().await?;
+        let syncs = directory.syncs().await?;
         state.node.host_identity(identity, &directory)?;
+        if let Some(manifest) = &state.manifest {
+            manifest.record(identity, directory.namespace())?;
+        }
+        let namespace = directory.namespace();
+        let observed = Observed::read(&directory).await?;
+        state.observed.insert(identity, observed);
         state
             .identities
             .insert(identity, HostedIdentity { directory });
         crate::connections::spawn_connection_armer(
             Arc::downgrade(&self.runtime.state),
             identity,
+            namespace,
             changes,
         );
+        spawn_sync_observer(Arc::downgrade(&self.runtime.state), identity, syncs);
         Ok(identity)
     }
 
+    async fn key_state(&self, identity: PdnId) -> Result<KeyState, ServiceError> {
+        let state = self.runtime.state.lock().await;
+        let directory = &state.hosted(identity)?.directory;
+        let (_log, key_state) = verified_key_event_log(directory, identity).await?;
+        Ok(key_state)
+    }
+
+    async fn rotate_key(
+        &self,
+        identity: PdnId,
+        compromised: bool,
+    ) -> Result<OperationalKey, ServiceError> {
+        let mut state = self.runtime.state.lock().await;
+        let directory = &state.hosted(identity)?.directory;
+        let (mut log, key_state) = verified_key_event_log(directory, identity).await?;
+        let held = state
+            .keys
+            .get(identity)
+            .ok_or(NoSigningKeys { identity })?
+            .clone();
+        let rotated = if held.current.public() == key_state.current {
+            IdentityKeys {
+                current: held.next.clone(),
+                next: KeyPair::from_seed(rand::random()),
+            }
+        } else if commit_to(&held.current.public()) == key_state.next {
+            // This device's last rotation lost a fork to a device removal
+            // and dropped out of the log, which still commits to the key
+            // it rotated to: the same rotation is made again.
+            held.clone()
+        } else {
+            return Err(ServiceError::storage(anyhow!(
+                "this device's keys for {identity} are not the log's current keys"
+            )));
+        };
+        let new_state = log
+            .rotate(&rotated.current, &rotated.next.public(), compromised)
+            .map_err(ServiceError::storage)?;
+        let event = log
+            .events()
+            .last()
+            .context("a rotated log holds its rotation")?
+            .clone();
+        // The secrets are durable before the log commits to them; if the
+        // append fails, the device goes back to the keys the log still
+        // names.
+        state.keys.insert(identity, rotated)?;
+        let directory = &state.hosted(identity)?.directory;
+        if let Err(err) = append_key_event(directory, &event).await {
+            let _best_effort = state.keys.insert(identity, held);
+            return Err(err.into());
+        }
+        // The device's record names the key it now signs with. The log is
+        // the authority; the record only describes.
+        let own = state.node.node_id();
+        let record = DeviceRecord {
+            key: Some(new_state.current),
+            ..directory.device_record(own).await?.unwrap_or_default()
+        };
+        directory.put_device(own, &record).await?;
+        if compromised {
+            // What the retired key signed is void at every anchor now: the
+            // grants made under it are republished re-signed.
+            for peer in directory.list_connections().await? {
+                if let Some(pair) = open_pair(&mut state, identity, peer).await? {
+                    reissue_grants(&state, identity, &pair.own, None).await?;
+                }
+            }
+        }
+        Ok(new_state.current)
+    }
+
+    async fn devices(&self, identity: PdnId) -> Result<NonEmpty<DeviceInfo>, ServiceError> {
+        let state = self.runtime.state.lock().await;
+        let directory = &state.hosted(identity)?.directory;
+        let own = state.node.node_id();
+        let mut devices = Vec::new();
+        for node in directory.list_devices().await? {
+            let record = directory.device_record(node).await?.unwrap_or_default();
+            let last_sync = if node == own {
+                None
+            } else {
+                let seen = state.last_sync.get(&node).copied().map(unix_ms);
+                seen.max(directory.sighting(own, node).await?)
+            };
+            devices.push(DeviceInfo {
+                node,
+                label: record.label,
+                linked_at: record.linked_at,
+                key: record.key,
+                last_sync,
+            });
+        }
+        let devices = NonEmpty::from_vec(devices)
+            .with_context(|| format!("the directory of {identity} lists no devices yet"))?;
+        Ok(devices)
+    }
+
+    async fn set_device_label(&self, identity: PdnId, label: &str) -> Result<(), ServiceError> {
+        let state = self.runtime.state.lock().await;
+        let directory = &state.hosted(identity)?.directory;
+        let own = state.node.node_id();
+        let record = DeviceRecord {
+            label: Some(label.to_owned()),
+            ..directory.device_record(own).await?.unwrap_or_default()
+        };
+        directory.put_device(own, &record).await?;
+        Ok(())
+    }
+
+    async fn revoke_device(&self, identity: PdnId, device: NodeId) -> Result<(), ServiceError> {
+        crate::revocation::revoke_device(&self.runtime.state, identity, device).await?;
+        Ok(())
+    }
+
     async fn linking_invite(
         &self,
         identity: PdnId,
         lifetime: Option<Duration>,
-    ) -> Result<LinkingPayload> {
+    ) -> Result<LinkingPayload, ServiceError> {
         let mut state = self.runtime.state.lock().await;
         state.hosted(identity)?;
+        // The linking authorizes the newcomer's key with this device's; a
+        // device without one refuses here rather than at every dial.
+        if state.keys.device_signer(identity).is_none() {
+            return Err(NoSigningKeys { identity }.into());
+        }
         let secret = state.pending_linking_invites.mint(
             identity,
             lifetime.unwrap_or(DEFAULT_INVITE_LIFETIME),
@@ -130,7 +396,12 @@ impl IdentityService for RuntimeIdentityService<'_> {
         })
     }
 
-    async fn link(&self, payload: LinkingPayload, timeout: Duration) -> Result<()> {
+    async fn link(
+        &self,
+        payload: LinkingPayload,
+        label: Option<&str>,
+        timeout: Duration,
+    ) -> Result<(), ServiceError> {
         // The version refusal precedes the dial; the already-hosted refusal
         // runs inside the dialogue, also before dialing. The dialogue takes
         // the runtime lock per phase and never holds it across the network
@@ -141,6 +412,197 @@ impl IdentityService for RuntimeIdentityService<'_> {
             }
             .into());
         }
-        link_via_dialogue(&self.runtime.state, &payload, timeout).await
+        link_via_dialogue(&self.runtime.state, &payload, label, timeout).await?;
+        Ok(())
+    }
+}
+
+/// Keep [`State::last_sync`] current from hosted `identity`'s directory's
+/// successful sync sessions — only the identity's own devices hold the
+/// directory, so each session is a sighting of one of them — and record
+/// the sightings in the directory, so they survive a restart. Holds the
+/// state weakly, like the connection armer, and ends with the directory's
+/// event stream.
+pub(crate) fn spawn_sync_observer(
+    state: Weak<Mutex<State>>,
+    identity: PdnId,
+    syncs: impl Stream<Item = Result<(NodeId, SystemTime), NodeError>> + Send + Unpin + 'static,
+) {
+    let mut syncs = syncs;
+    let _detached = tokio::spawn(async move {
+        while let Some(Ok((device, finished))) = syncs.next().await {
+            let Some(strong) = state.upgrade() else {
+                return;
+            };
+            let mut guard = strong.lock().await;
+            let seen = guard.last_sync.entry(device).or_insert(finished);
+            *seen = (*seen).max(finished);
+            let _recorded_or_next_sighting =
+                record_sighting(&guard, identity, device, finished).await;
+        }
+    });
+}
+
+/// Record this device's sighting of `device` at `finished` in hosted
+/// `identity`'s directory — once the recorded one is
+/// [`SIGHTING_INTERVAL_MS`] old. The record syncs like any other, and that
+/// sync is a sighting in turn: recording every one would keep the devices
+/// writing to each other for good.
+async fn record_sighting(
+    state: &State,
+    identity: PdnId,
+    device: NodeId,
+    finished: SystemTime,
+) -> Result<()> {
+    let directory = &state.hosted(identity)?.directory;
+    let own = state.node.node_id();
+    let at = unix_ms(finished);
+    let recorded = directory.sighting(own, device).await?;
+    if recorded.is_some_and(|recorded| at < recorded.saturating_add(SIGHTING_INTERVAL_MS)) {
+        return Ok(());
+    }
+    directory.put_sighting(own, device, at).await?;
+    Ok(())
+}
+
+/// Record `event` in `directory`'s key event log, at its sequence number.
+/// JSON, like the other structured records the stores carry.
+///
+/// Appends on one device are serialized by the runtime's state lock, held
+/// from reading the log to this write. Devices appending while apart each
+/// keep their record at the position, and every reading resolves the fork
+/// alike ([`decode_key_events`]): the losing event drops out of the log.
+pub(crate) async fn append_key_event(
+    directory: &PrivateMetadataStore,
+    event: &SignedKeyEvent,
+) -> Result<()> {
+    directory
+        .put_key_event(event.event.sn, &serde_json::to_vec(event)?)
+        .await?;
+    Ok(())
+}
+
+/// The key event log of `identity` in `directory` with the key state it
+/// verifies to — refused while no inception of `identity` is readable
+/// there. Events of another identity a directory writer recorded are no
+/// part of it.
+pub(crate) async fn verified_key_event_log(
+    directory: &PrivateMetadataStore,
+    identity: PdnId,
+) -> Result<(KeyEventLog, KeyState)> {
+    let log = read_key_event_log(directory, identity).await?;
+    let key_state = log.verify()?;
+    Ok((log, key_state))
+}
+
+/// This device's evidence that `identity` issued `statement`: the
+/// identity's verified key event log and a proof by this device's signer
+/// — the current key where this device incepted the identity, else the
+/// device key its linking added — what a peer that holds no copy of the
+/// log needs to check the proof. Refused with [`NoSigningKeys`] on a
+/// device holding neither.
+pub(crate) async fn prove_as(
+    state: &State,
+    identity: PdnId,
+    statement: &[u8],
+) -> Result<(KeyEventLog, PdnIdentityProof)> {
+    let directory = &state.hosted(identity)?.directory;
+    let (log, key_state) = verified_key_event_log(directory, identity).await?;
+    let signer = state
+        .keys
+        .device_signer(identity)
+        .ok_or(NoSigningKeys { identity })?;
+    ensure!(
+        key_state.authorizes_devices(&signer.public()),
+        "the key event log of {identity} no longer authorizes this device's key"
+    );
+    Ok((log, signer.prove(statement)))
+}
+
+/// The key event log of `identity` that `directory` holds, as far as its
+/// replica has received it — verified event by event, possibly empty.
+pub(crate) async fn read_key_event_log(
+    directory: &PrivateMetadataStore,
+    identity: PdnId,
+) -> Result<KeyEventLog> {
+    Ok(decode_key_events(directory.key_events().await?, identity))
+}
+
+/// The log of `identity` in the encoded `records` at each sequence number —
+/// the directory's, or its mirror in a connection metadata store. Records
+/// that do not decode are skipped, and the rest resolved as
+/// [`KeyEventLog::resolve`] does: what extends the log validly, one event
+/// per position. Every writer of these stores can write garbage, a revoked
+/// device holding an old ticket included, and none of it stops the log.
+pub(crate) fn decode_key_events(records: Vec<Vec<Vec<u8>>>, identity: PdnId) -> KeyEventLog {
+    let candidates = records.into_iter().map(|recorded| {
+        recorded
+            .iter()
+            .filter_map(|bytes| serde_json::from_slice::<SignedKeyEvent>(bytes).ok())
+            .collect()
+    });
+    KeyEventLog::resolve(identity, candidates)
+}
+
+/// The log of `identity` in the encoded `records` a connection metadata
+/// store carries — its mirror, or a relayed copy — with the key state it
+/// verifies to: `None` while no event of it has arrived, which vouches for
+/// no key.
+pub(crate) fn verified_copy(
+    records: Vec<Vec<Vec<u8>>>,
+    identity: PdnId,
+) -> Option<(KeyEventLog, KeyState)> {
+    let log = decode_key_events(records, identity);
+    let key_state = log.verify().ok()?;
+    Some((log, key_state))
+}
+
+/// The events of `log` missing from `held`, a store's records at each
+/// sequence number: at a position the store has no record of, or one
+/// where only another record — a fork's losing side — is held.
+fn missing_events<'log>(
+    log: &'log KeyEventLog,
+    held: Vec<Vec<Vec<u8>>>,
+) -> Result<Vec<(&'log SignedKeyEvent, Vec<u8>)>> {
+    let mut held = held.into_iter();
+    let mut missing = Vec::new();
+    for event in log.events() {
+        let bytes = serde_json::to_vec(event)?;
+        if !held
+            .next()
+            .is_some_and(|recorded| recorded.contains(&bytes))
+        {
+            missing.push((event, bytes));
+        }
+    }
+    Ok(missing)
+}
+
+/// Mirror `log` into `store`, one of the identity's own connection metadata
+/// stores: the events the store does not hold yet, encoded as in the
+/// directory — so the counterparty checks the identity's proofs against
+/// the log its devices keep.
+pub(crate) async fn mirror_key_events(
+    log: &KeyEventLog,
+    store: &ConnectionMetadataStore,
+) -> Result<()> {
+    for (event, bytes) in missing_events(log, store.key_events().await?)? {
+        store.put_key_event(event.event.sn, &bytes).await?;
+    }
+    Ok(())
+}
+
+/// Relay `issuer`'s `log` into `store`, toward a delegate of the issuer's
+/// data: the events the store does not hold yet.
+pub(crate) async fn relay_key_events(
+    issuer: PdnId,
+    log: &KeyEventLog,
+    store: &ConnectionMetadataStore,
+) -> Result<()> {
+    for (event, bytes) in missing_events(log, store.issuer_key_events(issuer).await?)? {
+        store
+            .put_issuer_key_event(issuer, event.event.sn, &bytes)
+            .await?;
     }
+    Ok(())
 }
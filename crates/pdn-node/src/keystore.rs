@@ -0,0 +1,170 @@
+//! The device keystore: the secret halves of the identity keys this device
+//! holds — for each identity it incepted, the current signing key and the
+//! pre-rotated next key — and, for each identity it joined by linking, the
+//! device key the identity's log authorized.
+//!
+//! Device-local by construction. Nothing here ever enters a replica: the
+//! key event log in the identity's directory carries public keys and
+//! commitments only, and the seeds stay on the device that minted them —
+//! in memory on an in-memory runtime, in one owner-only file under the
+//! storage root otherwise.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use pdn_layer::kel::KeyPair;
+use pdn_types::PdnId;
+use serde::{Deserialize, Serialize};
+
+use crate::files::replace_file;
+
+/// The keystore's file under the storage root.
+const KEYSTORE_FILE: &str = "keys";
+
+/// One identity's key pair set on this device.
+#[derive(Debug, Clone)]
+pub(crate) struct IdentityKeys {
+    /// The key the identity's log currently names.
+    pub(crate) current: KeyPair,
+    /// The key the log's last event commits to as `current`'s successor.
+    pub(crate) next: KeyPair,
+}
+
+/// One stored record: the identity and its two seeds.
+#[derive(Serialize, Deserialize)]
+struct StoredKeys {
+    identity: PdnId,
+    current: [u8; 32],
+    next: [u8; 32],
+}
+
+/// One stored device key: the identity it was authorized under and its
+/// seed.
+#[derive(Serialize, Deserialize)]
+struct StoredDeviceKey {
+    identity: PdnId,
+    seed: [u8; 32],
+}
+
+/// The keystore file's contents.
+#[derive(Default, Serialize, Deserialize)]
+struct Stored {
+    identities: Vec<StoredKeys>,
+    devices: Vec<StoredDeviceKey>,
+}
+
+/// The device's identity keys and device keys, keyed by identity.
+#[derive(Debug, Default)]
+pub(crate) struct KeyStore {
+    /// Where the keys persist; `None` on an in-memory runtime.
+    path: Option<PathBuf>,
+    keys: HashMap<PdnId, IdentityKeys>,
+    devices: HashMap<PdnId, KeyPair>,
+}
+
+impl KeyStore {
+    /// The keystore of the storage root at `root` as last written, or an
+    /// empty in-memory one without a root.
+    pub(crate) fn load(root: Option<&Path>) -> Result<Self> {
+        let Some(root) = root else {
+            return Ok(Self::default());
+        };
+        let path = root.join(KEYSTORE_FILE);
+        let stored: Stored = match std::fs::read(&path) {
+            Ok(bytes) => postcard::from_bytes(&bytes)
+                .with_context(|| format!("unreadable keystore {}", path.display()))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Stored::default(),
+            Err(err) => return Err(err).with_context(|| format!("cannot read {}", path.display())),
+        };
+        let keys = stored
+            .identities
+            .into_iter()
+            .map(|record| {
+                (
+                    record.identity,
+                    IdentityKeys {
+                        current: KeyPair::from_seed(record.current),
+                        next: KeyPair::from_seed(record.next),
+                    },
+                )
+            })
+            .collect();
+        let devices = stored
+            .devices
+            .into_iter()
+            .map(|record| (record.identity, KeyPair::from_seed(record.seed)))
+            .collect();
+        Ok(Self {
+            path: Some(path),
+            keys,
+            devices,
+        })
+    }
+
+    /// The keys this device holds for `identity`, if any.
+    pub(crate) fn get(&self, identity: PdnId) -> Option<&IdentityKeys> {
+        self.keys.get(&identity)
+    }
+
+    /// The key this device signs for `identity`'s devices with: the
+    /// identity's current key where this device incepted it, else the
+    /// device key its linking authorized — if any.
+    pub(crate) fn device_signer(&self, identity: PdnId) -> Option<&KeyPair> {
+        self.get(identity)
+            .map(|keys| &keys.current)
+            .or_else(|| self.devices.get(&identity))
+    }
+
+    /// Hold `keys` for `identity`, replacing what was held — written
+    /// through to the file before the in-memory set changes, so a failed
+    /// write leaves both as they were.
+    pub(crate) fn insert(&mut self, identity: PdnId, keys: IdentityKeys) -> Result<()> {
+        let mut held = self.keys.clone();
+        held.insert(identity, keys);
+        self.write_through(&held, &self.devices)?;
+        self.keys = held;
+        Ok(())
+    }
+
+    /// Hold `key` as this device's key under `identity`, replacing what was
+    /// held — written through like [`insert`](Self::insert).
+    pub(crate) fn insert_device(&mut self, identity: PdnId, key: KeyPair) -> Result<()> {
+        let mut held = self.devices.clone();
+        held.insert(identity, key);
+        self.write_through(&self.keys, &held)?;
+        self.devices = held;
+        Ok(())
+    }
+
+    /// Persist `keys` and `devices` as the whole keystore, when it has a
+    /// file.
+    fn write_through(
+        &self,
+        keys: &HashMap<PdnId, IdentityKeys>,
+        devices: &HashMap<PdnId, KeyPair>,
+    ) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let stored = Stored {
+            identities: keys
+                .iter()
+                .map(|(identity, keys)| StoredKeys {
+                    identity: *identity,
+                    current: keys.current.seed(),
+                    next: keys.next.seed(),
+                })
+                .collect(),
+            devices: devices
+                .iter()
+                .map(|(identity, key)| StoredDeviceKey {
+                    identity: *identity,
+                    seed: key.seed(),
+                })
+                .collect(),
+        };
+        replace_file(path, &postcard::to_stdvec(&stored)?)
+    }
+}
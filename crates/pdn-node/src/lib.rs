@@ -1,5 +1,5 @@
-//! The embeddable node runtime: identity, connections, data, and sync
-//! services as thin glue over `data-layer`.
+//! The embeddable node runtime: identity, connections, data, claims and
+//! sync services as thin glue over `data-layer`.
 //!
 //! Each [`Runtime`] is one running node — a host embeds one, in-process
 //! tests embed several to stand up several nodes. One runtime hosts any
@@ -8,6 +8,15 @@
 //! ([`linking`], ADR-0012), and identities become connected by the
 //! establishment dialogue ([`pairing`], ADR-0011) — the runtime's two
 //! protocols, riding the data-layer assembly slot on the node's endpoint.
+//! Establishment can also run through a store-and-forward relay
+//! ([`mailbox`]) when the two devices are not online together.
+//! A device leaves an identity by revocation, which re-keys the stores it
+//! held; the remaining devices follow over a third, internal dialogue.
+//! A grant made under `UWill` tokens can be shared on by its grantee
+//! ([`delegate`]), who re-serves the delegated claims from its replica.
+//! What changes under the hosted identities is published as a feed of
+//! typed [`events`]. The services fail with [`ServiceError`], each
+//! refusal a host can act on as its own variant.
 //!
 //! The runtime adds no sync or authorization mechanics of its own: every
 //! store operation delegates to a `data-layer` primitive, and session
@@ -17,25 +26,55 @@
 //!
 //! [`create`]: IdentityService::create
 //! [`link`]: IdentityService::link
+//! [`delegate`]: ConnectionsService::delegate
 
+pub mod claims;
 pub mod connections;
 pub mod data;
+mod delegation;
+pub mod encoding;
+mod error;
+pub mod events;
+mod files;
 pub mod identity;
+mod keystore;
 pub mod linking;
+pub mod mailbox;
+mod manifest;
 pub mod pairing;
+mod revocation;
 pub mod runtime;
 pub mod sync;
 
+pub use claims::{
+    claim_path, ClaimsService, InvalidAttributeName, RuntimeClaimsService, UnsupportedClaimVersion,
+    CLAIM_FORMAT_VERSION,
+};
 pub use connections::{
-    ConnectionsService, DelegationUnsupported, PeerGrant, RuntimeConnectionsService,
+    ConnectionsService, PeerGrant, RuntimeConnectionsService, UnknownCapability,
 };
-pub use data::{DataService, RuntimeDataService};
-pub use identity::{IdentityService, RuntimeIdentityService};
+pub use data::{DataService, DataWatch, RuntimeDataService, RuntimeEntries};
+pub use delegation::NotDelegable;
+#[cfg(feature = "qr")]
+pub use encoding::QrMatrix;
+pub use encoding::{PayloadParseError, INVITE_URI_SCHEME, LINK_URI_SCHEME};
+pub use error::{DialogueRefused, ServiceError};
+pub use events::{RuntimeEvent, RuntimeEvents};
+pub use identity::{DeviceInfo, IdentityService, NoSigningKeys, RuntimeIdentityService};
 pub use linking::{LinkingPayload, UnsupportedLinkingVersion, LINKING_FORMAT_VERSION};
+pub use mailbox::{MailboxRelay, MAILBOX_ALPN};
 pub use pairing::{InvitePayload, UnsupportedInviteVersion, INVITE_FORMAT_VERSION};
 pub use runtime::{Runtime, UnknownIdentity};
 pub use sync::{RuntimeSyncService, SyncService};
 
 // Vocabulary re-exports, so hosts depend on `pdn-node` alone.
-pub use data_layer::{claim_id_of, DocTicket, ReadGrant, ShareMode, SpawnOptions, UnknownIssuer};
-pub use pdn_types::{ClaimId, EntryInfo, EntryPath, NodeId, NonEmpty, PdnId};
+pub use data_layer::{
+    claim_id_of, CatchUpTimeout, DataLayerError, DocTicket, EntriesMissed, EntryWatch, ReadGrant,
+    ShareMode, SpawnOptions, UnknownIssuer,
+};
+pub use pdn_layer::kel::{KeyState, KeyStatus};
+pub use pdn_layer::{AccessMode, Capability, DelegatedClaim};
+pub use pdn_types::{
+    CapabilityCid, ClaimId, EntryEvent, EntryEventKind, EntryInfo, EntryOrigin, EntryPath, NodeId,
+    NonEmpty, OperationalKey, PdnId, ValidityWindow,
+};
@@ -8,12 +8,14 @@
 //! whole directory, not per-connection read tickets, so the two dialogues
 //! evolve their wire formats independently. The inviting device mints a
 //! one-time secret and a self-contained [`LinkingPayload`]; the new device
-//! dials the payload's address and presents the secret; the inviter
-//! atomically verifies-and-burns it *before any state change*, registers
-//! the newcomer in its own directory replica — the node id taken from the
-//! connection's authenticated peer identity, never from a claimed field —
-//! and answers with freshly minted write tickets to the directory and the
-//! identity's data namespace. Commit precedes the reply: a response lost
+//! dials the payload's address and presents the secret with the device key
+//! it generated for the identity; the inviter atomically verifies-and-burns
+//! the secret *before any state change*, authorizes the key in the
+//! identity's key event log, registers the newcomer in its own directory
+//! replica — the node id taken from the connection's authenticated peer
+//! identity, never from a claimed field — and answers with the log and
+//! freshly minted write tickets to the directory and the identity's data
+//! namespace. Commit precedes the reply: a response lost
 //! from there on leaves a registered-but-absent device record (harmless —
 //! device records carry no liveness semantics), and a fresh invite
 //! converges.
@@ -33,22 +35,37 @@
 //! closes the connection without a distinguishing answer, and a refused
 //! attempt leaves no observable state. A wrong secret burns nothing.
 //!
-//! The dialogue carries no KERI proof of control over the identity —
-//! deferred, exactly as in pairing (ADR-0008). Both devices must be online:
-//! there are no pending linking invites.
+//! Proof of control over the identity runs the other way than in pairing:
+//! the newcomer holds none of the identity's keys, so the inviter proves
+//! the identity *to* it. No ticket is released before the log authorizes
+//! the newcomer's key with an `AddDevice` event signed by a key of the
+//! inviting device — the identity's current key on the device that
+//! incepted it, its own device key on a linked one — and the newcomer
+//! commits to nothing before the log in the reply verifies, certifies the
+//! identity the payload named, and authorizes its key. A device linked
+//! before device keys existed holds none, and cannot invite further
+//! devices. Two devices linking newcomers at the same moment race for the
+//! log's next sequence number; the loser's event does not survive, and
+//! its newcomer — linked, but unauthorized — cannot invite in turn until
+//! it links again. Both devices must be online: there are no pending
+//! linking invites.
 
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
 use data_layer::{
-    AcceptError, AddrInfoOptions, Connection, DocTicket, EndpointAddr, NamespaceId,
+    AcceptError, AddrInfoOptions, Connection, DeviceRecord, DocTicket, EndpointAddr, NamespaceId,
     NamespaceImport, PrivateMetadataStore, ProtocolHandler, ShareMode, SyncNode,
 };
-use pdn_types::{NodeId, PdnId};
+use pdn_layer::kel::{KeyEventLog, KeyPair};
+use pdn_types::{NodeId, OperationalKey, PdnId};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
+use crate::error::DialogueRefused;
+use crate::events::Observed;
+use crate::identity::{append_key_event, verified_key_event_log};
 use crate::pairing::{read_message, write_message, StateSlot};
 use crate::runtime::{HostedIdentity, State};
 
@@ -60,11 +77,12 @@ pub(crate) const LINKING_ALPN: &[u8] = b"/pdn/linking/0";
 /// payload with any other version refuses it before dialing; the inviter
 /// likewise refuses a request carrying an unknown version (uniformly, like
 /// every other refusal).
-pub const LINKING_FORMAT_VERSION: u8 = 0;
+pub const LINKING_FORMAT_VERSION: u8 = 1;
 
 /// The self-contained linking payload — what the inviting device shows and
-/// the new device consumes. In-process it travels as a value; its string/QR
-/// encoding is a host concern.
+/// the new device consumes. In-process it travels as a value; between
+/// devices as its `pdn-link:` URI ([`crate::encoding`]) — its `Display` and
+/// `FromStr`.
 ///
 /// Deliberately bearer-free: a format version, the inviting device's node
 /// address (the dial target), the one-time secret, and the identity's
@@ -85,8 +103,9 @@ pub struct LinkingPayload {
 }
 
 /// `link` was handed a linking payload whose format version this runtime
-/// does not speak; refused before dialing. Downcast from the
-/// `anyhow::Error` of the identity service's `link`.
+/// does not speak; refused before dialing. Carried by
+/// [`ServiceError::UnsupportedLinkingVersion`](crate::ServiceError) from the
+/// identity service's `link`.
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 #[error("unsupported linking payload version: {version}")]
 pub struct UnsupportedLinkingVersion {
@@ -94,22 +113,26 @@ pub struct UnsupportedLinkingVersion {
     pub version: u8,
 }
 
-/// The new device's half of the dialogue: the format version and the
-/// secret — nothing else. In particular no node id: the inviter takes the
+/// The new device's half of the dialogue: the format version, the secret,
+/// and the public half of the device key it generated for the identity —
+/// nothing else. In particular no node id: the inviter takes the
 /// newcomer's id from the connection's authenticated peer identity.
 #[derive(Debug, Serialize, Deserialize)]
 struct LinkingRequest {
     version: u8,
     secret: [u8; 32],
+    key: OperationalKey,
 }
 
-/// The inviter's half, sent only after the verify-and-burn and the
-/// registration: write tickets to the identity's directory and to its data
-/// namespace, both minted fresh from replicas the inviting device hosts
-/// locally — the ceremony reads nothing through directory ticket entries,
-/// so no payload wait sits in the critical path.
+/// The inviter's half, sent only after the verify-and-burn, the key's
+/// authorization and the registration: the identity's key event log, ending
+/// in that authorization, and write tickets to the identity's directory and
+/// to its data namespace, both minted fresh from replicas the inviting
+/// device hosts locally — the ceremony reads nothing through directory
+/// ticket entries, so no payload wait sits in the critical path.
 #[derive(Debug, Serialize, Deserialize)]
 struct LinkingResponse {
+    kel: KeyEventLog,
     directory: DocTicket,
     data: DocTicket,
 }
@@ -173,11 +196,22 @@ impl LinkingHandler {
                 .pending_linking_invites
                 .verify_and_burn(&request.secret, Instant::now())?;
 
-            // The commit: register the newcomer in this device's own
-            // replica — a local write on a device that already holds the
-            // directory, so no cross-node delivery sits in the linking
-            // critical path.
+            // The commit: authorize the newcomer's key in the identity's
+            // log, then register the newcomer in this device's own replica
+            // — local writes on a device that already holds the directory,
+            // so no cross-node delivery sits in the linking critical path.
+            // The state lock is held from the read of the log to the
+            // append, so no other append on this device lands in between;
+            // one by another device refuses this append as a conflict. A
+            // device holding no key to sign with refuses like any other
+            // failure.
             let directory = &state.hosted(identity).ok()?.directory;
+            let (mut kel, _key_state) = verified_key_event_log(directory, identity).await.ok()?;
+            let signer = state.keys.device_signer(identity)?;
+            kel.add_device(signer, &request.key).ok()?;
+            append_key_event(directory, kel.events().last()?)
+                .await
+                .ok()?;
             directory.add_device(newcomer).await.ok()?;
 
             // Both bootstrap tickets, minted fresh from local replicas:
@@ -198,6 +232,7 @@ impl LinkingHandler {
                 .await
                 .ok()?;
             LinkingResponse {
+                kel,
                 directory: directory_ticket,
                 data,
             }
@@ -227,9 +262,10 @@ impl ProtocolHandler for LinkingHandler {
     }
 }
 
-/// The new device's side of one linking: dial the payload's address on the
-/// linking ALPN, run the exchange, import the bootstrap tickets from the
-/// reply, and return caught up.
+/// The new device's side of one linking: generate this device's key for the
+/// identity, dial the payload's address on the linking ALPN, run the
+/// exchange, check that the reply's log authorizes the key, import the
+/// bootstrap tickets from the reply, and return caught up.
 ///
 /// The runtime lock is taken *per phase* and never held across the network
 /// round-trip or the catch-up wait — so a link in flight blocks no other
@@ -247,6 +283,7 @@ impl ProtocolHandler for LinkingHandler {
 pub(crate) async fn link_via_dialogue(
     state: &Arc<Mutex<State>>,
     payload: &LinkingPayload,
+    label: Option<&str>,
     timeout: Duration,
 ) -> Result<()> {
     // A brief lock for the hosted check and the dial handle (a cheap
@@ -263,8 +300,16 @@ pub(crate) async fn link_via_dialogue(
     };
 
     // Network — no lock held, and nothing local minted yet, so a failure
-    // anywhere up to the reply rolls back nothing.
-    let response = run_linking_dialogue(&dial, payload).await?;
+    // anywhere up to the reply rolls back nothing: the device key is kept
+    // only once the link commits.
+    let device_key = KeyPair::from_seed(rand::random());
+    let response = run_linking_dialogue(&dial, payload, device_key.public()).await?;
+    let key_state = response.kel.verify()?;
+    ensure!(
+        key_state.pdn_id() == payload.identity && key_state.has_device(&device_key.public()),
+        "the inviter's key event log does not authorize this device under {}",
+        payload.identity
+    );
 
     // Import both replicas under a brief lock — local acts, no network
     // wait. Sessions the imports start count for the catch-up below: they
@@ -286,7 +331,7 @@ pub(crate) async fn link_via_dialogue(
         // long-lived namespace id.
         if let Err(err) = state.node.host_identity(payload.identity, &directory) {
             undo_link(&state.node, payload.identity, directory.namespace(), None).await;
-            return Err(err);
+            return Err(err.into());
         }
         match state
             .node
@@ -298,7 +343,7 @@ pub(crate) async fn link_via_dialogue(
                 // The rollback begins with the import: a directory whose
                 // sibling import failed must not survive it.
                 undo_link(&state.node, payload.identity, directory.namespace(), None).await;
-                return Err(err);
+                return Err(err.into());
             }
         }
     };
@@ -326,10 +371,37 @@ pub(crate) async fn link_via_dialogue(
     // pairs established on the identity's other devices — already
     // replicated or arriving later — register here as their records and
     // ticket payloads land, so this device serves and is servable without
-    // ever touching the grant surface itself.
+    // ever touching the grant surface itself. This device describes itself
+    // in the directory first — its label is its own to choose.
     let mut guard = state.lock().await;
-    let changes = match directory.changes().await {
-        Ok(changes) => changes,
+    let key = device_key.public();
+    if let Err(err) = guard.keys.insert_device(payload.identity, device_key) {
+        undo_link(
+            &guard.node,
+            payload.identity,
+            directory.namespace(),
+            Some(data_import),
+        )
+        .await;
+        return Err(err);
+    }
+    // From here a failure leaves the device key behind in the keystore,
+    // unused: the identity is not hosted, and a later link replaces it.
+    let prepared = async {
+        directory
+            .put_device(
+                guard.node.node_id(),
+                &DeviceRecord {
+                    key: Some(key),
+                    ..DeviceRecord::linked_now(label.map(str::to_owned))
+                },
+            )
+            .await?;
+        anyhow::Ok((directory.changes().await?, directory.syncs().await?))
+    }
+    .await;
+    let (changes, syncs) = match prepared {
+        Ok(subscriptions) => subscriptions,
         Err(err) => {
             undo_link(
                 &guard.node,
@@ -341,19 +413,44 @@ pub(crate) async fn link_via_dialogue(
             return Err(err);
         }
     };
+    if let Some(manifest) = &guard.manifest {
+        if let Err(err) = manifest.record(payload.identity, directory.namespace()) {
+            undo_link(
+                &guard.node,
+                payload.identity,
+                directory.namespace(),
+                Some(data_import),
+            )
+            .await;
+            return Err(err);
+        }
+    }
+    let namespace = directory.namespace();
+    // The baseline is what the catch-up brought in: the devices and
+    // connections the identity already had are not news to report.
+    if let Ok(observed) = Observed::read(&directory).await {
+        guard.observed.insert(payload.identity, observed);
+    }
     guard
         .identities
         .insert(payload.identity, HostedIdentity { directory });
-    crate::connections::spawn_connection_armer(Arc::downgrade(state), payload.identity, changes);
+    crate::connections::spawn_connection_armer(
+        Arc::downgrade(state),
+        payload.identity,
+        namespace,
+        changes,
+    );
+    crate::identity::spawn_sync_observer(Arc::downgrade(state), payload.identity, syncs);
     Ok(())
 }
 
 /// The network half of `link`: dial the inviter on the linking ALPN,
-/// present the secret, read the bootstrap reply. No local state is touched,
-/// so a failure anywhere here rolls back nothing.
+/// present the secret and the device key, read the bootstrap reply. No
+/// local state is touched, so a failure anywhere here rolls back nothing.
 async fn run_linking_dialogue(
     dial: &data_layer::DialHandle,
     payload: &LinkingPayload,
+    key: OperationalKey,
 ) -> Result<LinkingResponse> {
     let connection = dial
         .connect(payload.inviter_addr.clone(), LINKING_ALPN)
@@ -366,15 +463,17 @@ async fn run_linking_dialogue(
             &LinkingRequest {
                 version: LINKING_FORMAT_VERSION,
                 secret: payload.secret,
+                key,
             },
         )
         .await?;
         send.finish()?;
         // Refusals are uniform by design: the connection just closes, and
         // this read fails without saying why.
-        read_message(&mut recv)
-            .await
-            .context("linking refused by the inviter")
+        read_message(&mut recv).await.context(DialogueRefused {
+            dialogue: "linking",
+            by: "the inviter",
+        })
     }
     .await?;
     connection.close(0u32.into(), b"done");
@@ -0,0 +1,664 @@
+//! Mailbox establishment: the pairing ceremony (ADR-0011) for peers that
+//! are not online together.
+//!
+//! A live invite needs both devices up at once. A mailbox invite routes
+//! the same dialogue through a store-and-forward relay instead — any node
+//! serving [`MAILBOX_ALPN`], typically a `SyncNode` carrying
+//! [`MailboxRelay`] as an extra protocol. Three deposits, each under a slot
+//! derived from the invite's secret:
+//!
+//! 1. the inviter deposits its handshake package — the identity's key event
+//!    log and the deadline — and shares the payload as usual;
+//! 2. the scanner reads the package, checks the payload's invite proof
+//!    against it, and deposits its half: the same request the live dialogue
+//!    sends;
+//! 3. the inviter, on its next collection, takes the request, answers it
+//!    exactly as its accept side would — verify-and-burn, both proofs,
+//!    assembly — and deposits the response, which the scanner completes
+//!    from on its own next collection.
+//!
+//! The relay learns nothing it could use: slot ids and the sealing key are
+//! derived from the secret one-way, every deposit is sealed
+//! (AES-256-GCM, the slot as associated data), and every deposit expires.
+//! The package is read, not taken, so a scanner whose first attempt fails
+//! can try again; the inviter removes it once it answers. The request and
+//! the answer are taken — removed by the one read that counts. A relay can
+//! withhold or drop deposits — which stalls the ceremony, as an
+//! unreachable inviter does — but cannot read, forge or redirect one.
+//!
+//! The limits, as they stand:
+//!
+//! - nothing steps a ceremony on its own: the runtime does not poll
+//!   relays, so a host calls `collect_mailboxes` on the connections
+//!   service when it sees fit — on start, on a timer, when the app comes
+//!   to the foreground — and a ceremony waits between calls;
+//! - a [`MailboxRelay`] holds its deposits in memory, so a relay restart
+//!   drops every ceremony routed through it;
+//! - a relay holds at most 4096 live deposits and refuses the next one
+//!   rather than displace any: a relay filled — by load or by anyone who
+//!   can dial it — refuses new mailbox invites until deposits are taken or
+//!   expire.
+//!
+//! Both sides' bookkeeping lives in runtime memory, like the pending
+//! invites themselves: a restart drops every mailbox ceremony in progress,
+//! and a fresh invite is the recovery path.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as SyncMutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use aes_gcm::aead::{Aead as _, KeyInit as _, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Context, Result};
+use data_layer::{
+    AcceptError, AddrInfoOptions, Connection, ConnectionMetadataStore, DialHandle, EndpointAddr,
+    ProtocolHandler, ShareMode,
+};
+use pdn_layer::kel::KeyEventLog;
+use pdn_types::{NodeId, PdnId};
+use rand::{rngs::SysRng, TryRng as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::error::DialogueRefused;
+use crate::pairing::{
+    answer_request, assemble_connection, mint_invite, own_store_toward, prove_as_scanner,
+    read_message, verify_invite, verify_response, write_message, InvitePayload, PairingRequest,
+    PairingResponse, INVITE_FORMAT_VERSION,
+};
+use crate::runtime::State;
+
+/// The mailbox relay's ALPN — served by whichever node relays, dialed by
+/// both parties to a mailbox invite.
+pub const MAILBOX_ALPN: &[u8] = b"/pdn/mailbox/0";
+
+/// How long a mailbox invite lives unless the invite overrides it: long
+/// enough for a payload shared by message to be opened the same day.
+pub(crate) const DEFAULT_MAILBOX_LIFETIME: Duration = Duration::from_hours(24);
+
+/// How long the inviter's answer waits on the relay for the scanner to
+/// collect it — counted from the answer, since the scanner may come back
+/// only after the invite itself has lapsed.
+const ANSWER_HOLD: Duration = Duration::from_hours(24);
+
+/// The longest a relay keeps one deposit, whatever the depositor asks for.
+const MAX_DEPOSIT_LIFETIME: Duration = Duration::from_hours(24 * 7);
+
+/// How many live deposits one relay holds at most; a deposit beyond it is
+/// refused rather than displacing one already held.
+const MAX_DEPOSITS: usize = 4096;
+
+/// The three deposits of one mailbox ceremony, in order.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Package,
+    Request,
+    Response,
+}
+
+impl Slot {
+    /// The slot id under `secret`: what the relay keys the deposit by, and
+    /// the associated data it is sealed under.
+    fn of(self, secret: &[u8; 32]) -> [u8; 32] {
+        let context = match self {
+            Self::Package => "pdn mailbox v0 package slot",
+            Self::Request => "pdn mailbox v0 request slot",
+            Self::Response => "pdn mailbox v0 response slot",
+        };
+        blake3::derive_key(context, secret)
+    }
+}
+
+/// The inviter's handshake package: the log its invite proof verifies
+/// under, and when its secret stops being answered (seconds since the
+/// Unix epoch).
+#[derive(Debug, Serialize, Deserialize)]
+struct InvitePackage {
+    kel: KeyEventLog,
+    respond_by: u64,
+}
+
+/// One relay operation: leave a sealed deposit under a slot for at most
+/// `lifetime_secs`, take (and so remove) what a slot holds, or read it and
+/// leave it there.
+#[derive(Debug, Serialize, Deserialize)]
+enum RelayRequest {
+    Put {
+        slot: [u8; 32],
+        sealed: Vec<u8>,
+        lifetime_secs: u64,
+    },
+    Take {
+        slot: [u8; 32],
+    },
+    Read {
+        slot: [u8; 32],
+    },
+}
+
+/// The relay's answer: a put stored or refused (the slot already holds a
+/// live deposit, or the relay is full), or what a take or read found.
+#[derive(Debug, Serialize, Deserialize)]
+enum RelayReply {
+    Stored,
+    Refused,
+    Found(Option<Vec<u8>>),
+}
+
+/// A deposit held by a relay.
+#[derive(Debug)]
+struct Deposit {
+    sealed: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// A store-and-forward relay for mailbox establishment: a protocol handler
+/// any node can serve under [`MAILBOX_ALPN`] — registered as a data-layer
+/// extra protocol, `(MAILBOX_ALPN.to_vec(), Box::new(MailboxRelay::new()))`.
+///
+/// It holds opaque deposits keyed by slot id, each until it is taken or
+/// expires, in memory: a relay restart drops what it held. At most 4096
+/// are live at once, and a deposit past that is refused.
+/// Expiry is lazy, swept at the next deposit.
+#[derive(Debug, Clone, Default)]
+pub struct MailboxRelay {
+    deposits: Arc<SyncMutex<HashMap<[u8; 32], Deposit>>>,
+}
+
+impl MailboxRelay {
+    /// An empty relay.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one request to the held deposits. A put never replaces a live
+    /// deposit: the first depositor under a slot is the one that held the
+    /// secret first.
+    fn apply(&self, request: RelayRequest, now: Instant) -> RelayReply {
+        let Ok(mut deposits) = self.deposits.lock() else {
+            return RelayReply::Refused;
+        };
+        match request {
+            RelayRequest::Put {
+                slot,
+                sealed,
+                lifetime_secs,
+            } => {
+                deposits.retain(|_, deposit| deposit.expires_at > now);
+                if deposits.contains_key(&slot) || deposits.len() >= MAX_DEPOSITS {
+                    return RelayReply::Refused;
+                }
+                let lifetime = Duration::from_secs(lifetime_secs).min(MAX_DEPOSIT_LIFETIME);
+                deposits.insert(
+                    slot,
+                    Deposit {
+                        sealed,
+                        expires_at: now + lifetime,
+                    },
+                );
+                RelayReply::Stored
+            }
+            RelayRequest::Take { slot } => RelayReply::Found(
+                deposits
+                    .remove(&slot)
+                    .filter(|deposit| deposit.expires_at > now)
+                    .map(|deposit| deposit.sealed),
+            ),
+            RelayRequest::Read { slot } => RelayReply::Found(
+                deposits
+                    .get(&slot)
+                    .filter(|deposit| deposit.expires_at > now)
+                    .map(|deposit| deposit.sealed.clone()),
+            ),
+        }
+    }
+
+    /// Serve one connection: one request, one reply.
+    async fn serve(&self, connection: &Connection) -> Result<()> {
+        let (mut send, mut recv) = connection.accept_bi().await?;
+        let request: RelayRequest = read_message(&mut recv).await?;
+        write_message(&mut send, &self.apply(request, Instant::now())).await?;
+        send.finish()?;
+        connection.closed().await;
+        Ok(())
+    }
+}
+
+impl ProtocolHandler for MailboxRelay {
+    async fn accept(&self, connection: Connection) -> Result<(), AcceptError> {
+        if self.serve(&connection).await.is_err() {
+            connection.close(0u32.into(), b"");
+        }
+        Ok(())
+    }
+}
+
+/// One mailbox ceremony in progress on this runtime, keyed by its secret
+/// in [`State::mailboxes`].
+#[derive(Debug, Clone)]
+pub(crate) enum MailboxCeremony {
+    /// This runtime invited: the scanner's request is awaited.
+    Inviting {
+        relay: EndpointAddr,
+        expires_at: SystemTime,
+    },
+    /// This runtime scanned and deposited its half: the inviter's answer is
+    /// awaited. `own` is this side's half of the pair, already shared in
+    /// the request; `answered` is the statement the answer must prove, and
+    /// `known` the inviter's log as this side last stored it, which the
+    /// answer's must extend.
+    Scanning {
+        identity: PdnId,
+        payload: Box<InvitePayload>,
+        own: ConnectionMetadataStore,
+        created_fresh: bool,
+        answered: Vec<u8>,
+        known: Option<KeyEventLog>,
+        expires_at: SystemTime,
+    },
+}
+
+impl MailboxCeremony {
+    fn expires_at(&self) -> SystemTime {
+        match self {
+            Self::Inviting { expires_at, .. } | Self::Scanning { expires_at, .. } => *expires_at,
+        }
+    }
+}
+
+/// Mint a mailbox invite for hosted `identity`: a pending secret with
+/// `lifetime`, its package deposited with `relay`, and the payload naming
+/// both. A package the relay does not take burns the secret again.
+pub(crate) async fn open_mailbox_invite(
+    state: &Mutex<State>,
+    identity: PdnId,
+    relay: EndpointAddr,
+    lifetime: Duration,
+) -> Result<InvitePayload> {
+    let (mut payload, kel, dial) = {
+        let mut state = state.lock().await;
+        let (payload, kel) = mint_invite(&mut state, identity, lifetime).await?;
+        (payload, kel, state.node.dial_handle())
+    };
+    let expires_at = SystemTime::now() + lifetime;
+    let package = InvitePackage {
+        kel,
+        respond_by: expires_at.duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    let deposited = deposit(
+        &dial,
+        &relay,
+        &payload.secret,
+        Slot::Package,
+        &package,
+        lifetime,
+    )
+    .await;
+    let mut state = state.lock().await;
+    if let Err(err) = deposited {
+        let _discarded = state
+            .pending_invites
+            .verify_and_burn(&payload.secret, Instant::now());
+        return Err(err.context("could not deposit the invite with the mailbox"));
+    }
+    state.mailboxes.insert(
+        payload.secret,
+        MailboxCeremony::Inviting {
+            relay: relay.clone(),
+            expires_at,
+        },
+    );
+    payload.mailbox = Some(relay);
+    Ok(payload)
+}
+
+/// The scanner's half of a mailbox establishment: prove the identity, read
+/// the inviter's package and check the invite proof against it, then
+/// deposit the request the live dialogue would send. Nothing is created
+/// before the package verified; a deposit that fails forgets a freshly
+/// created own store again.
+pub(crate) async fn deposit_request(
+    state: &Mutex<State>,
+    identity: PdnId,
+    payload: &InvitePayload,
+    relay: &EndpointAddr,
+) -> Result<()> {
+    let (dial, scanner) = {
+        let state = state.lock().await;
+        (
+            state.node.dial_handle(),
+            prove_as_scanner(&state, identity, payload).await?,
+        )
+    };
+    let package: InvitePackage = read(&dial, relay, &payload.secret, Slot::Package)
+        .await
+        .context("could not reach the mailbox")?
+        .context("no invite in the mailbox: expired, answered, or never deposited")??;
+    verify_invite(payload, &package.kel, scanner.known.as_ref())?;
+    let respond_by = UNIX_EPOCH + Duration::from_secs(package.respond_by);
+    let lifetime = respond_by
+        .duration_since(SystemTime::now())
+        .map_err(|_lapsed| anyhow!("the mailbox invite has expired"))?;
+
+    let (own, created_fresh) = {
+        let state = state.lock().await;
+        own_store_toward(&state, identity, payload.inviter).await?
+    };
+    let deposited = async {
+        let ticket = own
+            .share_ticket(ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
+            .await?;
+        let request = PairingRequest {
+            version: INVITE_FORMAT_VERSION,
+            secret: payload.secret,
+            scanner: identity,
+            kel: scanner.kel,
+            proof: scanner.proof,
+            scanner_addr: dial.addr(),
+            ticket,
+        };
+        deposit(
+            &dial,
+            relay,
+            &payload.secret,
+            Slot::Request,
+            &request,
+            lifetime,
+        )
+        .await
+    }
+    .await;
+    let mut state = state.lock().await;
+    if let Err(err) = deposited {
+        if created_fresh {
+            let _ = state.node.forget_doc(own.namespace()).await;
+        }
+        return Err(err);
+    }
+    state.mailboxes.insert(
+        payload.secret,
+        MailboxCeremony::Scanning {
+            identity,
+            payload: Box::new(payload.clone()),
+            own,
+            created_fresh,
+            answered: scanner.answered,
+            known: scanner.known,
+            expires_at: respond_by + ANSWER_HOLD,
+        },
+    );
+    Ok(())
+}
+
+/// One collection pass over this runtime's mailbox ceremonies: sweep the
+/// expired ones, then step each of the rest — the lock taken per ceremony
+/// and per phase, never across a relay round-trip.
+pub(crate) async fn collect_mailboxes(state: &Mutex<State>) -> Result<usize> {
+    let (dial, ceremonies) = {
+        let mut state = state.lock().await;
+        let now = SystemTime::now();
+        let expired: Vec<[u8; 32]> = state
+            .mailboxes
+            .iter()
+            .filter(|(_, ceremony)| ceremony.expires_at() <= now)
+            .map(|(secret, _)| *secret)
+            .collect();
+        for secret in expired {
+            if let Some(MailboxCeremony::Scanning {
+                own,
+                created_fresh: true,
+                ..
+            }) = state.mailboxes.remove(&secret)
+            {
+                let _ = state.node.forget_doc(own.namespace()).await;
+            }
+        }
+        let ceremonies: Vec<([u8; 32], MailboxCeremony)> = state
+            .mailboxes
+            .iter()
+            .map(|(secret, ceremony)| (*secret, ceremony.clone()))
+            .collect();
+        (state.node.dial_handle(), ceremonies)
+    };
+    let mut established = 0;
+    for (secret, ceremony) in ceremonies {
+        let stepped = match ceremony {
+            MailboxCeremony::Inviting { relay, .. } => {
+                answer_from_mailbox(state, &dial, &secret, &relay).await
+            }
+            MailboxCeremony::Scanning { .. } => {
+                finish_from_mailbox(state, &dial, &secret, ceremony).await
+            }
+        };
+        // A failed step has already ended its ceremony or left it for the
+        // next pass; neither stops the others.
+        if stepped.unwrap_or(false) {
+            established += 1;
+        }
+    }
+    Ok(established)
+}
+
+/// The inviter's step: take the scanner's request, if one is there, answer
+/// it as the accept side would, and deposit the answer. Once taken the
+/// ceremony ends whatever follows — a request that does not open or is
+/// refused burns the secret, as a refused dialogue does — and the package,
+/// left readable until now, is taken off the relay.
+async fn answer_from_mailbox(
+    state: &Mutex<State>,
+    dial: &DialHandle,
+    secret: &[u8; 32],
+    relay: &EndpointAddr,
+) -> Result<bool> {
+    let Some(request) = take::<PairingRequest>(dial, relay, secret, Slot::Request).await? else {
+        return Ok(false);
+    };
+    // Best effort: an unremoved package expires with the invite.
+    let _cleared = take::<InvitePackage>(dial, relay, secret, Slot::Package).await;
+    let response = {
+        let mut state = state.lock().await;
+        state.mailboxes.remove(secret);
+        let answered = match &request {
+            Ok(request) => {
+                let scanner_endpoint = NodeId::from_bytes(*request.scanner_addr.id.as_bytes());
+                answer_request(&mut state, request, scanner_endpoint).await
+            }
+            Err(_unopened) => None,
+        };
+        let _burned = state
+            .pending_invites
+            .verify_and_burn(secret, Instant::now());
+        answered.context("the scanner's mailbox request was refused")?
+    };
+    deposit(dial, relay, secret, Slot::Response, &response, ANSWER_HOLD).await?;
+    Ok(true)
+}
+
+/// The scanner's step: take the inviter's answer, if one is there, check
+/// both of the inviter's proofs, and assemble. Once taken the ceremony ends
+/// either way; an answer that does not verify commits nothing and forgets a
+/// freshly created own store.
+async fn finish_from_mailbox(
+    state: &Mutex<State>,
+    dial: &DialHandle,
+    secret: &[u8; 32],
+    ceremony: MailboxCeremony,
+) -> Result<bool> {
+    let MailboxCeremony::Scanning {
+        identity,
+        payload,
+        own,
+        created_fresh,
+        answered,
+        known,
+        ..
+    } = ceremony
+    else {
+        return Ok(false);
+    };
+    let Some(relay) = &payload.mailbox else {
+        return Ok(false);
+    };
+    let Some(response) = take::<PairingResponse>(dial, relay, secret, Slot::Response).await? else {
+        return Ok(false);
+    };
+    let response = response.and_then(|response| {
+        verify_response(&payload, &response, &answered, known.as_ref())?;
+        Ok(response)
+    });
+    let mut state = state.lock().await;
+    state.mailboxes.remove(secret);
+    let response = match response {
+        Ok(response) => response,
+        Err(err) => {
+            if created_fresh {
+                let _ = state.node.forget_doc(own.namespace()).await;
+            }
+            return Err(err);
+        }
+    };
+    assemble_connection(
+        &mut state,
+        identity,
+        payload.inviter,
+        own,
+        response.ticket,
+        Some(payload.inviter_addr.clone()),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// Seal `message` and leave it with `relay` under `slot` for `lifetime`.
+async fn deposit<T: Serialize>(
+    dial: &DialHandle,
+    relay: &EndpointAddr,
+    secret: &[u8; 32],
+    slot: Slot,
+    message: &T,
+    lifetime: Duration,
+) -> Result<()> {
+    let slot = slot.of(secret);
+    let request = RelayRequest::Put {
+        slot,
+        sealed: seal(secret, &slot, message)?,
+        lifetime_secs: lifetime.as_secs(),
+    };
+    match exchange(dial, relay, &request).await? {
+        RelayReply::Stored => Ok(()),
+        RelayReply::Refused | RelayReply::Found(_) => Err(DialogueRefused {
+            dialogue: "the deposit",
+            by: "the mailbox",
+        }
+        .into()),
+    }
+}
+
+/// Take what `relay` holds under `slot`. The outer error is the relay
+/// round-trip; the inner one a deposit that does not open — already removed
+/// from the relay, so the caller decides what its failure ends.
+async fn take<T: DeserializeOwned>(
+    dial: &DialHandle,
+    relay: &EndpointAddr,
+    secret: &[u8; 32],
+    slot: Slot,
+) -> Result<Option<Result<T>>> {
+    fetch(dial, relay, secret, slot, |slot| RelayRequest::Take {
+        slot,
+    })
+    .await
+}
+
+/// [`take`], leaving the deposit on the relay for a later read.
+async fn read<T: DeserializeOwned>(
+    dial: &DialHandle,
+    relay: &EndpointAddr,
+    secret: &[u8; 32],
+    slot: Slot,
+) -> Result<Option<Result<T>>> {
+    fetch(dial, relay, secret, slot, |slot| RelayRequest::Read {
+        slot,
+    })
+    .await
+}
+
+/// Send `request` for `slot` to `relay` and open what it found.
+async fn fetch<T: DeserializeOwned>(
+    dial: &DialHandle,
+    relay: &EndpointAddr,
+    secret: &[u8; 32],
+    slot: Slot,
+    request: impl FnOnce([u8; 32]) -> RelayRequest,
+) -> Result<Option<Result<T>>> {
+    let slot = slot.of(secret);
+    match exchange(dial, relay, &request(slot)).await? {
+        RelayReply::Found(sealed) => Ok(sealed.map(|sealed| open(secret, &slot, &sealed))),
+        RelayReply::Stored | RelayReply::Refused => Err(DialogueRefused {
+            dialogue: "the fetch",
+            by: "the mailbox",
+        }
+        .into()),
+    }
+}
+
+/// One request–reply round-trip with `relay`.
+async fn exchange(
+    dial: &DialHandle,
+    relay: &EndpointAddr,
+    request: &RelayRequest,
+) -> Result<RelayReply> {
+    let connection = dial
+        .connect(relay.clone(), MAILBOX_ALPN)
+        .await
+        .context("could not reach the mailbox")?;
+    let (mut send, mut recv) = connection.open_bi().await?;
+    write_message(&mut send, request).await?;
+    send.finish()?;
+    let reply = read_message(&mut recv).await;
+    connection.close(0u32.into(), b"done");
+    reply
+}
+
+/// The cipher every deposit of one ceremony is sealed with, keyed from the
+/// secret.
+fn cipher(secret: &[u8; 32]) -> Result<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(&blake3::derive_key("pdn mailbox v0 seal", secret))
+        .map_err(|_| anyhow!("mailbox sealing key rejected"))
+}
+
+/// Seal `message` under the ceremony's key, bound to `slot`: a fresh
+/// 96-bit nonce followed by the ciphertext.
+fn seal<T: Serialize>(secret: &[u8; 32], slot: &[u8; 32], message: &T) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; 12];
+    SysRng
+        .try_fill_bytes(&mut nonce)
+        .context("operating-system randomness unavailable")?;
+    let plain = postcard::to_stdvec(message)?;
+    let sealed = cipher(secret)?
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plain,
+                aad: slot,
+            },
+        )
+        .map_err(|_| anyhow!("mailbox deposit could not be sealed"))?;
+    Ok([nonce.as_slice(), sealed.as_slice()].concat())
+}
+
+/// Open a deposit [`seal`] made for `slot` under the same secret.
+fn open<T: DeserializeOwned>(secret: &[u8; 32], slot: &[u8; 32], sealed: &[u8]) -> Result<T> {
+    let (nonce, ciphertext) = sealed
+        .split_at_checked(12)
+        .context("mailbox deposit too short")?;
+    let plain = cipher(secret)?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: slot,
+            },
+        )
+        .map_err(|_| anyhow!("mailbox deposit does not open under this invite"))?;
+    Ok(postcard::from_bytes(&plain)?)
+}
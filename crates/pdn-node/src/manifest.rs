@@ -0,0 +1,69 @@
+//! The hosted-identity manifest: which identities a runtime on a storage
+//! root hosts, and which replica is each one's directory.
+//!
+//! The directory does not name its owning identity (the handle's holder
+//! knows which identity it serves), and a persistent node holds many
+//! replicas besides directories — so the one fact a restart cannot recover
+//! from the stores alone is written down here, once per identity, when
+//! `create` or `link` succeeds. Everything else a hosted identity needs —
+//! its data namespace, its connection pairs, its grants — is read back out
+//! of the directory itself.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use data_layer::NamespaceId;
+use pdn_types::PdnId;
+use serde::{Deserialize, Serialize};
+
+use crate::files::replace_file;
+
+/// The manifest's file under the storage root, beside the node's own state.
+const MANIFEST_FILE: &str = "identities";
+
+/// One hosted identity and the replica that is its directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ManifestEntry {
+    pub(crate) identity: PdnId,
+    pub(crate) directory: NamespaceId,
+}
+
+/// The manifest file of one storage root. Absent until the first identity
+/// is recorded; an absent file is an empty manifest.
+#[derive(Debug)]
+pub(crate) struct HostedManifest {
+    path: PathBuf,
+}
+
+impl HostedManifest {
+    /// The manifest of the storage root at `root`.
+    pub(crate) fn at(root: &Path) -> Self {
+        Self {
+            path: root.join(MANIFEST_FILE),
+        }
+    }
+
+    /// Every recorded identity, in recording order.
+    pub(crate) fn load(&self) -> Result<Vec<ManifestEntry>> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => postcard::from_bytes(&bytes)
+                .with_context(|| format!("unreadable manifest {}", self.path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err).with_context(|| format!("cannot read {}", self.path.display())),
+        }
+    }
+
+    /// Record `identity` as hosted, with `directory` as its directory —
+    /// replacing an earlier record of the same identity. The file is
+    /// rewritten whole ([`replace_file`]).
+    pub(crate) fn record(&self, identity: PdnId, directory: NamespaceId) -> Result<()> {
+        let mut entries = self.load()?;
+        entries.retain(|entry| entry.identity != identity);
+        entries.push(ManifestEntry {
+            identity,
+            directory,
+        });
+        replace_file(&self.path, &postcard::to_stdvec(&entries)?)
+    }
+}
@@ -19,25 +19,40 @@
 //! attempt leaves no observable state. A wrong secret burns nothing: a
 //! guess cannot extinguish a ceremony in progress.
 //!
-//! The dialogue carries no KERI proof of control over a presented `PdnId`
-//! — deferred (ADR-0008): the exchange is bearer-level, secret plus
-//! tickets. Both peers must be online: there are no pending invitations.
+//! Each side proves the `PdnId` it presents: it sends its identity's key
+//! event log and a proof by the log's current key over the transcript —
+//! the secret, both `PdnId`s, both endpoint ids, and the signer's role —
+//! and the other side verifies both before any connection record is
+//! written. A scanner can therefore not claim an identity it holds no keys
+//! for, and a failed proof is refused like every other failure: uniformly
+//! on the inviter's side, before a reply. The invite payload itself
+//! carries the inviter's proof over the half of the transcript it knows,
+//! checked by the scanner once the response hands it the inviter's log.
+//! Only the device holding an identity's keys can pair on its behalf.
+//!
+//! The dialogue as written here needs both peers online at once. The same
+//! request and response can instead travel through a mailbox relay
+//! ([`crate::mailbox`]), which carries them between two devices that are
+//! never up together; the checks on each side are the ones below.
 
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock, Weak};
 use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
 use data_layer::{
     own_ticket_kind, peer_ticket_kind, AcceptError, AddrInfoOptions, Connection,
     ConnectionMetadata, ConnectionMetadataStore, DocTicket, EndpointAddr, ProtocolHandler,
     RecvStream, SendStream, ShareMode,
 };
-use pdn_types::PdnId;
+use pdn_layer::kel::KeyEventLog;
+use pdn_types::{NodeId, PdnId, PdnIdentityProof};
 use rand::{rngs::SysRng, TryRng as _};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::Mutex;
 
+use crate::error::DialogueRefused;
+use crate::identity::{prove_as, verified_copy};
 use crate::runtime::State;
 
 /// The dedicated pairing ALPN — the protocol the runtime registers at spawn
@@ -48,7 +63,7 @@ pub(crate) const PAIRING_ALPN: &[u8] = b"/pdn/pairing/0";
 /// payload with any other version refuses it before dialing; the inviter
 /// likewise refuses a request carrying an unknown version (uniformly, like
 /// every other refusal).
-pub const INVITE_FORMAT_VERSION: u8 = 0;
+pub const INVITE_FORMAT_VERSION: u8 = 2;
 
 /// How long a pending invite lives unless the invite overrides it.
 pub(crate) const DEFAULT_INVITE_LIFETIME: Duration = Duration::from_mins(2);
@@ -60,14 +75,17 @@ pub(crate) const DEFAULT_INVITE_LIFETIME: Duration = Duration::from_mins(2);
 pub(crate) const MAX_WIRE_MESSAGE_LEN: u32 = 64 * 1024;
 
 /// The self-contained invite payload — what the inviter's device shows and
-/// the scanner's device consumes. In-process it travels as a value; its
-/// string/QR encoding is a host concern.
+/// the scanner's device consumes. In-process it travels as a value; between
+/// devices as its `pdn-invite:` URI ([`crate::encoding`]) — its `Display`
+/// and `FromStr`.
 ///
 /// Deliberately bearer-free: a format version, the inviter device's node
-/// address (the dial target), the one-time secret, and the inviting
-/// identity's `PdnId` — no tickets and no identity proof. The payload is
+/// address (the dial target), the one-time secret, the inviting identity's
+/// `PdnId`, its proof over those, and — for a mailbox invite — the relay
+/// holding the inviter's handshake package; no tickets. The payload is
 /// semi-public (shown on a screen, photographable), so nothing in it may
-/// grant durable access; the secret it carries is one-time and short-lived.
+/// grant durable access; the secret it carries is one-time and short-lived,
+/// and the proof only binds it to the inviter.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvitePayload {
     /// Payload format version ([`INVITE_FORMAT_VERSION`]).
@@ -78,11 +96,18 @@ pub struct InvitePayload {
     pub secret: [u8; 32],
     /// The inviting identity.
     pub inviter: PdnId,
+    /// The inviter's proof over the secret, its `PdnId` and its endpoint
+    /// id, by the identity's current key.
+    pub proof: PdnIdentityProof,
+    /// The mailbox relay the establishment runs through instead of a dial
+    /// to the inviter ([`crate::mailbox`]); `None` for a live invite.
+    pub mailbox: Option<EndpointAddr>,
 }
 
 /// `establish` was handed an invite payload whose format version this
-/// runtime does not speak; refused before dialing. Downcast from the
-/// `anyhow::Error` of the connections service's `establish`.
+/// runtime does not speak; refused before dialing. Carried by
+/// [`ServiceError::UnsupportedInviteVersion`](crate::ServiceError) from the
+/// connections service's `establish`.
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 #[error("unsupported invite payload version: {version}")]
 pub struct UnsupportedInviteVersion {
@@ -90,24 +115,150 @@ pub struct UnsupportedInviteVersion {
     pub version: u8,
 }
 
-/// The scanner's half of the dialogue: the secret, who is scanning, where
-/// to reach it, and the read ticket to the metadata store it issues toward
-/// the inviter.
+/// The scanner's half of the dialogue: the secret, who is scanning and
+/// its evidence for that, where to reach it, and the read ticket to the
+/// metadata store it issues toward the inviter.
 #[derive(Debug, Serialize, Deserialize)]
-struct PairingRequest {
-    version: u8,
-    secret: [u8; 32],
-    scanner: PdnId,
-    scanner_addr: EndpointAddr,
-    ticket: DocTicket,
+pub(crate) struct PairingRequest {
+    pub(crate) version: u8,
+    pub(crate) secret: [u8; 32],
+    pub(crate) scanner: PdnId,
+    pub(crate) kel: KeyEventLog,
+    pub(crate) proof: PdnIdentityProof,
+    pub(crate) scanner_addr: EndpointAddr,
+    pub(crate) ticket: DocTicket,
 }
 
-/// The inviter's half, sent only after the verify-and-burn and the state
-/// assembly: the read ticket to the metadata store it issues toward the
-/// scanner.
+/// The inviter's half, sent only after the verify-and-burn, the scanner's
+/// proof and the state assembly: the inviter's evidence for its `PdnId`
+/// and the read ticket to the metadata store it issues toward the scanner.
 #[derive(Debug, Serialize, Deserialize)]
-struct PairingResponse {
-    ticket: DocTicket,
+pub(crate) struct PairingResponse {
+    pub(crate) kel: KeyEventLog,
+    pub(crate) proof: PdnIdentityProof,
+    pub(crate) ticket: DocTicket,
+}
+
+/// Who signs a pairing statement. Part of what is signed, so no proof
+/// stands in for another: the scanner's cannot be replayed as the
+/// inviter's, nor the invite's as either.
+#[derive(Debug, Clone, Copy, Serialize)]
+enum Signer {
+    Invite,
+    Scanner,
+    Inviter,
+}
+
+/// The bytes a pairing proof covers: the signer's role, the secret, the
+/// inviting identity and device, and — except in the invite, minted before
+/// anyone scanned it — the scanning identity and device.
+#[derive(Debug, Serialize)]
+struct Transcript<'a> {
+    signer: Signer,
+    secret: &'a [u8; 32],
+    inviter: PdnId,
+    inviter_endpoint: NodeId,
+    scanner: Option<(PdnId, NodeId)>,
+}
+
+impl Transcript<'_> {
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(postcard::to_stdvec(self)?)
+    }
+}
+
+/// The statement an invite payload's proof covers.
+pub(crate) fn invite_statement(
+    secret: &[u8; 32],
+    inviter: PdnId,
+    inviter_endpoint: NodeId,
+) -> Result<Vec<u8>> {
+    Transcript {
+        signer: Signer::Invite,
+        secret,
+        inviter,
+        inviter_endpoint,
+        scanner: None,
+    }
+    .to_bytes()
+}
+
+/// Mint a pending invite for hosted `identity` with `lifetime` and the
+/// payload naming it, returning the identity's log beside it — the
+/// evidence for the payload's proof. The proof is over the secret, so the
+/// secret is minted first; a device that cannot prove the identity
+/// discards it unused.
+pub(crate) async fn mint_invite(
+    state: &mut State,
+    identity: PdnId,
+    lifetime: Duration,
+) -> Result<(InvitePayload, KeyEventLog)> {
+    state.hosted(identity)?;
+    let secret = state
+        .pending_invites
+        .mint(identity, lifetime, Instant::now())?;
+    let statement = invite_statement(&secret, identity, state.node.node_id())?;
+    let (kel, proof) = match prove_as(state, identity, &statement).await {
+        Ok(proven) => proven,
+        Err(err) => {
+            let _discarded = state
+                .pending_invites
+                .verify_and_burn(&secret, Instant::now());
+            return Err(err);
+        }
+    };
+    let payload = InvitePayload {
+        version: INVITE_FORMAT_VERSION,
+        inviter_addr: state.node.dial_handle().addr(),
+        secret,
+        inviter: identity,
+        proof,
+        mailbox: None,
+    };
+    Ok((payload, kel))
+}
+
+/// Whether `kel` certifies `claimed` and one of its devices made `proof`
+/// over `statement` — the current key, or a device key the log added and
+/// has not removed, so a linked device pairs for its identity as the
+/// incepting one does. The log arrives from the peer, so it is verified
+/// here from inception — its self-certifying root is what ties it to
+/// `claimed` — and must extend `known`, the peer's log as this side last
+/// stored it: a peer presenting a log from before a rotation or a removal
+/// cannot pass off a key the log has since dropped.
+fn proves(
+    claimed: PdnId,
+    kel: &KeyEventLog,
+    known: Option<&KeyEventLog>,
+    statement: &[u8],
+    proof: &PdnIdentityProof,
+) -> bool {
+    let extends_known = known.is_none_or(|known| kel.events().starts_with(known.events()));
+    extends_known
+        && kel.verify().is_ok_and(|key_state| {
+            key_state.pdn_id() == claimed && key_state.verify_device_proof(statement, proof)
+        })
+}
+
+/// The log of `peer` as hosted `identity` last stored it — mirrored in the
+/// peer's connection metadata store toward it — or `None` before the two
+/// were connected, or while the mirror has not arrived.
+pub(crate) async fn stored_peer_log(
+    state: &State,
+    identity: PdnId,
+    peer: PdnId,
+) -> Result<Option<KeyEventLog>> {
+    let peer_store = match state.metadata_pairs.get(&(identity, peer)) {
+        Some(pair) => pair.peer.clone(),
+        None => {
+            let directory = &state.hosted(identity)?.directory;
+            match directory.get_ticket(&peer_ticket_kind(&peer)).await? {
+                Some(ticket) => ConnectionMetadataStore::import(&state.node, ticket).await?,
+                None => return Ok(None),
+            }
+        }
+    };
+    Ok(verified_copy(peer_store.key_events().await?, peer).map(|(log, _key_state)| log))
 }
 
 /// One pending invite: the identity it invites for and when it expires.
@@ -209,9 +360,6 @@ impl PairingHandler {
     async fn serve(&self, connection: &Connection) -> Option<()> {
         let (mut send, mut recv) = connection.accept_bi().await.ok()?;
         let request: PairingRequest = read_message(&mut recv).await.ok()?;
-        if request.version != INVITE_FORMAT_VERSION {
-            return None;
-        }
 
         // The runtime state is held only for the local verify-and-assemble,
         // inside this block: both the guard and the strong `Arc` drop at its
@@ -220,61 +368,21 @@ impl PairingHandler {
         // never waiting on the dialer to close — were the `Arc` held across
         // `connection.closed()`, `shutdown` would busy-spin until the
         // transport's idle timeout.
-        let response_ticket = {
+        let response = {
             // The late-bound slot: unfilled (no invite can exist yet) or a
             // runtime already gone both refuse.
             let state = self.state.get()?.upgrade()?;
             let mut state = state.lock().await;
-
-            // The atomic verify-and-burn, before any state change. Everything
-            // below only runs for a live, unburned secret.
-            let identity = state
-                .pending_invites
-                .verify_and_burn(&request.secret, Instant::now())?;
-
-            // Assembly, mirroring the dial side: create-or-reuse own, import
-            // the scanner's ticket as peer (its node address supplements the
-            // ticket's own first-sync contacts), connections entry, directory
-            // kinds.
-            let (own, created_fresh) = own_store_toward(&state, identity, request.scanner)
-                .await
-                .ok()?;
-            let Ok(ticket) = own
-                .share_ticket(ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
-                .await
-            else {
-                // Sharing failed before assemble committed: forget a freshly
-                // created own so a failed accept leaves no orphan, the same
-                // rollback the dial side does.
-                if created_fresh {
-                    let _ = state.node.forget_doc(own.namespace()).await;
-                }
-                return None;
-            };
-            assemble_connection(
-                &mut state,
-                identity,
-                request.scanner,
-                own,
-                request.ticket,
-                Some(request.scanner_addr),
-            )
-            .await
-            .ok()?;
-            ticket
+            // The scanner's endpoint is the connection's authenticated peer,
+            // not what the request says.
+            let scanner_endpoint = NodeId::from_bytes(*connection.remote_id().as_bytes());
+            answer_request(&mut state, &request, scanner_endpoint).await?
         };
 
         // Commit precedes the reply: if the response is lost, the inviter
         // keeps its half and a fresh invite converges the rest
         // (re-establishment).
-        write_message(
-            &mut send,
-            &PairingResponse {
-                ticket: response_ticket,
-            },
-        )
-        .await
-        .ok()?;
+        write_message(&mut send, &response).await.ok()?;
         send.finish().ok()?;
         // Hold the connection until the dialer closes it, so the response
         // is not cut off by dropping this side first.
@@ -283,6 +391,89 @@ impl PairingHandler {
     }
 }
 
+/// The inviter's half of one establishment, once a request is in hand —
+/// read off the pairing ALPN or collected from a mailbox
+/// ([`crate::mailbox`]): the version check, the verify-and-burn, both
+/// proofs and the assembly, run under the runtime lock. `None` is a
+/// refusal, any reason at all; `Some` is the response to hand back, sent
+/// only after this side committed.
+pub(crate) async fn answer_request(
+    state: &mut State,
+    request: &PairingRequest,
+    scanner_endpoint: NodeId,
+) -> Option<PairingResponse> {
+    if request.version != INVITE_FORMAT_VERSION {
+        return None;
+    }
+
+    // The atomic verify-and-burn, before any state change. Everything
+    // below only runs for a live, unburned secret.
+    let identity = state
+        .pending_invites
+        .verify_and_burn(&request.secret, Instant::now())?;
+
+    // The scanner proves the identity it claims over the transcript as
+    // this side sees it, and this side's own proof is made before anything
+    // is created, so a device without the identity's keys refuses without
+    // residue. A failed proof burns the secret like a completed dialogue:
+    // whoever presented it held it.
+    let transcript = |signer| {
+        Transcript {
+            signer,
+            secret: &request.secret,
+            inviter: identity,
+            inviter_endpoint: state.node.node_id(),
+            scanner: Some((request.scanner, scanner_endpoint)),
+        }
+        .to_bytes()
+    };
+    let scanned = transcript(Signer::Scanner).ok()?;
+    let known = stored_peer_log(state, identity, request.scanner)
+        .await
+        .ok()?;
+    if !proves(
+        request.scanner,
+        &request.kel,
+        known.as_ref(),
+        &scanned,
+        &request.proof,
+    ) {
+        return None;
+    }
+    let answered = transcript(Signer::Inviter).ok()?;
+    let (kel, proof) = prove_as(state, identity, &answered).await.ok()?;
+
+    // Assembly, mirroring the dial side: create-or-reuse own, import the
+    // scanner's ticket as peer (its node address supplements the ticket's
+    // own first-sync contacts), connections entry, directory kinds.
+    let (own, created_fresh) = own_store_toward(state, identity, request.scanner)
+        .await
+        .ok()?;
+    let Ok(ticket) = own
+        .share_ticket(ShareMode::Read, AddrInfoOptions::RelayAndAddresses)
+        .await
+    else {
+        // Sharing failed before assemble committed: forget a freshly
+        // created own so a failed accept leaves no orphan, the same rollback
+        // the dial side does.
+        if created_fresh {
+            let _ = state.node.forget_doc(own.namespace()).await;
+        }
+        return None;
+    };
+    assemble_connection(
+        state,
+        identity,
+        request.scanner,
+        own,
+        request.ticket.clone(),
+        Some(request.scanner_addr.clone()),
+    )
+    .await
+    .ok()?;
+    Some(PairingResponse { kel, proof, ticket })
+}
+
 impl ProtocolHandler for PairingHandler {
     async fn accept(&self, connection: Connection) -> Result<(), AcceptError> {
         if self.serve(&connection).await.is_none() {
@@ -296,8 +487,9 @@ impl ProtocolHandler for PairingHandler {
 }
 
 /// The scanner's side of one establishment: dial the payload's address on
-/// the pairing ALPN, run the exchange, and assemble the same connection
-/// state as the accept side, mirrored.
+/// the pairing ALPN, run the exchange, verify the inviter's proofs — the
+/// payload's and the response's — and assemble the same connection state as
+/// the accept side, mirrored.
 ///
 /// The runtime lock is taken *per phase* and never held across the network
 /// round-trip — mirroring the accept side, which reads the request before
@@ -312,12 +504,14 @@ pub(crate) async fn establish_via_dialogue(
     identity: PdnId,
     payload: &InvitePayload,
 ) -> Result<()> {
-    // A brief lock for the hosted check and the dial handle (a cheap
-    // snapshot); released before any network I/O.
-    let dial = {
+    // A brief lock for the hosted check, the dial handle (a cheap
+    // snapshot) and this side's proof; released before any network I/O.
+    let (dial, scanner) = {
         let state = state.lock().await;
-        state.hosted(identity)?;
-        state.node.dial_handle()
+        (
+            state.node.dial_handle(),
+            prove_as_scanner(&state, identity, payload).await?,
+        )
     };
 
     // Network — no lock held. Dial before minting `own`, so an unreachable
@@ -351,6 +545,8 @@ pub(crate) async fn establish_via_dialogue(
                 version: INVITE_FORMAT_VERSION,
                 secret: payload.secret,
                 scanner: identity,
+                kel: scanner.kel,
+                proof: scanner.proof,
                 scanner_addr: dial.addr(),
                 ticket,
             },
@@ -359,9 +555,17 @@ pub(crate) async fn establish_via_dialogue(
         send.finish()?;
         // Refusals are uniform by design: the connection just closes, and
         // this read fails without saying why.
-        read_message(&mut recv)
-            .await
-            .context("establishment refused by the inviter")
+        let response: PairingResponse = read_message(&mut recv).await.context(DialogueRefused {
+            dialogue: "establishment",
+            by: "the inviter",
+        })?;
+        verify_response(
+            payload,
+            &response,
+            &scanner.answered,
+            scanner.known.as_ref(),
+        )?;
+        Ok(response)
     }
     .await;
     let response = match response {
@@ -391,6 +595,87 @@ pub(crate) async fn establish_via_dialogue(
     .await
 }
 
+/// A scanner's evidence for the identity it presents, made before its
+/// request leaves: its identity's log and proof, and the statement the
+/// inviter's answer must prove — with the inviter's log as this side last
+/// stored it, which the inviter's must extend.
+pub(crate) struct ScannerProof {
+    pub(crate) kel: KeyEventLog,
+    pub(crate) proof: PdnIdentityProof,
+    pub(crate) answered: Vec<u8>,
+    pub(crate) known: Option<KeyEventLog>,
+}
+
+/// Prove hosted `identity` as the scanner of `payload` from this runtime's
+/// endpoint — under the lock the caller holds, and before anything is
+/// created, so a device without the identity's keys fails without residue.
+pub(crate) async fn prove_as_scanner(
+    state: &State,
+    identity: PdnId,
+    payload: &InvitePayload,
+) -> Result<ScannerProof> {
+    state.hosted(identity)?;
+    let transcript = |signer| {
+        Transcript {
+            signer,
+            secret: &payload.secret,
+            inviter: payload.inviter,
+            inviter_endpoint: NodeId::from_bytes(*payload.inviter_addr.id.as_bytes()),
+            scanner: Some((identity, state.node.node_id())),
+        }
+        .to_bytes()
+    };
+    let (kel, proof) = prove_as(state, identity, &transcript(Signer::Scanner)?).await?;
+    Ok(ScannerProof {
+        kel,
+        proof,
+        answered: transcript(Signer::Inviter)?,
+        known: stored_peer_log(state, identity, payload.inviter).await?,
+    })
+}
+
+/// Check the payload's invite proof against `kel`, the log the inviter
+/// handed over — in the response, or ahead of it in a mailbox package —
+/// which must extend `known`, the inviter's log as this side last stored
+/// it.
+pub(crate) fn verify_invite(
+    payload: &InvitePayload,
+    kel: &KeyEventLog,
+    known: Option<&KeyEventLog>,
+) -> Result<()> {
+    let inviter_endpoint = NodeId::from_bytes(*payload.inviter_addr.id.as_bytes());
+    let invited = invite_statement(&payload.secret, payload.inviter, inviter_endpoint)?;
+    ensure!(
+        proves(payload.inviter, kel, known, &invited, &payload.proof),
+        "the inviter did not prove it is {}",
+        payload.inviter
+    );
+    Ok(())
+}
+
+/// Check both of the inviter's proofs a response completes: its answer
+/// over the full transcript (`answered`) and the payload's invite proof,
+/// each under the log the response carries — which must extend `known`.
+pub(crate) fn verify_response(
+    payload: &InvitePayload,
+    response: &PairingResponse,
+    answered: &[u8],
+    known: Option<&KeyEventLog>,
+) -> Result<()> {
+    ensure!(
+        proves(
+            payload.inviter,
+            &response.kel,
+            known,
+            answered,
+            &response.proof
+        ),
+        "the inviter did not prove it is {}",
+        payload.inviter
+    );
+    verify_invite(payload, &response.kel, known)
+}
+
 /// Create-or-reuse this side's own metadata store toward `peer`: the
 /// cached pair first, then the directory's own-kind write ticket — so
 /// re-establishment and linked devices converge on one replica — and only
@@ -399,7 +684,7 @@ pub(crate) async fn establish_via_dialogue(
 /// The bool is `true` only when a fresh replica was created — so the caller
 /// can forget it if establishment then fails before commit, while a reused
 /// or directory-imported replica (which other devices depend on) survives.
-async fn own_store_toward(
+pub(crate) async fn own_store_toward(
     state: &State,
     identity: PdnId,
     peer: PdnId,
@@ -422,7 +707,7 @@ async fn own_store_toward(
 /// contacts), record the counterparty among the directory's connections
 /// records, publish the pair's tickets in the same directory under the
 /// per-connection kinds, and cache the pair for the grant surface.
-async fn assemble_connection(
+pub(crate) async fn assemble_connection(
     state: &mut State,
     identity: PdnId,
     peer: PdnId,
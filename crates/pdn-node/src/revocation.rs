@@ -0,0 +1,580 @@
+//! Device revocation: removing a device from an identity so it stops
+//! syncing the identity's stores — not only stops being listed.
+//!
+//! A revoked device still holds every ticket it was handed: write tickets
+//! to the directory, the data namespace and the identity's own connection
+//! metadata stores, and the read tickets of the counterparties' stores.
+//! Removing its record is therefore four acts, all local to the revoking
+//! device. The key event log records a
+//! [`RemoveDevice`](pdn_layer::kel::KeyEventKind::RemoveDevice) event, so
+//! the device's key speaks for the identity no more. The directory records
+//! the revocation — a `revoked/` record beside the tombstone over the
+//! device record — which the access book on every device that has seen it
+//! turns into a refusal on each of the identity's replicas. Every
+//! connection's own metadata store withdraws the device from its published
+//! set, so counterparties stop serving it the grants made to the identity.
+//! And the directory, the data namespace and the own metadata stores are
+//! **re-keyed**: copied onto fresh replicas whose namespace secrets the
+//! revoked device never held, the new tickets republished in the new
+//! directory and in every grant the identity made, and each retired
+//! replica left a successor record ([`Successor`]). A token the revoked
+//! device signed is void from the removal on, whatever its anchor, so each
+//! grant made under one is republished under the token signed again.
+//!
+//! A successor record proves who wrote it: a proof by the revoking
+//! device's key over the retired replica, its successor and the device
+//! hosting it. The revoked device can write such records too — it keeps
+//! the retired replicas' write secrets — so readers follow only a record
+//! whose proof verifies under a key the identity's log still authorizes,
+//! which excludes the revoked device's from the moment the removal event
+//! has reached the reader.
+//!
+//! The remaining devices follow the directory's successor: their connection
+//! armer sees it arrive in the retired directory, checks that it was
+//! written by a device the retired directory lists and has not revoked,
+//! with the key that device's record names, and runs the rekey dialogue
+//! ([`REKEY_ALPN`]) toward it. That device answers with write tickets to
+//! the new directory and data namespace only when the caller is a device
+//! the new directory lists — never to a revoked one. The follower then
+//! checks the new directory's own log — verified, certifying the identity,
+//! extending the retired log, and still authorizing the writer — before it
+//! moves over exactly as a linking device arrives, and drops the retired
+//! data replica.
+//!
+//! The counterparties follow the own stores' successors: their grant
+//! binders find the record in the store they read, check its proof against
+//! the identity's log as mirrored in that same store, and move the pair
+//! onto the successor's replica.
+//!
+//! What this does not reach: the device that incepted the identity holds
+//! the identity's key itself, not a device key, and no removal event takes
+//! that away — revoking it needs a rotation by the key's holder, which is
+//! the revoked device. A successor the revoked device writes before the
+//! removal event reaches a reader verifies there as well as the true one
+//! does; the window closes as the log syncs.
+
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{bail, ensure, Context, Result};
+use data_layer::{
+    own_ticket_kind, peer_ticket_kind, AcceptError, AddrInfoOptions, Connection,
+    ConnectionMetadata, ConnectionMetadataStore, DocTicket, EndpointAddr, EndpointId, NamespaceId,
+    PrivateMetadataStore, ProtocolHandler, ShareMode, Successor,
+};
+use futures_lite::StreamExt;
+use pdn_layer::kel::{KeyEventLog, KeyPair, KeyState};
+use pdn_types::{NodeId, PdnId};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::connections::open_pair;
+use crate::delegation::reissue_grants;
+use crate::error::DialogueRefused;
+use crate::identity::{
+    append_key_event, decode_key_events, mirror_key_events, read_key_event_log,
+    verified_key_event_log, NoSigningKeys, DATA_TICKET_KIND,
+};
+use crate::pairing::{read_message, write_message, StateSlot};
+use crate::runtime::{HostedIdentity, State};
+
+/// The rekey ALPN — registered at spawn beside pairing and linking.
+pub(crate) const REKEY_ALPN: &[u8] = b"/pdn/rekey/0";
+
+/// How long a follower waits for the new directory's first sync, and then
+/// for its key event log, before it gives up this attempt and tries again
+/// on the next change.
+const FOLLOW_CATCH_UP: Duration = Duration::from_secs(10);
+
+/// A remaining device's request: the successor directory it was pointed
+/// at. Who asks is the connection's authenticated peer identity.
+#[derive(Debug, Serialize, Deserialize)]
+struct RekeyRequest {
+    directory: NamespaceId,
+}
+
+/// The answer: write tickets to the successor directory and the identity's
+/// re-keyed data namespace — the linking reply's shape.
+#[derive(Debug, Serialize, Deserialize)]
+struct RekeyResponse {
+    directory: DocTicket,
+    data: DocTicket,
+}
+
+/// One connection's own store across the re-keying: the pair as it was,
+/// and the fresh replica replacing its `own`.
+struct MovedPair {
+    peer: PdnId,
+    pair: ConnectionMetadata,
+    moved: ConnectionMetadataStore,
+}
+
+/// Revoke `device` from hosted `identity` on this runtime — the body of
+/// [`IdentityService::revoke_device`](crate::IdentityService::revoke_device).
+/// Local acts only, under the one lock.
+///
+/// Idempotent: a device already revoked passes the membership check, so a
+/// revocation that failed part-way is finished by calling it again — every
+/// act below either repeats harmlessly or re-keys once more.
+pub(crate) async fn revoke_device(
+    state: &Arc<Mutex<State>>,
+    identity: PdnId,
+    device: NodeId,
+) -> Result<()> {
+    let mut guard = state.lock().await;
+    let own = guard.node.node_id();
+    ensure!(device != own, "a device cannot revoke itself");
+    let signer = guard
+        .keys
+        .device_signer(identity)
+        .cloned()
+        .ok_or(NoSigningKeys { identity })?;
+    let retired = &guard.hosted(identity)?.directory;
+    ensure!(
+        retired.list_devices().await?.contains(&device) || retired.is_revoked(device).await?,
+        "{device} is not a device of {identity}"
+    );
+
+    // The device's key leaves the log before its record leaves the
+    // directory: a retry after a failure in between still finds the key.
+    let (mut log, key_state) = verified_key_event_log(retired, identity).await?;
+    let revoked_key = retired
+        .device_record(device)
+        .await?
+        .and_then(|record| record.key)
+        .filter(|key| key_state.has_device(key));
+    if let Some(key) = revoked_key {
+        log.remove_device(&signer, &key)?;
+        append_key_event(
+            retired,
+            log.events().last().context("a log holds its removal")?,
+        )
+        .await?;
+    }
+    retired.remove_device(device).await?;
+    let peers = retired.list_connections().await?;
+
+    // Counterparties stop resolving the device as this identity's, and
+    // hold the log its successor proofs are checked against.
+    let mut pairs = Vec::new();
+    for peer in peers {
+        let pair = open_pair(&mut guard, identity, peer)
+            .await?
+            .with_context(|| format!("no connection metadata pair toward {peer}"))?;
+        pair.own.withdraw_device(device).await?;
+        mirror_key_events(&log, &pair.own).await?;
+        pairs.push((peer, pair));
+    }
+
+    // The re-keying: data first, so the successor directory is written
+    // with the new data ticket from the start.
+    guard.node.rekey_namespace(identity).await?;
+    let retired = &guard.hosted(identity)?.directory;
+    let successor = retired.rekeyed(&guard.node).await?;
+    let data = guard
+        .node
+        .share_ticket(
+            identity,
+            ShareMode::Write,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+    successor.put_ticket(DATA_TICKET_KIND, &data).await?;
+    let mut moved_pairs = Vec::with_capacity(pairs.len());
+    for (peer, pair) in pairs {
+        let moved = pair.own.rekeyed(&guard.node).await?;
+        republish_grants(&guard, identity, &moved).await?;
+        let ticket = moved
+            .share_ticket(ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
+            .await?;
+        successor
+            .put_ticket(&own_ticket_kind(&peer), &ticket)
+            .await?;
+        moved_pairs.push(MovedPair { peer, pair, moved });
+    }
+
+    // The swap: the successors are hosted and recorded before the retired
+    // replicas point at them, so a follower's rekey request finds them
+    // here.
+    let changes = successor.changes().await?;
+    let syncs = successor.syncs().await?;
+    guard.node.host_identity(identity, &successor)?;
+    if let Some(manifest) = &guard.manifest {
+        manifest.record(identity, successor.namespace())?;
+    }
+    let namespace = successor.namespace();
+    let retired = guard
+        .identities
+        .insert(
+            identity,
+            HostedIdentity {
+                directory: successor,
+            },
+        )
+        .context("a hosted identity has a directory")?;
+    for MovedPair { peer, pair, moved } in moved_pairs {
+        guard
+            .node
+            .host_connection(identity, peer, &moved, &pair.peer)?;
+        let pointer = successor_of(&signer, pair.own.namespace(), moved.namespace(), own);
+        guard.metadata_pairs.insert(
+            (identity, peer),
+            ConnectionMetadata {
+                own: moved,
+                peer: pair.peer,
+            },
+        );
+        pair.own.put_successor(&pointer).await?;
+    }
+    let pointer = successor_of(&signer, retired.directory.namespace(), namespace, own);
+    retired.directory.put_successor(&pointer).await?;
+    crate::connections::spawn_connection_armer(Arc::downgrade(state), identity, namespace, changes);
+    crate::identity::spawn_sync_observer(Arc::downgrade(state), identity, syncs);
+    Ok(())
+}
+
+/// Republish the grants `identity` made in a re-keyed own store: the grant
+/// of its own data under the re-keyed data's ticket — re-keying the data
+/// changes its ticket, not what was granted — and each grant made under a
+/// token the removed device signed under that token signed again
+/// ([`reissue_grants`]).
+async fn republish_grants(
+    state: &State,
+    identity: PdnId,
+    store: &ConnectionMetadataStore,
+) -> Result<()> {
+    let data = match store.read_grant(identity).await? {
+        Some((grant, _retired_ticket)) => {
+            let mode = if grant.write {
+                ShareMode::Write
+            } else {
+                ShareMode::Read
+            };
+            let ticket = state
+                .node
+                .share_ticket(identity, mode, AddrInfoOptions::RelayAndAddresses)
+                .await?;
+            Some(ticket)
+        }
+        None => None,
+    };
+    reissue_grants(state, identity, store, data.as_ref()).await
+}
+
+/// The successor record retiring `retired` in favor of `namespace`, hosted
+/// by `via` and proven by `signer`.
+fn successor_of(
+    signer: &KeyPair,
+    retired: NamespaceId,
+    namespace: NamespaceId,
+    via: NodeId,
+) -> Successor {
+    Successor {
+        namespace,
+        via,
+        proof: signer.prove(&Successor::statement(retired, namespace, via)),
+    }
+}
+
+/// Whether `successor`, found in the retired replica `retired`, is proven
+/// by a device of the identity `key_state` is the state of: signed over
+/// the statement naming `retired` by a key the log still authorizes.
+fn proven(key_state: &KeyState, retired: NamespaceId, successor: &Successor) -> bool {
+    key_state.verify_device_proof(
+        &Successor::statement(retired, successor.namespace, successor.via),
+        &successor.proof,
+    )
+}
+
+/// Whether `successor` was written by a device the retired directory
+/// still counts as `identity`'s: listed, not revoked, with the key its
+/// record names, and proven under the retired directory's log.
+async fn vouched_for(
+    retired: &PrivateMetadataStore,
+    key_state: &KeyState,
+    successor: &Successor,
+) -> Result<bool> {
+    let via = successor.via;
+    if retired.is_revoked(via).await? || !retired.list_devices().await?.contains(&via) {
+        return Ok(false);
+    }
+    let recorded = retired
+        .device_record(via)
+        .await?
+        .and_then(|record| record.key);
+    Ok(recorded == Some(successor.proof.key) && proven(key_state, retired.namespace(), successor))
+}
+
+/// Move hosted `identity` onto the directory a successor record of its
+/// retired directory names — the remaining device's half of a revocation,
+/// run by its connection armer. Only records [`vouched_for`] by the
+/// retired directory are tried, in turn, until one is followed. The lock
+/// is taken per phase and never held across the dialogue or the catch-up
+/// wait, as in linking. A failure leaves the identity on its retired
+/// directory, to be retried on the next change.
+pub(crate) async fn follow_successor(state: &Weak<Mutex<State>>, identity: PdnId) -> Result<()> {
+    let (dial, retired, candidates) = {
+        let strong = state.upgrade().context("the runtime is gone")?;
+        let guard = strong.lock().await;
+        let directory = &guard.hosted(identity)?.directory;
+        let (log, key_state) = verified_key_event_log(directory, identity).await?;
+        let mut candidates = Vec::new();
+        for successor in directory.successors().await? {
+            if vouched_for(directory, &key_state, &successor).await? {
+                candidates.push(successor);
+            }
+        }
+        (
+            guard.node.dial_handle(),
+            (directory.namespace(), log),
+            candidates,
+        )
+    };
+    let mut refused = None;
+    for successor in candidates {
+        match follow_one(state, identity, &dial, &retired, &successor).await {
+            Ok(()) => return Ok(()),
+            Err(err) => refused = Some(err),
+        }
+    }
+    Err(refused.unwrap_or_else(|| anyhow::anyhow!("no proven successor of {identity}'s directory")))
+}
+
+/// Follow one vouched-for `successor` of the directory `retired` names,
+/// whose log is the one `retired` carries.
+async fn follow_one(
+    state: &Weak<Mutex<State>>,
+    identity: PdnId,
+    dial: &data_layer::DialHandle,
+    retired: &(NamespaceId, KeyEventLog),
+    successor: &Successor,
+) -> Result<()> {
+    let via = EndpointAddr::new(EndpointId::from_bytes(successor.via.as_bytes())?);
+    let response = run_rekey_dialogue(dial, via.clone(), successor.namespace).await?;
+    ensure!(
+        response.directory.capability.id() == successor.namespace,
+        "the rekey reply names another directory"
+    );
+
+    let before_import = SystemTime::now();
+    let directory = {
+        let strong = state.upgrade().context("the runtime is gone")?;
+        let guard = strong.lock().await;
+        let mut ticket = response.directory;
+        ticket.nodes.push(via);
+        PrivateMetadataStore::import(&guard.node, ticket).await?
+    };
+    let checked = async {
+        directory
+            .wait_caught_up(before_import, FOLLOW_CATCH_UP)
+            .await
+            .context("the successor directory did not catch up in time")?;
+        check_successor_log(&directory, identity, retired, successor).await
+    }
+    .await;
+    if let Err(err) = checked {
+        if let Some(strong) = state.upgrade() {
+            let _ = strong
+                .lock()
+                .await
+                .node
+                .forget_doc(directory.namespace())
+                .await;
+        }
+        return Err(err);
+    }
+
+    let strong = state.upgrade().context("the runtime is gone")?;
+    let mut guard = strong.lock().await;
+    let changes = directory.changes().await?;
+    let syncs = directory.syncs().await?;
+    guard.node.host_identity(identity, &directory)?;
+    let data = guard.node.import_namespace(identity, response.data).await?;
+    if let Some(manifest) = &guard.manifest {
+        manifest.record(identity, directory.namespace())?;
+    }
+    let namespace = directory.namespace();
+    guard
+        .identities
+        .insert(identity, HostedIdentity { directory });
+    // The cached pairs address the retired own stores; the next sweep
+    // re-opens them from the new directory's tickets.
+    guard
+        .metadata_pairs
+        .retain(|(paired, _peer), _pair| *paired != identity);
+    if let Some(retired) = data.displaced_namespace() {
+        let _gone_or_already_gone = guard.node.forget_doc(retired).await;
+    }
+    crate::connections::spawn_connection_armer(state.clone(), identity, namespace, changes);
+    crate::identity::spawn_sync_observer(state.clone(), identity, syncs);
+    Ok(())
+}
+
+/// Check the key event log of an imported successor directory, waiting up
+/// to [`FOLLOW_CATCH_UP`] for it to arrive as far as the retired one: it
+/// must verify, certify `identity`, begin with every event of the retired
+/// directory's log, and still authorize the key `successor` was proven by.
+async fn check_successor_log(
+    directory: &PrivateMetadataStore,
+    identity: PdnId,
+    (retired, retired_log): &(NamespaceId, KeyEventLog),
+    successor: &Successor,
+) -> Result<()> {
+    let mut changes = directory.changes().await?;
+    let deadline = Instant::now() + FOLLOW_CATCH_UP;
+    loop {
+        let log = read_key_event_log(directory, identity).await?;
+        if log.events().len() >= retired_log.events().len() {
+            ensure!(
+                log.events().starts_with(retired_log.events()),
+                "the successor directory's key event log does not extend the retired one's"
+            );
+            let (_log, key_state) = verified_key_event_log(directory, identity).await?;
+            ensure!(
+                proven(&key_state, *retired, successor),
+                "the successor directory's key event log does not authorize its writer"
+            );
+            return Ok(());
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match tokio::time::timeout(remaining, changes.next()).await {
+            Ok(Some(Ok(()))) => {}
+            Ok(Some(Err(err))) => return Err(err.into()),
+            Ok(None) | Err(_) => {
+                bail!("the successor directory's key event log did not arrive in time")
+            }
+        }
+    }
+}
+
+/// Move `identity`'s pair toward `peer` onto the successor of the peer's
+/// store `peer_store`, when a revocation of the peer's retired it — the
+/// counterparty's half of re-keying a connection, run by its grant binder.
+/// A successor is followed when a device the store publishes hosts it and
+/// its proof verifies under the peer's log as mirrored in the same store.
+/// Answers whether the pair moved.
+pub(crate) async fn follow_peer_successor(
+    state: &mut State,
+    identity: PdnId,
+    peer: PdnId,
+    peer_store: &ConnectionMetadataStore,
+) -> Result<bool> {
+    let successors = peer_store.successors().await?;
+    if successors.is_empty() {
+        return Ok(false);
+    }
+    let key_state = decode_key_events(peer_store.key_events().await?, peer).verify()?;
+    let published = peer_store.published_devices().await?;
+    let retired = peer_store.namespace();
+    let Some(successor) = successors.into_iter().find(|successor| {
+        published.contains(&successor.via) && proven(&key_state, retired, successor)
+    }) else {
+        return Ok(false);
+    };
+    let directory = &state.hosted(identity)?.directory;
+    directory
+        .put_ticket(&peer_ticket_kind(&peer), &successor.read_ticket()?)
+        .await?;
+    open_pair(state, identity, peer).await?;
+    Ok(true)
+}
+
+/// The network half of following a successor: dial the device that wrote
+/// it on the rekey ALPN and read the tickets it answers with.
+async fn run_rekey_dialogue(
+    dial: &data_layer::DialHandle,
+    via: EndpointAddr,
+    directory: NamespaceId,
+) -> Result<RekeyResponse> {
+    let connection = dial
+        .connect(via, REKEY_ALPN)
+        .await
+        .context("could not reach the revoking device")?;
+    let response: RekeyResponse = async {
+        let (mut send, mut recv) = connection.open_bi().await?;
+        write_message(&mut send, &RekeyRequest { directory }).await?;
+        send.finish()?;
+        read_message(&mut recv).await.context(DialogueRefused {
+            dialogue: "rekey",
+            by: "the revoking device",
+        })
+    }
+    .await?;
+    connection.close(0u32.into(), b"done");
+    Ok(response)
+}
+
+/// The accept side of the rekey dialogue, registered at `Runtime::spawn`
+/// beside the ceremonies' handlers.
+#[derive(Debug, Clone)]
+pub(crate) struct RekeyHandler {
+    state: StateSlot,
+}
+
+impl RekeyHandler {
+    /// A handler with an unfilled state slot; [`Runtime::spawn`] fills the
+    /// slot right after the node comes up.
+    ///
+    /// [`Runtime::spawn`]: crate::Runtime::spawn
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::default(),
+        }
+    }
+
+    /// The slot to fill with the spawned runtime's state.
+    pub(crate) fn slot(&self) -> StateSlot {
+        Arc::clone(&self.state)
+    }
+
+    /// Answer one rekey request. `None` is a refusal, uniform as in the
+    /// ceremonies: a directory this runtime does not host, or a caller its
+    /// successor does not list as a device — a revoked one above all.
+    async fn serve(&self, connection: &Connection) -> Option<()> {
+        let (mut send, mut recv) = connection.accept_bi().await.ok()?;
+        let request: RekeyRequest = read_message(&mut recv).await.ok()?;
+        let caller = NodeId::from_bytes(*connection.remote_id().as_bytes());
+        let response = {
+            let state = self.state.get()?.upgrade()?;
+            let state = state.lock().await;
+            let (identity, hosted) = state
+                .identities
+                .iter()
+                .find(|(_identity, hosted)| hosted.directory.namespace() == request.directory)?;
+            let directory = &hosted.directory;
+            if directory.is_revoked(caller).await.ok()?
+                || !directory.list_devices().await.ok()?.contains(&caller)
+            {
+                return None;
+            }
+            RekeyResponse {
+                directory: directory
+                    .share_ticket(ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
+                    .await
+                    .ok()?,
+                data: state
+                    .node
+                    .share_ticket(
+                        *identity,
+                        ShareMode::Write,
+                        AddrInfoOptions::RelayAndAddresses,
+                    )
+                    .await
+                    .ok()?,
+            }
+        };
+        write_message(&mut send, &response).await.ok()?;
+        send.finish().ok()?;
+        connection.closed().await;
+        Some(())
+    }
+}
+
+impl ProtocolHandler for RekeyHandler {
+    async fn accept(&self, connection: Connection) -> Result<(), AcceptError> {
+        if self.serve(&connection).await.is_none() {
+            connection.close(0u32.into(), b"");
+        }
+        Ok(())
+    }
+}
@@ -3,27 +3,34 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use data_layer::{
     AuthorId, ConnectionMetadata, NamespaceId, PrivateMetadataStore, SpawnOptions, SyncNode,
 };
 use pdn_types::{NodeId, PdnId};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
+use crate::claims::RuntimeClaimsService;
 use crate::connections::RuntimeConnectionsService;
-use crate::data::RuntimeDataService;
-use crate::identity::RuntimeIdentityService;
+use crate::data::{RuntimeDataService, RuntimeEntries};
+use crate::events::{Observed, RuntimeEvent, EVENT_FEED_CAPACITY};
+use crate::identity::{RuntimeIdentityService, DATA_TICKET_KIND};
+use crate::keystore::KeyStore;
 use crate::linking::{LinkingHandler, LINKING_ALPN};
+use crate::mailbox::MailboxCeremony;
+use crate::manifest::HostedManifest;
 use crate::pairing::{PairingHandler, PendingInvites, PAIRING_ALPN};
+use crate::revocation::{RekeyHandler, REKEY_ALPN};
 use crate::sync::RuntimeSyncService;
 
 /// An operation addressed an identity this runtime does not host: `identity`
-/// was neither created nor linked here. Downcast from the `anyhow::Error`
-/// of identity-addressed service operations. Data-namespace operations
-/// report the analogous [`data_layer::UnknownIssuer`] instead — namespaces
-/// are registered by creation or import, not by hosting.
+/// was neither created nor linked here. Carried by
+/// [`ServiceError::NotHosted`](crate::ServiceError) from identity-addressed
+/// service operations. Data-namespace operations report the analogous
+/// [`data_layer::UnknownIssuer`] instead — namespaces are registered by
+/// creation or import, not by hosting.
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 #[error("identity not hosted on this runtime: {identity}")]
 pub struct UnknownIdentity {
@@ -64,11 +71,24 @@ pub(crate) struct State {
     pub(crate) node: SyncNode,
     /// The author for this runtime's data-namespace writes. The author
     /// dimension carries no meaning (see [`pdn_types::EntryInfo`]), so one
-    /// per runtime suffices.
+    /// per runtime suffices: the node's default author, stable across
+    /// restarts on a storage root.
     pub(crate) author: AuthorId,
     /// The hosted identities' store handles, keyed by identity: exactly
-    /// those created or linked on this runtime.
+    /// those created or linked on this runtime — or, on a storage root, on
+    /// an earlier run of it.
     pub(crate) identities: HashMap<PdnId, HostedIdentity>,
+    /// The secret halves of the identity keys this device holds — exactly
+    /// those of the identities it incepted.
+    pub(crate) keys: KeyStore,
+    /// When this runtime last finished a successful directory sync session
+    /// with each device, as its hosted directories' sync observers saw it.
+    /// Starts empty at every spawn: what outlives a restart is the coarser
+    /// sighting each observer records in the directory.
+    pub(crate) last_sync: HashMap<NodeId, SystemTime>,
+    /// Where a hosted identity is recorded so a restart can host it again;
+    /// `None` on an in-memory runtime, whose identities end with it.
+    pub(crate) manifest: Option<HostedManifest>,
     /// The pairing protocol's pending invites, keyed by secret bytes. In
     /// runtime memory on purpose: an invite is a live ceremony that does
     /// not survive a restart, and every operation on the set is a map
@@ -78,6 +98,10 @@ pub(crate) struct State {
     /// same set, deliberately separate from pairing's: a secret minted for
     /// one ceremony must never verify in the other.
     pub(crate) pending_linking_invites: PendingInvites,
+    /// Mailbox establishments in progress, keyed by their invites' secret
+    /// bytes — on either side of the ceremony. In runtime memory for the
+    /// reason the pending invites are: a restart ends the ceremony.
+    pub(crate) mailboxes: HashMap<[u8; 32], MailboxCeremony>,
     /// Connection metadata pairs opened on this runtime, keyed by
     /// `(hosted identity, counterparty)`: filled by establishment on the
     /// pairing device, by each hosted identity's connection armer as pair
@@ -98,6 +122,14 @@ pub(crate) struct State {
     /// itself brought in, so a namespace imported any other way is never
     /// dropped from under its owner.
     pub(crate) bound_grants: HashMap<(PdnId, PdnId, PdnId), NamespaceId>,
+    /// The change feed's sending half; every emitter reaches it through
+    /// this state (see [`crate::events`]).
+    pub(crate) events: broadcast::Sender<RuntimeEvent>,
+    /// Each hosted identity's directory as the connection armer last saw
+    /// it — the baseline its connection and device events diff against.
+    /// Taken when the identity comes to be hosted, kept across a
+    /// revocation's move onto a successor directory.
+    pub(crate) observed: HashMap<PdnId, Observed>,
 }
 
 impl State {
@@ -109,6 +141,56 @@ impl State {
     }
 }
 
+/// Host again every identity the manifest records — the restart half of
+/// `create` and `link`, in the same order: the directory first, armed for
+/// session classification before the data binding exists, then the data
+/// namespace (named by the directory's `data` ticket), then the armer.
+/// Everything else is the armer's first sweep: the pairs reopen from the
+/// directory's per-connection tickets and get their grant binders, which
+/// re-import the granted namespaces.
+///
+/// A recorded identity that cannot be hosted again fails the spawn rather
+/// than coming up silently absent: its replicas are on disk or the root is
+/// damaged, and either way the caller should hear of it.
+async fn rehost_recorded(state: &Arc<Mutex<State>>) -> Result<()> {
+    let mut guard = state.lock().await;
+    let recorded = match &guard.manifest {
+        Some(manifest) => manifest.load()?,
+        None => return Ok(()),
+    };
+    for entry in recorded {
+        let identity = entry.identity;
+        let directory = PrivateMetadataStore::open(&guard.node, entry.directory)
+            .await
+            .with_context(|| format!("cannot reopen the directory of {identity}"))?;
+        let data = directory
+            .get_ticket(DATA_TICKET_KIND)
+            .await?
+            .with_context(|| format!("the directory of {identity} names no data namespace"))?;
+        let changes = directory.changes().await?;
+        let syncs = directory.syncs().await?;
+        guard.node.host_identity(identity, &directory)?;
+        guard
+            .node
+            .open_namespace(identity, data.capability.id())
+            .await
+            .with_context(|| format!("cannot reopen the data namespace of {identity}"))?;
+        let observed = Observed::read(&directory).await?;
+        guard.observed.insert(identity, observed);
+        guard
+            .identities
+            .insert(identity, HostedIdentity { directory });
+        crate::connections::spawn_connection_armer(
+            Arc::downgrade(state),
+            identity,
+            entry.directory,
+            changes,
+        );
+        crate::identity::spawn_sync_observer(Arc::downgrade(state), identity, syncs);
+    }
+    Ok(())
+}
+
 /// The embeddable runtime core: one running node plus the identities it
 /// hosts. Spawn one per process (hosts) or several (in-process tests),
 /// drive it through its services — [`identity`](Self::identity),
@@ -122,8 +204,12 @@ impl State {
 /// through the data-layer assembly slot, and handed the shared state right
 /// after.
 pub struct Runtime {
-    /// Cached at spawn; stable for the runtime's lifetime.
+    /// Cached at spawn; stable for the runtime's lifetime, and across
+    /// restarts on the same [`SpawnOptions::storage`] root.
     node_id: NodeId,
+    /// The change feed, subscribed to without taking the state lock; the
+    /// state holds a clone to emit through.
+    events: broadcast::Sender<RuntimeEvent>,
     pub(crate) state: Arc<Mutex<State>>,
 }
 
@@ -138,30 +224,51 @@ impl Runtime {
 
     /// [`spawn`](Self::spawn), tuned by `options` — passed through to the
     /// node assembly.
+    ///
+    /// With a [`SpawnOptions::storage`] root, every identity hosted on an
+    /// earlier run over the same root is hosted again before this returns:
+    /// its directory and data namespace reopened, classification re-armed,
+    /// and its connection armer restarted — which reopens the connection
+    /// pairs from the directory's tickets and puts grant binders back on
+    /// them. The runtime resumes serving and receiving grants with no act
+    /// of the caller's; only the ceremonies' pending invites are gone.
     pub async fn spawn_with(options: SpawnOptions) -> Result<Self> {
+        let manifest = options.storage.as_deref().map(HostedManifest::at);
+        let keys = KeyStore::load(options.storage.as_deref())?;
         let pairing = PairingHandler::new();
         let pairing_slot = pairing.slot();
         let linking = LinkingHandler::new();
         let linking_slot = linking.slot();
+        let rekey = RekeyHandler::new();
+        let rekey_slot = rekey.slot();
         let node = SyncNode::spawn_with(
             vec![
                 (PAIRING_ALPN.to_vec(), Box::new(pairing)),
                 (LINKING_ALPN.to_vec(), Box::new(linking)),
+                (REKEY_ALPN.to_vec(), Box::new(rekey)),
             ],
             options,
         )
         .await?;
-        let author = node.create_author().await?;
+        let author = node.default_author().await?;
         let node_id = node.node_id();
+        let events = broadcast::channel(EVENT_FEED_CAPACITY).0;
+        crate::events::spawn_entry_relay(node.subscribe_entries(), events.clone());
         let state = Arc::new(Mutex::new(State {
             node,
             author,
             identities: HashMap::new(),
+            keys,
+            last_sync: HashMap::new(),
+            manifest,
             pending_invites: PendingInvites::default(),
             pending_linking_invites: PendingInvites::default(),
+            mailboxes: HashMap::new(),
             metadata_pairs: HashMap::new(),
             grant_binders: HashSet::new(),
             bound_grants: HashMap::new(),
+            events: events.clone(),
+            observed: HashMap::new(),
         }));
         pairing_slot
             .set(Arc::downgrade(&state))
@@ -169,7 +276,15 @@ impl Runtime {
         linking_slot
             .set(Arc::downgrade(&state))
             .map_err(|_already_filled| anyhow::anyhow!("linking state slot filled twice"))?;
-        Ok(Self { node_id, state })
+        rekey_slot
+            .set(Arc::downgrade(&state))
+            .map_err(|_already_filled| anyhow::anyhow!("rekey state slot filled twice"))?;
+        rehost_recorded(&state).await?;
+        Ok(Self {
+            node_id,
+            events,
+            state,
+        })
     }
 
     /// This runtime's node id (its endpoint id), stable from spawn to
@@ -197,11 +312,21 @@ impl Runtime {
         RuntimeDataService::new(self)
     }
 
+    /// The claims service: claims as entries of their issuer's namespace.
+    pub fn claims(&self) -> RuntimeClaimsService<RuntimeEntries<'_>> {
+        RuntimeClaimsService::new(self)
+    }
+
     /// The sync service: node id and hosted identities.
     pub fn sync(&self) -> RuntimeSyncService<'_> {
         RuntimeSyncService::new(self)
     }
 
+    /// A fresh subscription to the change feed.
+    pub(crate) fn subscribe(&self) -> crate::events::RuntimeEvents {
+        crate::events::RuntimeEvents::new(self.events.subscribe())
+    }
+
     /// Shut the node down, closing the endpoint and all protocols.
     /// Consumes the runtime; services borrow it, so none can outlive this.
     pub async fn shutdown(self) -> Result<()> {
@@ -221,6 +346,7 @@ impl Runtime {
                 }
             }
         };
-        state.node.shutdown().await
+        state.node.shutdown().await?;
+        Ok(())
     }
 }
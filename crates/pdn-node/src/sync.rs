@@ -1,12 +1,14 @@
-//! The sync service: what this runtime is on the network and whom it
-//! hosts.
+//! The sync service: what this runtime is on the network, whom it hosts,
+//! and what changes under them.
 
-use anyhow::Result;
 use pdn_types::{NodeId, PdnId};
 
+use crate::error::ServiceError;
+use crate::events::RuntimeEvents;
 use crate::runtime::Runtime;
 
-/// Reporting the runtime's node id and hosted identities.
+/// Reporting the runtime's node id and hosted identities, and feeding its
+/// changes to whoever watches.
 #[allow(async_fn_in_trait)]
 pub trait SyncService {
     /// This runtime's node id — its endpoint id, stable for the runtime's
@@ -15,7 +17,13 @@ pub trait SyncService {
 
     /// The identities this runtime hosts: exactly those created or linked
     /// on it, in no particular order.
-    async fn hosted_identities(&self) -> Result<Vec<PdnId>>;
+    async fn hosted_identities(&self) -> Result<Vec<PdnId>, ServiceError>;
+
+    /// Subscribe to the change feed: entries written or synced into data
+    /// namespaces bound here, connections added and removed, grants
+    /// received and withdrawn, and devices linked — each from the moment of
+    /// subscribing (see [`crate::events`]).
+    fn events(&self) -> RuntimeEvents;
 }
 
 /// The production [`SyncService`], backed by the runtime's `data-layer`
@@ -36,8 +44,12 @@ impl SyncService for RuntimeSyncService<'_> {
         self.runtime.node_id()
     }
 
-    async fn hosted_identities(&self) -> Result<Vec<PdnId>> {
+    async fn hosted_identities(&self) -> Result<Vec<PdnId>, ServiceError> {
         let state = self.runtime.state.lock().await;
         Ok(state.identities.keys().copied().collect())
     }
+
+    fn events(&self) -> RuntimeEvents {
+        self.runtime.subscribe()
+    }
 }
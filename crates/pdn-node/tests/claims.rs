@@ -0,0 +1,176 @@
+//! The claims service over the in-memory backend: claims written at their
+//! canonical path read back by subject and attribute name, list by subject
+//! and by issuer, replicate as entries do, and keep a pinned payload
+//! encoding — a stored claim of another version is refused by number.
+
+use anyhow::Result;
+use data_layer::{claim_id_of, DataLayer as _, MemNetwork};
+use pdn_layer::{AccessMode, Attribute, AttributeValue, Capability, Claim};
+use pdn_node::{
+    claim_path, ClaimsService as _, InvalidAttributeName, RuntimeClaimsService, ServiceError,
+    UnsupportedClaimVersion, CLAIM_FORMAT_VERSION,
+};
+use pdn_types::{EntryPath, OperationalKey, PdnId, PdnIdentityProof};
+use test_utils::ids;
+
+/// A claim by `issued_by` about `about`: a string attribute, readable by
+/// `about`. The proof is a fixed placeholder — the service stores proofs
+/// as given.
+fn claim(issued_by: PdnId, about: PdnId, name: &str, value: &str) -> Claim {
+    Claim {
+        about,
+        issued_by,
+        proof_of_issued_by: PdnIdentityProof {
+            key: OperationalKey::from_bytes([0x11; 32]),
+            signature: vec![1, 2],
+        },
+        attribute: Attribute {
+            name: name.to_owned(),
+            value: AttributeValue::String(value.to_owned()),
+        },
+        capability: Capability {
+            holders: vec![about],
+            access: AccessMode::Read,
+            expires_at: None,
+        },
+    }
+}
+
+#[tokio::test]
+async fn claims_read_back_by_attribute_and_list_by_subject_and_issuer() -> Result<()> {
+    let network = MemNetwork::new();
+    let phone = network.device(ids::ALICE)?;
+    let laptop = network.device(ids::ALICE)?;
+    let claims = RuntimeClaimsService::with_entries(phone.clone());
+
+    let email = claim(ids::ALICE, ids::BOB, "email", "bob@example.org");
+    let nickname = claim(ids::ALICE, ids::BOB, "nickname", "bobby");
+    let employer = claim(ids::ALICE, ids::CAROL, "employer", "Example Ltd");
+    let email_id = claims.write(&email).await?;
+    let nickname_id = claims.write(&nickname).await?;
+    let employer_id = claims.write(&employer).await?;
+
+    // The id is the grant vocabulary's id of the canonical path.
+    assert_eq!(
+        email_id,
+        claim_id_of(&ids::ALICE, &claim_path(&ids::BOB, "email")?)
+    );
+    assert_eq!(
+        claims.get(ids::ALICE, ids::BOB, "email").await?,
+        Some(email.clone())
+    );
+    assert_eq!(claims.get(ids::BOB, ids::BOB, "email").await?, None);
+    assert_eq!(claims.get(ids::ALICE, ids::CAROL, "email").await?, None);
+
+    let about_bob = claims.list_about(ids::ALICE, ids::BOB).await?;
+    assert_eq!(about_bob.len(), 2);
+    assert!(about_bob.contains(&email_id) && about_bob.contains(&nickname_id));
+    let mine = claims.list_mine(ids::ALICE).await?;
+    assert_eq!(mine.len(), 3);
+    assert!(mine.contains(&employer_id));
+
+    // Rewriting the same subject and attribute replaces the claim in place.
+    let changed = claim(ids::ALICE, ids::BOB, "email", "b@example.org");
+    assert_eq!(claims.write(&changed).await?, email_id);
+    assert_eq!(
+        claims.get(ids::ALICE, ids::BOB, "email").await?,
+        Some(changed)
+    );
+    assert_eq!(claims.list_mine(ids::ALICE).await?.len(), 3);
+
+    // Entries that are not claims are not listed as claims.
+    phone
+        .insert_entry(ids::ALICE, &EntryPath::new("claims/notes")?, b"x")
+        .await?;
+    assert_eq!(claims.list_mine(ids::ALICE).await?.len(), 3);
+
+    // Claims replicate to the issuer's other devices as entries do.
+    network.settle()?;
+    let on_laptop = RuntimeClaimsService::with_entries(laptop);
+    assert_eq!(
+        on_laptop.get(ids::ALICE, ids::CAROL, "employer").await?,
+        Some(employer)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn an_attribute_name_that_is_not_a_path_component_is_refused() -> Result<()> {
+    let network = MemNetwork::new();
+    let claims = RuntimeClaimsService::with_entries(network.device(ids::ALICE)?);
+
+    for name in ["", "contact/email"] {
+        let err = claims
+            .write(&claim(ids::ALICE, ids::BOB, name, "x"))
+            .await
+            .unwrap_err();
+        let ServiceError::InvalidAttributeName(InvalidAttributeName { name: refused }) = &err
+        else {
+            anyhow::bail!("{name:?} was not refused as a name: {err}");
+        };
+        assert_eq!(refused, name);
+    }
+    assert!(claims.list_mine(ids::ALICE).await?.is_empty());
+    let err = claims
+        .get(ids::ALICE, ids::BOB, "contact/email")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ServiceError::InvalidAttributeName(_)),
+        "{err}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn the_payload_encoding_is_pinned_and_versioned() -> Result<()> {
+    let network = MemNetwork::new();
+    let device = network.device(ids::ALICE)?;
+    let claims = RuntimeClaimsService::with_entries(device.clone());
+
+    let email = claim(ids::ALICE, ids::BOB, "email", "bob@example.org");
+    claims.write(&email).await?;
+    let path = claim_path(&ids::BOB, "email")?;
+
+    // The stored bytes, pinned: a change here breaks every stored claim.
+    assert_eq!(CLAIM_FORMAT_VERSION, 1);
+    let alice = "a1".repeat(32);
+    let bob = "b0".repeat(32);
+    let key = "11".repeat(32);
+    let expected = format!(
+        r#"{{"version":1,"claim":{{"about":"{bob}","issued_by":"{alice}","proof_of_issued_by":{{"key":"{key}","signature":[1,2]}},"attribute":{{"name":"email","value":{{"String":"bob@example.org"}}}},"capability":{{"holders":["{bob}"],"access":"Read","expires_at":null}}}}}}"#
+    );
+    let stored = device.get_entry(ids::ALICE, &path).await?;
+    assert_eq!(stored.as_deref(), Some(expected.as_bytes()));
+
+    // A stored claim of another version is refused by its number.
+    device
+        .insert_entry(
+            ids::ALICE,
+            &path,
+            br#"{"version":99,"claim":"whatever comes next"}"#,
+        )
+        .await?;
+    let err = claims.get(ids::ALICE, ids::BOB, "email").await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ServiceError::UnsupportedClaimVersion(UnsupportedClaimVersion { version: 99 })
+        ),
+        "{err}"
+    );
+
+    // A claim stored where it does not belong is not served as the claim
+    // of that place.
+    let misplaced = claim(ids::ALICE, ids::CAROL, "email", "carol@example.org");
+    device
+        .insert_entry(
+            ids::ALICE,
+            &path,
+            &serde_json::to_vec(&serde_json::json!({ "version": 1, "claim": misplaced }))?,
+        )
+        .await?;
+    let err = claims.get(ids::ALICE, ids::BOB, "email").await.unwrap_err();
+    assert!(matches!(err, ServiceError::Storage(_)), "{err}");
+    Ok(())
+}
@@ -14,6 +14,7 @@
 
 use anyhow::{ensure, Context, Result};
 use data_layer::{Connection, DocTicket, PrivateMetadataStore, RecvStream, SendStream, SyncNode};
+use pdn_layer::kel::{KeyEventLog, KeyPair};
 use pdn_node::{
     ConnectionsService as _, IdentityService as _, InvitePayload, LinkingPayload, PeerGrant,
     Runtime,
@@ -47,33 +48,51 @@ pub async fn read_frame(recv: &mut RecvStream) -> Result<Vec<u8>> {
     Ok(bytes)
 }
 
+/// The raw linking request: the protocol's `{version, secret, key}`
+/// message (postcard encodes the struct exactly as this tuple).
+fn linking_request(payload: &LinkingPayload, key: &KeyPair) -> Result<Vec<u8>> {
+    Ok(postcard::to_stdvec(&(
+        payload.version,
+        payload.secret,
+        key.public(),
+    ))?)
+}
+
 /// Run the linking dialogue raw from a bare node: dial the payload's
-/// address on the linking ALPN, present the secret, and return the reply's
-/// directory and data write tickets — what `link` does, without a runtime
-/// around it. The request mirrors the protocol's `{version, secret}`
-/// message (postcard encodes the struct exactly as this tuple), the reply
-/// its `{directory, data}`.
+/// address on the linking ALPN, present the secret and a fresh device key
+/// — the inviter refuses to authorize one key twice — and return the
+/// reply's directory and data write tickets: what `link` does, without a
+/// runtime around it.
 pub async fn dial_linking(
     node: &SyncNode,
     payload: &LinkingPayload,
 ) -> Result<(DocTicket, DocTicket)> {
+    let key = KeyPair::from_seed(rand::random());
+    let (_kel, directory, data) = dial_linking_as(node, payload, &key).await?;
+    Ok((directory, data))
+}
+
+/// [`dial_linking`] presenting `key` as the device key, returning the whole
+/// reply — the protocol's `{kel, directory, data}`, the log decoded but not
+/// checked — so the caller can act as the device the log now authorizes.
+pub async fn dial_linking_as(
+    node: &SyncNode,
+    payload: &LinkingPayload,
+    key: &KeyPair,
+) -> Result<(KeyEventLog, DocTicket, DocTicket)> {
     let connection = node
         .dial_handle()
         .connect(payload.inviter_addr.clone(), LINKING_ALPN)
         .await?;
     let (mut send, mut recv) = connection.open_bi().await?;
-    write_frame(
-        &mut send,
-        &postcard::to_stdvec(&(payload.version, payload.secret))?,
-    )
-    .await?;
+    write_frame(&mut send, &linking_request(payload, key)?).await?;
     send.finish()?;
     let reply = read_frame(&mut recv)
         .await
         .context("linking refused by the inviter")?;
-    let (directory, data): (DocTicket, DocTicket) = postcard::from_bytes(&reply)?;
+    let reply = postcard::from_bytes(&reply)?;
     connection.close(0u32.into(), b"done");
-    Ok((directory, data))
+    Ok(reply)
 }
 
 /// Present `payload`'s secret and never read the reply — the lost-response
@@ -89,11 +108,8 @@ pub async fn dial_linking_without_reading(
         .connect(payload.inviter_addr.clone(), LINKING_ALPN)
         .await?;
     let (mut send, _dropped_recv) = connection.open_bi().await?;
-    write_frame(
-        &mut send,
-        &postcard::to_stdvec(&(payload.version, payload.secret))?,
-    )
-    .await?;
+    let key = KeyPair::from_seed(rand::random());
+    write_frame(&mut send, &linking_request(payload, &key)?).await?;
     send.finish()?;
     Ok(connection)
 }
@@ -122,7 +138,8 @@ pub async fn link_probe(
 /// or must be the one burned — call `link` directly instead.
 pub async fn link_patiently(linker: &Runtime, inviter: &Runtime, identity: PdnId) -> Result<()> {
     let payload = inviter.identity().linking_invite(identity, None).await?;
-    linker.identity().link(payload, TIMEOUT).await
+    linker.identity().link(payload, None, TIMEOUT).await?;
+    Ok(())
 }
 
 /// Establish `scanner`'s side by presenting `invite`, once. A single
@@ -138,7 +155,8 @@ pub async fn establish_patiently(
     _inviter_id: PdnId,
     invite: InvitePayload,
 ) -> Result<()> {
-    scanner.connections().establish(scanner_id, invite).await
+    scanner.connections().establish(scanner_id, invite).await?;
+    Ok(())
 }
 
 /// The nominal claim these scenarios grant on: every grant is
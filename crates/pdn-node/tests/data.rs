@@ -9,7 +9,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use pdn_node::{
-    DataService as _, IdentityService as _, Runtime, ShareMode, SpawnOptions, UnknownIssuer,
+    DataService as _, IdentityService as _, Runtime, ServiceError, ShareMode, SpawnOptions,
 };
 use pdn_types::EntryPath;
 
@@ -29,6 +29,7 @@ const SWARM_WINDOW: Duration = Duration::from_secs(15);
 async fn writes_read_back_list_exactly_and_hand_over_by_ticket() -> Result<()> {
     let options = SpawnOptions {
         reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
     };
     let a = Runtime::spawn_with(options.clone()).await?;
     let b = Runtime::spawn_with(options).await?;
@@ -60,15 +61,15 @@ async fn writes_read_back_list_exactly_and_hand_over_by_ticket() -> Result<()> {
     // created nor imported, so read, write, and list are each refused as
     // specifically unknown, and nothing is read, written, or listed.
     let read_err = b.data().read(alice, &email).await.unwrap_err();
-    assert!(read_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(read_err, ServiceError::UnknownIssuer(_)));
     let write_err = b
         .data()
         .write(alice, &email, b"intruder")
         .await
         .unwrap_err();
-    assert!(write_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(write_err, ServiceError::UnknownIssuer(_)));
     let list_err = b.data().list(alice, None).await.unwrap_err();
-    assert!(list_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(list_err, ServiceError::UnknownIssuer(_)));
 
     // Denied: an out-of-band ticket does not deliver. A's identity is
     // armed at creation, and B's runtime resolves to no device and no
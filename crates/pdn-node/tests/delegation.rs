@@ -0,0 +1,264 @@
+//! Delegation across three identities: X grants Y one claim under a token
+//! Y may delegate on, Y shares it on to Z, and Z reads exactly that claim
+//! of X's data from Y's replica — X never hearing of Z. The paired denials
+//! ride beside it: a claim without a delegable grant, write access, and the
+//! delegate's link carrying no delegation of its own. A second scenario
+//! rotates the signers' keys under a delegation that must outlive them.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use pdn_node::{
+    claim_id_of, AccessMode, Capability, ConnectionsService as _, DataService as _, DelegatedClaim,
+    IdentityService as _, NotDelegable, PdnId, Runtime, ServiceError, SpawnOptions,
+};
+use pdn_types::EntryPath;
+use test_utils::eventually;
+
+mod common;
+use common::establish_patiently;
+
+/// The reconcile cadence of this scenario, as for the scoped grants.
+const RECONCILE: Duration = Duration::from_millis(500);
+
+/// Spawn a runtime with the test's short reconcile cadence.
+async fn spawn_runtime() -> Result<Runtime> {
+    Runtime::spawn_with(SpawnOptions {
+        reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
+    })
+    .await
+}
+
+/// Read-only, open-ended conditions toward `holder`.
+fn read_for(holder: PdnId) -> Capability {
+    Capability {
+        holders: vec![holder],
+        access: AccessMode::Read,
+        expires_at: None,
+    }
+}
+
+/// Whether `err` refuses a delegation by `identity`.
+fn refused(err: &ServiceError, identity: PdnId) -> bool {
+    matches!(
+        err,
+        ServiceError::NotDelegable(NotDelegable { identity: by, .. }) if *by == identity
+    )
+}
+
+/// Allowed: X delegates `contact/email` to Y, Y shares it on to Z, and Z
+/// converges on exactly that entry of X's data — served by Y. Both sides
+/// list the delegation: Y's outgoing toward Z, Z's incoming from Y.
+///
+/// Denied: Y sharing a claim X never delegated, Y asking for write, and Z
+/// sharing on what Y delegated to it.
+///
+/// The reads poll through the binder's import: until it lands, the
+/// namespace is unknown on the holder's side.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_delegated_claim_is_shared_on_and_served_by_the_delegator() -> Result<()> {
+    let rt_a = spawn_runtime().await?;
+    let rt_b = spawn_runtime().await?;
+    let rt_c = spawn_runtime().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let z = rt_c.identity().create().await?;
+
+    let invite = rt_a.connections().invite(x, None).await?;
+    establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
+    let invite = rt_b.connections().invite(y, None).await?;
+    establish_patiently(&rt_c, z, &rt_b, y, invite).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    let phone = EntryPath::new("contact/phone")?;
+    rt_a.data().write(x, &email, b"x@example.org").await?;
+    rt_a.data().write(x, &phone, b"+1-555-0100").await?;
+    let email_claim = claim_id_of(&x, &email);
+    let phone_claim = claim_id_of(&x, &phone);
+
+    // Denied (no grant): Y holds nothing of X's to share on yet.
+    let err = rt_b
+        .connections()
+        .delegate(y, x, email_claim, read_for(z))
+        .await
+        .unwrap_err();
+    assert!(
+        refused(&err, y),
+        "an ungranted claim must refuse, got: {err:?}"
+    );
+
+    // X delegates the email claim to Y; Y's binder imports it.
+    let delegation = rt_a
+        .connections()
+        .delegate(x, x, email_claim, read_for(y))
+        .await?;
+    assert_eq!(
+        delegation,
+        DelegatedClaim {
+            source: email_claim,
+            issuer: x,
+            delegator: x,
+            conditions: read_for(y),
+        }
+    );
+    assert_eq!(
+        rt_a.connections().delegations_to(x, y).await?,
+        vec![delegation.clone()]
+    );
+    assert!(
+        eventually(|| async {
+            Ok(rt_b.data().read(x, &email).await.ok().flatten().as_deref()
+                == Some(&b"x@example.org"[..])
+                && rt_b.connections().incoming_delegations(y).await? == vec![delegation.clone()])
+        })
+        .await?,
+        "the delegated claim did not reach its holder"
+    );
+
+    // Denied (write): a delegation carries read only.
+    let write = Capability {
+        access: AccessMode::Write,
+        ..read_for(z)
+    };
+    let err = rt_b
+        .connections()
+        .delegate(y, x, email_claim, write)
+        .await
+        .unwrap_err();
+    assert!(
+        refused(&err, y),
+        "a write delegation must refuse, got: {err:?}"
+    );
+
+    // Denied (not delegated): the phone claim was never granted to Y.
+    let err = rt_b
+        .connections()
+        .delegate(y, x, phone_claim, read_for(z))
+        .await
+        .unwrap_err();
+    assert!(
+        refused(&err, y),
+        "an undelegated claim must refuse, got: {err:?}"
+    );
+
+    // Y shares the email claim on to Z.
+    let shared = rt_b
+        .connections()
+        .delegate(y, x, email_claim, read_for(z))
+        .await?;
+    let expected = DelegatedClaim {
+        source: email_claim,
+        issuer: x,
+        delegator: y,
+        conditions: read_for(z),
+    };
+    assert_eq!(shared, expected);
+    assert_eq!(
+        rt_b.connections().delegations_to(y, z).await?,
+        vec![expected.clone()]
+    );
+
+    // Allowed: Z converges on exactly the delegated entry, served by Y.
+    assert!(
+        eventually(|| async {
+            Ok(rt_c.data().read(x, &email).await.ok().flatten().as_deref()
+                == Some(&b"x@example.org"[..])
+                && rt_c.connections().incoming_delegations(z).await? == vec![expected.clone()])
+        })
+        .await?,
+        "the shared-on claim did not reach the delegate"
+    );
+    tokio::time::sleep(RECONCILE * 3).await;
+    let listed: Vec<String> = rt_c
+        .data()
+        .list(x, None)
+        .await?
+        .into_iter()
+        .map(|e| e.path.to_string())
+        .collect();
+    assert_eq!(
+        listed,
+        vec!["contact/email".to_owned()],
+        "the delegate's view must contain exactly the delegated claim"
+    );
+
+    // Denied (one link deep): Z's link carries no delegation of its own,
+    // so Z holds nothing of X's it may share on.
+    let err = rt_c
+        .connections()
+        .delegate(z, x, email_claim, read_for(y))
+        .await
+        .unwrap_err();
+    assert!(
+        refused(&err, z),
+        "a delegate must not share on, got: {err:?}"
+    );
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    rt_c.shutdown().await?;
+    Ok(())
+}
+
+/// A delegation outlives orderly rotations of the keys behind it: X's
+/// token to Y is signed before X rotates, and Y shares it on to Z only
+/// once Y holds X's rotated log — known by Y reading a claim X granted
+/// under its new key. Y then rotates too, and Z still converges on the
+/// claim.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_delegation_survives_rotations_of_its_signers() -> Result<()> {
+    let rt_a = spawn_runtime().await?;
+    let rt_b = spawn_runtime().await?;
+    let rt_c = spawn_runtime().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let z = rt_c.identity().create().await?;
+
+    let invite = rt_a.connections().invite(x, None).await?;
+    establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
+    let invite = rt_b.connections().invite(y, None).await?;
+    establish_patiently(&rt_c, z, &rt_b, y, invite).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    let phone = EntryPath::new("contact/phone")?;
+    rt_a.data().write(x, &email, b"x@example.org").await?;
+    rt_a.data().write(x, &phone, b"+1-555-0100").await?;
+    let email_claim = claim_id_of(&x, &email);
+    let phone_claim = claim_id_of(&x, &phone);
+
+    rt_a.connections()
+        .delegate(x, x, email_claim, read_for(y))
+        .await?;
+    rt_a.identity().rotate_key(x, false).await?;
+    rt_a.connections()
+        .delegate(x, x, phone_claim, read_for(y))
+        .await?;
+    assert!(
+        eventually(|| async {
+            Ok(rt_b.data().read(x, &phone).await.ok().flatten().as_deref()
+                == Some(&b"+1-555-0100"[..]))
+        })
+        .await?,
+        "the grant made under X's rotated key did not reach Y"
+    );
+
+    // The email token predates X's rotation, and still carries Y's link.
+    rt_b.connections()
+        .delegate(y, x, email_claim, read_for(z))
+        .await?;
+    rt_b.identity().rotate_key(y, false).await?;
+    assert!(
+        eventually(|| async {
+            Ok(rt_c.data().read(x, &email).await.ok().flatten().as_deref()
+                == Some(&b"x@example.org"[..]))
+        })
+        .await?,
+        "the delegation did not survive its signers' rotations"
+    );
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    rt_c.shutdown().await?;
+    Ok(())
+}
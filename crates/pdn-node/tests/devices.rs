@@ -0,0 +1,114 @@
+//! The device list end to end: each device of an identity described by its
+//! own record — the label it was linked with, its linking time, the key it
+//! signs with — and by the last successful sync this
+//! runtime saw with it, which a restart on a storage root keeps. The
+//! paired denial: a runtime not hosting the identity lists nothing and
+//! refuses as unknown.
+
+use anyhow::{Context, Result};
+use pdn_node::{DeviceInfo, IdentityService as _, Runtime, ServiceError, SpawnOptions};
+use test_utils::{eventually, TIMEOUT};
+
+/// The description of `node` in `devices`, if listed.
+fn find(devices: &[DeviceInfo], node: pdn_node::NodeId) -> Option<&DeviceInfo> {
+    devices.iter().find(|device| device.node == node)
+}
+
+/// Allowed: the incepting device lists itself with its key and no sync;
+/// a device linked with a label appears on it labelled, with a device key
+/// of its own and, once the two have synced, a last sync time — and a
+/// relabel replicates back.
+/// Denied: the runtime that has not linked yet refuses as unknown.
+#[tokio::test(flavor = "multi_thread")]
+async fn devices_describe_themselves_and_their_last_sync() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let b = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+    let current = a.identity().key_state(alice).await?.current;
+
+    let devices = a.identity().devices(alice).await?.into_vec();
+    assert_eq!(devices.len(), 1);
+    let own = find(&devices, a.node_id()).expect("the creating device is listed");
+    assert_eq!(own.key, Some(current));
+    assert!(own.linked_at.is_some());
+    assert_eq!(own.label, None);
+    assert_eq!(own.last_sync, None);
+
+    // Denied before linking: B does not host Alice.
+    let err = b.identity().devices(alice).await.unwrap_err();
+    assert!(matches!(err, ServiceError::NotHosted(_)));
+
+    let payload = a.identity().linking_invite(alice, None).await?;
+    b.identity().link(payload, Some("laptop"), TIMEOUT).await?;
+    assert!(
+        eventually(|| async {
+            let devices = a.identity().devices(alice).await?.into_vec();
+            Ok(find(&devices, b.node_id()).is_some_and(|laptop| {
+                laptop.label.as_deref() == Some("laptop")
+                    && laptop.linked_at.is_some()
+                    && laptop.key.is_some_and(|key| key != current)
+                    && laptop.last_sync.is_some()
+            }))
+        })
+        .await?,
+        "the linked device never appeared described and synced on the inviter"
+    );
+
+    a.identity().set_device_label(alice, "phone").await?;
+    assert!(
+        eventually(|| async {
+            let devices = b.identity().devices(alice).await?.into_vec();
+            Ok(find(&devices, a.node_id()).is_some_and(|phone| {
+                phone.label.as_deref() == Some("phone") && phone.key == Some(current)
+            }))
+        })
+        .await?,
+        "the relabel never reached the linked device"
+    );
+
+    a.shutdown().await?;
+    b.shutdown().await?;
+    Ok(())
+}
+
+/// Allowed: the last sync a runtime on a storage root saw with a linked
+/// device is still reported after it restarts, with the device gone
+/// meanwhile — read back from the directory, not seen anew.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_last_sync_survives_a_restart() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let persistent = SpawnOptions {
+        storage: Some(root.path().to_path_buf()),
+        ..SpawnOptions::default()
+    };
+    let a = Runtime::spawn_with(persistent.clone()).await?;
+    let b = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+    let payload = a.identity().linking_invite(alice, None).await?;
+    b.identity().link(payload, None, TIMEOUT).await?;
+    let laptop = b.node_id();
+    assert!(
+        eventually(|| async {
+            let devices = a.identity().devices(alice).await?.into_vec();
+            Ok(find(&devices, laptop).is_some_and(|device| device.last_sync.is_some()))
+        })
+        .await?,
+        "the linked device was never seen syncing"
+    );
+    let devices = a.identity().devices(alice).await?.into_vec();
+    let seen = find(&devices, laptop)
+        .and_then(|device| device.last_sync)
+        .context("the sighting went missing")?;
+
+    b.shutdown().await?;
+    a.shutdown().await?;
+    let a = Runtime::spawn_with(persistent).await?;
+    let devices = a.identity().devices(alice).await?.into_vec();
+    let recorded = find(&devices, laptop)
+        .and_then(|device| device.last_sync)
+        .context("the restart forgot the last sync")?;
+    assert!(recorded <= seen);
+
+    a.shutdown().await?;
+    Ok(())
+}
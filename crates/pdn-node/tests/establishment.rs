@@ -3,16 +3,17 @@
 //! flow over the exchanged metadata pair, visibility from linked devices,
 //! idempotent re-establishment, and the refusal pairs of the
 //! verify-and-burn requirement — each refusal probed for no observable
-//! state on the inviter, next to its allowed counterpart.
+//! state on the inviter, next to its allowed counterpart — and the identity
+//! proofs that keep either side from presenting an identity it cannot sign
+//! for.
 
 use std::time::Duration;
 
 use anyhow::Result;
 use data_layer::{AddrInfoOptions, ConnectionMetadataStore, PrivateMetadataStore, ShareMode};
 use pdn_node::{
-    claim_id_of, ConnectionsService as _, DataService as _, DelegationUnsupported,
-    IdentityService as _, InvitePayload, Runtime, UnknownIdentity, UnsupportedInviteVersion,
-    INVITE_FORMAT_VERSION,
+    claim_id_of, ConnectionsService as _, DataService as _, IdentityService as _, InvitePayload,
+    Runtime, ServiceError, UnsupportedInviteVersion, INVITE_FORMAT_VERSION,
 };
 use pdn_types::{EntryPath, NodeId, NonEmpty};
 use test_utils::{eventually, ids, TIMEOUT};
@@ -49,9 +50,9 @@ async fn establishment_completes_and_grants_flow_end_to_end() -> Result<()> {
     let z = rt_c.identity().create().await?;
 
     // The payload is self-contained and bearer-free: exactly the format
-    // version, the inviter device's address, the one-time secret, and the
-    // inviting identity — no ticket and no identity proof (there are no
-    // such fields to carry one in).
+    // version, the inviter device's address, the one-time secret, the
+    // inviting identity and its proof over them — no ticket (there is no
+    // field to carry one in).
     let first = rt_a.connections().invite(x, None).await?;
     assert_eq!(first.version, INVITE_FORMAT_VERSION);
     assert_eq!(first.inviter, x);
@@ -94,7 +95,7 @@ async fn establishment_completes_and_grants_flow_end_to_end() -> Result<()> {
     // asserting it: Y writes into X's namespace under its own author, and the
     // entry reaches X — the issuer — through ordinary sync. Nothing rejects
     // it: the store's capability is swarm membership, not access control, and
-    // no ingest hook is installed (ADR-0008) — a whole-store grant is
+    // no write check runs at ingest (ADR-0008) — a whole-store grant is
     // unscoped in both directions.
     let peer_path = EntryPath::new("contact/note-from-y")?;
     rt_b.data().write(x, &peer_path, b"Y was here").await?;
@@ -305,17 +306,18 @@ async fn re_establishment_converges_and_may_swap_directions() -> Result<()> {
     Ok(())
 }
 
-/// Granting another identity's data is refused loudly as unsupported
-/// delegation. The classifier scans the connections of the *data issuer's*
-/// identity, so a grant recorded under a different granting identity could
-/// never be honored — an accepted publish would replicate, the recipient
-/// would read a live grant, and enforcement would deny everything: a
-/// silent no-op on both sides. The sharpest form is exercised here: the
-/// foreign issuer is *hosted on the same runtime*. Paired with the allowed
-/// side: the identity's own grant, published after the refusals, crosses —
-/// and nothing of the refused grant ever appears beside it.
+/// Granting another identity's data without a delegable grant on it is
+/// refused loudly. Granting another identity's data is delegation, and a
+/// delegation stands only on a grant the delegator holds whose token lets
+/// it share on — without one, an accepted publish would replicate, the
+/// recipient would read a live grant, and enforcement would deny
+/// everything: a silent no-op on both sides. The sharpest form is
+/// exercised here: the foreign issuer is *hosted on the same runtime*.
+/// Paired with the allowed side: the identity's own grant, published after
+/// the refusals, crosses — and nothing of the refused grant ever appears
+/// beside it.
 #[tokio::test(flavor = "multi_thread")]
-async fn granting_a_foreign_issuers_data_is_refused_as_unsupported_delegation() -> Result<()> {
+async fn granting_a_foreign_issuers_data_without_a_delegable_grant_is_refused() -> Result<()> {
     let rt_a = Runtime::spawn().await?;
     let rt_b = Runtime::spawn().await?;
     let x = rt_a.identity().create().await?;
@@ -325,16 +327,17 @@ async fn granting_a_foreign_issuers_data_is_refused_as_unsupported_delegation()
     let invite = rt_a.connections().invite(x, None).await?;
     establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
 
-    // Denied: the whole-store surface refuses a foreign issuer — even one
-    // hosted right here — before anything is minted or written.
+    // Denied: the whole-store surface refuses a foreign issuer the identity
+    // holds no grant from — even one hosted right here — before anything is
+    // minted or written.
     let err = rt_a
         .connections()
         .publish_grant(x, y, b, common::nominal_claims(b), false)
         .await
         .unwrap_err();
     assert!(
-        err.downcast_ref::<DelegationUnsupported>().is_some(),
-        "a foreign-issuer grant must refuse as unsupported delegation, got: {err:?}"
+        matches!(err, ServiceError::NotDelegable(_)),
+        "a foreign-issuer grant must refuse as not delegable, got: {err:?}"
     );
 
     // Denied: the scoped surface refuses the same way.
@@ -345,8 +348,8 @@ async fn granting_a_foreign_issuers_data_is_refused_as_unsupported_delegation()
         .await
         .unwrap_err();
     assert!(
-        err.downcast_ref::<DelegationUnsupported>().is_some(),
-        "a foreign-issuer scoped grant must refuse as unsupported delegation, got: {err:?}"
+        matches!(err, ServiceError::NotDelegable(_)),
+        "a foreign-issuer scoped grant must refuse as not delegable, got: {err:?}"
     );
 
     // Allowed, and the proof nothing was recorded: the identity's own
@@ -417,14 +420,14 @@ async fn refusals_are_uniform_and_leave_no_state_on_the_inviter() -> Result<()>
         .invite(ids::DAVE, None)
         .await
         .unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
     let live = rt_a.connections().invite(x, None).await?;
     let err = rt_b
         .connections()
         .establish(ids::DAVE, live.clone())
         .await
         .unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
 
     // A wrong secret is refused and burns nothing: the guess fails with no
     // state anywhere...
@@ -450,10 +453,13 @@ async fn refusals_are_uniform_and_leave_no_state_on_the_inviter() -> Result<()>
         .establish(z, unversioned)
         .await
         .unwrap_err();
-    let version_err = err
-        .downcast_ref::<UnsupportedInviteVersion>()
-        .expect("the version refusal is typed and precedes the dial");
-    assert_eq!(version_err.version, 99);
+    assert!(
+        matches!(
+            err,
+            ServiceError::UnsupportedInviteVersion(UnsupportedInviteVersion { version: 99 })
+        ),
+        "the version refusal is typed and precedes the dial, got: {err:#}"
+    );
 
     // The allowed counterpart: the live secret — having survived the
     // unhosted attempt, the wrong guess, and the version probe — still
@@ -487,6 +493,49 @@ async fn refusals_are_uniform_and_leave_no_state_on_the_inviter() -> Result<()>
     Ok(())
 }
 
+/// Denied: a payload retargeted to claim another inviting identity is
+/// refused on both sides — the scanner's proof covers the identity it was
+/// shown, which is not the one the inviter invited for — with nothing
+/// listed anywhere. Allowed: a linked device, which holds only the device
+/// key its linking added, invites and establishes for its identity — its
+/// proofs verify under the identity's log like the incepting device's.
+#[tokio::test(flavor = "multi_thread")]
+async fn presented_identities_must_be_proven() -> Result<()> {
+    let rt_a = Runtime::spawn().await?;
+    let rt_b = Runtime::spawn().await?;
+    let rt_laptop = Runtime::spawn().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    link_patiently(&rt_laptop, &rt_b, y).await?;
+
+    // The retargeted payload names Y's identity as the inviter.
+    let live = rt_a.connections().invite(x, None).await?;
+    let retargeted = InvitePayload {
+        inviter: y,
+        ..live.clone()
+    };
+    assert!(
+        rt_b.connections().establish(y, retargeted).await.is_err(),
+        "a payload claiming another inviting identity must be refused"
+    );
+    assert!(rt_a.connections().list(x).await?.is_empty());
+    assert!(rt_b.connections().list(y).await?.is_empty());
+
+    // The linked laptop hosts Y and signs for it with its device key: its
+    // invite carries a proof, and it establishes toward X.
+    let laptop_invite = rt_laptop.connections().invite(y, None).await?;
+    assert_eq!(laptop_invite.inviter, y);
+    let fresh = rt_a.connections().invite(x, None).await?;
+    rt_laptop.connections().establish(y, fresh).await?;
+    assert_eq!(rt_a.connections().list(x).await?, vec![y]);
+    assert_eq!(rt_laptop.connections().list(y).await?, vec![x]);
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    rt_laptop.shutdown().await?;
+    Ok(())
+}
+
 /// Two runtimes invite each other and both `establish` toward the other at
 /// the same time. The dialogue must not hold the runtime lock across the
 /// network round-trip, or the two establishments deadlock — each holding
@@ -0,0 +1,139 @@
+//! The change feed end to end: an establishment reports the connection on
+//! both sides, a local write reports the entry, a grant crossing the pair
+//! reports it received and its withdrawal withdrawn, and a linked device
+//! reports itself on the device that linked it in. A data watch on a
+//! sibling device reports a synced write, then its payload readable.
+
+use anyhow::{Context, Result};
+use futures_lite::StreamExt as _;
+use pdn_node::{
+    ConnectionsService as _, DataService as _, IdentityService as _, Runtime, RuntimeEvent,
+    RuntimeEvents, SyncService as _,
+};
+use pdn_types::{EntryEventKind, EntryOrigin, EntryPath};
+use test_utils::TIMEOUT;
+
+mod common;
+use common::{establish_patiently, link_patiently, nominal_claims};
+
+/// Wait for the first event on `feed` that `wanted` picks, skipping others.
+async fn next_matching(
+    feed: &mut RuntimeEvents,
+    wanted: impl Fn(&RuntimeEvent) -> bool,
+) -> Result<RuntimeEvent> {
+    let wait = async {
+        while let Some(event) = feed.next().await {
+            if wanted(&event) {
+                return Some(event);
+            }
+        }
+        None
+    };
+    tokio::time::timeout(TIMEOUT, wait)
+        .await?
+        .context("the runtime's feed closed")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connections_entries_and_grants_are_reported() -> Result<()> {
+    let rt_a = Runtime::spawn().await?;
+    let rt_b = Runtime::spawn().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let mut feed_a = rt_a.sync().events();
+    let mut feed_b = rt_b.sync().events();
+
+    let invite = rt_a.connections().invite(x, None).await?;
+    establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
+    for (feed, identity, peer) in [(&mut feed_a, x, y), (&mut feed_b, y, x)] {
+        let added = RuntimeEvent::ConnectionAdded { identity, peer };
+        next_matching(feed, |event| *event == added).await?;
+    }
+
+    let email = EntryPath::new("contact/email")?;
+    rt_a.data().write(x, &email, b"x@example.org").await?;
+    let written = next_matching(
+        &mut feed_a,
+        |event| matches!(event, RuntimeEvent::EntryChanged(entry) if entry.info.path == email),
+    )
+    .await?;
+    let RuntimeEvent::EntryChanged(entry) = written else {
+        anyhow::bail!("not an entry event: {written:?}");
+    };
+    assert_eq!((entry.info.issuer, entry.info.payload_len), (x, 13));
+    assert_eq!(entry.kind, EntryEventKind::Inserted);
+
+    rt_a.connections()
+        .publish_grant(x, y, x, nominal_claims(x), false)
+        .await?;
+    let received = RuntimeEvent::GrantReceived {
+        identity: y,
+        peer: x,
+        issuer: x,
+    };
+    next_matching(&mut feed_b, |event| *event == received).await?;
+    rt_a.connections().withdraw_grant(x, y, x).await?;
+    let withdrawn = RuntimeEvent::GrantWithdrawn {
+        identity: y,
+        peer: x,
+        issuer: x,
+    };
+    next_matching(&mut feed_b, |event| *event == withdrawn).await?;
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_linked_device_is_reported_by_its_siblings() -> Result<()> {
+    let phone = Runtime::spawn().await?;
+    let laptop = Runtime::spawn().await?;
+    let x = phone.identity().create().await?;
+    let mut feed = phone.sync().events();
+
+    link_patiently(&laptop, &phone, x).await?;
+    let linked = RuntimeEvent::DeviceLinked {
+        identity: x,
+        device: laptop.node_id(),
+    };
+    next_matching(&mut feed, |event| *event == linked).await?;
+
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_data_watch_reports_a_synced_write_then_its_payload() -> Result<()> {
+    let phone = Runtime::spawn().await?;
+    let laptop = Runtime::spawn().await?;
+    let x = phone.identity().create().await?;
+    link_patiently(&laptop, &phone, x).await?;
+
+    let contacts = EntryPath::new("contacts")?;
+    let mut watch = laptop.data().watch(x, Some(&contacts)).await?;
+    let name = EntryPath::new("contacts/bob")?;
+    phone.data().write(x, &name, b"Bob").await?;
+
+    let mut kinds = Vec::new();
+    while kinds.len() < 2 {
+        let event = tokio::time::timeout(TIMEOUT, watch.next())
+            .await?
+            .context("the watch ended")??;
+        assert_eq!((event.info.issuer, &event.info.path), (x, &name));
+        assert_eq!(event.origin, EntryOrigin::Remote(phone.node_id()));
+        kinds.push(event.kind);
+    }
+    assert_eq!(
+        kinds,
+        [EntryEventKind::Inserted, EntryEventKind::PayloadReady]
+    );
+    // Readable the moment it says so — no polling.
+    assert_eq!(laptop.data().read(x, &name).await?, Some(b"Bob".to_vec()));
+
+    drop(watch);
+    phone.shutdown().await?;
+    laptop.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Identity key material end to end: creation incepts the identity with a
+//! real ed25519 key — the `PdnId` is the inception key — and records the
+//! key event log in the directory, where each linked device's key is
+//! authorized and which the device replicates and verifies; a rotation — a recovery rotation included — replicates the
+//! same way. The paired denials: a runtime not hosting the identity is
+//! refused as unknown, a device without the identity's keys cannot rotate,
+//! a compromised key is rejected from the rotation point on, and a log
+//! rewritten by a directory writer to certify another key does not verify
+//! as the identity's.
+
+use anyhow::Result;
+use pdn_layer::kel::{KeyEventLog, KeyPair};
+use pdn_node::{IdentityService as _, KeyStatus, Runtime, ServiceError};
+use test_utils::eventually;
+
+mod common;
+use common::{link_patiently, link_probe};
+
+/// Allowed: the created identity's key state verifies from inception and
+/// names the identity; a linked device reads the same state from its own
+/// replica. Denied: a runtime hosting nothing refuses as unknown.
+#[tokio::test(flavor = "multi_thread")]
+async fn created_identity_is_its_inception_key_on_every_device() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let b = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+
+    let state = a.identity().key_state(alice).await?;
+    assert_eq!(state.pdn_id(), alice);
+    assert_eq!(state.aid.as_bytes(), alice.as_bytes());
+    assert_eq!(state.current.as_bytes(), alice.as_bytes());
+    assert_eq!(state.sn, 0);
+
+    // Denied before linking: B does not host Alice.
+    let err = b.identity().key_state(alice).await.unwrap_err();
+    assert!(matches!(err, ServiceError::NotHosted(_)));
+
+    // The link authorizes B's device key in the log: the identity's key
+    // stays current, and B reads the same state from its own replica.
+    link_patiently(&b, &a, alice).await?;
+    let linked = a.identity().key_state(alice).await?;
+    assert_eq!(linked.sn, 1);
+    assert_eq!(linked.current, state.current);
+    let devices = b.identity().devices(alice).await?.into_vec();
+    let b_key = devices
+        .iter()
+        .find(|device| device.node == b.node_id())
+        .and_then(|device| device.key);
+    assert!(
+        b_key.is_some_and(|key| linked.devices == vec![key]),
+        "the log must authorize exactly the key B describes itself with"
+    );
+    assert!(
+        eventually(|| async {
+            Ok(b.identity().key_state(alice).await.ok().as_ref() == Some(&linked))
+        })
+        .await?,
+        "the linked device never verified the identity's key event log"
+    );
+
+    a.shutdown().await?;
+    b.shutdown().await?;
+    Ok(())
+}
+
+/// Allowed: a recovery rotation on the incepting device installs the
+/// pre-rotated key, and the linked device verifies the rotated log with
+/// the old key marked compromised at the rotation point. Denied: the linked
+/// device holds no keys to rotate with, and the compromised key is not
+/// accepted for anything, whatever state it claims to have signed under.
+#[tokio::test(flavor = "multi_thread")]
+async fn recovery_rotation_replicates_and_retires_the_stolen_key() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let b = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+    link_patiently(&b, &a, alice).await?;
+    let incepted = a.identity().key_state(alice).await?;
+
+    // The link's device addition precedes the rotation in the log.
+    let at = incepted.sn + 1;
+    let rotated_to = a.identity().rotate_key(alice, true).await?;
+    let state = a.identity().key_state(alice).await?;
+    assert_eq!(state.sn, at);
+    assert_eq!(state.current, rotated_to);
+    assert_ne!(rotated_to, incepted.current);
+    assert_eq!(state.pdn_id(), alice);
+    assert_eq!(
+        state.status_of(&incepted.current),
+        Some(KeyStatus::Compromised { at })
+    );
+    assert!(!state.accepts(&incepted.current, incepted.sn));
+    assert!(!state.accepts(&incepted.current, at));
+    assert!(state.accepts(&rotated_to, at));
+
+    assert!(
+        eventually(|| async {
+            Ok(b.identity().key_state(alice).await.ok().as_ref() == Some(&state))
+        })
+        .await?,
+        "the rotation never reached the linked device"
+    );
+
+    // Denied: the linked device verifies the log but never held its keys.
+    let err = b.identity().rotate_key(alice, false).await.unwrap_err();
+    assert!(matches!(err, ServiceError::NoSigningKeys(_)));
+
+    // An orderly rotation next: the pre-rotated key again, no compromise.
+    let second = a.identity().rotate_key(alice, false).await?;
+    let state = a.identity().key_state(alice).await?;
+    assert_eq!(state.current, second);
+    assert_eq!(
+        state.status_of(&rotated_to),
+        Some(KeyStatus::RotatedOut { at: at + 1 })
+    );
+
+    a.shutdown().await?;
+    b.shutdown().await?;
+    Ok(())
+}
+
+/// Denied: a directory writer replaces the inception with one of its own
+/// key. The forged log is well-formed and verifies on its own — but it
+/// certifies another identity, so the runtime refuses it as this one's.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_rewritten_log_does_not_certify_the_identity() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+    let (probe, directory) = link_probe(&a, alice).await?;
+
+    // The forged log covers the probe's own device addition too, so it is
+    // whole rather than broken where the real log continues.
+    let forger = KeyPair::from_seed([7; 32]);
+    let mut forged = KeyEventLog::incept(&forger, &KeyPair::from_seed([8; 32]).public());
+    forged.add_device(&forger, &KeyPair::from_seed([9; 32]).public())?;
+    for event in forged.events() {
+        directory
+            .put_key_event(event.event.sn, &serde_json::to_vec(event)?)
+            .await?;
+    }
+
+    assert!(
+        eventually(|| async { Ok(a.identity().key_state(alice).await.is_err()) }).await?,
+        "the forged inception never reached the identity's directory"
+    );
+
+    probe.shutdown().await?;
+    a.shutdown().await?;
+    Ok(())
+}
@@ -3,29 +3,31 @@
 //! bootstraps, the non-founder chain, the refusal pairs of the
 //! verify-and-burn requirement — each probed for no observable state on
 //! either side — lost-reply convergence, the rollback of a link that could
-//! not catch up, per-identity isolation across several linkings, and a
-//! linked device serving a grant its identity established and published
-//! elsewhere (connection arming by replication).
+//! not catch up, the refusal of a reply whose key event log does not
+//! authorize the new device under the identity the payload named,
+//! per-identity isolation across several linkings, and a linked device
+//! serving a grant its identity established and published elsewhere
+//! (connection arming by replication).
 
 use std::time::Duration;
 
 use anyhow::Result;
 use data_layer::{
-    AcceptError, AddrInfoOptions, CatchUpTimeout, Connection, DocTicket, PrivateMetadataStore,
-    ProtocolHandler, ShareMode, SyncNode,
+    AcceptError, AddrInfoOptions, Connection, DocTicket, PrivateMetadataStore, ProtocolHandler,
+    ShareMode, SyncNode,
 };
+use pdn_layer::kel::{KeyEventLog, KeyPair};
 use pdn_node::{
     ConnectionsService as _, DataService as _, IdentityService as _, LinkingPayload, Runtime,
-    SyncService as _, UnknownIdentity, UnknownIssuer, UnsupportedLinkingVersion,
-    LINKING_FORMAT_VERSION,
+    ServiceError, SyncService as _, UnsupportedLinkingVersion, LINKING_FORMAT_VERSION,
 };
-use pdn_types::{EntryPath, NodeId, PdnId};
+use pdn_types::{EntryPath, NodeId, OperationalKey, PdnId};
 use test_utils::{eventually, ids, wait_devices, TIMEOUT};
 
 mod common;
 use common::{
-    dial_linking, dial_linking_without_reading, establish_patiently, granted_patiently,
-    link_patiently, link_probe, read_frame, write_frame, LINKING_ALPN,
+    dial_linking, dial_linking_as, dial_linking_without_reading, establish_patiently,
+    granted_patiently, link_patiently, link_probe, read_frame, write_frame, LINKING_ALPN,
 };
 
 /// Wait until the probe's directory lists exactly `devices` (order-free).
@@ -248,12 +250,12 @@ async fn refusals_are_uniform_and_leave_no_state() -> Result<()> {
     let expired = rt_a.identity().linking_invite(x, tiny).await?;
     tokio::time::sleep(Duration::from_millis(50)).await;
     assert!(
-        rt_b.identity().link(expired, TIMEOUT).await.is_err(),
+        rt_b.identity().link(expired, None, TIMEOUT).await.is_err(),
         "an expired secret must be refused"
     );
     assert_eq!(rt_b.sync().hosted_identities().await?, vec![]);
     let err = rt_b.data().read(x, &path).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(err, ServiceError::UnknownIssuer(_)));
     assert_directory_is(&probe_dir, &baseline_devices, &baseline_kinds).await?;
 
     // A linking invite for an unhosted identity is refused with the typed
@@ -263,7 +265,7 @@ async fn refusals_are_uniform_and_leave_no_state() -> Result<()> {
         .linking_invite(ids::DAVE, None)
         .await
         .unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
 
     // A wrong secret is refused and burns nothing...
     let live = rt_a.identity().linking_invite(x, None).await?;
@@ -272,7 +274,7 @@ async fn refusals_are_uniform_and_leave_no_state() -> Result<()> {
         ..live.clone()
     };
     assert!(
-        rt_c.identity().link(forged, TIMEOUT).await.is_err(),
+        rt_c.identity().link(forged, None, TIMEOUT).await.is_err(),
         "a never-minted secret must be refused"
     );
     assert_eq!(rt_c.sync().hosted_identities().await?, vec![]);
@@ -286,19 +288,22 @@ async fn refusals_are_uniform_and_leave_no_state() -> Result<()> {
     };
     let err = rt_c
         .identity()
-        .link(unversioned, TIMEOUT)
+        .link(unversioned, None, TIMEOUT)
         .await
         .unwrap_err();
-    let version_err = err
-        .downcast_ref::<UnsupportedLinkingVersion>()
-        .expect("the version refusal is typed and precedes the dial");
-    assert_eq!(version_err.version, 99);
+    assert!(
+        matches!(
+            err,
+            ServiceError::UnsupportedLinkingVersion(UnsupportedLinkingVersion { version: 99 })
+        ),
+        "the version refusal is typed and precedes the dial, got: {err:#}"
+    );
 
     // The allowed counterpart: the live secret — having survived the wrong
     // guess and the version probe — still links. Direct (not patient): this
     // must burn *this* secret so the replay below is refused; the path is
     // warm from the probe and the expired-secret dial above.
-    rt_b.identity().link(live.clone(), TIMEOUT).await?;
+    rt_b.identity().link(live.clone(), None, TIMEOUT).await?;
     assert_eq!(rt_b.sync().hosted_identities().await?, vec![x]);
     let after_link = [rt_a.node_id(), probe_id, rt_b.node_id()];
     assert!(
@@ -310,7 +315,7 @@ async fn refusals_are_uniform_and_leave_no_state() -> Result<()> {
     // A second presentation of the burned secret is refused, and both
     // sides are exactly as the first linking left them.
     assert!(
-        rt_c.identity().link(live, TIMEOUT).await.is_err(),
+        rt_c.identity().link(live, None, TIMEOUT).await.is_err(),
         "a replayed secret must be refused"
     );
     assert_eq!(rt_c.sync().hosted_identities().await?, vec![]);
@@ -338,7 +343,7 @@ async fn linking_into_a_hosted_identity_refuses_before_dialing() -> Result<()> {
     let fresh = rt_a.identity().linking_invite(x, None).await?;
     let err = rt_b
         .identity()
-        .link(fresh.clone(), TIMEOUT)
+        .link(fresh.clone(), None, TIMEOUT)
         .await
         .unwrap_err();
     assert!(
@@ -347,7 +352,7 @@ async fn linking_into_a_hosted_identity_refuses_before_dialing() -> Result<()> {
     );
 
     // The refused payload's secret was never presented: C links with it.
-    rt_c.identity().link(fresh, TIMEOUT).await?;
+    rt_c.identity().link(fresh, None, TIMEOUT).await?;
     assert_eq!(rt_c.sync().hosted_identities().await?, vec![x]);
 
     rt_a.shutdown().await?;
@@ -417,9 +422,13 @@ async fn a_dialogue_lost_after_commit_converges_on_a_fresh_invite() -> Result<()
 /// A linking "inviter" that speaks the dialogue but answers every request
 /// with fixed tickets to replicas whose only host is already gone, so the
 /// dialing runtime's catch-up can never complete — the harness that forces
-/// `link` down its rollback path.
+/// `link` down its rollback path. It holds a key `kel` lets add devices,
+/// so the log it answers with authorizes the dialing device's key and the
+/// dialer gets as far as the imports.
 #[derive(Debug)]
 struct DeadTicketInviter {
+    signer: KeyPair,
+    kel: KeyEventLog,
     directory: DocTicket,
     data: DocTicket,
 }
@@ -428,9 +437,13 @@ impl ProtocolHandler for DeadTicketInviter {
     async fn accept(&self, connection: Connection) -> Result<(), AcceptError> {
         let served = async {
             let (mut send, mut recv) = connection.accept_bi().await.ok()?;
-            // Read (and ignore) the request — every secret "verifies" here.
-            read_frame(&mut recv).await.ok()?;
-            let reply = postcard::to_stdvec(&(&self.directory, &self.data)).ok()?;
+            // Every secret "verifies" here; only the device key is used.
+            let request = read_frame(&mut recv).await.ok()?;
+            let (_version, _secret, key): (u8, [u8; 32], OperationalKey) =
+                postcard::from_bytes(&request).ok()?;
+            let mut kel = self.kel.clone();
+            kel.add_device(&self.signer, &key).ok()?;
+            let reply = postcard::to_stdvec(&(&kel, &self.directory, &self.data)).ok()?;
             write_frame(&mut send, &reply).await.ok()?;
             send.finish().ok()?;
             connection.closed().await;
@@ -453,18 +466,16 @@ impl ProtocolHandler for DeadTicketInviter {
 async fn a_timed_out_link_leaves_nothing_behind_on_the_dialing_node() -> Result<()> {
     // Mint real tickets from a scratch node, then take it away: the
     // namespaces they address end up hosted nowhere.
+    let inception = KeyPair::from_seed([0x21; 32]);
+    let dave = PdnId::from_bytes(*inception.public().as_bytes());
     let scratch = SyncNode::spawn().await?;
     let dead_directory = PrivateMetadataStore::create(&scratch).await?;
     let directory_ticket = dead_directory
         .share_ticket(ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
         .await?;
-    scratch.create_namespace(ids::DAVE).await?;
+    scratch.create_namespace(dave).await?;
     let data_ticket = scratch
-        .share_ticket(
-            ids::DAVE,
-            ShareMode::Write,
-            AddrInfoOptions::RelayAndAddresses,
-        )
+        .share_ticket(dave, ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
         .await?;
     scratch.shutdown().await?;
 
@@ -472,6 +483,8 @@ async fn a_timed_out_link_leaves_nothing_behind_on_the_dialing_node() -> Result<
     let fake_inviter = SyncNode::spawn_with_protocols(vec![(
         LINKING_ALPN.to_vec(),
         Box::new(DeadTicketInviter {
+            kel: KeyEventLog::incept(&inception, &KeyPair::from_seed([0x22; 32]).public()),
+            signer: inception,
             directory: directory_ticket,
             data: data_ticket,
         }),
@@ -481,7 +494,7 @@ async fn a_timed_out_link_leaves_nothing_behind_on_the_dialing_node() -> Result<
         version: LINKING_FORMAT_VERSION,
         inviter_addr: fake_inviter.dial_handle().addr(),
         secret: [0x42; 32],
-        identity: ids::DAVE,
+        identity: dave,
     };
 
     // The exchange completes, the imports land, the catch-up cannot: the
@@ -492,16 +505,16 @@ async fn a_timed_out_link_leaves_nothing_behind_on_the_dialing_node() -> Result<
     let err = loop {
         let err = rt_b
             .identity()
-            .link(payload.clone(), Duration::from_secs(2))
+            .link(payload.clone(), None, Duration::from_secs(2))
             .await
             .unwrap_err();
-        if err.downcast_ref::<CatchUpTimeout>().is_some() || std::time::Instant::now() > deadline {
+        if matches!(err, ServiceError::Timeout(_)) || std::time::Instant::now() > deadline {
             break err;
         }
         tokio::time::sleep(Duration::from_millis(500)).await;
     };
     assert!(
-        err.downcast_ref::<CatchUpTimeout>().is_some(),
+        matches!(err, ServiceError::Timeout(_)),
         "the failure must be the catch-up timeout, got: {err:#}"
     );
 
@@ -511,22 +524,84 @@ async fn a_timed_out_link_leaves_nothing_behind_on_the_dialing_node() -> Result<
     assert_eq!(rt_b.sync().hosted_identities().await?, vec![]);
     let read_err = rt_b
         .data()
-        .read(ids::DAVE, &EntryPath::new("contact/name")?)
+        .read(dave, &EntryPath::new("contact/name")?)
         .await
         .unwrap_err();
     assert!(
-        read_err.downcast_ref::<UnknownIssuer>().is_some(),
+        matches!(read_err, ServiceError::UnknownIssuer(_)),
         "reads under the rolled-back identity must refuse as unknown, got: {read_err:#}"
     );
     let write_err = rt_b
         .data()
-        .write(ids::DAVE, &EntryPath::new("contact/name")?, b"residue")
+        .write(dave, &EntryPath::new("contact/name")?, b"residue")
+        .await
+        .unwrap_err();
+    assert!(matches!(write_err, ServiceError::UnknownIssuer(_)));
+
+    rt_b.shutdown().await?;
+    fake_inviter.shutdown().await?;
+    Ok(())
+}
+
+/// Denied: an inviter whose reply carries a log certifying another identity
+/// than the payload names — one that authorizes the dialing device's key
+/// all the same — is refused before anything is imported: no catch-up is
+/// waited on, and the identity stays unknown to the dialing runtime.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_reply_whose_log_certifies_another_identity_is_refused() -> Result<()> {
+    let scratch = SyncNode::spawn().await?;
+    let directory = PrivateMetadataStore::create(&scratch).await?;
+    let directory_ticket = directory
+        .share_ticket(ShareMode::Write, AddrInfoOptions::RelayAndAddresses)
+        .await?;
+    scratch.create_namespace(ids::DAVE).await?;
+    let data_ticket = scratch
+        .share_ticket(
+            ids::DAVE,
+            ShareMode::Write,
+            AddrInfoOptions::RelayAndAddresses,
+        )
+        .await?;
+
+    let impostor = KeyPair::from_seed([0x31; 32]);
+    let fake_inviter = SyncNode::spawn_with_protocols(vec![(
+        LINKING_ALPN.to_vec(),
+        Box::new(DeadTicketInviter {
+            kel: KeyEventLog::incept(&impostor, &KeyPair::from_seed([0x32; 32]).public()),
+            signer: impostor,
+            directory: directory_ticket,
+            data: data_ticket,
+        }),
+    )])
+    .await?;
+    let payload = LinkingPayload {
+        version: LINKING_FORMAT_VERSION,
+        inviter_addr: fake_inviter.dial_handle().addr(),
+        secret: [0x42; 32],
+        identity: ids::DAVE,
+    };
+
+    let rt_b = Runtime::spawn().await?;
+    let err = rt_b
+        .identity()
+        .link(payload, None, TIMEOUT)
+        .await
+        .unwrap_err();
+    assert!(
+        !matches!(err, ServiceError::Timeout(_)),
+        "the refusal must precede the imports, got: {err:#}"
+    );
+    assert_eq!(rt_b.sync().hosted_identities().await?, vec![]);
+    let read_err = rt_b
+        .data()
+        .read(ids::DAVE, &EntryPath::new("contact/name")?)
         .await
         .unwrap_err();
-    assert!(write_err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(read_err, ServiceError::UnknownIssuer(_)));
 
     rt_b.shutdown().await?;
     fake_inviter.shutdown().await?;
+    scratch.shutdown().await?;
     Ok(())
 }
 
@@ -578,7 +653,14 @@ async fn a_failed_link_leaves_a_granted_namespace_of_the_same_issuer_intact() ->
     );
 
     // The person now adds X to the laptop too. The link fails: its tickets
-    // address replicas hosted nowhere, so the catch-up cannot complete.
+    // address replicas hosted nowhere, so the catch-up cannot complete. The
+    // inviter is a device of X in its own right — linked raw, so it holds
+    // a device key X's log authorized — that has lost its replicas.
+    let lost = SyncNode::spawn().await?;
+    let signer = KeyPair::from_seed(rand::random());
+    let invite = rt_phone.identity().linking_invite(x, None).await?;
+    let (kel, _directory, _data) = dial_linking_as(&lost, &invite, &signer).await?;
+    lost.shutdown().await?;
     let scratch = SyncNode::spawn().await?;
     let dead_directory = PrivateMetadataStore::create(&scratch).await?;
     let directory_ticket = dead_directory
@@ -597,6 +679,8 @@ async fn a_failed_link_leaves_a_granted_namespace_of_the_same_issuer_intact() ->
     let fake_inviter = SyncNode::spawn_with_protocols(vec![(
         LINKING_ALPN.to_vec(),
         Box::new(DeadTicketInviter {
+            signer,
+            kel,
             directory: directory_ticket,
             data: data_ticket,
         }),
@@ -613,16 +697,16 @@ async fn a_failed_link_leaves_a_granted_namespace_of_the_same_issuer_intact() ->
     let err = loop {
         let err = rt_laptop
             .identity()
-            .link(payload.clone(), Duration::from_secs(2))
+            .link(payload.clone(), None, Duration::from_secs(2))
             .await
             .unwrap_err();
-        if err.downcast_ref::<CatchUpTimeout>().is_some() || std::time::Instant::now() > deadline {
+        if matches!(err, ServiceError::Timeout(_)) || std::time::Instant::now() > deadline {
             break err;
         }
         tokio::time::sleep(Duration::from_millis(500)).await;
     };
     assert!(
-        err.downcast_ref::<CatchUpTimeout>().is_some(),
+        matches!(err, ServiceError::Timeout(_)),
         "the failure must be the catch-up timeout, got: {err:#}"
     );
 
@@ -637,7 +721,7 @@ async fn a_failed_link_leaves_a_granted_namespace_of_the_same_issuer_intact() ->
     assert_eq!(rt_laptop.sync().hosted_identities().await?, vec![y]);
     let err = rt_laptop.connections().list(x).await.unwrap_err();
     assert!(
-        err.downcast_ref::<UnknownIdentity>().is_some(),
+        matches!(err, ServiceError::NotHosted(_)),
         "a restored grant binding must not make the identity hosted, got: {err:#}"
     );
 
@@ -681,23 +765,23 @@ async fn second_identity_requires_its_own_linking() -> Result<()> {
     // grant operations — and to its data namespaces; specifically unknown,
     // not a generic failure.
     let err = rt_b.connections().list(y).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
     let err = rt_b.identity().linking_invite(y, None).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
     let err = rt_b
         .connections()
         .publish_grant(y, pc, y, common::nominal_claims(y), false)
         .await
         .unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
     let err = rt_b.connections().read_grants(y, pc).await.unwrap_err();
-    assert!(err.downcast_ref::<UnknownIdentity>().is_some());
+    assert!(matches!(err, ServiceError::NotHosted(_)));
     let err = rt_b
         .data()
         .read(y, &EntryPath::new("contact/email")?)
         .await
         .unwrap_err();
-    assert!(err.downcast_ref::<UnknownIssuer>().is_some());
+    assert!(matches!(err, ServiceError::UnknownIssuer(_)));
 
     // Y's stores appear on B only after a separate linking act with a
     // linking invite for Y — and the two identities stay disjoint.
@@ -0,0 +1,132 @@
+//! Mailbox establishment end to end: an invite deposited with a stand-in
+//! relay node completes over two collections — the inviter answering the
+//! scanner's deposited half, then the scanner completing from the answer —
+//! with neither runtime ever dialing the other; and the refusals: a
+//! payload retargeted to another inviter, a second request under one
+//! secret, a package already answered, and a secret the relay never saw.
+
+use anyhow::Result;
+use data_layer::{SpawnOptions, SyncNode};
+use pdn_node::{
+    ConnectionsService as _, IdentityService as _, InvitePayload, MailboxRelay, Runtime,
+    MAILBOX_ALPN,
+};
+
+mod common;
+use common::{granted_patiently, nominal_claims};
+
+/// A bare node serving nothing but the mailbox relay.
+async fn spawn_relay() -> Result<SyncNode> {
+    let relay = SyncNode::spawn_with(
+        vec![(MAILBOX_ALPN.to_vec(), Box::new(MailboxRelay::new()))],
+        SpawnOptions::default(),
+    )
+    .await?;
+    Ok(relay)
+}
+
+/// The full flow: the scanner's `establish` only deposits, each collection
+/// moves the ceremony one step, and the pair it opens carries a grant like
+/// a live establishment's does.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_mailbox_invite_establishes_over_two_collections() -> Result<()> {
+    let relay = spawn_relay().await?;
+    let rt_a = Runtime::spawn().await?;
+    let rt_b = Runtime::spawn().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+
+    let invite = rt_a
+        .connections()
+        .invite_via_mailbox(x, relay.dial_handle().addr(), None)
+        .await?;
+    assert!(invite.mailbox.is_some());
+    // Nothing to answer before the scanner has been by.
+    assert_eq!(rt_a.connections().collect_mailboxes().await?, 0);
+
+    rt_b.connections().establish(y, invite).await?;
+    assert!(rt_b.connections().list(y).await?.is_empty());
+    assert!(rt_a.connections().list(x).await?.is_empty());
+    // The scanner finds no answer until the inviter has collected.
+    assert_eq!(rt_b.connections().collect_mailboxes().await?, 0);
+
+    assert_eq!(rt_a.connections().collect_mailboxes().await?, 1);
+    assert_eq!(rt_a.connections().list(x).await?, vec![y]);
+    assert_eq!(rt_b.connections().collect_mailboxes().await?, 1);
+    assert_eq!(rt_b.connections().list(y).await?, vec![x]);
+    // Both ceremonies ended with their connections.
+    assert_eq!(rt_a.connections().collect_mailboxes().await?, 0);
+    assert_eq!(rt_b.connections().collect_mailboxes().await?, 0);
+
+    let grant = granted_patiently(&rt_a, x, &rt_b, y, x, nominal_claims(x), false).await?;
+    assert_eq!(grant.grant.issuer, x);
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    relay.shutdown().await?;
+    Ok(())
+}
+
+/// Denied: a payload retargeted to claim another inviting identity fails
+/// the scanner's check of the package, before anything is deposited, and
+/// a secret the relay never saw finds nothing. Allowed: the failed attempt
+/// burned nothing — the genuine payload still finds the package and
+/// completes. Denied again: a second request under the same secret, and
+/// once the inviter answered, a third scanner, which finds no package.
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_packages_are_proven_and_outlive_a_failed_attempt() -> Result<()> {
+    let relay = spawn_relay().await?;
+    let rt_a = Runtime::spawn().await?;
+    let rt_b = Runtime::spawn().await?;
+    let rt_c = Runtime::spawn().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let z = rt_c.identity().create().await?;
+
+    let invite = rt_a
+        .connections()
+        .invite_via_mailbox(x, relay.dial_handle().addr(), None)
+        .await?;
+    let retargeted = InvitePayload {
+        inviter: y,
+        ..invite.clone()
+    };
+    assert!(
+        rt_b.connections().establish(y, retargeted).await.is_err(),
+        "a payload claiming another inviting identity must be refused"
+    );
+    let forged = InvitePayload {
+        secret: [0x5a; 32],
+        ..invite.clone()
+    };
+    assert!(
+        rt_b.connections().establish(y, forged).await.is_err(),
+        "a never-deposited secret must find nothing"
+    );
+    assert_eq!(rt_a.connections().collect_mailboxes().await?, 0);
+    assert!(rt_a.connections().list(x).await?.is_empty());
+
+    rt_b.connections().establish(y, invite.clone()).await?;
+    assert!(
+        rt_b.connections()
+            .establish(y, invite.clone())
+            .await
+            .is_err(),
+        "a second request under one secret must be refused"
+    );
+    assert_eq!(rt_a.connections().collect_mailboxes().await?, 1);
+    assert_eq!(rt_b.connections().collect_mailboxes().await?, 1);
+    assert_eq!(rt_b.connections().list(y).await?, vec![x]);
+
+    assert!(
+        rt_c.connections().establish(z, invite).await.is_err(),
+        "an answered invite's package must be gone"
+    );
+    assert!(rt_c.connections().list(z).await?.is_empty());
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    rt_c.shutdown().await?;
+    relay.shutdown().await?;
+    Ok(())
+}
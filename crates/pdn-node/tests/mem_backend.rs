@@ -0,0 +1,155 @@
+//! The in-memory backend as application tests use it: replication between
+//! simulated devices and a grantee moves only when stepped, records before
+//! payloads, within what the grant covers — deletions included — and the
+//! runtime's data service runs over it unchanged.
+
+use anyhow::{Context, Result};
+use data_layer::{claim_id_of, DataLayer as _, DataLayerError, MemNetwork, ReadGrant};
+use futures_lite::StreamExt as _;
+use pdn_node::{DataService as _, Runtime, RuntimeDataService, ServiceError};
+use pdn_types::{EntryEventKind, EntryOrigin, EntryPath, NonEmpty, ValidityWindow};
+use test_utils::ids;
+
+#[tokio::test]
+async fn replication_moves_only_when_stepped_and_within_the_grant() -> Result<()> {
+    let network = MemNetwork::new();
+    let phone = network.device(ids::ALICE)?;
+    let laptop = network.device(ids::ALICE)?;
+    let bob = network.device(ids::BOB)?;
+
+    let email = EntryPath::new("contact/email")?;
+    let diary = EntryPath::new("notes/diary")?;
+    network.grant(ReadGrant {
+        issuer: ids::ALICE,
+        audience: ids::BOB,
+        claims: NonEmpty::new(claim_id_of(&ids::ALICE, &email)),
+        write: false,
+        window: None,
+    })?;
+    let mut watch = laptop.watch_entries(ids::ALICE, None).await?;
+
+    phone
+        .insert_entry(ids::ALICE, &email, b"a@example.org")
+        .await?;
+    phone
+        .insert_entry(ids::ALICE, &diary, b"dear diary")
+        .await?;
+    assert_eq!(laptop.get_entry(ids::ALICE, &email).await?, None);
+
+    // A step moves the records; the payloads follow separately.
+    assert_eq!(network.step()?, 3);
+    assert_eq!(laptop.get_entry(ids::ALICE, &email).await?, None);
+    let listed = bob.list_entries(ids::ALICE, None).await?;
+    let listed: Vec<_> = listed.try_collect::<_, _, Vec<_>>().await?;
+    assert_eq!(
+        listed.len(),
+        1,
+        "the grantee holds exactly the granted claim"
+    );
+    network.deliver_payloads()?;
+    assert_eq!(
+        laptop.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"a@example.org".as_ref())
+    );
+    assert_eq!(bob.get_entry(ids::ALICE, &diary).await?, None);
+
+    let inserted = watch.next().await.context("the watch ended")??;
+    assert_eq!(inserted.kind, EntryEventKind::Inserted);
+    assert_eq!(inserted.origin, EntryOrigin::Remote(phone.node_id()));
+
+    // The grantee cannot write under a read grant.
+    assert!(matches!(
+        bob.insert_entry(ids::ALICE, &email, b"x").await,
+        Err(DataLayerError::NotAuthorizedToWrite { .. })
+    ));
+
+    // A deletion on one device reaches its sibling and the grantee.
+    assert!(laptop.delete_entry(ids::ALICE, &email).await?);
+    network.settle()?;
+    assert_eq!(phone.get_entry(ids::ALICE, &email).await?, None);
+    assert_eq!(bob.get_entry(ids::ALICE, &email).await?, None);
+
+    // An offline device neither sends nor receives.
+    laptop.set_online(false)?;
+    phone.insert_entry(ids::ALICE, &email, b"again").await?;
+    network.settle()?;
+    assert_eq!(laptop.get_entry(ids::ALICE, &email).await?, None);
+    laptop.set_online(true)?;
+    network.settle()?;
+    assert_eq!(
+        laptop.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"again".as_ref())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn a_windowed_grant_replicates_only_inside_its_window() -> Result<()> {
+    let network = MemNetwork::new();
+    let alice = network.device(ids::ALICE)?;
+    let bob = network.device(ids::BOB)?;
+
+    let email = EntryPath::new("contact/email")?;
+    network.grant(ReadGrant {
+        issuer: ids::ALICE,
+        audience: ids::BOB,
+        claims: NonEmpty::new(claim_id_of(&ids::ALICE, &email)),
+        write: true,
+        window: Some(ValidityWindow {
+            nbf: 1_000,
+            exp: 2_000,
+        }),
+    })?;
+    alice
+        .insert_entry(ids::ALICE, &email, b"a@example.org")
+        .await?;
+
+    // Before the window opens: nothing moves, and the grant writes nothing.
+    network.set_time(500)?;
+    assert_eq!(network.step()?, 0);
+    assert!(matches!(
+        bob.insert_entry(ids::ALICE, &email, b"x").await,
+        Err(DataLayerError::NotAuthorizedToWrite { .. })
+    ));
+
+    // Inside it: the claim arrives.
+    network.set_time(1_500)?;
+    network.settle()?;
+    assert_eq!(
+        bob.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"a@example.org".as_ref())
+    );
+
+    // From its expiry on: later writes stay behind.
+    network.set_time(2_000)?;
+    alice.insert_entry(ids::ALICE, &email, b"later").await?;
+    assert_eq!(network.step()?, 0);
+    assert_eq!(
+        bob.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"a@example.org".as_ref())
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_data_service_runs_over_another_backend() -> Result<()> {
+    let runtime = Runtime::spawn().await?;
+    let network = MemNetwork::new();
+    let device = network.device(ids::ALICE)?;
+    let data = RuntimeDataService::with_entries(&runtime, device.clone());
+
+    let email = EntryPath::new("contact/email")?;
+    data.write(ids::ALICE, &email, b"a@example.org").await?;
+    assert_eq!(
+        device.get_entry(ids::ALICE, &email).await?.as_deref(),
+        Some(b"a@example.org".as_ref())
+    );
+    let listed = data.list(ids::ALICE, None).await?;
+    assert_eq!(listed.len(), 1);
+    // The backend's refusals keep their variants.
+    let err = data.read(ids::BOB, &email).await.unwrap_err();
+    assert!(matches!(err, ServiceError::UnknownIssuer(_)));
+
+    runtime.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! The payloads' text form: both URIs round-trip and the parsed payload
+//! still does its job, the scheme and checksum catch a payload altered,
+//! truncated or of the other kind, and a payload of another format version
+//! is named as such.
+
+use anyhow::Result;
+use pdn_node::{
+    ConnectionsService as _, IdentityService as _, InvitePayload, LinkingPayload,
+    PayloadParseError, Runtime, INVITE_URI_SCHEME, LINK_URI_SCHEME,
+};
+use test_utils::TIMEOUT;
+
+/// Replace the base64 character at `at` of `text` with another one.
+fn flip_char(text: &str, at: usize) -> String {
+    text.char_indices()
+        .map(|(i, c)| match (i == at, c) {
+            (false, c) => c,
+            (true, 'A') => 'B',
+            (true, _) => 'A',
+        })
+        .collect()
+}
+
+/// An invite and a linking payload survive their text form — the parsed
+/// values print back to the same strings — and the parsed ones still
+/// establish and link.
+#[tokio::test(flavor = "multi_thread")]
+async fn payload_uris_round_trip_and_still_work() -> Result<()> {
+    let rt_a = Runtime::spawn().await?;
+    let rt_b = Runtime::spawn().await?;
+    let rt_laptop = Runtime::spawn().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+
+    let invite = rt_a.connections().invite(x, None).await?;
+    let text = invite.to_string();
+    assert!(text.starts_with(&format!("{INVITE_URI_SCHEME}:")));
+    assert!(text
+        .chars()
+        .skip(INVITE_URI_SCHEME.len() + 1)
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    let parsed: InvitePayload = text.parse()?;
+    assert_eq!(parsed.to_string(), text);
+    assert_eq!(parsed.inviter, x);
+    assert_eq!(parsed.secret, invite.secret);
+    rt_b.connections().establish(y, parsed).await?;
+    assert_eq!(rt_a.connections().list(x).await?, vec![y]);
+
+    let linking = rt_b.identity().linking_invite(y, None).await?;
+    let text = linking.to_string();
+    assert!(text.starts_with(&format!("{LINK_URI_SCHEME}:")));
+    let parsed: LinkingPayload = text.parse()?;
+    assert_eq!(parsed.to_string(), text);
+    assert_eq!(parsed.identity, y);
+    rt_laptop.identity().link(parsed, None, TIMEOUT).await?;
+    assert_eq!(rt_laptop.sync().hosted_identities().await?, vec![y]);
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    rt_laptop.shutdown().await?;
+    Ok(())
+}
+
+/// Every alteration is caught before a payload is produced: a single
+/// changed character or a cut-off string is malformed or fails the
+/// checksum, and each scheme refuses the other kind's URI. A payload of a
+/// version this runtime does not speak parses as far as its version.
+#[tokio::test(flavor = "multi_thread")]
+async fn altered_payload_uris_are_refused() -> Result<()> {
+    let rt = Runtime::spawn().await?;
+    let x = rt.identity().create().await?;
+    let invite = rt.connections().invite(x, None).await?;
+    let text = invite.to_string();
+
+    let body_start = INVITE_URI_SCHEME.len() + 1;
+    for at in body_start..text.len() {
+        // The last character also carries padding bits, which decoding
+        // checks before the checksum does.
+        let err = flip_char(&text, at).parse::<InvitePayload>().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PayloadParseError::Malformed | PayloadParseError::ChecksumMismatch
+            ),
+            "a change at {at} must be caught, got {err:?}"
+        );
+    }
+    for len in body_start..text.len() {
+        let err = text[..len].parse::<InvitePayload>().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PayloadParseError::Malformed | PayloadParseError::ChecksumMismatch
+            ),
+            "a cut at {len} must be caught, got {err:?}"
+        );
+    }
+
+    let linking = rt.identity().linking_invite(x, None).await?.to_string();
+    assert_eq!(
+        linking.parse::<InvitePayload>().unwrap_err(),
+        PayloadParseError::WrongScheme {
+            expected: INVITE_URI_SCHEME
+        }
+    );
+    assert_eq!(
+        text.parse::<LinkingPayload>().unwrap_err(),
+        PayloadParseError::WrongScheme {
+            expected: LINK_URI_SCHEME
+        }
+    );
+    // Relabelling an invite does not make it a linking payload: the
+    // checksum is bound to the scheme.
+    let renamed = text.replacen(INVITE_URI_SCHEME, LINK_URI_SCHEME, 1);
+    assert_eq!(
+        renamed.parse::<LinkingPayload>().unwrap_err(),
+        PayloadParseError::ChecksumMismatch
+    );
+
+    let future = InvitePayload {
+        version: 99,
+        ..invite
+    };
+    assert_eq!(
+        future.to_string().parse::<InvitePayload>().unwrap_err(),
+        PayloadParseError::UnsupportedVersion { version: 99 }
+    );
+
+    rt.shutdown().await?;
+    Ok(())
+}
+
+/// With the `qr` feature, a payload renders as a square matrix and as SVG.
+#[cfg(feature = "qr")]
+#[tokio::test(flavor = "multi_thread")]
+async fn payloads_render_as_qr_codes() -> Result<()> {
+    let rt = Runtime::spawn().await?;
+    let x = rt.identity().create().await?;
+    let invite = rt.connections().invite(x, None).await?;
+
+    let matrix = invite.to_qr_matrix()?;
+    assert_eq!(matrix.modules.len(), matrix.width * matrix.width);
+    // Every QR code's top-left finder pattern starts dark.
+    assert!(matrix.is_dark(0, 0));
+    assert!(!matrix.is_dark(matrix.width, 0));
+    assert!(invite.to_qr_svg()?.contains("<svg"));
+
+    let linking = rt.identity().linking_invite(x, None).await?;
+    assert!(linking.to_qr_matrix()?.width > 0);
+
+    rt.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Restart recovery: a runtime on a storage root hosts again, after a
+//! restart, every identity it hosted before — and resumes serving and
+//! receiving grants over the connections those identities established,
+//! with no act of the caller's. The paired deny: an in-memory runtime
+//! comes back hosting nothing, and refuses the identity as unknown.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use pdn_node::{
+    ConnectionsService as _, DataService as _, IdentityService as _, Runtime, ServiceError,
+    SpawnOptions, SyncService as _,
+};
+use pdn_types::EntryPath;
+use test_utils::eventually;
+
+mod common;
+use common::{claims_on, establish_patiently};
+
+/// The reconcile cadence of this scenario: the restarted runtime finds its
+/// way back to its peers through reconcile passes, made cheap here.
+const RECONCILE: Duration = Duration::from_millis(500);
+
+/// Allowed: A hosts Alice on a storage root, connected to Bob on B. After
+/// A restarts, Alice is hosted again with her entries intact, a grant Alice
+/// publishes reaches Bob and serves him the granted entry, and a grant Bob
+/// publishes is bound on A and delivers Bob's entry there.
+#[tokio::test(flavor = "multi_thread")]
+async fn restarted_runtime_resumes_serving_and_receiving_grants() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let persistent = SpawnOptions {
+        reconcile_interval: RECONCILE,
+        storage: Some(root.path().to_path_buf()),
+        ..SpawnOptions::default()
+    };
+    let a = Runtime::spawn_with(persistent.clone()).await?;
+    let b = Runtime::spawn_with(SpawnOptions {
+        reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
+    })
+    .await?;
+    let alice = a.identity().create().await?;
+    let bob = b.identity().create().await?;
+    let invite = a.connections().invite(alice, None).await?;
+    establish_patiently(&b, bob, &a, alice, invite).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    let phone = EntryPath::new("contact/phone")?;
+    a.data().write(alice, &email, b"alice@example.org").await?;
+    b.data().write(bob, &phone, b"+1-555-0199").await?;
+
+    let node_id = a.node_id();
+    a.shutdown().await?;
+    let a = Runtime::spawn_with(persistent).await?;
+
+    // Hosted again, as the same node, with the entries on disk.
+    assert_eq!(a.node_id(), node_id);
+    assert_eq!(a.sync().hosted_identities().await?, vec![alice]);
+    assert_eq!(
+        a.data().read(alice, &email).await?.as_deref(),
+        Some(&b"alice@example.org"[..])
+    );
+    assert_eq!(a.connections().list(alice).await?, vec![bob]);
+
+    // Serving: a grant published after the restart reaches Bob, whose
+    // binder imports the namespace; the granted entry converges there.
+    a.connections()
+        .publish_grant(alice, bob, alice, claims_on(alice, &email), false)
+        .await?;
+    assert!(
+        eventually(|| async {
+            Ok(b.data().read(alice, &email).await.ok().flatten().as_deref()
+                == Some(&b"alice@example.org"[..]))
+        })
+        .await?,
+        "the restarted runtime did not serve its grant"
+    );
+
+    // Receiving: the grant binder came back with the pair, so a grant Bob
+    // publishes is bound on A without any act there.
+    b.connections()
+        .publish_grant(bob, alice, bob, claims_on(bob, &phone), false)
+        .await?;
+    assert!(
+        eventually(|| async {
+            Ok(a.data().read(bob, &phone).await.ok().flatten().as_deref()
+                == Some(&b"+1-555-0199"[..]))
+        })
+        .await?,
+        "the restarted runtime did not bind a fresh grant"
+    );
+
+    a.shutdown().await?;
+    b.shutdown().await?;
+    Ok(())
+}
+
+/// Denied: an in-memory runtime keeps nothing across a restart — the
+/// identity it hosted is unknown to the runtime that replaces it, and so
+/// is its data namespace.
+#[tokio::test(flavor = "multi_thread")]
+async fn in_memory_runtime_comes_back_hosting_nothing() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+    let email = EntryPath::new("contact/email")?;
+    a.data().write(alice, &email, b"alice@example.org").await?;
+    a.shutdown().await?;
+
+    let a = Runtime::spawn().await?;
+    assert!(a.sync().hosted_identities().await?.is_empty());
+    let err = a.connections().list(alice).await.unwrap_err();
+    assert!(matches!(err, ServiceError::NotHosted(_)));
+    let err = a.data().read(alice, &email).await.unwrap_err();
+    assert!(matches!(err, ServiceError::UnknownIssuer(_)));
+
+    a.shutdown().await?;
+    Ok(())
+}
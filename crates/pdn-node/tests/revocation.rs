@@ -0,0 +1,184 @@
+//! Device revocation end to end, over three devices of one identity: the
+//! revoking device removes the device's key from the log and re-keys the
+//! directory and data namespace, the remaining device follows onto the new
+//! replicas and keeps receiving, and the revoked device — still holding
+//! every ticket it was handed — receives no new entry and reaches no device
+//! with its own writes. A counterparty follows the re-keyed connection
+//! store and receives the grants made after the revocation. The paired
+//! refusals: a device cannot revoke itself, and a node that never was a
+//! device cannot be revoked.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use pdn_node::{
+    ConnectionsService as _, DataService as _, IdentityService as _, Runtime, SpawnOptions,
+};
+use pdn_types::{EntryPath, OperationalKey, PdnId};
+use test_utils::eventually;
+
+mod common;
+use common::{claims_on, establish_patiently, granted_patiently, link_patiently};
+
+/// The key `device`'s record under `identity` names, as `on` lists it.
+async fn device_key(on: &Runtime, identity: PdnId, device: &Runtime) -> Result<OperationalKey> {
+    on.identity()
+        .devices(identity)
+        .await?
+        .into_iter()
+        .find(|info| info.node == device.node_id())
+        .and_then(|info| info.key)
+        .ok_or_else(|| anyhow::anyhow!("no key recorded for the device"))
+}
+
+/// The reconcile cadence this scenario runs at, so "nothing arrived" is
+/// probed over a few intervals rather than the production default's.
+const RECONCILE: Duration = Duration::from_millis(500);
+
+/// How long a would-be delivery to the revoked device gets before "it never
+/// came" counts: swarm formation takes around ten seconds (see the `data`
+/// suite), well past a few reconcile intervals.
+const QUIET_WINDOW: Duration = Duration::from_secs(15);
+
+/// Allowed: before the revocation every device receives; after it the
+/// remaining device receives the next entry from the re-keyed namespace.
+/// Denied: the revoked device receives nothing written after the
+/// revocation, and what it writes into its stale replica reaches neither
+/// remaining device.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_revoked_device_stops_receiving_new_entries() -> Result<()> {
+    let options = SpawnOptions {
+        reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
+    };
+    let a = Runtime::spawn_with(options.clone()).await?;
+    let b = Runtime::spawn_with(options.clone()).await?;
+    let c = Runtime::spawn_with(options).await?;
+    let alice = a.identity().create().await?;
+    link_patiently(&b, &a, alice).await?;
+    link_patiently(&c, &a, alice).await?;
+
+    let before = EntryPath::new("notes/before")?;
+    a.data().write(alice, &before, b"before").await?;
+    for device in [&b, &c] {
+        assert!(
+            eventually(|| async {
+                Ok(device.data().read(alice, &before).await?.as_deref() == Some(&b"before"[..]))
+            })
+            .await?,
+            "a linked device never received the entry written before the revocation"
+        );
+    }
+
+    let revoked_key = device_key(&a, alice, &c).await?;
+    a.identity().revoke_device(alice, c.node_id()).await?;
+
+    // The device's key no longer speaks for the identity — on the revoking
+    // device at once, on the remaining one once it has followed.
+    let key_state = a.identity().key_state(alice).await?;
+    assert!(key_state.removed.contains(&revoked_key));
+    assert!(!key_state.authorizes_devices(&revoked_key));
+
+    // The entry written before the revocation survives the re-keying.
+    assert_eq!(
+        a.data().read(alice, &before).await?.as_deref(),
+        Some(&b"before"[..])
+    );
+
+    let after = EntryPath::new("notes/after")?;
+    a.data().write(alice, &after, b"after").await?;
+    assert!(
+        eventually(|| async {
+            Ok(b.data().read(alice, &after).await?.as_deref() == Some(&b"after"[..]))
+        })
+        .await?,
+        "the remaining device never followed the re-keyed namespace"
+    );
+    assert!(b
+        .identity()
+        .key_state(alice)
+        .await?
+        .removed
+        .contains(&revoked_key));
+
+    // Denied: the revoked device writes into the replica it still holds.
+    let forged = EntryPath::new("notes/forged")?;
+    c.data().write(alice, &forged, b"forged").await?;
+    tokio::time::sleep(QUIET_WINDOW).await;
+    assert!(
+        c.data().read(alice, &after).await?.is_none(),
+        "the revoked device must not receive entries written after its revocation"
+    );
+    assert!(a.data().read(alice, &forged).await?.is_none());
+    assert!(b.data().read(alice, &forged).await?.is_none());
+
+    a.shutdown().await?;
+    b.shutdown().await?;
+    c.shutdown().await?;
+    Ok(())
+}
+
+/// Allowed: a counterparty connected before the revocation follows the
+/// identity's re-keyed store toward it — its proof checked against the log
+/// mirrored in the retired store — and receives a grant published only
+/// into the new one.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_counterparty_follows_the_rekeyed_connection_store() -> Result<()> {
+    let options = SpawnOptions {
+        reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
+    };
+    let a = Runtime::spawn_with(options.clone()).await?;
+    let c = Runtime::spawn_with(options.clone()).await?;
+    let rt_bob = Runtime::spawn_with(options).await?;
+    let alice = a.identity().create().await?;
+    let bob = rt_bob.identity().create().await?;
+    link_patiently(&c, &a, alice).await?;
+    let invite = a.connections().invite(alice, None).await?;
+    establish_patiently(&rt_bob, bob, &a, alice, invite).await?;
+
+    a.identity().revoke_device(alice, c.node_id()).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    a.data().write(alice, &email, b"alice@example.org").await?;
+    let received = granted_patiently(
+        &a,
+        alice,
+        &rt_bob,
+        bob,
+        alice,
+        claims_on(alice, &email),
+        false,
+    )
+    .await?;
+    assert_eq!(received.grant.issuer, alice);
+
+    a.shutdown().await?;
+    c.shutdown().await?;
+    rt_bob.shutdown().await?;
+    Ok(())
+}
+
+/// Denied: the revoking device cannot revoke itself, and a node that is not
+/// a device of the identity cannot be revoked.
+#[tokio::test(flavor = "multi_thread")]
+async fn only_another_device_of_the_identity_can_be_revoked() -> Result<()> {
+    let a = Runtime::spawn().await?;
+    let stranger = Runtime::spawn().await?;
+    let alice = a.identity().create().await?;
+
+    assert!(a
+        .identity()
+        .revoke_device(alice, a.node_id())
+        .await
+        .is_err());
+    assert!(a
+        .identity()
+        .revoke_device(alice, stranger.node_id())
+        .await
+        .is_err());
+
+    a.shutdown().await?;
+    stranger.shutdown().await?;
+    Ok(())
+}
@@ -7,14 +7,15 @@
 //! outsider (no connection, no ticket — refused as unknown), the holder of
 //! the replica's leaked ticket without a grant (obtains nothing), the
 //! existence-hidden withheld claims, and the read-only holder's refused
-//! write.
+//! write. A revoked capability then voids the grant it was made under, and
+//! a time-bounded grant serves only within its window.
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use pdn_node::{
-    claim_id_of, ConnectionsService as _, DataService as _, IdentityService as _, NonEmpty,
-    PeerGrant, Runtime, SpawnOptions, UnknownIssuer,
+    claim_id_of, CapabilityCid, ConnectionsService as _, DataService as _, IdentityService as _,
+    NonEmpty, PeerGrant, Runtime, ServiceError, SpawnOptions, UnknownCapability, ValidityWindow,
 };
 use pdn_types::EntryPath;
 use test_utils::eventually;
@@ -31,10 +32,17 @@ const RECONCILE: Duration = Duration::from_millis(500);
 async fn spawn_runtime() -> Result<Runtime> {
     Runtime::spawn_with(SpawnOptions {
         reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
     })
     .await
 }
 
+/// The wall clock as unix ms — the clock grant windows are read against.
+fn now_ms() -> u64 {
+    let since = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    u64::try_from(since.as_millis()).unwrap()
+}
+
 /// Poll until the peer's scoped grant for `issuer` is readable.
 async fn scoped_grant_patiently(
     receives: &Runtime,
@@ -121,7 +129,7 @@ async fn scoped_grant_flows_through_the_services() -> Result<()> {
     // there, and no connection exists.
     let outsider_err = rt_c.data().read(x, &email).await.unwrap_err();
     assert!(
-        outsider_err.downcast_ref::<UnknownIssuer>().is_some(),
+        matches!(outsider_err, ServiceError::UnknownIssuer(_)),
         "an outsider must be refused as unknown, got: {outsider_err:?}"
     );
 
@@ -190,3 +198,164 @@ async fn scoped_grant_flows_through_the_services() -> Result<()> {
     rt_c.shutdown().await?;
     Ok(())
 }
+
+/// Allowed, then revoked: X grants Y read on one claim, made under one
+/// capability token whose CID the publication returns; Y converges on the
+/// claim. X revokes the token: the revocation crosses the pair, Y reads
+/// the grant as gone and forgets the namespace, and X's devices classify
+/// Y's sessions without it — Y re-importing the grant's ticket afterwards
+/// obtains nothing.
+///
+/// Denied, unknown token: a CID no grant of X is made under is refused
+/// rather than published nowhere.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_revoked_capability_voids_its_grant() -> Result<()> {
+    let rt_a = spawn_runtime().await?;
+    let rt_b = spawn_runtime().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let invite = rt_a.connections().invite(x, None).await?;
+    establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    rt_a.data().write(x, &email, b"x@example.org").await?;
+    let tokens = rt_a
+        .connections()
+        .publish_grant(x, y, x, NonEmpty::new(claim_id_of(&x, &email)), false)
+        .await?;
+    assert_eq!(tokens.len(), 1, "one capability token per granted claim");
+
+    // The grant binder imports the granted namespace on its own.
+    let received = scoped_grant_patiently(&rt_b, y, x, x).await?;
+    assert!(
+        eventually(|| async {
+            Ok(rt_b.data().read(x, &email).await?.as_deref() == Some(&b"x@example.org"[..]))
+        })
+        .await?,
+        "the granted entry did not reach the granted peer"
+    );
+
+    // Denied (unknown token).
+    let unknown = CapabilityCid::from_bytes([0x5c; 32]);
+    let err = rt_a.connections().revoke(x, unknown).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ServiceError::UnknownCapability(UnknownCapability { cid, .. }) if cid == unknown
+        ),
+        "a token no grant is made under must be refused, got: {err:?}"
+    );
+
+    // The revocation: once its record reaches Y, Y reads no grant and its
+    // binder forgets the namespace.
+    rt_a.connections().revoke(x, tokens[0]).await?;
+    assert!(
+        eventually(|| async {
+            Ok(rt_b.connections().read_grants(y, x).await?.is_empty()
+                && matches!(
+                    rt_b.data().read(x, &email).await,
+                    Err(ServiceError::UnknownIssuer(_))
+                ))
+        })
+        .await?,
+        "the revocation did not void the grant on the grantee's side"
+    );
+
+    // Denied (revoked): X's book no longer admits Y to the claim — Y
+    // importing the grant's ticket by hand obtains nothing through several
+    // of its reconcile intervals.
+    rt_b.data().import_scoped(x, received.ticket).await?;
+    tokio::time::sleep(RECONCILE * 3).await;
+    assert!(
+        rt_b.data().list(x, None).await?.is_empty(),
+        "a revoked grant must deliver nothing"
+    );
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    Ok(())
+}
+
+/// Allowed only within the window: X grants Y one claim from a few seconds
+/// from now until a few seconds after. Y reads the grant, window included,
+/// as soon as it crosses, but its binder imports nothing before the window
+/// opens; inside it Y converges on the claim, and once it closes Y's binder
+/// forgets the namespace — with no change of the replica to prompt it —
+/// and X's devices classify Y's sessions without the grant: Y
+/// re-importing the grant's ticket afterwards obtains nothing.
+#[tokio::test(flavor = "multi_thread")]
+async fn a_grant_serves_only_within_its_window() -> Result<()> {
+    let rt_a = spawn_runtime().await?;
+    let rt_b = spawn_runtime().await?;
+    let x = rt_a.identity().create().await?;
+    let y = rt_b.identity().create().await?;
+    let invite = rt_a.connections().invite(x, None).await?;
+    establish_patiently(&rt_b, y, &rt_a, x, invite).await?;
+
+    let email = EntryPath::new("contact/email")?;
+    rt_a.data().write(x, &email, b"x@example.org").await?;
+    let now = now_ms();
+    let window = ValidityWindow {
+        nbf: now + 4_000,
+        exp: now + 10_000,
+    };
+    rt_a.connections()
+        .publish_grant_within(
+            x,
+            y,
+            x,
+            NonEmpty::new(claim_id_of(&x, &email)),
+            false,
+            window,
+        )
+        .await?;
+    let received = scoped_grant_patiently(&rt_b, y, x, x).await?;
+    assert_eq!(received.grant.window, Some(window));
+
+    // Denied (not yet): before the window opens the binder has imported
+    // nothing — asserted only while it has not opened, so a slow crossing
+    // skips it rather than failing it.
+    let unknown = matches!(
+        rt_b.data().read(x, &email).await,
+        Err(ServiceError::UnknownIssuer(_))
+    );
+    assert!(
+        unknown || now_ms() >= window.nbf,
+        "a grant must import nothing before its window opens"
+    );
+
+    // Allowed: inside the window the granted entry converges.
+    assert!(
+        eventually(|| async {
+            Ok(rt_b.data().read(x, &email).await.ok().flatten().as_deref()
+                == Some(&b"x@example.org"[..]))
+        })
+        .await?,
+        "the granted entry did not reach the grantee inside the window"
+    );
+
+    // Expired: the binder forgets the namespace at the window's end.
+    assert!(
+        eventually(|| async {
+            Ok(now_ms() >= window.exp
+                && matches!(
+                    rt_b.data().read(x, &email).await,
+                    Err(ServiceError::UnknownIssuer(_))
+                ))
+        })
+        .await?,
+        "the grantee did not forget the namespace once the window closed"
+    );
+
+    // Denied (expired): X's book no longer admits Y to the claim.
+    rt_b.data().import_scoped(x, received.ticket).await?;
+    tokio::time::sleep(RECONCILE * 3).await;
+    assert!(
+        rt_b.data().list(x, None).await?.is_empty(),
+        "an expired grant must deliver nothing"
+    );
+
+    rt_a.shutdown().await?;
+    rt_b.shutdown().await?;
+    Ok(())
+}
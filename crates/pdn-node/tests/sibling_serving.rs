@@ -33,6 +33,7 @@ const RECONCILE: Duration = Duration::from_millis(500);
 async fn spawn_runtime() -> Result<Runtime> {
     Runtime::spawn_with(SpawnOptions {
         reconcile_interval: RECONCILE,
+        ..SpawnOptions::default()
     })
     .await
 }
@@ -99,7 +100,10 @@ async fn a_linked_device_catches_up_from_its_sibling_while_the_issuer_is_offline
     // Alice lives on the phone; the laptop joins by the linking ceremony.
     let alice = rt_phone.identity().create().await?;
     let link_invite = rt_phone.identity().linking_invite(alice, None).await?;
-    rt_laptop.identity().link(link_invite, TIMEOUT).await?;
+    rt_laptop
+        .identity()
+        .link(link_invite, None, TIMEOUT)
+        .await?;
 
     // Bob connects to Alice by establishment, writes a granted claim and a
     // withheld one, and publishes a scoped grant on the granted claim.
@@ -51,6 +51,18 @@ impl EntryPath {
         self.0.split('/')
     }
 
+    /// Whether this path lies under `prefix`, matching whole components:
+    /// `contacts` is a prefix of `contacts` and `contacts/a`, not of
+    /// `contactsx/c`. Both are validated paths (no empty components, no
+    /// trailing slash), so a byte prefix plus a component boundary is
+    /// exactly component semantics.
+    pub fn starts_with(&self, prefix: &EntryPath) -> bool {
+        match self.0.strip_prefix(prefix.as_str()) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+
     fn validate(s: &str) -> Result<(), PathValidationError> {
         if s.is_empty() {
             return Err(PathValidationError {
@@ -140,6 +152,46 @@ pub struct EntryInfo {
     pub payload_len: u64,
 }
 
+/// One change to an entry, as the data layer's watches report it: the
+/// entry's metadata as of the change, what happened, and where it came from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryEvent {
+    #[serde(flatten)]
+    pub info: EntryInfo,
+    pub kind: EntryEventKind,
+    pub origin: EntryOrigin,
+}
+
+/// What an [`EntryEvent`] reports.
+///
+/// Records and payloads travel separately, so a record's arrival and its
+/// payload's are two events: every `Inserted` or `Updated` is followed by
+/// one `PayloadReady` once the payload can be read — straight away for a
+/// local write or a payload already held, later for one still in transit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryEventKind {
+    /// A record at a path that held none.
+    Inserted,
+    /// A newer record at a path that already held one.
+    Updated,
+    /// A tombstone: the entry at the path is gone. A prefix deletion
+    /// reports each entry it deleted.
+    Deleted,
+    /// The payload of the path's latest record is now readable.
+    PayloadReady,
+}
+
+/// Where the change behind an [`EntryEvent`] was made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryOrigin {
+    /// Written on this node.
+    Local,
+    /// Arrived by sync from the given node.
+    Remote(NodeId),
+}
+
 // ---------------------------------------------------------------------------
 // Namespace roles
 // ---------------------------------------------------------------------------
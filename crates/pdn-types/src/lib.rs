@@ -3,7 +3,10 @@ use thiserror::Error;
 
 mod data;
 mod non_empty;
-pub use data::{EntryInfo, EntryPath, NamespaceRole, NodeAddr, PathValidationError};
+pub use data::{
+    EntryEvent, EntryEventKind, EntryInfo, EntryOrigin, EntryPath, NamespaceRole, NodeAddr,
+    PathValidationError,
+};
 pub use non_empty::NonEmpty;
 
 // ---------------------------------------------------------------------------
@@ -151,9 +154,19 @@ define_byte_id! {
     pub struct PdnId;
 }
 
-/// Cryptographic evidence that a particular `PdnId` issued a statement.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
-pub struct PdnIdentityProof {}
+/// Cryptographic evidence that a particular `PdnId` issued a statement: a
+/// signature over the statement by one of the identity's operational keys.
+///
+/// Only half the evidence — which keys speak for a `PdnId` is its key event
+/// log's to say, so a verifier checks the proof against a verified key
+/// state (`pdn_layer::kel::KeyState::verify_proof`), never on its own.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PdnIdentityProof {
+    /// The operational key that signed.
+    pub key: OperationalKey,
+    /// The ed25519 signature, 64 bytes.
+    pub signature: Vec<u8>,
+}
 
 // -- KERI identity types ----------------------------------------------------
 
@@ -181,3 +194,62 @@ define_byte_id! {
     /// storage-level locations.
     pub struct ClaimId;
 }
+
+define_byte_id! {
+    /// CID of a `UWill` delegation — used for revocation references. Holds
+    /// the SHA-256 digest of the token's signed envelope; the token format
+    /// itself lives in `pdn_layer::uwill`, and below it the CID is an
+    /// opaque reference, as [`ClaimId`] is.
+    pub struct CapabilityCid;
+}
+
+impl CapabilityCid {
+    /// The prefix that makes the digest a binary CID: version 1, the
+    /// DAG-CBOR codec, and a 32-byte SHA-256 multihash.
+    pub const PREFIX: [u8; 4] = [0x01, 0x71, 0x12, 0x20];
+
+    /// The binary CID: [`PREFIX`](Self::PREFIX), then the digest.
+    pub fn to_cid_bytes(&self) -> Vec<u8> {
+        let mut bytes = Self::PREFIX.to_vec();
+        bytes.extend_from_slice(self.as_bytes());
+        bytes
+    }
+}
+
+/// Wall-clock validity window of a capability: a `UWill` token's, and a
+/// grant's that is time-bounded.
+///
+/// Both bounds are absolute unix-ms timestamps, matching the wire format
+/// of `nbf` / `exp` in `pdn_layer::uwill::UwillCapability`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ValidityWindow {
+    /// Not before: the first instant the window holds.
+    pub nbf: u64,
+    /// Expiry: the first instant it no longer holds.
+    pub exp: u64,
+}
+
+impl ValidityWindow {
+    /// Whether `now` falls in the window: from `nbf` on, until `exp`.
+    pub fn contains(&self, now: u64) -> bool {
+        (self.nbf..self.exp).contains(&now)
+    }
+
+    /// Whether this window lies within `outer`.
+    pub fn within(&self, outer: &Self) -> bool {
+        outer.nbf <= self.nbf && self.exp <= outer.exp
+    }
+
+    /// The next instant after `now` at which the window opens or closes —
+    /// `nbf` before it opens, `exp` while it holds — or `None` once it has
+    /// closed for good.
+    pub fn next_edge(&self, now: u64) -> Option<u64> {
+        if now < self.nbf {
+            Some(self.nbf)
+        } else if now < self.exp {
+            Some(self.exp)
+        } else {
+            None
+        }
+    }
+}